@@ -0,0 +1,49 @@
+// Ejemplo de plug-in de shader (ver src/plugin.rs). Se compila aparte como
+// biblioteca dinámica:
+//
+//   rustc --crate-type cdylib -O plugins/example_shader.rs -o plugins/libexample_shader.so
+//
+// y se registra en assets/scene.toml:
+//
+//   [plugins.ejemplo]
+//   library = "plugins/libexample_shader.so"
+//   bodies = ["mars"]
+//
+// Las estructuras tienen que coincidir campo a campo con las de src/plugin.rs.
+
+#[repr(C)]
+pub struct PluginFragment {
+    pub position: [f32; 3],
+    pub world_position: [f32; 3],
+    pub normal: [f32; 3],
+    pub intensity: f32,
+}
+
+#[repr(C)]
+pub struct PluginParams {
+    pub time: f32,
+    pub band_frequency: f32,
+    pub noise_scale: f32,
+    pub hue_shift: f32,
+}
+
+#[repr(C)]
+pub struct PluginColor {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+}
+
+// Bandas de latitud que se desplazan con el tiempo, entre un naranja y un crema
+#[no_mangle]
+pub extern "C" fn shade(fragment: *const PluginFragment, params: *const PluginParams) -> PluginColor {
+    let (fragment, params) = unsafe { (&*fragment, &*params) };
+    let frequency = if params.band_frequency > 0.0 { params.band_frequency } else { 8.0 };
+    let phase = params.time * 0.01;
+    let t = 0.5 + 0.5 * (fragment.position[1] * frequency + phase).sin();
+    PluginColor {
+        r: 0.85 + 0.1 * t,
+        g: 0.45 + 0.35 * t,
+        b: 0.2 + 0.45 * t,
+    }
+}
@@ -0,0 +1,142 @@
+use nalgebra_glm::{DVec3, Mat4, Vec3, Vec4};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::color::Color;
+use crate::effects_budget;
+use crate::framebuffer::Framebuffer;
+use crate::memory::{vec_bytes, MemoryUsage};
+use crate::primitives;
+use crate::orbit::Orbit;
+use crate::render_queue::RenderFn;
+use crate::solar_system::{self, SolarSystem};
+use crate::vertex::Vertex;
+use crate::Uniforms;
+
+// El cinturón ocupa esta fracción del espacio entre las órbitas de Marte y Júpiter;
+// la semilla fija deja siempre las mismas rocas
+const BELT_START: f64 = 0.3;
+const BELT_END: f64 = 0.6;
+const SEED: u64 = 23;
+// Dispersión de las órbitas: excentricidad e inclinación máximas (radianes)
+const MAX_ECCENTRICITY: f64 = 0.05;
+const MAX_INCLINATION: f64 = 0.06;
+// Radio de las rocas en unidades de la escena; cada eje se estira hasta ROUGHNESS
+// veces para que no sean esferas
+const SIZE: (f32, f32) = (0.012, 0.05);
+const ROUGHNESS: f32 = 1.8;
+// Giro de cada roca sobre sí misma, en radianes por unidad de tiempo
+const MAX_TUMBLE: f32 = 0.05;
+// Bajo este radio en pantalla (pixeles) una roca se dibuja como un punto
+pub const POINT_PIXELS: f32 = 1.5;
+// Color de las rocas vistas como punto (la malla usa el shader de la Luna) y
+// luz ambiente de los puntos, para que se vean aun a contraluz
+const ROCK_COLOR: Color = Color::new(138, 124, 108);
+const POINT_AMBIENT: f32 = 0.05;
+
+#[derive(Debug, Clone)]
+struct Rock {
+    orbit: Orbit,
+    // Radio medio y estiramiento por eje
+    size: f32,
+    stretch: Vec3,
+    // Orientación inicial y velocidad de giro por eje
+    attitude: Vec3,
+    tumble: Vec3,
+    // Variación del brillo de cada roca
+    shade: f32,
+}
+
+// Cinturón de asteroides: unos miles de rocas en órbitas keplerianas alrededor del
+// Sol, entre Marte y Júpiter. Comparten una malla de pocos triángulos cuyo arreglo
+// de vértices se arma una vez; cada roca solo aporta su matriz de modelo (posición,
+// giro y escala por eje). Las que quedan de menos de POINT_PIXELS en pantalla se
+// dibujan como un punto, sin pasar por el rasterizador.
+pub struct AsteroidBelt {
+    rocks: Vec<Rock>,
+    // Rocas que se dibujan con el presupuesto de efectos al 100%; se generan las que
+    // pide el presupuesto máximo, en orden al azar, y se dibujan las primeras
+    count: usize,
+    // Malla compartida, ya armada (ver Obj::vertices)
+    vertices: Vec<Vertex>,
+    indices: Vec<u32>,
+}
+
+impl AsteroidBelt {
+    // None si el sistema no tiene a Marte y a Júpiter en órbita
+    pub fn new(system: &SolarSystem, count: usize) -> Option<Self> {
+        let (mars, jupiter) = (system.orbit("mars")?, system.orbit("jupiter")?);
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let rocks = (0..effects_budget::scaled(count, effects_budget::MAX_SCALE))
+            .map(|_| {
+                // Entre las dos órbitas la velocidad angular se interpola con la distancia
+                let t = rng.gen_range(BELT_START..BELT_END);
+                let radius = mars.radius + (jupiter.radius - mars.radius) * t;
+                let speed = mars.angular_speed + (jupiter.angular_speed - mars.angular_speed) * t;
+                let tau = std::f64::consts::TAU;
+                let orbit = Orbit::circular(radius, speed, 0.0)
+                    .with_eccentricity(rng.gen_range(0.0..MAX_ECCENTRICITY), rng.gen_range(0.0..tau))
+                    .with_inclination(rng.gen_range(0.0..MAX_INCLINATION), rng.gen_range(0.0..tau))
+                    .with_mean_anomaly(rng.gen_range(0.0..tau));
+                let mut axis = || rng.gen_range(1.0..ROUGHNESS);
+                let stretch = Vec3::new(axis(), axis(), axis());
+                let mut angle = |max: f32| rng.gen_range(-max..max);
+                let attitude = Vec3::new(angle(std::f32::consts::PI), angle(std::f32::consts::PI), angle(std::f32::consts::PI));
+                let tumble = Vec3::new(angle(MAX_TUMBLE), angle(MAX_TUMBLE), angle(MAX_TUMBLE));
+                // Muchas chicas y pocas grandes
+                let size = SIZE.0 + (SIZE.1 - SIZE.0) * rng.gen::<f32>().powi(3);
+                Rock { orbit, size, stretch, attitude, tumble, shade: rng.gen_range(0.6..1.2) }
+            })
+            .collect();
+        let mesh = primitives::uv_sphere(8, 5);
+        Some(AsteroidBelt { rocks, count, vertices: mesh.vertices(), indices: mesh.indices() })
+    }
+
+    // `uniforms` trae vista, proyección, luces y material de las rocas; su matriz de
+    // modelo se reemplaza por la de cada una. `render` dibuja la malla compartida y
+    // `effects_scale` recorta cuántas rocas se dibujan (ver effects_budget.rs).
+    pub fn draw(&self, framebuffer: &mut Framebuffer, mut uniforms: Uniforms, system: &SolarSystem, origin: DVec3, render: RenderFn, effects_scale: f32) {
+        let view_projection = uniforms.projection_matrix * uniforms.view_matrix;
+        let sun = system.position("sun");
+        let (width, height) = (framebuffer.width as f32, framebuffer.height as f32);
+        let point_size = framebuffer.supersampling();
+        let time = system.time as f32;
+        for rock in self.rocks.iter().take(effects_budget::scaled(self.count, effects_scale)) {
+            let center = solar_system::to_render(sun + rock.orbit.position_at(system.time), origin);
+            let clip = view_projection * Vec4::new(center.x, center.y, center.z, 1.0);
+            if clip.w <= 0.0 {
+                continue;
+            }
+            let pixels = uniforms.projection_matrix[(1, 1)] * rock.size * ROUGHNESS / clip.w * height / 2.0;
+            let screen = uniforms.viewport_matrix * (clip / clip.w);
+            if screen.x + pixels < 0.0 || screen.y + pixels < 0.0 || screen.x - pixels >= width || screen.y - pixels >= height {
+                continue;
+            }
+
+            if pixels >= POINT_PIXELS {
+                let rotation = rock.attitude + rock.tumble * time;
+                let stretch = Mat4::new_nonuniform_scaling(&(rock.stretch * rock.size));
+                uniforms.model_matrix = crate::create_model_matrix(center, 1.0, rotation) * stretch;
+                render(framebuffer, &uniforms, &self.vertices, &self.indices);
+                continue;
+            }
+            // Punto: brillo según la fase, como la fracción iluminada de una esfera vista
+            // desde la cámara
+            let to_camera = (uniforms.camera_position - center).normalize();
+            let lit = uniforms.lights.first().map_or(1.0, |light| 0.5 + 0.5 * light.direction(&center).dot(&to_camera));
+            let brightness = rock.shade * (POINT_AMBIENT + (1.0 - POINT_AMBIENT) * lit);
+            let color = ROCK_COLOR.to_linear().map(|channel| channel * brightness);
+            let (x, y) = (screen.x.max(0.0) as usize, screen.y.max(0.0) as usize);
+            for py in y..y + point_size {
+                for px in x..x + point_size {
+                    framebuffer.point_linear(px, py, screen.z, color);
+                }
+            }
+        }
+    }
+}
+
+impl MemoryUsage for AsteroidBelt {
+    fn memory_bytes(&self) -> usize {
+        vec_bytes(&self.rocks) + vec_bytes(&self.vertices) + vec_bytes(&self.indices)
+    }
+}
@@ -0,0 +1,76 @@
+use std::time::Duration;
+use crate::layers::{LAYERS, LAYER_COUNT};
+use crate::stats::FrameStats;
+
+// Modo benchmark: corre un número fijo de frames sin esperas ni entrada del
+// usuario y resume tiempos y fragmentos sombreados, para comparar ajustes de
+// render (p. ej. con y sin foveado) sobre la misma escena
+pub struct Benchmark {
+    frames: usize,
+    frame_times: Vec<Duration>,
+    fragments: usize,
+    shaded: usize,
+    // Cuerpos dibujados, descartados, con malla simple y como impostor (ver stress.rs)
+    draw_calls: usize,
+    culled: usize,
+    low_detail: usize,
+    impostors: usize,
+    // Tiempo acumulado por capa de render, en ms
+    layer_ms: [f64; LAYER_COUNT],
+}
+
+impl Benchmark {
+    pub fn new(frames: usize) -> Self {
+        Benchmark { frames, frame_times: Vec::with_capacity(frames), fragments: 0, shaded: 0, draw_calls: 0, culled: 0, low_detail: 0, impostors: 0, layer_ms: [0.0; LAYER_COUNT] }
+    }
+
+    pub fn record(&mut self, stats: &FrameStats) {
+        self.frame_times.push(stats.frame_time);
+        self.fragments += stats.fragments;
+        self.shaded += stats.fragments.saturating_sub(stats.skipped_fragments);
+        self.draw_calls += stats.draw_calls;
+        self.culled += stats.culled_bodies;
+        self.low_detail += stats.low_detail_bodies;
+        self.impostors += stats.impostors;
+        for (total, ms) in self.layer_ms.iter_mut().zip(stats.layer_ms) {
+            *total += ms as f64;
+        }
+    }
+
+    pub fn done(&self) -> bool {
+        self.frame_times.len() >= self.frames
+    }
+
+    // `bodies`: cuerpos de la escena, para ver cómo escala el costo con --stress
+    pub fn report(&self, label: &str, bodies: usize) -> String {
+        let count = self.frame_times.len().max(1);
+        let mut sorted = self.frame_times.clone();
+        sorted.sort();
+        let millis = |d: Duration| d.as_secs_f64() * 1000.0;
+        let average = millis(sorted.iter().sum::<Duration>()) / count as f64;
+        let p95 = sorted.get((sorted.len() * 95 / 100).min(sorted.len().saturating_sub(1))).copied().unwrap_or_default();
+        let shaded_ratio = if self.fragments > 0 { self.shaded as f64 / self.fragments as f64 } else { 1.0 };
+        let layers = LAYERS
+            .iter()
+            .zip(self.layer_ms)
+            .map(|(layer, total)| format!("{} {:.2} ms", layer.label(), total / count as f64))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(
+            "Benchmark ({}, {} cuerpos): {} frames, {:.2} ms promedio, {:.2} ms p95, {:.1} fps; {} fragmentos sombreados por frame ({:.0}% de los rasterizados); por frame: {} llamadas, {} descartados, {} con malla simple, {} impostores; por capa: {}",
+            label,
+            bodies,
+            self.frame_times.len(),
+            average,
+            millis(p95),
+            if average > 0.0 { 1000.0 / average } else { 0.0 },
+            self.shaded / count,
+            shaded_ratio * 100.0,
+            self.draw_calls / count,
+            self.culled / count,
+            self.low_detail / count,
+            self.impostors / count,
+            layers
+        )
+    }
+}
@@ -0,0 +1,81 @@
+use nalgebra_glm::{DVec3, Vec3, Vec4};
+use crate::framebuffer::Framebuffer;
+use crate::solar_system::{self, SolarSystem};
+use crate::text;
+use crate::Uniforms;
+
+// Escala máxima de la fuente: de cerca el texto deja de crecer para no tapar la escena
+const MAX_SCALE: f32 = 3.0;
+// Las etiquetas de los cuerpos se ponen sobre el borde superior, a esta fracción
+// del radio, y miden esta fracción del radio de alto
+const LABEL_OFFSET: f32 = 1.25;
+const LABEL_HEIGHT: f32 = 0.6;
+const LABEL_COLOR: u32 = 0xE8E8E8;
+
+// Texto anclado en un punto de la escena (espacio de render). Mira siempre a la
+// cámara: todo el cartel está a la profundidad del ancla y cada punto de los glifos
+// pasa la prueba de profundidad, así que un cuerpo más cercano lo tapa. El alto
+// `height` está en unidades de la escena y se convierte a una escala entera de la
+// fuente (entre 1 y MAX_SCALE) según la distancia.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Billboard {
+    pub anchor: Vec3,
+    pub text: String,
+    pub color: u32,
+    pub height: f32,
+}
+
+impl Billboard {
+    pub fn new(anchor: Vec3, text: impl Into<String>, color: u32, height: f32) -> Self {
+        Billboard { anchor, text: text.into(), color, height }
+    }
+
+    // `uniforms` con matriz de modelo identidad (ver overlay_uniforms en main)
+    pub fn draw(&self, framebuffer: &mut Framebuffer, uniforms: &Uniforms) {
+        let clip = uniforms.projection_matrix * uniforms.view_matrix * Vec4::new(self.anchor.x, self.anchor.y, self.anchor.z, 1.0);
+        if clip.w <= 0.0 {
+            return;
+        }
+        let ndc = clip.xyz() / clip.w;
+        if !(-1.0..=1.0).contains(&ndc.z) {
+            return;
+        }
+        let screen = uniforms.viewport_matrix * Vec4::new(ndc.x, ndc.y, ndc.z, 1.0);
+
+        let pixels = uniforms.projection_matrix[(1, 1)] * self.height / clip.w * framebuffer.height as f32 / 2.0;
+        let scale = (pixels / text::TEXT_HEIGHT as f32).round().clamp(1.0, MAX_SCALE) as usize;
+        // Centrado sobre el ancla, con la base del texto en ella
+        let left = screen.x as isize - (text::text_width(&self.text, scale) / 2) as isize;
+        let top = screen.y as isize - (text::TEXT_HEIGHT * scale) as isize;
+        let depth = ndc.z;
+
+        for (offset, color) in [(scale as isize, 0x000000), (0, self.color)] {
+            text::for_each_dot(&self.text, scale, |dx, dy| {
+                for py in 0..scale {
+                    for px in 0..scale {
+                        let x = left + offset + (dx + px) as isize;
+                        let y = top + offset + (dy + py) as isize;
+                        if x >= 0 && y >= 0 {
+                            framebuffer.depth_tested_pixel(x as usize, y as usize, depth, color);
+                        }
+                    }
+                }
+            });
+        }
+    }
+}
+
+// Nombre de cada cuerpo sobre su borde superior, visto desde la cámara
+pub fn body_labels(system: &SolarSystem, origin: DVec3, uniforms: &Uniforms) -> Vec<Billboard> {
+    let view = &uniforms.view_matrix;
+    let up = Vec3::new(view[(1, 0)], view[(1, 1)], view[(1, 2)]);
+    system
+        .bodies()
+        .iter()
+        .map(|body| {
+            let radius = body.radius as f32;
+            let anchor = solar_system::to_render(body.position, origin) + up * radius * LABEL_OFFSET;
+            Billboard::new(anchor, solar_system::display_name(body.name), LABEL_COLOR, radius * LABEL_HEIGHT)
+        })
+        .collect()
+}
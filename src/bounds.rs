@@ -0,0 +1,90 @@
+use nalgebra_glm::{Mat4, Vec3, Vec4};
+
+// Volúmenes envolventes de un mesh, calculados al cargarlo. Sirven para
+// descartar objetos fuera de cámara, selección y colisiones.
+#[derive(Debug, Clone, Copy)]
+pub struct BoundingSphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn from_points<'a>(points: impl Iterator<Item = &'a Vec3>) -> Self {
+        let mut min = Vec3::repeat(f32::INFINITY);
+        let mut max = Vec3::repeat(f32::NEG_INFINITY);
+        for point in points {
+            min = min.inf(point);
+            max = max.sup(point);
+        }
+        if min.x.is_finite() {
+            Aabb { min, max }
+        } else {
+            Aabb { min: Vec3::zeros(), max: Vec3::zeros() }
+        }
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn corners(&self) -> [Vec3; 8] {
+        let (a, b) = (self.min, self.max);
+        [
+            Vec3::new(a.x, a.y, a.z),
+            Vec3::new(b.x, a.y, a.z),
+            Vec3::new(b.x, b.y, a.z),
+            Vec3::new(a.x, b.y, a.z),
+            Vec3::new(a.x, a.y, b.z),
+            Vec3::new(b.x, a.y, b.z),
+            Vec3::new(b.x, b.y, b.z),
+            Vec3::new(a.x, b.y, b.z),
+        ]
+    }
+
+    // Aristas de la caja como pares de índices de `corners()`
+    pub const EDGES: [(usize, usize); 12] = [
+        (0, 1), (1, 2), (2, 3), (3, 0),
+        (4, 5), (5, 6), (6, 7), (7, 4),
+        (0, 4), (1, 5), (2, 6), (3, 7),
+    ];
+}
+
+impl BoundingSphere {
+    // Esfera centrada en la caja envolvente que contiene todos los puntos
+    pub fn from_points<'a>(points: impl Iterator<Item = &'a Vec3> + Clone) -> Self {
+        let center = Aabb::from_points(points.clone()).center();
+        let radius = points.map(|p| (p - center).magnitude()).fold(0.0, f32::max);
+        BoundingSphere { center, radius }
+    }
+
+    // Esfera en espacio de mundo; el radio usa la mayor escala de la matriz
+    pub fn transformed(&self, model_matrix: &Mat4) -> Self {
+        let center = model_matrix * Vec4::new(self.center.x, self.center.y, self.center.z, 1.0);
+        let scale = (0..3)
+            .map(|axis| model_matrix.fixed_view::<3, 1>(0, axis).magnitude())
+            .fold(0.0, f32::max);
+        BoundingSphere {
+            center: Vec3::new(center.x, center.y, center.z),
+            radius: self.radius * scale,
+        }
+    }
+
+    // Si la esfera (en espacio de mundo) toca el volumen de vista de proyección ×
+    // vista. Los planos salen de las filas de la matriz (Gribb-Hartmann): la fila 4
+    // más o menos una de las otras. Queda fuera si está por completo del lado
+    // negativo de alguno.
+    pub fn in_frustum(&self, view_projection: &Mat4) -> bool {
+        let center = Vec4::new(self.center.x, self.center.y, self.center.z, 1.0);
+        let w = view_projection.row(3);
+        !(0..3).flat_map(|axis| [w + view_projection.row(axis), w - view_projection.row(axis)]).any(|plane| {
+            let normal = plane.fixed_columns::<3>(0).norm();
+            normal > 0.0 && plane.dot(&center.transpose()) / normal < -self.radius
+        })
+    }
+}
@@ -1,7 +1,42 @@
 
-use nalgebra_glm::{Vec3, rotate_vec3};
+use nalgebra_glm::{Vec3, Mat4, look_at, quat_angle_axis, quat_rotate_vec3};
 use std::f32::consts::PI;
 
+// Qué mueve la cámara: las teclas (orbitando un punto o volando por su cuenta), o
+// la nave del jugador, desde atrás o desde la cabina
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CameraMode {
+  Orbit,
+  Fly,
+  Chase,
+  Cockpit,
+}
+
+impl CameraMode {
+  pub fn next(self) -> Self {
+    match self {
+      CameraMode::Orbit => CameraMode::Fly,
+      CameraMode::Fly => CameraMode::Chase,
+      CameraMode::Chase => CameraMode::Cockpit,
+      CameraMode::Cockpit => CameraMode::Orbit,
+    }
+  }
+
+  // Las que siguen a la nave del jugador y le pasan las teclas
+  pub fn follows_ship(self) -> bool {
+    matches!(self, CameraMode::Chase | CameraMode::Cockpit)
+  }
+
+  pub fn label(self) -> &'static str {
+    match self {
+      CameraMode::Orbit => "libre",
+      CameraMode::Fly => "vuelo libre",
+      CameraMode::Chase => "persecución",
+      CameraMode::Cockpit => "cabina",
+    }
+  }
+}
+
 pub struct Camera {
   pub eye: Vec3,
   pub center: Vec3,
@@ -19,38 +54,47 @@ impl Camera {
     }
   }
 
-  pub fn basis_change(&self, vector: &Vec3) -> Vec3 {
-    let forward = (self.center - self.eye).normalize();
-    let right = forward.cross(&self.up).normalize();
-    let up = right.cross(&forward).normalize();
-
-    let rotated = 
-    vector.x * right +
-    vector.y * up +
-    - vector.z * forward;
-
-    rotated.normalize()
+  // Origen flotante: el render trabaja relativo a la cámara para que la precisión
+  // de f32 se concentre cerca del observador aunque la escena sea enorme
+  pub fn render_origin(&self) -> Vec3 {
+    self.eye
   }
 
-  pub fn orbit(&mut self, delta_yaw: f32, delta_pitch: f32) {
-    let radius_vector = self.eye - self.center;
-    let radius = radius_vector.magnitude();
-
-    let current_yaw = radius_vector.z.atan2(radius_vector.x);
+  // Matriz de vista con el ojo en el origen de render
+  pub fn relative_view_matrix(&self) -> Mat4 {
+    let origin = self.render_origin();
+    Camera::new(self.eye - origin, self.center - origin, self.up).view_matrix()
+  }
 
-    let radius_xz = (radius_vector.x * radius_vector.x + radius_vector.z * radius_vector.z).sqrt();
-    let current_pitch = (-radius_vector.y).atan2(radius_xz);
+  // Matriz de vista en coordenadas del mundo
+  pub fn view_matrix(&self) -> Mat4 {
+    look_at(&self.eye, &self.center, &self.up)
+  }
 
-    let new_yaw = (current_yaw + delta_yaw) % (2.0 * PI);
-    let new_pitch = (current_pitch + delta_pitch).clamp(-PI / 2.0 + 0.1, PI / 2.0 - 0.1);
+  // Ejes de la vista: hacia dónde mira, su derecha y su arriba, ortonormales
+  pub fn axes(&self) -> (Vec3, Vec3, Vec3) {
+    let forward = (self.center - self.eye).normalize();
+    let right = forward.cross(&self.up).normalize();
+    (forward, right, right.cross(&forward))
+  }
 
-    let new_eye = self.center + Vec3::new(
-      radius * new_yaw.cos() * new_pitch.cos(),
-      -radius * new_pitch.sin(),
-      radius * new_yaw.sin() * new_pitch.cos()
-    );
+  // Los giros se hacen con cuaterniones alrededor de los ejes de la propia cámara y
+  // el vector arriba gira con ella: no hay polos donde la vista se dé vuelta de
+  // golpe, pasar por encima del punto de enfoque deja la escena cabeza abajo.
+  // `delta_yaw` positivo lleva el ojo hacia la izquierda y `delta_pitch` hacia abajo.
+  pub fn orbit(&mut self, delta_yaw: f32, delta_pitch: f32) {
+    let (_, right, up) = self.axes();
+    let rotation = quat_angle_axis(delta_pitch, &right) * quat_angle_axis(-delta_yaw, &up);
+    self.eye = self.center + quat_rotate_vec3(&rotation, &(self.eye - self.center));
+    self.up = quat_rotate_vec3(&rotation, &up);
+    self.has_changed = true;
+  }
 
-    self.eye = new_eye;
+  // Gira la cámara alrededor de la dirección en que mira; positivo la inclina hacia
+  // la derecha (la escena gira al revés)
+  pub fn roll(&mut self, angle: f32) {
+    let (forward, _, up) = self.axes();
+    self.up = quat_rotate_vec3(&quat_angle_axis(angle, &forward), &up);
     self.has_changed = true;
   }
 
@@ -60,28 +104,83 @@ impl Camera {
     self.has_changed = true;
   }
 
-  pub fn move_center(&mut self, direction: Vec3) {
-    let radius_vector = self.center - self.eye;
-    let radius = radius_vector.magnitude();
+  // Gira la mirada sin mover el ojo, alrededor de los ejes de la cámara: `delta_yaw`
+  // positivo mira a la derecha y `delta_pitch` positivo hacia arriba
+  pub fn look(&mut self, delta_yaw: f32, delta_pitch: f32) {
+    let (_, right, up) = self.axes();
+    let rotation = quat_angle_axis(delta_pitch, &right) * quat_angle_axis(-delta_yaw, &up);
+    self.center = self.eye + quat_rotate_vec3(&rotation, &(self.center - self.eye));
+    self.up = quat_rotate_vec3(&rotation, &up);
+    self.has_changed = true;
+  }
 
+  // `direction.x` hacia los costados (positivo a la izquierda) y `direction.y` hacia arriba
+  pub fn move_center(&mut self, direction: Vec3) {
     let angle_x = direction.x * 0.05; // Adjust this factor to control rotation speed
     let angle_y = direction.y * 0.05;
+    self.look(-angle_x, angle_y);
+  }
 
-    let rotated = rotate_vec3(&radius_vector, angle_x, &Vec3::new(0.0, 1.0, 0.0));
-
-    let right = rotated.cross(&self.up).normalize();
-    let final_rotated = rotate_vec3(&rotated, angle_y, &right);
+  // Mueve el ojo y el punto de mira juntos
+  pub fn translate(&mut self, offset: Vec3) {
+    self.eye += offset;
+    self.center += offset;
+    self.has_changed = true;
+  }
 
-    self.center = self.eye + final_rotated.normalize() * radius;
+  // Se acerca a `target` una fracción `smoothing` (0 a 1) de lo que le falta en cada
+  // llamada: sigue a un objeto con algo de retraso, sin saltos
+  pub fn follow(&mut self, target: &Camera, smoothing: f32) {
+    self.eye += (target.eye - self.eye) * smoothing;
+    self.center += (target.center - self.center) * smoothing;
+    self.up = (self.up + (target.up - self.up) * smoothing).normalize();
     self.has_changed = true;
   }
+}
 
-  pub fn check_if_changed(&mut self) -> bool {
-    if self.has_changed {
-      self.has_changed = false;
-      true
-    } else {
-      false
-    }
+// Avance de una transición de 0 a 1: sale y llega despacio (medio coseno)
+fn ease_in_out(t: f32) -> f32 {
+  0.5 - 0.5 * (t.clamp(0.0, 1.0) * PI).cos()
+}
+
+// Transición de la cámara hacia otra en `duration` frames: el ojo y el punto de
+// mira se interpolan en línea recta y el vector arriba gira sobre la esfera (slerp),
+// así la vista nunca salta al cambiar de modo. El destino se pasa en cada update y
+// puede moverse (un planeta en su órbita, la nave).
+#[derive(Debug, Clone)]
+pub struct CameraAnimator {
+  eye: Vec3,
+  center: Vec3,
+  up: Vec3,
+  duration: f32,
+  elapsed: f32,
+}
+
+impl CameraAnimator {
+  pub fn new(from: &Camera, duration: f32) -> Self {
+    CameraAnimator { eye: from.eye, center: from.center, up: from.up, duration: duration.max(1.0), elapsed: 0.0 }
+  }
+
+  // Avanza `dt` frames y deja `camera` en el punto de la transición hacia `target`;
+  // devuelve true al terminar, con la cámara ya igual a `target`
+  pub fn update(&mut self, camera: &mut Camera, target: &Camera, dt: f32) -> bool {
+    self.elapsed += dt;
+    let t = ease_in_out(self.elapsed / self.duration);
+    camera.eye = self.eye + (target.eye - self.eye) * t;
+    camera.center = self.center + (target.center - self.center) * t;
+    camera.up = slerp(&self.up, &target.up, t);
+    camera.has_changed = true;
+    self.elapsed >= self.duration
+  }
+}
+
+// Entre dos direcciones a velocidad angular constante; casi iguales u opuestas, en
+// línea recta
+fn slerp(from: &Vec3, to: &Vec3, t: f32) -> Vec3 {
+  let (from, to) = (from.normalize(), to.normalize());
+  let angle = from.dot(&to).clamp(-1.0, 1.0).acos();
+  if angle < 1e-4 || (PI - angle) < 1e-4 {
+    return (from + (to - from) * t).try_normalize(f32::EPSILON).unwrap_or(to);
   }
+  (from * ((1.0 - t) * angle).sin() + to * (t * angle).sin()) / angle.sin()
 }
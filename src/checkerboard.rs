@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use nalgebra_glm::{Vec2, Vec3};
+use crate::debug_draw;
+use crate::framebuffer::Framebuffer;
+use crate::memory::{vec_bytes, MemoryUsage};
+use crate::render_queue::DrawCall;
+
+// Renderizado en tablero de ajedrez: cada frame se sombrea solo la mitad de los
+// pixeles (los de una paridad, alternando) y la otra mitad se reconstruye con el
+// frame anterior, desplazado según la velocidad en pantalla de cada objeto. Si el
+// pixel reproyectado pertenecía a otro objeto (desoclusión), se promedian los
+// vecinos sombreados. Cuesta la mitad de fragmentos a cambio de algo de estela.
+#[derive(Default)]
+pub struct Checkerboard {
+    pub enabled: bool,
+    parity: usize,
+    // Color (HDR) e IDs del frame anterior ya reconstruido (sin overlays ni HUD)
+    history: Vec<[f32; 3]>,
+    history_ids: Vec<u32>,
+    // Centro en pantalla de cada objeto en el frame anterior, por cuerpo y aparición
+    previous_centers: HashMap<(&'static str, usize), Vec2>,
+}
+
+impl Checkerboard {
+    pub fn new(enabled: bool) -> Self {
+        Checkerboard { enabled, ..Default::default() }
+    }
+
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+        if !self.enabled {
+            self.history.clear();
+            self.history_ids.clear();
+            self.previous_centers.clear();
+        }
+    }
+
+    // Elige la paridad del frame; el framebuffer descarta los fragmentos de la otra
+    pub fn begin_frame(&mut self, framebuffer: &mut Framebuffer) {
+        self.parity ^= 1;
+        framebuffer.checkerboard = self.enabled.then_some(self.parity);
+    }
+
+    // Velocidad en pantalla (pixeles por frame) de cada llamada, en el orden de los
+    // IDs de objeto (ID - 1)
+    pub fn object_velocities(&mut self, calls: &[DrawCall]) -> Vec<Vec2> {
+        if !self.enabled {
+            return Vec::new();
+        }
+        let mut seen: HashMap<&'static str, usize> = HashMap::new();
+        let mut centers = HashMap::with_capacity(calls.len());
+        let velocities = calls
+            .iter()
+            .map(|call| {
+                let occurrence = seen.entry(call.body).or_default();
+                let key = (call.body, *occurrence);
+                *occurrence += 1;
+                let Some(center) = debug_draw::project(&call.uniforms, Vec3::zeros()) else {
+                    return Vec2::zeros();
+                };
+                centers.insert(key, center);
+                self.previous_centers.get(&key).map_or(Vec2::zeros(), |previous| center - previous)
+            })
+            .collect();
+        self.previous_centers = centers;
+        velocities
+    }
+
+    // Completa los pixeles que no se sombrearon este frame y guarda el resultado
+    // como historia del siguiente
+    pub fn reconstruct(&mut self, framebuffer: &mut Framebuffer, velocities: &[Vec2]) {
+        let Some(parity) = framebuffer.checkerboard else {
+            return;
+        };
+        let (width, height) = (framebuffer.width, framebuffer.height);
+        let has_history = self.history.len() == framebuffer.hdr.len();
+
+        for y in 0..height {
+            for x in 0..width {
+                if (x + y + parity) & 1 == 0 {
+                    continue;
+                }
+                let index = y * width + x;
+
+                // Los cuatro vecinos en cruz sí se sombrearon; el más cercano decide
+                let neighbors = [
+                    (x > 0).then(|| index - 1),
+                    (x + 1 < width).then_some(index + 1),
+                    (y > 0).then(|| index - width),
+                    (y + 1 < height).then_some(index + width),
+                ];
+                let Some(nearest) = neighbors
+                    .iter()
+                    .flatten()
+                    .copied()
+                    .min_by(|a, b| framebuffer.zbuffer[*a].total_cmp(&framebuffer.zbuffer[*b]))
+                else {
+                    continue;
+                };
+                let depth = framebuffer.zbuffer[nearest];
+                // Sin superficie alrededor, o con algo dibujado delante (líneas, rastros)
+                if depth == f32::INFINITY || framebuffer.zbuffer[index] <= depth {
+                    continue;
+                }
+                let id = framebuffer.ids[nearest];
+                let same: Vec<usize> = neighbors.iter().flatten().copied().filter(|n| framebuffer.ids[*n] == id).collect();
+                // Un solo vecino es el borde de la silueta: no se engorda el objeto
+                if same.len() < 2 {
+                    continue;
+                }
+
+                let velocity = (id as usize).checked_sub(1).and_then(|i| velocities.get(i)).copied().unwrap_or_else(Vec2::zeros);
+                let source_x = (x as f32 - velocity.x).round();
+                let source_y = (y as f32 - velocity.y).round();
+                let reprojected = (has_history && source_x >= 0.0 && source_y >= 0.0)
+                    .then_some((source_x as usize, source_y as usize))
+                    .filter(|(sx, sy)| *sx < width && *sy < height)
+                    .map(|(sx, sy)| sy * width + sx)
+                    .filter(|source| self.history_ids[*source] == id)
+                    .map(|source| self.history[source]);
+
+                framebuffer.hdr[index] = reprojected.unwrap_or_else(|| average(framebuffer, &same));
+                framebuffer.zbuffer[index] = depth;
+                framebuffer.ids[index] = id;
+            }
+        }
+
+        self.history.clone_from(&framebuffer.hdr);
+        self.history_ids.clone_from(&framebuffer.ids);
+    }
+}
+
+impl MemoryUsage for Checkerboard {
+    fn memory_bytes(&self) -> usize {
+        vec_bytes(&self.history) + vec_bytes(&self.history_ids)
+    }
+}
+
+fn average(framebuffer: &Framebuffer, indices: &[usize]) -> [f32; 3] {
+    let mut sum = [0.0; 3];
+    for index in indices {
+        let color = framebuffer.hdr[*index];
+        (0..3).for_each(|channel| sum[channel] += color[channel]);
+    }
+    let count = indices.len().max(1) as f32;
+    sum.map(|channel| channel / count)
+}
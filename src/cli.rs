@@ -0,0 +1,198 @@
+use std::env;
+use crate::pacing::FramePacing;
+
+// Opciones de línea de comandos
+//
+//   --stats-file <ruta>    escribe estadísticas por frame (JSON lines) en un archivo
+//   --stats-port <puerto>  publica las mismas líneas en 127.0.0.1:<puerto> (TCP)
+//   --compare <a> <b>      compara dos capturas PNG y termina (sin abrir ventana)
+//   --diff-out <ruta>      mapa de calor de --compare (por defecto diff.png)
+//   --scene-diff <a> <b>   cambios entre dos archivos de escena y termina
+//   --scene-merge <base> <nuestra> <suya>  mezcla de tres vías de escenas y termina
+//   --merge-out <ruta>     salida de --scene-merge (por defecto scene.merged.toml)
+//   --phase-test [grados]  escena fija para validar las fases de la Luna (90 por defecto)
+//   --traffic <n>          naves de tráfico en vuelo a la vez (0 lo desactiva)
+//   --export-aux           las capturas (F12) incluyen profundidad e IDs de objeto
+//   --deferred             sombreado diferido (requiere compilar con la feature "deferred")
+//   --deferred-parity      compara un frame forward contra el diferido y termina
+//   --system <ruta>        lee el sistema (cuerpos, órbitas, shaders y cámara) de un archivo TOML
+//   --stress <n>           agrega n cuerpos generados (planetas y lunas) para medir cómo escala el render
+//   --roche                escenario: un cometa se rompe al cruzar el límite de Roche de Júpiter
+//   --foveated             periferia a media resolución aunque [render] no lo active
+//   --tiled                rasterización por bloques en paralelo aunque [render] no la active
+//   --ssaa <n>             supermuestreo con n muestras por eje (1, 2 o 4), en lugar de [render]
+//   --fps <modo>           ritmo de frames: 30, 60, 120, uncapped o display
+//   --benchmark [frames]   corre esa cantidad de frames (300 por defecto), resume tiempos y termina
+//   --record <carpeta>     registra posiciones y velocidades de cada paso en CSV (ver recorder.rs)
+#[derive(Debug)]
+pub struct Options {
+    pub stats_file: Option<String>,
+    pub stats_port: Option<u16>,
+    pub compare: Option<(String, String)>,
+    pub diff_output: Option<String>,
+    pub scene_diff: Option<(String, String)>,
+    pub scene_merge: Option<(String, String, String)>,
+    pub merge_output: Option<String>,
+    pub phase_test: Option<f32>,
+    pub traffic: usize,
+    pub system: Option<String>,
+    pub stress: Option<usize>,
+    pub roche: bool,
+    pub export_aux: bool,
+    pub deferred: bool,
+    pub deferred_parity: bool,
+    pub foveated: bool,
+    pub tiled: bool,
+    pub ssaa: Option<usize>,
+    pub benchmark: Option<usize>,
+    pub frame_pacing: Option<FramePacing>,
+    pub record: Option<String>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            stats_file: None,
+            stats_port: None,
+            compare: None,
+            diff_output: None,
+            scene_diff: None,
+            scene_merge: None,
+            merge_output: None,
+            phase_test: None,
+            traffic: 6,
+            system: None,
+            stress: None,
+            roche: false,
+            export_aux: false,
+            deferred: false,
+            deferred_parity: false,
+            foveated: false,
+            tiled: false,
+            ssaa: None,
+            benchmark: None,
+            frame_pacing: None,
+            record: None,
+        }
+    }
+}
+
+impl Options {
+    pub fn from_env() -> Self {
+        Options::parse(env::args().skip(1))
+    }
+
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Self {
+        let mut options = Options::default();
+        let mut args = args.into_iter().peekable();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--stats-file" => options.stats_file = args.next(),
+                "--stats-port" => {
+                    options.stats_port = args.next().and_then(|port| port.parse().ok());
+                    if options.stats_port.is_none() {
+                        eprintln!("--stats-port requiere un número de puerto válido");
+                    }
+                }
+                "--compare" => match (args.next(), args.next()) {
+                    (Some(a), Some(b)) => options.compare = Some((a, b)),
+                    _ => eprintln!("--compare requiere dos rutas de imagen"),
+                },
+                "--diff-out" => options.diff_output = args.next(),
+                "--scene-diff" => match (args.next(), args.next()) {
+                    (Some(a), Some(b)) => options.scene_diff = Some((a, b)),
+                    _ => eprintln!("--scene-diff requiere dos archivos de escena"),
+                },
+                "--scene-merge" => match (args.next(), args.next(), args.next()) {
+                    (Some(base), Some(ours), Some(theirs)) => options.scene_merge = Some((base, ours, theirs)),
+                    _ => eprintln!("--scene-merge requiere la escena base, la nuestra y la suya"),
+                },
+                "--merge-out" => options.merge_output = args.next(),
+                "--phase-test" => {
+                    // El ángulo es opcional: solo se consume si el siguiente argumento es un número
+                    let angle = args.peek().and_then(|value| value.parse::<f32>().ok());
+                    if angle.is_some() {
+                        args.next();
+                    }
+                    options.phase_test = Some(angle.unwrap_or(90.0));
+                }
+                "--traffic" => match args.next().and_then(|n| n.parse().ok()) {
+                    Some(count) => options.traffic = count,
+                    None => eprintln!("--traffic requiere un número de naves"),
+                },
+                "--system" => match args.next() {
+                    Some(path) => options.system = Some(path),
+                    None => eprintln!("--system requiere la ruta de un archivo de sistema"),
+                },
+                "--stress" => match args.next().and_then(|n| n.parse().ok()) {
+                    Some(count) => options.stress = Some(count),
+                    None => eprintln!("--stress requiere un número de cuerpos"),
+                },
+                "--roche" => options.roche = true,
+                "--export-aux" => options.export_aux = true,
+                "--deferred" => options.deferred = true,
+                "--deferred-parity" => options.deferred_parity = true,
+                "--foveated" => options.foveated = true,
+                "--tiled" => options.tiled = true,
+                "--ssaa" => match args.next().and_then(|n| n.parse().ok()).filter(|n| [1, 2, 4].contains(n)) {
+                    Some(factor) => options.ssaa = Some(factor),
+                    None => eprintln!("--ssaa requiere 1, 2 o 4"),
+                },
+                "--fps" => match args.next().as_deref().and_then(FramePacing::parse) {
+                    Some(pacing) => options.frame_pacing = Some(pacing),
+                    None => eprintln!("--fps requiere 30, 60, 120, uncapped o display"),
+                },
+                "--benchmark" => {
+                    let frames = args.peek().and_then(|value| value.parse::<usize>().ok());
+                    if frames.is_some() {
+                        args.next();
+                    }
+                    options.benchmark = Some(frames.unwrap_or(300).max(1));
+                }
+                "--record" => match args.next() {
+                    Some(directory) => options.record = Some(directory),
+                    None => eprintln!("--record requiere una carpeta de salida"),
+                },
+                "-h" | "--help" => {
+                    print_usage();
+                    std::process::exit(0);
+                }
+                other => eprintln!("Argumento desconocido ignorado: {}", other),
+            }
+        }
+
+        if (options.deferred || options.deferred_parity) && !cfg!(feature = "deferred") {
+            eprintln!("--deferred y --deferred-parity requieren compilar con --features deferred; se ignoran");
+        }
+
+        options
+    }
+}
+
+fn print_usage() {
+    println!("Uso: Proyecto3SpaceTravel [opciones]");
+    println!();
+    println!("  --stats-file <ruta>    estadísticas por frame en formato JSON lines");
+    println!("  --stats-port <puerto>  estadísticas por TCP en 127.0.0.1:<puerto>");
+    println!("  --compare <a> <b>      compara dos capturas PNG (PSNR, SSIM, mapa de calor)");
+    println!("  --diff-out <ruta>      salida del mapa de calor de --compare (diff.png)");
+    println!("  --scene-diff <a> <b>   cuerpos agregados o quitados y parámetros cambiados entre dos escenas");
+    println!("  --scene-merge <base> <nuestra> <suya>");
+    println!("                         aplica sobre la nuestra los cambios de la suya que no chocan");
+    println!("  --merge-out <ruta>     salida de --scene-merge (scene.merged.toml)");
+    println!("  --phase-test [grados]  cámara fija frente a la Luna con ese ángulo de fase");
+    println!("  --traffic <n>          naves de tráfico en vuelo a la vez (6 por defecto, 0 las desactiva)");
+    println!("  --export-aux           las capturas (F12) incluyen profundidad lineal e IDs de objeto");
+    println!("  --deferred             sombreado diferido experimental (feature \"deferred\")");
+    println!("  --deferred-parity      compara un frame forward contra el diferido (sale con 0 si coinciden)");
+    println!("  --system <ruta>        sistema propio: cuerpos, órbitas, escalas, shaders y cámara (ver assets/system.example.toml)");
+    println!("  --stress <n>           n cuerpos generados con lunas; con --benchmark compara cuánto cuesta cada cantidad");
+    println!("  --roche                cometa que se rompe al cruzar el límite de Roche de Júpiter");
+    println!("  --foveated             periferia a media resolución (ver [render] en assets/scene.toml)");
+    println!("  --tiled                pantalla en bloques de 64x64 sombreados en paralelo");
+    println!("  --ssaa <n>             supermuestreo: n x n muestras por pixel (1, 2 o 4; también con F8)");
+    println!("  --fps <modo>           ritmo de frames: 30, 60, 120, uncapped (sin límite) o display (refresco)");
+    println!("  --benchmark [frames]   mide esa cantidad de frames sin esperas (300 por defecto) y termina");
+    println!("  --record <carpeta>     guarda bodies.csv y ships.csv con la simulación paso a paso");
+}
@@ -0,0 +1,77 @@
+use nalgebra_glm::{Mat4, Vec4};
+use crate::varyings::Varyings;
+use crate::vertex::Vertex;
+
+// Planos del volumen de vista en coordenadas de recorte: un punto está adentro si
+// queda del lado positivo de los seis (-w <= x, y, z <= w). El cercano va primero:
+// es el que evita dividir por una w casi nula o negativa.
+const PLANES: [Vec4; 6] = [
+    Vec4::new(0.0, 0.0, 1.0, 1.0),
+    Vec4::new(0.0, 0.0, -1.0, 1.0),
+    Vec4::new(1.0, 0.0, 0.0, 1.0),
+    Vec4::new(-1.0, 0.0, 0.0, 1.0),
+    Vec4::new(0.0, 1.0, 0.0, 1.0),
+    Vec4::new(0.0, -1.0, 0.0, 1.0),
+];
+
+// Recorta un triángulo contra el volumen de vista (Sutherland-Hodgman, un plano a
+// la vez) antes de la división de perspectiva y lo rearma en abanico. Los vértices
+// nuevos interpolan todos sus atributos sobre la arista en espacio de recorte y
+// se proyectan a pantalla con `viewport`. Lo que está adentro por completo sale
+// intacto y lo que queda afuera de un mismo plano, descartado.
+pub fn clip_triangle<V: Varyings>(tri: [Vertex<V>; 3], viewport: &Mat4) -> Vec<[Vertex<V>; 3]> {
+    let distances = |vertex: &Vertex<V>| PLANES.map(|plane| plane.dot(&vertex.clip_position));
+    let [d0, d1, d2] = [distances(&tri[0]), distances(&tri[1]), distances(&tri[2])];
+    if (0..PLANES.len()).all(|i| d0[i] >= 0.0 && d1[i] >= 0.0 && d2[i] >= 0.0) {
+        return vec![tri];
+    }
+    if (0..PLANES.len()).any(|i| d0[i] < 0.0 && d1[i] < 0.0 && d2[i] < 0.0) {
+        return Vec::new();
+    }
+
+    let mut polygon: Vec<Vertex<V>> = tri.into();
+    for plane in PLANES {
+        let input = std::mem::take(&mut polygon);
+        for (i, current) in input.iter().enumerate() {
+            let next = &input[(i + 1) % input.len()];
+            let (from, to) = (plane.dot(&current.clip_position), plane.dot(&next.clip_position));
+            if from >= 0.0 {
+                polygon.push(current.clone());
+            }
+            if (from >= 0.0) != (to >= 0.0) {
+                polygon.push(project(lerp(current, next, from / (from - to)), viewport));
+            }
+        }
+        if polygon.len() < 3 {
+            return Vec::new();
+        }
+    }
+
+    (1..polygon.len() - 1)
+        .map(|i| [polygon[0].clone(), polygon[i].clone(), polygon[i + 1].clone()])
+        .collect()
+}
+
+// Punto de la arista a→b en la fracción `t`
+fn lerp<V: Varyings>(a: &Vertex<V>, b: &Vertex<V>, t: f32) -> Vertex<V> {
+    let (s, t) = (1.0 - t, t);
+    Vertex {
+        position: a.position * s + b.position * t,
+        normal: a.normal * s + b.normal * t,
+        tex_coords: a.tex_coords * s + b.tex_coords * t,
+        color: Varyings::interpolate(&a.color, &b.color, &a.color, s, t, 0.0),
+        transformed_position: a.transformed_position,
+        transformed_normal: a.transformed_normal * s + b.transformed_normal * t,
+        world_position: a.world_position * s + b.world_position * t,
+        clip_position: a.clip_position * s + b.clip_position * t,
+        varyings: V::interpolate(&a.varyings, &b.varyings, &a.varyings, s, t, 0.0),
+    }
+}
+
+// División de perspectiva y viewport, como al final de vertex_shader
+fn project<V>(mut vertex: Vertex<V>, viewport: &Mat4) -> Vertex<V> {
+    let clip = vertex.clip_position;
+    let ndc = Vec4::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+    vertex.transformed_position = (viewport * ndc).xyz();
+    vertex
+}
@@ -0,0 +1,66 @@
+// Multiplicadores de la velocidad de la simulación que recorren , y .
+pub const SPEEDS: [f64; 9] = [0.1, 0.25, 0.5, 1.0, 2.0, 5.0, 10.0, 100.0, 1000.0];
+const NORMAL_SPEED: usize = 3;
+
+// Reloj de la simulación: cuánto avanza por frame (en unidades de tiempo del
+// sistema, una por frame a x1) y el tiempo de las animaciones de los shaders, que
+// lo sigue. En pausa no avanza nada salvo un paso suelto cuando se pide.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimulationClock {
+    speed: usize,
+    pub paused: bool,
+    step_requested: bool,
+    // Tiempo acumulado desde el arranque; lo leen los shaders como `uniforms.time`
+    pub time: f64,
+}
+
+impl Default for SimulationClock {
+    fn default() -> Self {
+        SimulationClock { speed: NORMAL_SPEED, paused: false, step_requested: false, time: 0.0 }
+    }
+}
+
+impl SimulationClock {
+    pub fn speed(&self) -> f64 {
+        SPEEDS[self.speed]
+    }
+
+    pub fn faster(&mut self) {
+        self.speed = (self.speed + 1).min(SPEEDS.len() - 1);
+    }
+
+    pub fn slower(&mut self) {
+        self.speed = self.speed.saturating_sub(1);
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    // Un frame a x1 en el próximo `advance`; solo con la pausa puesta
+    pub fn step(&mut self) {
+        self.step_requested = self.paused;
+    }
+
+    // Lo que avanza la simulación en este frame; 0 en pausa
+    pub fn advance(&mut self) -> f64 {
+        let dt = match (self.paused, std::mem::take(&mut self.step_requested)) {
+            (false, _) => self.speed(),
+            (true, true) => 1.0,
+            (true, false) => 0.0,
+        };
+        self.time += dt;
+        dt
+    }
+
+    // Para el HUD; nada si corre a x1
+    pub fn hud_line(&self) -> Option<String> {
+        if self.paused {
+            Some(String::from("Simulación en pausa (P: continúa, ;: avanza un paso)"))
+        } else if self.speed != NORMAL_SPEED {
+            Some(format!("Simulación a x{}", self.speed()))
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use nalgebra_glm::DVec3;
+use crate::solar_system::SolarSystem;
+
+// Aceleración para alejarse de los cuerpos cercanos. Cada cuerpo empuja en
+// dirección opuesta a su centro cuando la distancia entre superficies baja de
+// `margin`, con más fuerza cuanto más cerca.
+pub fn avoidance(system: &SolarSystem, position: DVec3, radius: f64, margin: f64, ignore: &[&str]) -> DVec3 {
+    let mut push = DVec3::zeros();
+    for body in system.bodies() {
+        if ignore.contains(&body.name) {
+            continue;
+        }
+        let offset = position - body.position;
+        let distance = offset.magnitude();
+        let gap = distance - body.radius - radius;
+        if gap < margin && distance > 0.0 {
+            let strength = (1.0 - gap.max(0.0) / margin).min(1.0);
+            push += offset / distance * strength;
+        }
+    }
+    push
+}
+
+// Si dos esferas se tocan
+pub fn intersects(a: DVec3, radius_a: f64, b: DVec3, radius_b: f64) -> bool {
+    (a - b).magnitude() < radius_a + radius_b
+}
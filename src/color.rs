@@ -1,6 +1,7 @@
 use std::fmt;
+use std::sync::LazyLock;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Color {
     r: u8,
     g: u8,
@@ -8,6 +9,9 @@ pub struct Color {
 }
 
 impl Color {
+    pub const BLACK: Color = Color::new(0, 0, 0);
+    pub const WHITE: Color = Color::new(255, 255, 255);
+
     pub const fn new(r: u8, g: u8, b: u8) -> Self {
         Color { r, g, b }
     }
@@ -20,14 +24,40 @@ impl Color {
     }
 
     pub const fn black() -> Self {
-        Color { r: 0, g: 0, b: 0 }
+        Color::BLACK
+    }
+
+    pub const fn r(&self) -> u8 {
+        self.r
+    }
+
+    pub const fn g(&self) -> u8 {
+        self.g
+    }
+
+    pub const fn b(&self) -> u8 {
+        self.b
+    }
+
+    // Construye un color a partir de componentes en [0, 1] (espacio sRGB)
+    pub fn from_f32(r: f32, g: f32, b: f32) -> Self {
+        Color {
+            r: (r.clamp(0.0, 1.0) * 255.0).round() as u8,
+            g: (g.clamp(0.0, 1.0) * 255.0).round() as u8,
+            b: (b.clamp(0.0, 1.0) * 255.0).round() as u8,
+        }
+    }
+
+    pub fn to_f32(self) -> (f32, f32, f32) {
+        (self.r as f32 / 255.0, self.g as f32 / 255.0, self.b as f32 / 255.0)
     }
 
-    pub fn to_hex(&self) -> u32 {
+    pub const fn to_hex(self) -> u32 {
         ((self.r as u32) << 16) | ((self.g as u32) << 8) | (self.b as u32)
     }
 
-    // Linear interpolation between two colors
+    // Linear interpolation between two colors, en sRGB: para colores de interfaz y
+    // mapas de color; los shaders usan lerp_linear
     pub fn lerp(&self, other: &Color, t: f32) -> Self {
         let t = t.clamp(0.0, 1.0);
         Color {
@@ -37,14 +67,72 @@ impl Color {
         }
     }
 
+    // Interpolación en espacio lineal: evita los tonos "sucios" de mezclar en sRGB
+    pub fn lerp_linear(&self, other: &Color, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let a = self.to_linear();
+        let b = other.to_linear();
+        Color::from_linear([
+            a[0] + (b[0] - a[0]) * t,
+            a[1] + (b[1] - a[1]) * t,
+            a[2] + (b[2] - a[2]) * t,
+        ])
+    }
+
+    // Intensidad (luz, brillo) aplicada en espacio lineal. Con `*`, que escala los
+    // valores sRGB, una superficie a media luz queda mucho más oscura de lo que es
+    pub fn scale_linear(self, factor: f32) -> Self {
+        Color::from_linear(self.to_linear().map(|channel| channel * factor))
+    }
+
+    // Suma de luz en espacio lineal; satura en blanco
+    pub fn add_linear(self, other: Color) -> Self {
+        let (a, b) = (self.to_linear(), other.to_linear());
+        Color::from_linear([a[0] + b[0], a[1] + b[1], a[2] + b[2]])
+    }
+
+    pub fn to_linear(self) -> [f32; 3] {
+        [srgb_to_linear(self.r), srgb_to_linear(self.g), srgb_to_linear(self.b)]
+    }
+
+    pub fn from_linear(rgb: [f32; 3]) -> Self {
+        Color {
+            r: linear_to_srgb(rgb[0]),
+            g: linear_to_srgb(rgb[1]),
+            b: linear_to_srgb(rgb[2]),
+        }
+    }
+
+    // Hue en grados [0, 360), saturación y valor en [0, 1]
+    pub fn to_hsv(self) -> (f32, f32, f32) {
+        let (r, g, b) = self.to_f32();
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+
+        let s = if max > 0.0 { delta / max } else { 0.0 };
+        (hue(r, g, b, max, delta), s, max)
+    }
+
+    pub fn from_hsv(h: f32, s: f32, v: f32) -> Self {
+        let s = s.clamp(0.0, 1.0);
+        let v = v.clamp(0.0, 1.0);
+        let c = v * s;
+        let (r, g, b) = hue_to_rgb(h, c);
+        let m = v - c;
+        Color::from_f32(r + m, g + m, b + m)
+    }
+
+    // Rota el tono manteniendo saturación y valor
+    pub fn shift_hue(&self, degrees: f32) -> Self {
+        let (h, s, v) = self.to_hsv();
+        Color::from_hsv(h + degrees, s, v)
+    }
+
     pub fn is_black(&self) -> bool {
-        self.r == 0 && self.g == 0 && self.b == 0 
+        self.r == 0 && self.g == 0 && self.b == 0
     }
 
-    pub fn blend_normal(&self, blend: &Color) -> Color {
-        if blend.is_black() { *self } else { *blend }
-      }
-    
     pub fn blend_multiply(&self, blend: &Color) -> Color {
         Color::new(
             ((self.r as f32 * blend.r as f32) / 255.0) as u8,
@@ -52,7 +140,8 @@ impl Color {
             ((self.b as f32 * blend.b as f32) / 255.0) as u8
         )
     }
-    
+
+    #[cfg_attr(not(feature = "deferred"), allow(dead_code))]
     pub fn blend_add(&self, blend: &Color) -> Color {
         Color::new(
             (self.r as u16 + blend.r as u16).min(255) as u8,
@@ -60,16 +149,160 @@ impl Color {
             (self.b as u16 + blend.b as u16).min(255) as u8
         )
     }
-    
-    pub fn blend_subtract(&self, blend: &Color) -> Color {
-        let r = (self.r as i16 - blend.r as i16).max(0).min(255) as u8;
-        let g = (self.g as i16 - blend.g as i16).max(0).min(255) as u8;
-        let b = (self.b as i16 - blend.b as i16).max(0).min(255) as u8;
+}
+
+fn hue(r: f32, g: f32, b: f32, max: f32, delta: f32) -> f32 {
+    if delta == 0.0 {
+        return 0.0;
+    }
+    let h = if max == r {
+        60.0 * (((g - b) / delta) % 6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    h.rem_euclid(360.0)
+}
+
+fn hue_to_rgb(h: f32, chroma: f32) -> (f32, f32, f32) {
+    let h = h.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+    match h as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    }
+}
+
+// Los 256 valores posibles en una tabla: se convierte un color por fragmento escrito
+static SRGB_TO_LINEAR: LazyLock<[f32; 256]> = LazyLock::new(|| {
+    std::array::from_fn(|value| {
+        let c = value as f32 / 255.0;
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    })
+});
+
+pub fn srgb_to_linear(value: u8) -> f32 {
+    SRGB_TO_LINEAR[value as usize]
+}
+
+// Codificación sRGB de [0, 1] en una tabla: evita un powf por canal en cada
+// fragmento iluminado y en cada pixel del mapeo de tonos
+const ENCODE_STEPS: usize = 16384;
+static LINEAR_TO_SRGB: LazyLock<Vec<u8>> = LazyLock::new(|| {
+    (0..ENCODE_STEPS)
+        .map(|step| {
+            let c = step as f32 / (ENCODE_STEPS - 1) as f32;
+            let s = if c <= 0.003_130_8 {
+                c * 12.92
+            } else {
+                1.055 * c.powf(1.0 / 2.4) - 0.055
+            };
+            (s * 255.0).round() as u8
+        })
+        .collect()
+});
+
+pub fn linear_to_srgb(value: f32) -> u8 {
+    LINEAR_TO_SRGB[(value.clamp(0.0, 1.0) * (ENCODE_STEPS - 1) as f32 + 0.5) as usize]
+}
 
-        Color::new(r, g, b)
+// Mapas de color perceptualmente uniformes para visualizaciones científicas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Colormap {
+    Viridis,
+    Inferno,
+}
+
+const VIRIDIS: [Color; 9] = [
+    Color::new(68, 1, 84),
+    Color::new(71, 44, 122),
+    Color::new(59, 81, 139),
+    Color::new(44, 113, 142),
+    Color::new(33, 144, 141),
+    Color::new(39, 173, 129),
+    Color::new(92, 200, 99),
+    Color::new(170, 220, 50),
+    Color::new(253, 231, 37),
+];
+
+const INFERNO: [Color; 9] = [
+    Color::new(0, 0, 4),
+    Color::new(31, 12, 72),
+    Color::new(85, 15, 109),
+    Color::new(136, 34, 106),
+    Color::new(186, 54, 85),
+    Color::new(227, 89, 51),
+    Color::new(249, 140, 10),
+    Color::new(249, 201, 50),
+    Color::new(252, 255, 164),
+];
+
+impl Colormap {
+    // `t` en [0, 1]
+    pub fn sample(self, t: f32) -> Color {
+        let stops = match self {
+            Colormap::Viridis => &VIRIDIS,
+            Colormap::Inferno => &INFERNO,
+        };
+        let scaled = t.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+        let index = (scaled.floor() as usize).min(stops.len() - 2);
+        stops[index].lerp(&stops[index + 1], scaled - index as f32)
     }
 }
 
+// Colores con nombre usados por los shaders de cada cuerpo
+pub mod palette {
+    use super::Color;
+
+    pub const SUN_CORE: Color = Color::new(255, 223, 0);
+    pub const SUN_EMISSION: Color = Color::new(255, 255, 102);
+
+    pub const EARTH_OCEAN: Color = Color::new(0, 0, 255);
+    pub const EARTH_LAND: Color = Color::new(34, 139, 34);
+    pub const EARTH_CLOUD: Color = Color::new(255, 255, 255);
+
+    pub const VENUS_HAZE: Color = Color::new(255, 228, 181);
+    pub const VENUS_CLOUD: Color = Color::new(220, 220, 220);
+    pub const VENUS_HIGHLIGHT: Color = Color::new(255, 250, 240);
+
+    pub const MARS_RED: Color = Color::new(204, 102, 51);
+    pub const MARS_DARK: Color = Color::new(139, 69, 19);
+    pub const MARS_ROCK: Color = Color::new(160, 82, 45);
+
+    pub const JUPITER_LIGHT: Color = Color::new(210, 180, 140);
+    pub const JUPITER_DARK: Color = Color::new(139, 69, 19);
+    pub const JUPITER_WHITE: Color = Color::new(245, 245, 245);
+    pub const JUPITER_SPOT: Color = Color::new(255, 69, 0);
+
+    pub const SATURN_LIGHT: Color = Color::new(205, 133, 63);
+    pub const SATURN_DARK: Color = Color::new(139, 69, 19);
+    pub const SATURN_WHITE: Color = Color::new(245, 245, 245);
+    pub const SATURN_RING: Color = Color::new(160, 160, 160);
+
+    pub const URANUS_BASE: Color = Color::new(0, 255, 255);
+    pub const URANUS_DARK: Color = Color::new(0, 128, 128);
+    pub const URANUS_RING: Color = Color::new(62, 64, 70);
+    pub const URANUS_RING_LIGHT: Color = Color::new(128, 132, 140);
+
+    pub const NEPTUNE_BASE: Color = Color::new(0, 0, 255);
+    pub const NEPTUNE_DARK: Color = Color::new(0, 0, 139);
+
+    pub const MOON_GRAY: Color = Color::new(200, 200, 200);
+    pub const MOON_CRATER: Color = Color::new(150, 150, 150);
+
+    pub const LAVA_BRIGHT: Color = Color::new(255, 240, 0);
+    pub const LAVA_DARK: Color = Color::new(130, 20, 0);
+}
+
 use std::ops::Add;
 
 impl Add for Color {
@@ -0,0 +1,105 @@
+use nalgebra_glm::DVec3;
+use rand::Rng;
+use crate::color::Color;
+use crate::debug_draw;
+use crate::framebuffer::Framebuffer;
+use crate::memory::MemoryUsage;
+use crate::particles::{ColorOverLife, Emitter, ParticleSystem};
+use crate::solar_system::{self, SolarSystem};
+use crate::Uniforms;
+
+// Cometa del sistema incorporado (ver SolarSystem::new)
+pub const COMET: &str = "halley";
+// Partículas de polvo con el presupuesto de efectos al 100%
+pub const DUST_CAPACITY: usize = 3000;
+// Polvo recién soltado casi blanco que se enrojece y se apaga al alejarse
+const DUST_COLOR: ColorOverLife = ColorOverLife::new(Color::new(255, 240, 210), Color::new(60, 45, 30));
+// En el perihelio: partículas por frame, vida en frames, dispersión y empuje de la
+// luz del Sol (unidades por frame); lejos del Sol todo cae con la actividad
+const DUST_RATE: f64 = 6.0;
+const DUST_LIFETIME: f64 = 120.0;
+const DUST_SPREAD: f64 = 0.002;
+const DUST_PUSH: f64 = 0.02;
+// Fracción de la velocidad del núcleo con que el polvo se queda atrás
+const DUST_LAG: f64 = 0.03;
+// Cada partícula mide esta fracción del radio del núcleo
+const DUST_SIZE: f64 = 0.5;
+// Cola de iones: recta, azulada y opuesta al Sol; largo en el perihelio y segmentos
+// en que se apaga. Las dos líneas de los costados le dan ancho.
+const ION_COLOR: Color = Color::new(110, 160, 255);
+const ION_LENGTH: f64 = 3.0;
+const ION_SEGMENTS: usize = 24;
+const ION_WIDTH: f64 = 0.6;
+
+// Colas de un cometa en órbita elíptica: polvo que la luz del Sol aleja del núcleo
+// y que se queda un poco atrás, así que se curva detrás de la órbita, y una cola de
+// iones recta en dirección opuesta al Sol. Las partículas viven en coordenadas
+// relativas al núcleo: la cola lo acompaña aun en el perihelio, donde el cometa
+// recorre en pocos frames más que su largo. La actividad, (perihelio / distancia)², escala la
+// cantidad de polvo, su empuje y el brillo y largo de la cola de iones: el cometa
+// se enciende cerca del Sol y casi desaparece en el afelio.
+pub struct Comet {
+    pub name: &'static str,
+    emitter: Emitter,
+    dust: ParticleSystem,
+    // De 0 a 1 (en el perihelio)
+    pub activity: f64,
+}
+
+impl Comet {
+    // None si el sistema no tiene ese cuerpo en órbita
+    pub fn new(system: &SolarSystem, name: &'static str) -> Option<Self> {
+        let radius = system.body(name).filter(|body| body.orbit.is_some())?.radius;
+        Some(Comet {
+            name,
+            emitter: Emitter::new(0.0, DUST_SPREAD, DUST_LIFETIME),
+            dust: ParticleSystem::new(DUST_CAPACITY, DUST_COLOR).with_size((radius * DUST_SIZE) as f32),
+            activity: 0.0,
+        })
+    }
+
+    // `capacity`: máximo de partículas de polvo (ver effects_budget.rs)
+    pub fn update(&mut self, system: &SolarSystem, dt: f64, capacity: usize, rng: &mut impl Rng) {
+        self.dust.set_capacity(capacity);
+        if let Some((position, orbit)) = system.body(self.name).and_then(|body| Some((body.position, body.orbit?))) {
+            let away = position - system.position("sun");
+            self.activity = (orbit.periapsis_distance() / away.magnitude()).powi(2).min(1.0);
+            let velocity = (orbit.position_at(system.time + dt) - orbit.position_at(system.time)) / dt;
+            // El emisor queda en el origen: el del núcleo
+            self.emitter.rate = DUST_RATE * self.activity;
+            self.emitter.velocity = away.normalize() * DUST_PUSH * self.activity - velocity * DUST_LAG;
+            self.emitter.update(&mut self.dust, dt, rng);
+        }
+        self.dust.update(dt);
+    }
+
+    // `uniforms` con matriz de modelo identidad (ver overlay_uniforms en main)
+    pub fn draw(&self, framebuffer: &mut Framebuffer, uniforms: &Uniforms, system: &SolarSystem, origin: DVec3) {
+        let Some(body) = system.body(self.name) else {
+            return;
+        };
+        // Del espacio del núcleo al del mundo corriendo el origen
+        self.dust.draw(framebuffer, uniforms, origin - body.position);
+        let away = (body.position - system.position("sun")).normalize();
+        // Los costados se corren en la dirección que se ve de lado desde la cámara
+        let camera = uniforms.camera_position.cast::<f64>() + origin;
+        let side = away.cross(&(body.position - camera)).try_normalize(f64::EPSILON).unwrap_or_else(DVec3::zeros);
+        let length = ION_LENGTH * self.activity.sqrt();
+        let strands = [(0.0, 1.0), (1.0, 0.5), (-1.0, 0.5)];
+        for (offset, strength) in strands {
+            let start = body.position + side * offset * body.radius * ION_WIDTH;
+            let point = |i: usize| solar_system::to_render(start + away * length * i as f64 / ION_SEGMENTS as f64, origin);
+            for i in 0..ION_SEGMENTS {
+                let fade = 1.0 - i as f32 / ION_SEGMENTS as f32;
+                let color = ION_COLOR * (self.activity as f32 * strength * fade);
+                debug_draw::draw_segment_additive(framebuffer, uniforms, point(i), point(i + 1), color);
+            }
+        }
+    }
+}
+
+impl MemoryUsage for Comet {
+    fn memory_bytes(&self) -> usize {
+        self.dust.memory_bytes()
+    }
+}
@@ -0,0 +1,136 @@
+use image::{Rgb, RgbImage};
+use crate::color::Colormap;
+
+// Métricas de diferencia entre dos capturas del mismo tamaño
+#[derive(Debug, Clone, Copy)]
+pub struct CompareMetrics {
+    // Mayor diferencia de un canal (0-255)
+    pub max_delta: u8,
+    // Píxeles con alguna diferencia
+    pub differing_pixels: usize,
+    // Infinito si las imágenes son idénticas
+    pub psnr: f64,
+    // 1.0 si las imágenes son idénticas
+    pub ssim: f64,
+}
+
+impl CompareMetrics {
+    pub fn identical(&self) -> bool {
+        self.max_delta == 0
+    }
+}
+
+// Modo `--compare a.png b.png`: escribe el mapa de calor y muestra las métricas
+pub fn run(path_a: &str, path_b: &str, output: &str) -> Result<CompareMetrics, String> {
+    let a = load(path_a)?;
+    let b = load(path_b)?;
+    if a.dimensions() != b.dimensions() {
+        return Err(format!(
+            "Las imágenes tienen tamaños distintos: {}x{} y {}x{}",
+            a.width(), a.height(), b.width(), b.height()
+        ));
+    }
+
+    let metrics = compare(&a, &b);
+    heatmap(&a, &b, metrics.max_delta)
+        .save(output)
+        .map_err(|err| format!("No se pudo guardar {}: {}", output, err))?;
+
+    println!("Máxima diferencia: {}", metrics.max_delta);
+    println!(
+        "Píxeles distintos: {} ({:.3}%)",
+        metrics.differing_pixels,
+        100.0 * metrics.differing_pixels as f64 / (a.width() * a.height()).max(1) as f64
+    );
+    println!("PSNR: {:.2} dB", metrics.psnr);
+    println!("SSIM: {:.5}", metrics.ssim);
+    println!("Mapa de diferencias: {}", output);
+    Ok(metrics)
+}
+
+fn load(path: &str) -> Result<RgbImage, String> {
+    image::open(path)
+        .map(|image| image.to_rgb8())
+        .map_err(|err| format!("No se pudo abrir {}: {}", path, err))
+}
+
+fn pixel_delta(a: &Rgb<u8>, b: &Rgb<u8>) -> u8 {
+    (0..3).map(|c| a[c].abs_diff(b[c])).max().unwrap_or(0)
+}
+
+pub fn compare(a: &RgbImage, b: &RgbImage) -> CompareMetrics {
+    let mut max_delta = 0;
+    let mut differing_pixels = 0;
+    let mut squared_error = 0.0;
+
+    for (pa, pb) in a.pixels().zip(b.pixels()) {
+        let delta = pixel_delta(pa, pb);
+        max_delta = max_delta.max(delta);
+        if delta > 0 {
+            differing_pixels += 1;
+        }
+        for c in 0..3 {
+            let d = pa[c] as f64 - pb[c] as f64;
+            squared_error += d * d;
+        }
+    }
+
+    let samples = (a.width() * a.height() * 3).max(1) as f64;
+    let mse = squared_error / samples;
+    let psnr = if mse > 0.0 { 10.0 * (255.0 * 255.0 / mse).log10() } else { f64::INFINITY };
+
+    CompareMetrics { max_delta, differing_pixels, psnr, ssim: ssim(a, b) }
+}
+
+// SSIM sobre la luminancia, promedio de ventanas de 8x8 sin solapamiento
+fn ssim(a: &RgbImage, b: &RgbImage) -> f64 {
+    const WINDOW: u32 = 8;
+    const C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+    const C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+
+    let luma = |p: &Rgb<u8>| 0.299 * p[0] as f64 + 0.587 * p[1] as f64 + 0.114 * p[2] as f64;
+    let (width, height) = a.dimensions();
+    let mut total = 0.0;
+    let mut windows = 0;
+
+    for wy in (0..height).step_by(WINDOW as usize) {
+        for wx in (0..width).step_by(WINDOW as usize) {
+            let (mut sum_a, mut sum_b, mut sum_aa, mut sum_bb, mut sum_ab, mut n) = (0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+            for y in wy..(wy + WINDOW).min(height) {
+                for x in wx..(wx + WINDOW).min(width) {
+                    let la = luma(a.get_pixel(x, y));
+                    let lb = luma(b.get_pixel(x, y));
+                    sum_a += la;
+                    sum_b += lb;
+                    sum_aa += la * la;
+                    sum_bb += lb * lb;
+                    sum_ab += la * lb;
+                    n += 1.0;
+                }
+            }
+            let (mean_a, mean_b) = (sum_a / n, sum_b / n);
+            let var_a = sum_aa / n - mean_a * mean_a;
+            let var_b = sum_bb / n - mean_b * mean_b;
+            let covariance = sum_ab / n - mean_a * mean_b;
+
+            total += ((2.0 * mean_a * mean_b + C1) * (2.0 * covariance + C2))
+                / ((mean_a * mean_a + mean_b * mean_b + C1) * (var_a + var_b + C2));
+            windows += 1;
+        }
+    }
+
+    if windows > 0 { total / windows as f64 } else { 1.0 }
+}
+
+// Negro donde coinciden; el resto se escala respecto a la mayor diferencia
+// para que los cambios pequeños también se vean
+fn heatmap(a: &RgbImage, b: &RgbImage, max_delta: u8) -> RgbImage {
+    RgbImage::from_fn(a.width(), a.height(), |x, y| {
+        let delta = pixel_delta(a.get_pixel(x, y), b.get_pixel(x, y));
+        if delta == 0 {
+            return Rgb([0, 0, 0]);
+        }
+        let color = Colormap::Inferno.sample(delta as f32 / max_delta as f32);
+        Rgb([color.r(), color.g(), color.b()])
+    })
+}
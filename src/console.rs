@@ -0,0 +1,28 @@
+use std::io::{self, BufRead};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+// Comandos escritos en la terminal mientras corre la ventana. Un hilo lee
+// stdin y el loop principal recoge las líneas sin bloquearse.
+pub struct Console {
+    receiver: Receiver<String>,
+}
+
+impl Console {
+    pub fn spawn() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for line in io::stdin().lock().lines() {
+                let Ok(line) = line else { break };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Console { receiver }
+    }
+
+    pub fn poll(&self) -> Vec<String> {
+        self.receiver.try_iter().collect()
+    }
+}
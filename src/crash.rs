@@ -0,0 +1,165 @@
+use std::backtrace::Backtrace;
+use std::fmt::Write as _;
+use std::fs;
+use std::panic::{self, PanicHookInfo};
+use std::sync::Mutex;
+use minifb::{Key, Window};
+use nalgebra_glm::Vec3;
+use crate::framebuffer::Framebuffer;
+use crate::stats::FrameStats;
+use crate::text;
+
+pub const REPORT_PATH: &str = "crash_report.txt";
+
+// Estado que el loop principal actualiza cada frame para incluirlo en el reporte
+#[derive(Debug, Clone, Default)]
+pub struct CrashContext {
+    pub frame: u64,
+    pub time: f32,
+    pub stats: Option<FrameStats>,
+    pub camera_eye: Vec3,
+    pub camera_center: Vec3,
+    pub camera_up: Vec3,
+    pub scene: String,
+    pub settings: String,
+}
+
+static CONTEXT: Mutex<Option<CrashContext>> = Mutex::new(None);
+static PANIC_MESSAGE: Mutex<Option<String>> = Mutex::new(None);
+
+pub fn update_context(context: CrashContext) {
+    if let Ok(mut slot) = CONTEXT.lock() {
+        *slot = Some(context);
+    }
+}
+
+// Reemplaza el hook por defecto: escribe el reporte de diagnóstico y guarda el
+// mensaje para mostrarlo en la ventana
+pub fn install_panic_hook() {
+    panic::set_hook(Box::new(|info| {
+        let report = build_report(info);
+        match fs::write(REPORT_PATH, &report) {
+            Ok(()) => eprintln!("El programa falló; reporte guardado en {}", REPORT_PATH),
+            Err(err) => eprintln!("El programa falló y no se pudo guardar el reporte: {}\n{}", err, report),
+        }
+        // try_lock: el pánico pudo ocurrir con el mutex tomado
+        if let Ok(mut message) = PANIC_MESSAGE.try_lock() {
+            *message = Some(panic_message(info));
+        }
+    }));
+}
+
+fn panic_message(info: &PanicHookInfo) -> String {
+    let payload = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| String::from("pánico sin mensaje"));
+
+    match info.location() {
+        Some(location) => format!("{} ({}:{})", payload, location.file(), location.line()),
+        None => payload,
+    }
+}
+
+fn build_report(info: &PanicHookInfo) -> String {
+    let mut report = String::new();
+    let _ = writeln!(report, "=== Reporte de fallo: Sistema solar ===");
+    let _ = writeln!(report, "Versión: {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(report, "Error: {}", panic_message(info));
+    let _ = writeln!(report);
+
+    match CONTEXT.try_lock().ok().and_then(|context| context.clone()) {
+        Some(context) => {
+            let _ = writeln!(report, "-- Último frame --");
+            let _ = writeln!(report, "frame: {}  time: {}", context.frame, context.time);
+            match &context.stats {
+                Some(stats) => {
+                    let _ = writeln!(report, "stats: {}", stats.to_json());
+                }
+                None => {
+                    let _ = writeln!(report, "stats: (sin datos)");
+                }
+            }
+            let _ = writeln!(report);
+            let _ = writeln!(report, "-- Cámara --");
+            let _ = writeln!(report, "eye: {:?}", context.camera_eye.as_slice());
+            let _ = writeln!(report, "center: {:?}", context.camera_center.as_slice());
+            let _ = writeln!(report, "up: {:?}", context.camera_up.as_slice());
+            let _ = writeln!(report);
+            let _ = writeln!(report, "-- Escena --");
+            let _ = writeln!(report, "{}", context.scene);
+            let _ = writeln!(report);
+            let _ = writeln!(report, "-- Configuración --");
+            let _ = writeln!(report, "{}", context.settings);
+        }
+        None => {
+            let _ = writeln!(report, "(el fallo ocurrió antes del primer frame)");
+        }
+    }
+
+    let _ = writeln!(report);
+    let _ = writeln!(report, "-- Backtrace --");
+    let _ = writeln!(report, "{}", Backtrace::force_capture());
+    report
+}
+
+// Mantiene la ventana abierta mostrando el error hasta que el usuario la cierre
+pub fn show_overlay(window: &mut Window, framebuffer: &mut Framebuffer) {
+    let message = PANIC_MESSAGE
+        .lock()
+        .ok()
+        .and_then(|message| message.clone())
+        .unwrap_or_default();
+
+    // El frame pudo quedar a medias con supermuestreo: el texto va a la resolución de la ventana
+    framebuffer.resolve_supersampling();
+    framebuffer.apply_tone_map();
+
+    // Oscurece el último frame para que el texto resalte
+    for pixel in framebuffer.buffer.iter_mut() {
+        *pixel = (*pixel >> 2) & 0x3F3F3F;
+    }
+
+    let x = 24;
+    let mut y = 24;
+    let max_chars = (framebuffer.width.saturating_sub(2 * x)) / text::CHAR_ADVANCE;
+    text::draw_text_shadowed(framebuffer, x, y, "El programa encontró un error", 0xFF5555, 2);
+    y += text::LINE_HEIGHT * 3;
+    for line in wrap(&message, max_chars) {
+        text::draw_text_shadowed(framebuffer, x, y, &line, 0xFFFFFF, 1);
+        y += text::LINE_HEIGHT;
+    }
+    y += text::LINE_HEIGHT;
+    let hint = format!("Reporte guardado en {}. Esc para salir.", REPORT_PATH);
+    text::draw_text_shadowed(framebuffer, x, y, &hint, 0xAAAAAA, 1);
+
+    while window.is_open() && !window.is_key_down(Key::Escape) {
+        if window
+            .update_with_buffer(&framebuffer.buffer, framebuffer.width, framebuffer.height)
+            .is_err()
+        {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+}
+
+fn wrap(message: &str, max_chars: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in message.split_whitespace() {
+        if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > max_chars {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
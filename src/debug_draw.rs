@@ -0,0 +1,112 @@
+use std::f32::consts::PI;
+use nalgebra_glm::{Mat4, Vec2, Vec3, Vec4};
+use crate::bounds::Aabb;
+use crate::color::Color;
+use crate::fragment::Fragment;
+use crate::framebuffer::Framebuffer;
+use crate::line::line;
+use crate::obj::Obj;
+use crate::shaders::vertex_shader;
+use crate::vertex::Vertex;
+use crate::Uniforms;
+
+// Ayudas visuales de depuración activas en el frame
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DebugFlags {
+    pub show_bounds: bool,
+    pub show_field_lines: bool,
+}
+
+const SPHERE_COLOR: u32 = 0x00FF88;
+const AABB_COLOR: u32 = 0xFFCC00;
+const CIRCLE_SEGMENTS: usize = 32;
+
+// Fragmentos de un segmento en espacio de modelo, transformado con las matrices de `uniforms`
+fn segment_fragments(framebuffer: &Framebuffer, uniforms: &Uniforms, a: Vec3, b: Vec3) -> Vec<Fragment> {
+    // Los extremos detrás de la cámara se proyectan invertidos; se descarta el segmento
+    let clip_w = |p: Vec3| (uniforms.projection_matrix * uniforms.view_matrix * uniforms.model_matrix * Vec4::new(p.x, p.y, p.z, 1.0)).w;
+    if clip_w(a) <= 0.0 || clip_w(b) <= 0.0 {
+        return Vec::new();
+    }
+
+    let start = vertex_shader(&Vertex::new_with_color(a, Color::WHITE), uniforms);
+    let end = vertex_shader(&Vertex::new_with_color(b, Color::WHITE), uniforms);
+
+    // Evita recorrer con Bresenham segmentos enormes casi paralelos al plano de la cámara
+    let limit = 4.0 * framebuffer.width.max(framebuffer.height) as f32;
+    let outside = |v: &Vertex| v.transformed_position.x.abs() > limit || v.transformed_position.y.abs() > limit;
+    if outside(&start) || outside(&end) {
+        return Vec::new();
+    }
+
+    line(&start, &end)
+        .into_iter()
+        .filter(|fragment| fragment.position.x >= 0.0 && fragment.position.y >= 0.0)
+        .collect()
+}
+
+// Punto en espacio de modelo proyectado a pixeles; None si queda detrás de la cámara
+pub fn project(uniforms: &Uniforms, point: Vec3) -> Option<Vec2> {
+    let clip = uniforms.projection_matrix * uniforms.view_matrix * uniforms.model_matrix * Vec4::new(point.x, point.y, point.z, 1.0);
+    if clip.w <= 0.0 {
+        return None;
+    }
+    let screen = uniforms.viewport_matrix * Vec4::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+    Some(Vec2::new(screen.x, screen.y))
+}
+
+// Punto del plano lejano bajo el centro del pixel (x, y); con la inversa de
+// proyección * vista, restarle la posición de la cámara da la dirección del rayo
+pub fn unproject_far(inverse_view_projection: &Mat4, framebuffer: &Framebuffer, x: usize, y: usize) -> Vec3 {
+    let ndc_x = 2.0 * (x as f32 + 0.5) / framebuffer.width as f32 - 1.0;
+    let ndc_y = 1.0 - 2.0 * (y as f32 + 0.5) / framebuffer.height as f32;
+    let far = inverse_view_projection * Vec4::new(ndc_x, ndc_y, 1.0, 1.0);
+    far.xyz() / far.w
+}
+
+pub fn draw_segment(framebuffer: &mut Framebuffer, uniforms: &Uniforms, a: Vec3, b: Vec3, color: u32) {
+    framebuffer.set_current_color(color);
+    for fragment in segment_fragments(framebuffer, uniforms, a, b) {
+        framebuffer.point(fragment.position.x as usize, fragment.position.y as usize, fragment.depth);
+    }
+}
+
+// Suma el color en vez de reemplazarlo y no escribe profundidad: las líneas
+// que se cruzan brillan más y no se tapan entre sí
+pub fn draw_segment_additive(framebuffer: &mut Framebuffer, uniforms: &Uniforms, a: Vec3, b: Vec3, color: Color) {
+    for fragment in segment_fragments(framebuffer, uniforms, a, b) {
+        framebuffer.add_point(fragment.position.x as usize, fragment.position.y as usize, fragment.depth, color);
+    }
+}
+
+// Como los translúcidos: encima con opacidad `alpha` o sumado, según el material
+pub fn draw_segment_blended(framebuffer: &mut Framebuffer, uniforms: &Uniforms, a: Vec3, b: Vec3, color: Color, alpha: f32) {
+    for fragment in segment_fragments(framebuffer, uniforms, a, b) {
+        framebuffer.blend_point(fragment.position.x as usize, fragment.position.y as usize, fragment.depth, color.to_linear(), alpha, uniforms.material.blend);
+    }
+}
+
+// Esfera envolvente (tres círculos máximos) y caja alineada a los ejes del modelo
+pub fn draw_bounds(framebuffer: &mut Framebuffer, uniforms: &Uniforms, obj: &Obj) {
+    let sphere = obj.bounding_sphere();
+    for axis in 0..3 {
+        let point = |i: usize| {
+            let angle = 2.0 * PI * i as f32 / CIRCLE_SEGMENTS as f32;
+            let (s, c) = angle.sin_cos();
+            let offset = match axis {
+                0 => Vec3::new(c, s, 0.0),
+                1 => Vec3::new(c, 0.0, s),
+                _ => Vec3::new(0.0, c, s),
+            };
+            sphere.center + offset * sphere.radius
+        };
+        for i in 0..CIRCLE_SEGMENTS {
+            draw_segment(framebuffer, uniforms, point(i), point(i + 1), SPHERE_COLOR);
+        }
+    }
+
+    let corners = obj.aabb().corners();
+    for (a, b) in Aabb::EDGES {
+        draw_segment(framebuffer, uniforms, corners[a], corners[b], AABB_COLOR);
+    }
+}
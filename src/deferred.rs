@@ -0,0 +1,201 @@
+use nalgebra_glm::{Vec3, dot};
+use crate::color::Color;
+use crate::fragment::Fragment;
+use crate::framebuffer::Framebuffer;
+use crate::lighting::{self, Light, PointLight};
+use crate::material::{DebugView, Material};
+use crate::memory::{vec_bytes, MemoryUsage};
+use crate::shaders;
+use crate::shadow::ShadowMap;
+use crate::visualization;
+use crate::Uniforms;
+
+// Camino experimental de sombreado diferido (feature "deferred"). El pase de
+// geometría guarda la superficie sin iluminar y las luces se resuelven después
+// en un pase de pantalla completa, con un costo que no depende de la geometría.
+pub struct GBuffer {
+    pub albedo: Vec<Color>,
+    pub normal: Vec<Vec3>,
+    // Posición en espacio de render; la profundidad sigue en el z-buffer
+    pub position: Vec<Vec3>,
+    // Índice + 1 en `materials`; 0 es un pixel que no es superficie (fondo, líneas, HUD)
+    pub material_id: Vec<u16>,
+    // Lo que se sumó con mezcla aditiva sobre una superficie antes de iluminarla
+    pub glow: Vec<Color>,
+    pub materials: Vec<Material>,
+    pub active: bool,
+}
+
+// Lo que el pase de geometría guarda de un fragmento
+pub struct Surface {
+    pub albedo: Color,
+    pub normal: Vec3,
+    pub position: Vec3,
+    pub material: Material,
+}
+
+impl Surface {
+    // El mismo shader del camino forward, sin la iluminación. El falso color y los
+    // canales del inspector no reciben luz, así que se marcan como material sin
+    // iluminar; la vista de solo luz ilumina un albedo blanco.
+    pub fn shade<V>(fragment: &Fragment<V>, uniforms: &Uniforms, shader: impl Fn(&Fragment<V>, &Uniforms) -> Color) -> Self {
+        let mut material = uniforms.material;
+        let albedo = match (material.debug_view, uniforms.false_color) {
+            (DebugView::Off, Some(color)) => {
+                material.unlit = true;
+                material.apply(visualization::false_color_shader(fragment, color), fragment)
+            }
+            (DebugView::Off, None) => material.apply(shader(fragment, uniforms), fragment),
+            (DebugView::Light, _) => Color::WHITE,
+            (view, _) => {
+                material.unlit = true;
+                shaders::debug_channel(view, fragment, uniforms, shader)
+            }
+        };
+        Surface {
+            albedo,
+            normal: fragment.normal,
+            position: fragment.world_position,
+            material,
+        }
+    }
+}
+
+impl GBuffer {
+    pub fn new(width: usize, height: usize) -> Self {
+        let size = width * height;
+        GBuffer {
+            albedo: vec![Color::BLACK; size],
+            normal: vec![Vec3::zeros(); size],
+            position: vec![Vec3::zeros(); size],
+            material_id: vec![0; size],
+            glow: vec![Color::BLACK; size],
+            materials: Vec::new(),
+            active: false,
+        }
+    }
+
+    pub fn begin(&mut self) {
+        self.material_id.fill(0);
+        self.glow.fill(Color::BLACK);
+        self.materials.clear();
+        self.active = true;
+    }
+
+    pub fn write(&mut self, index: usize, surface: Surface) {
+        self.albedo[index] = surface.albedo;
+        self.normal[index] = surface.normal;
+        self.position[index] = surface.position;
+        self.material_id[index] = self.material_index(surface.material);
+    }
+
+    // Los fragmentos de un mismo cuerpo llegan seguidos: casi siempre es el último
+    fn material_index(&mut self, material: Material) -> u16 {
+        let found = match self.materials.last() {
+            Some(last) if *last == material => Some(self.materials.len() - 1),
+            _ => self.materials.iter().position(|m| *m == material),
+        };
+        let index = found.unwrap_or_else(|| {
+            self.materials.push(material);
+            self.materials.len() - 1
+        });
+        index as u16 + 1
+    }
+
+    pub fn material_at(&self, index: usize) -> Option<&Material> {
+        match self.material_id[index] {
+            0 => None,
+            id => self.materials.get(id as usize - 1),
+        }
+    }
+}
+
+impl MemoryUsage for GBuffer {
+    fn memory_bytes(&self) -> usize {
+        vec_bytes(&self.albedo)
+            + vec_bytes(&self.normal)
+            + vec_bytes(&self.position)
+            + vec_bytes(&self.material_id)
+            + vec_bytes(&self.glow)
+            + vec_bytes(&self.materials)
+    }
+}
+
+// Pase de luces: reemplaza cada pixel de superficie por su color iluminado por las
+// luces de la escena, con las sombras de `shadow_map`, y por todas las luces puntuales
+// (sin tope). Todo en espacio de render; `object_ids` evita que una luz ilumine a su
+// propio cuerpo.
+pub fn resolve(
+    framebuffer: &mut Framebuffer,
+    scene_lights: &[Light],
+    shadow_map: Option<&ShadowMap>,
+    camera: Vec3,
+    lights: &[PointLight],
+    object_ids: &[&str],
+) {
+    let Some(gbuffer) = framebuffer.gbuffer.as_mut() else {
+        return;
+    };
+    gbuffer.active = false;
+    for index in 0..framebuffer.hdr.len() {
+        let Some(material) = gbuffer.material_at(index) else {
+            continue;
+        };
+        let albedo = gbuffer.albedo[index];
+        let color = if material.unlit {
+            albedo.to_linear()
+        } else {
+            let position = gbuffer.position[index];
+            let normal = gbuffer.normal[index];
+            let view_dir = (camera - position).normalize();
+            let sunlit = shadow_map.map_or(1.0, |shadow_map| shadow_map.visibility(&position));
+            let lit = lighting::shade(scene_lights, sunlit, albedo, &position, &normal, &view_dir, material);
+            let owner = (framebuffer.ids[index] as usize).checked_sub(1).and_then(|i| object_ids.get(i));
+            let local = lights
+                .iter()
+                .filter(|light| owner != Some(&light.owner))
+                .fold(Color::BLACK, |sum, light| sum.add_linear(light.contribution(&position, &normal, &view_dir, material.two_sided)));
+            let local = albedo.blend_multiply(&local).to_linear();
+            [0, 1, 2].map(|channel| lit[channel] + local[channel])
+        };
+        let glow = gbuffer.glow[index].to_linear();
+        framebuffer.hdr[index] = [0, 1, 2].map(|channel| color[channel] + glow[channel]);
+    }
+}
+
+// Color del borde iluminado de la atmósfera de cada cuerpo
+const ATMOSPHERES: [(&str, Color); 6] = [
+    ("venus", Color::new(255, 220, 150)),
+    ("earth", Color::new(110, 170, 255)),
+    ("mars", Color::new(230, 150, 110)),
+    ("jupiter", Color::new(230, 200, 160)),
+    ("uranus", Color::new(150, 230, 240)),
+    ("neptune", Color::new(110, 150, 255)),
+];
+
+// Pase de atmósfera: halo en el borde (1 - N·V) del lado de día, usando el buffer de
+// IDs de objeto para saber qué cuerpo hay en cada pixel. Va después de `resolve`.
+pub fn atmosphere(framebuffer: &mut Framebuffer, object_ids: &[&str], sun: Vec3, camera: Vec3) {
+    let Some(gbuffer) = framebuffer.gbuffer.as_ref() else {
+        return;
+    };
+    for index in 0..framebuffer.hdr.len() {
+        let Some(name) = (framebuffer.ids[index] as usize).checked_sub(1).and_then(|i| object_ids.get(i)) else {
+            continue;
+        };
+        let Some(&(_, tint)) = ATMOSPHERES.iter().find(|(body, _)| body == name) else {
+            continue;
+        };
+        if gbuffer.material_id[index] == 0 {
+            continue;
+        }
+        let position = gbuffer.position[index];
+        let normal = gbuffer.normal[index];
+        let view_dir = (camera - position).normalize();
+        let light_dir = (sun - position).normalize();
+        let rim = (1.0 - dot(&normal, &view_dir).max(0.0)).powi(4);
+        let day = 0.15 + 0.85 * dot(&normal, &light_dir).max(0.0);
+        let halo = (tint * (rim * day * 0.8)).to_linear();
+        (0..3).for_each(|channel| framebuffer.hdr[index][channel] += halo[channel]);
+    }
+}
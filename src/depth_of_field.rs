@@ -0,0 +1,101 @@
+use nalgebra_glm::{Mat4, Vec3, Vec4};
+use crate::color::Color;
+use crate::export::DepthRange;
+use crate::framebuffer::Framebuffer;
+
+// Muestras del disco de desenfoque (espiral con el ángulo áureo)
+const SAMPLES: usize = 24;
+const GOLDEN_ANGLE: f32 = 2.399_963;
+// Bajo este radio (en pixeles) el pixel se deja nítido
+const SHARP_RADIUS: f32 = 0.5;
+
+// Profundidad de campo enfocada en el cuerpo seleccionado. El círculo de confusión
+// de cada pixel sale de su distancia lineal (z-buffer) con el modelo de lente
+// delgada: `aperture * |1 - foco / distancia|` pixeles, hasta `max_radius`; el fondo
+// vacío cuenta como infinitamente lejos. El desenfoque se junta (gather) de un
+// disco de SAMPLES muestras del tamaño del círculo del pixel; una muestra más
+// cercana y más nítida que el pixel pesa menos, para que lo enfocado no se
+// derrame sobre el fondo desenfocado.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DepthOfField {
+    pub enabled: bool,
+    // Radio del desenfoque del fondo lejano, en pixeles
+    pub aperture: f32,
+    pub max_radius: f32,
+}
+
+impl Default for DepthOfField {
+    fn default() -> Self {
+        DepthOfField { enabled: false, aperture: 6.0, max_radius: 12.0 }
+    }
+}
+
+impl DepthOfField {
+    // Distancia de enfoque: la cara del cuerpo que mira a la cámara. `center` en
+    // espacio de render; `view` es la matriz de vista relativa al origen.
+    pub fn focus_distance(view: &Mat4, center: Vec3, radius: f32, range: &DepthRange) -> f32 {
+        let view_center = view * Vec4::new(center.x, center.y, center.z, 1.0);
+        (-view_center.z - radius).max(range.near)
+    }
+
+    fn confusion(&self, distance: f32, focus: f32) -> f32 {
+        if !distance.is_finite() {
+            return self.aperture.min(self.max_radius);
+        }
+        (self.aperture * (1.0 - focus / distance.max(f32::EPSILON)).abs()).min(self.max_radius)
+    }
+
+    pub fn apply(&self, framebuffer: &mut Framebuffer, focus: f32, range: &DepthRange) {
+        if !self.enabled {
+            return;
+        }
+        let (width, height) = (framebuffer.width, framebuffer.height);
+        let distances: Vec<f32> = framebuffer
+            .zbuffer
+            .iter()
+            .map(|&z| if z.is_finite() { range.linearize(z) } else { f32::INFINITY })
+            .collect();
+        let radii: Vec<f32> = distances.iter().map(|&distance| self.confusion(distance, focus)).collect();
+        let offsets: Vec<(f32, f32)> = (0..SAMPLES)
+            .map(|i| {
+                let r = ((i as f32 + 0.5) / SAMPLES as f32).sqrt();
+                let angle = i as f32 * GOLDEN_ANGLE;
+                (r * angle.cos(), r * angle.sin())
+            })
+            .collect();
+
+        let source = framebuffer.buffer.clone();
+        for y in 0..height {
+            for x in 0..width {
+                let index = y * width + x;
+                let radius = radii[index];
+                if radius < SHARP_RADIUS {
+                    continue;
+                }
+                // Promedio en luz lineal, así los puntos brillantes se abren en discos
+                let mut sum = [0.0f32; 3];
+                let mut total = 0.0;
+                let mut gather = |sample: usize, weight: f32| {
+                    let linear = Color::from_hex(source[sample]).to_linear();
+                    for (channel, value) in sum.iter_mut().zip(linear) {
+                        *channel += value * weight;
+                    }
+                    total += weight;
+                };
+                gather(index, 1.0);
+                for &(dx, dy) in &offsets {
+                    let sx = (x as f32 + dx * radius).round().clamp(0.0, (width - 1) as f32) as usize;
+                    let sy = (y as f32 + dy * radius).round().clamp(0.0, (height - 1) as f32) as usize;
+                    let sample = sy * width + sx;
+                    let weight = if distances[sample] < distances[index] { (radii[sample] / radius).min(1.0) } else { 1.0 };
+                    gather(sample, weight);
+                }
+                framebuffer.buffer[index] = Color::from_linear(sum.map(|channel| channel / total)).to_hex();
+            }
+        }
+    }
+
+    pub fn hud_line(&self, selected: &str, focus: f32) -> Option<String> {
+        self.enabled.then(|| format!("Profundidad de campo: foco en {} a {:.1}", selected, focus))
+    }
+}
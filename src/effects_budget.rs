@@ -0,0 +1,71 @@
+use std::time::Duration;
+
+// Sin tope de fps se mide el margen contra este ritmo
+const REFERENCE_FPS: u32 = 60;
+const MIN_SCALE: f32 = 0.25;
+pub const MAX_SCALE: f32 = 2.0;
+// Margen del frame (fracción libre del intervalo) bajo el cual se recorta y sobre
+// el cual se agrega; entre los dos el presupuesto queda quieto
+const LOW_HEADROOM: f32 = 0.1;
+const HIGH_HEADROOM: f32 = 0.35;
+// Se recorta rápido y se crece despacio, para no oscilar
+const SHRINK: f32 = 0.95;
+const GROW: f32 = 1.01;
+// Peso de cada frame en el promedio del tiempo de trabajo
+const SMOOTHING: f32 = 0.1;
+
+// Presupuesto global de efectos decorativos (rastros, partículas, calidad de la
+// luz zodiacal): una escala que sigue el margen medido de cada frame. El tiempo de
+// trabajo es el del frame sin la espera del ritmo; si sobra tiempo la escala sube
+// hasta MAX_SCALE y si el frame se acerca al intervalo baja hasta MIN_SCALE.
+pub struct EffectsBudget {
+    pub adaptive: bool,
+    scale: f32,
+    average_work_ms: f32,
+}
+
+impl EffectsBudget {
+    pub fn new(adaptive: bool) -> Self {
+        EffectsBudget { adaptive, scale: 1.0, average_work_ms: 0.0 }
+    }
+
+    pub fn update(&mut self, work_time: Duration, target_fps: u32) {
+        if !self.adaptive {
+            self.scale = 1.0;
+            return;
+        }
+        let work_ms = work_time.as_secs_f32() * 1000.0;
+        self.average_work_ms = if self.average_work_ms == 0.0 {
+            work_ms
+        } else {
+            self.average_work_ms + (work_ms - self.average_work_ms) * SMOOTHING
+        };
+
+        let fps = if target_fps == 0 { REFERENCE_FPS } else { target_fps };
+        let headroom = 1.0 - self.average_work_ms * fps as f32 / 1000.0;
+        if headroom < LOW_HEADROOM {
+            self.scale *= SHRINK;
+        } else if headroom > HIGH_HEADROOM {
+            self.scale *= GROW;
+        }
+        self.scale = self.scale.clamp(MIN_SCALE, MAX_SCALE);
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    pub fn count(&self, base: usize) -> usize {
+        scaled(base, self.scale)
+    }
+
+    // Solo se muestra cuando el presupuesto se aparta del nominal
+    pub fn hud_line(&self) -> Option<String> {
+        (self.adaptive && (self.scale - 1.0).abs() > 0.05).then(|| format!("Efectos: {:.0}% (margen del frame)", self.scale * 100.0))
+    }
+}
+
+// Cantidad base escalada por el presupuesto, al menos 1
+pub fn scaled(base: usize, scale: f32) -> usize {
+    ((base as f32 * scale).round() as usize).max(1)
+}
@@ -0,0 +1,194 @@
+use nalgebra_glm::DVec3;
+use crate::collision;
+use crate::ship::Ship;
+use crate::solar_system::SolarSystem;
+use crate::transfer::TransferPlan;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EscortOrder {
+    // Mantiene su lugar en la formación del líder
+    Follow,
+    // Orbita un cuerpo a cierta distancia de su centro
+    Orbit { target: String, radius: f64 },
+    // Piloto automático: espera sobre el planeta de origen, vuela la transferencia
+    // planificada y al llegar queda orbitando el destino
+    Transfer(TransferPlan),
+}
+
+// IA de la nave escolta: steering hacia un punto objetivo, separación del
+// líder y evasión de planetas
+pub struct Escort {
+    pub name: &'static str,
+    pub leader: &'static str,
+    pub ship: Ship,
+    pub order: EscortOrder,
+    // Lugar en la formación: x a la derecha del líder, z hacia atrás, y arriba
+    pub formation_offset: DVec3,
+    orbit_angle: f64,
+    leader_previous: Option<DVec3>,
+}
+
+// Distancia a la que empieza a frenar al acercarse al objetivo
+const SLOWING_RADIUS: f64 = 4.0;
+const AVOID_MARGIN: f64 = 1.5;
+
+impl Escort {
+    pub fn new(name: &'static str, leader: &'static str, position: DVec3) -> Self {
+        Escort {
+            name,
+            leader,
+            ship: Ship::new(position, 0.15, 0.01),
+            order: EscortOrder::Follow,
+            formation_offset: DVec3::new(6.0, 2.0, 4.0),
+            orbit_angle: 0.0,
+            leader_previous: None,
+        }
+    }
+
+    // Destino de la transferencia en curso o en espera de su ventana
+    pub fn transfer_destination(&self) -> Option<&'static str> {
+        match &self.order {
+            EscortOrder::Transfer(plan) => Some(plan.to),
+            _ => None,
+        }
+    }
+
+    pub fn update(&mut self, system: &SolarSystem, dt: f64) {
+        let leader_position = system.position(self.leader);
+        let leader_velocity = match self.leader_previous {
+            Some(previous) if dt > 0.0 => (leader_position - previous) / dt,
+            _ => DVec3::zeros(),
+        };
+        self.leader_previous = Some(leader_position);
+
+        let radius = system.radius(self.name);
+        if let EscortOrder::Transfer(plan) = &self.order {
+            let plan = plan.clone();
+            if self.fly_transfer(&plan, system, dt) {
+                return;
+            }
+        }
+
+        let (target, target_velocity) = match &self.order {
+            EscortOrder::Follow => (self.formation_slot(leader_position, leader_velocity), leader_velocity),
+            EscortOrder::Orbit { target, radius: orbit_radius } => {
+                self.orbit_angle += self.ship.max_speed * 0.5 / orbit_radius.max(0.1) * dt;
+                let center = system.position(target);
+                let slot = center + DVec3::new(self.orbit_angle.cos(), 0.0, self.orbit_angle.sin()) * *orbit_radius;
+                (slot, DVec3::zeros())
+            }
+            EscortOrder::Transfer(plan) => {
+                let slot = system.position(plan.from) + DVec3::new(0.0, self.parking_altitude(system, plan.from), 0.0);
+                (slot, orbital_velocity(system, plan.from, dt))
+            }
+        };
+
+        let mut steering = self.arrive(target, target_velocity);
+
+        // Separación: nunca se pega al líder (justo encima no hay un "lejos"; lo resuelve la llegada)
+        let leader_radius = system.radius(self.leader);
+        if collision::intersects(self.ship.position, radius + AVOID_MARGIN, leader_position, leader_radius) {
+            if let Some(away) = (self.ship.position - leader_position).try_normalize(f64::EPSILON) {
+                steering += away * self.ship.max_acceleration * 2.0;
+            }
+        }
+
+        // Evasión: domina sobre el resto cuando hay un planeta cerca
+        let avoid = collision::avoidance(system, self.ship.position, radius, AVOID_MARGIN, &[self.name, self.leader]);
+        steering += avoid * self.ship.max_acceleration * 3.0;
+
+        self.ship.integrate(steering, dt);
+    }
+
+    // Durante el vuelo la nave sigue la trayectoria exacta; la altura sobre el plano
+    // pasa de la de estacionamiento en el origen a la del destino. Devuelve false
+    // mientras espera la ventana de salida.
+    fn fly_transfer(&mut self, plan: &TransferPlan, system: &SolarSystem, dt: f64) -> bool {
+        let time = system.time;
+        if time >= plan.arrival() {
+            let radius = system.radius(plan.to) * 3.0 + system.radius(self.name);
+            self.order = EscortOrder::Orbit { target: plan.to.to_string(), radius };
+            return false;
+        }
+
+        let altitude = |t: f64| {
+            let progress = ((t - plan.departure) / plan.time_of_flight).clamp(0.0, 1.0);
+            let from = self.parking_altitude(system, plan.from);
+            let to = self.parking_altitude(system, plan.to);
+            DVec3::new(0.0, from + (to - from) * progress, 0.0)
+        };
+        let (Some(now), Some(next)) = (plan.position_at(time), plan.position_at((time + dt).min(plan.arrival()))) else {
+            return false;
+        };
+        let now = now + altitude(time);
+        let next = next + altitude(time + dt);
+        self.ship.position = now;
+        self.ship.velocity = if dt > 0.0 { (next - now) / dt } else { DVec3::zeros() };
+        true
+    }
+
+    fn parking_altitude(&self, system: &SolarSystem, body: &str) -> f64 {
+        system.radius(body) + system.radius(self.name) + AVOID_MARGIN * 2.0
+    }
+
+    pub fn start_transfer(&mut self, plan: TransferPlan, now: f64) -> String {
+        let message = format!(
+            "{}: transferencia {} -> {}, sale en {:.0} días, vuelo de {:.0} días",
+            self.name, plan.from, plan.to, plan.departure - now, plan.time_of_flight
+        );
+        self.order = EscortOrder::Transfer(plan);
+        message
+    }
+
+    fn formation_slot(&self, leader_position: DVec3, leader_velocity: DVec3) -> DVec3 {
+        let up = DVec3::new(0.0, 1.0, 0.0);
+        let forward = if leader_velocity.magnitude() > 1e-6 {
+            leader_velocity.normalize()
+        } else {
+            DVec3::new(0.0, 0.0, -1.0)
+        };
+        let right = forward.cross(&up).normalize();
+        leader_position + right * self.formation_offset.x + up * self.formation_offset.y - forward * self.formation_offset.z
+    }
+
+    // Velocidad deseada hacia el objetivo, más lenta al llegar, más la del objetivo
+    fn arrive(&self, target: DVec3, target_velocity: DVec3) -> DVec3 {
+        let to_target = target - self.ship.position;
+        let distance = to_target.magnitude();
+        let desired = if distance > 1e-6 {
+            to_target / distance * self.ship.max_speed * (distance / SLOWING_RADIUS).min(1.0)
+        } else {
+            DVec3::zeros()
+        };
+        desired + target_velocity - self.ship.velocity
+    }
+
+    // Órdenes desde la consola: `seguir` | `orbitar <cuerpo> [radio]`
+    pub fn command(&mut self, args: &[&str], system: &SolarSystem) -> Result<String, String> {
+        match args {
+            ["seguir"] => {
+                self.order = EscortOrder::Follow;
+                Ok(format!("{}: en formación con {}", self.name, self.leader))
+            }
+            ["orbitar", target, rest @ ..] => {
+                let body = system.body(target).ok_or_else(|| format!("Cuerpo desconocido: {}", target))?;
+                let radius = match rest {
+                    [] => body.radius * 3.0 + system.radius(self.name),
+                    [radius] => radius.parse().map_err(|_| format!("Radio inválido: {}", radius))?,
+                    _ => return Err(String::from("Uso: escolta orbitar <cuerpo> [radio]")),
+                };
+                self.order = EscortOrder::Orbit { target: target.to_string(), radius };
+                Ok(format!("{}: orbitando {} a {:.2}", self.name, target, radius))
+            }
+            _ => Err(String::from("Uso: escolta seguir | escolta orbitar <cuerpo> [radio]")),
+        }
+    }
+}
+
+// Velocidad de un cuerpo en su órbita (cero si no tiene)
+fn orbital_velocity(system: &SolarSystem, body: &str, dt: f64) -> DVec3 {
+    match system.orbit(body) {
+        Some(orbit) if dt > 0.0 => (orbit.position_at(system.time + dt) - orbit.position_at(system.time)) / dt,
+        _ => DVec3::zeros(),
+    }
+}
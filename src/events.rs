@@ -0,0 +1,151 @@
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use nalgebra_glm::DVec3;
+use crate::framebuffer::Framebuffer;
+use crate::hud;
+use crate::solar_system::{self, SolarSystem};
+use crate::text;
+
+// Sucesos de la simulación que otros sistemas publican y el registro muestra
+#[derive(Debug, Clone, PartialEq)]
+pub enum SimEvent {
+    EclipseStarted { body: &'static str, occluder: &'static str },
+    EclipseEnded { body: &'static str, occluder: &'static str },
+    CollisionWarning { body: &'static str, other: &'static str },
+    ShipArrived { from: &'static str, to: &'static str },
+    TidalBreakup { body: &'static str, host: &'static str, fragments: usize },
+    OrderChanged(String),
+    HeatWarning { body: &'static str, temperature: f64 },
+    HeatCleared { body: &'static str },
+    // La cámara llegó al cuerpo de un viaje (teclas 0 a 8)
+    WarpCompleted { body: &'static str },
+    // Cambio de la misión de una nave que no pidió nadie, p. ej. al terminar una
+    // transferencia (las órdenes de la consola son OrderChanged)
+    MissionUpdated { ship: &'static str, status: String },
+}
+
+impl fmt::Display for SimEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SimEvent::EclipseStarted { body, occluder } => write!(f, "Eclipse: {} en la sombra de {}", body, occluder),
+            SimEvent::EclipseEnded { body, occluder } => write!(f, "Fin del eclipse de {} por {}", body, occluder),
+            SimEvent::CollisionWarning { body, other } => write!(f, "Alerta de colisión: {} cerca de {}", body, other),
+            SimEvent::ShipArrived { from, to } => write!(f, "Tráfico: llegada a {} desde {}", to, from),
+            SimEvent::TidalBreakup { body, host, fragments } => {
+                write!(f, "Límite de Roche: {} se partió en {} fragmentos cerca de {}", body, fragments, host)
+            }
+            SimEvent::OrderChanged(order) => write!(f, "Orden: {}", order),
+            SimEvent::HeatWarning { body, temperature } => write!(f, "Alerta térmica: {} junto al Sol, {:.0} K", body, temperature),
+            SimEvent::HeatCleared { body } => write!(f, "Fin de la alerta térmica de {}", body),
+            SimEvent::WarpCompleted { body } => write!(f, "Viaje: llegada a {}", solar_system::display_name(body)),
+            SimEvent::MissionUpdated { ship, status } => write!(f, "Misión de {}: {}", ship, status),
+        }
+    }
+}
+
+// Cola de eventos del frame: los sistemas emiten y el loop principal los reparte
+#[derive(Debug, Default)]
+pub struct EventQueue {
+    pending: Vec<SimEvent>,
+}
+
+impl EventQueue {
+    pub fn emit(&mut self, event: SimEvent) {
+        self.pending.push(event);
+    }
+
+    pub fn drain(&mut self) -> impl Iterator<Item = SimEvent> + '_ {
+        self.pending.drain(..)
+    }
+}
+
+// Registro de telemetría: últimas líneas con su tiempo de simulación
+pub struct EventLog {
+    entries: VecDeque<(f64, String)>,
+    capacity: usize,
+    pub visible: bool,
+}
+
+impl EventLog {
+    pub fn new(capacity: usize) -> Self {
+        EventLog { entries: VecDeque::with_capacity(capacity), capacity, visible: false }
+    }
+
+    pub fn record(&mut self, time: f64, event: &SimEvent) {
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((time, event.to_string()));
+    }
+
+    // Panel en la esquina superior derecha, lo más reciente abajo
+    pub fn draw(&self, framebuffer: &mut Framebuffer) {
+        if !self.visible {
+            return;
+        }
+        let width = 340.min(framebuffer.width);
+        let x = framebuffer.width - width;
+        let height = (self.capacity + 1) * text::LINE_HEIGHT + 8;
+        hud::dim_rect(framebuffer, x, 0, width, height);
+
+        text::draw_text_shadowed(framebuffer, x + 6, 4, "Telemetría (T)", 0x88CCFF, 1);
+        for (i, (time, line)) in self.entries.iter().enumerate() {
+            let y = 4 + (i + 1) * text::LINE_HEIGHT;
+            text::draw_text_shadowed(framebuffer, x + 6, y, &format!("[{:>7.1}] {}", time, line), 0xFFFFFF, 1);
+        }
+    }
+}
+
+// Detecta cuándo un cuerpo entra o sale del cilindro de sombra de otro
+#[derive(Debug, Default)]
+pub struct EclipseMonitor {
+    active: HashSet<(&'static str, &'static str)>,
+}
+
+impl EclipseMonitor {
+    // `ignore`: cuerpos que no proyectan ni reciben sombra (naves)
+    pub fn update(&mut self, system: &SolarSystem, ignore: &[&str], events: &mut EventQueue) {
+        let sun = system.position("sun");
+        let bodies = || system.bodies().iter().filter(|b| b.name != "sun" && !ignore.contains(&b.name));
+        for occluder in bodies() {
+            let shadow_dir = (occluder.position - sun).try_normalize(1e-9).unwrap_or(DVec3::zeros());
+            for body in bodies().filter(|b| b.name != occluder.name) {
+                let offset = body.position - occluder.position;
+                let along = offset.dot(&shadow_dir);
+                let in_shadow = along > 0.0 && (offset - shadow_dir * along).magnitude() < occluder.radius;
+
+                let key = (body.name, occluder.name);
+                if in_shadow && self.active.insert(key) {
+                    events.emit(SimEvent::EclipseStarted { body: body.name, occluder: occluder.name });
+                } else if !in_shadow && self.active.remove(&key) {
+                    events.emit(SimEvent::EclipseEnded { body: body.name, occluder: occluder.name });
+                }
+            }
+        }
+    }
+}
+
+// Avisa una vez cuando una nave se acerca demasiado a otro cuerpo
+#[derive(Debug, Default)]
+pub struct ProximityMonitor {
+    active: HashSet<(&'static str, &'static str)>,
+}
+
+impl ProximityMonitor {
+    pub fn update(&mut self, system: &SolarSystem, watched: &[&'static str], margin: f64, events: &mut EventQueue) {
+        for &name in watched {
+            let Some(ship) = system.body(name) else { continue };
+            for other in system.bodies().iter().filter(|b| b.name != name) {
+                let gap = (ship.position - other.position).magnitude() - ship.radius - other.radius;
+                let key = (name, other.name);
+                if gap < margin {
+                    if self.active.insert(key) {
+                        events.emit(SimEvent::CollisionWarning { body: name, other: other.name });
+                    }
+                } else {
+                    self.active.remove(&key);
+                }
+            }
+        }
+    }
+}
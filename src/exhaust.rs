@@ -0,0 +1,89 @@
+use nalgebra_glm::DVec3;
+use rand::Rng;
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+use crate::memory::MemoryUsage;
+use crate::particles::{ColorOverLife, Emitter, ParticleSystem};
+use crate::solar_system::SolarSystem;
+use crate::Uniforms;
+
+// Partículas de cada estela con el presupuesto de efectos al 100%
+pub const EXHAUST_CAPACITY: usize = 600;
+// Gas caliente a la salida de la tobera que se enfría a un azul tenue
+const EXHAUST_COLOR: ColorOverLife = ColorOverLife::new(Color::new(255, 214, 160), Color::new(30, 50, 110));
+// Partículas por frame, vida en frames y dispersión, en unidades por frame
+const EXHAUST_RATE: f64 = 3.0;
+const EXHAUST_LIFETIME: f64 = 40.0;
+const EXHAUST_SPREAD: f64 = 0.004;
+// Velocidad del gas hacia atrás, relativa a la nave, y fracción de la velocidad de
+// la nave que conserva
+const EXHAUST_SPEED: f64 = 0.02;
+const INHERITED: f64 = 0.3;
+// La tobera queda detrás del centro, a esta fracción del radio de la nave; cada
+// partícula mide esta fracción del radio
+const NOZZLE: f64 = 0.5;
+const PUFF_SIZE: f64 = 0.08;
+// Por debajo de esta velocidad (unidades por frame) la nave está quieta y no expulsa gas
+const MIN_SPEED: f64 = 1e-4;
+
+struct Trail {
+    ship: &'static str,
+    emitter: Emitter,
+    particles: ParticleSystem,
+    // Posición en el paso anterior, para sacar la velocidad de cualquier nave (las
+    // que siguen una órbita no guardan la suya)
+    last: Option<DVec3>,
+}
+
+// Estelas de los motores: cada nave suelta gas por detrás, en sentido contrario a
+// su movimiento, que queda flotando donde salió y se apaga
+pub struct Exhaust {
+    trails: Vec<Trail>,
+}
+
+impl Exhaust {
+    pub fn new(system: &SolarSystem, ships: &[&'static str]) -> Self {
+        let trails = ships
+            .iter()
+            .filter_map(|&ship| system.body(ship).map(|body| (ship, body.radius)))
+            .map(|(ship, radius)| Trail {
+                ship,
+                emitter: Emitter::new(EXHAUST_RATE, EXHAUST_SPREAD, EXHAUST_LIFETIME),
+                particles: ParticleSystem::new(EXHAUST_CAPACITY, EXHAUST_COLOR).with_size((radius * PUFF_SIZE) as f32),
+                last: None,
+            })
+            .collect();
+        Exhaust { trails }
+    }
+
+    // `capacity`: máximo de partículas por estela (ver effects_budget.rs)
+    pub fn update(&mut self, system: &SolarSystem, dt: f64, capacity: usize, rng: &mut impl Rng) {
+        for trail in &mut self.trails {
+            trail.particles.set_capacity(capacity);
+            let Some(body) = system.body(trail.ship) else {
+                continue;
+            };
+            let velocity = trail.last.map_or(DVec3::zeros(), |last| (body.position - last) / dt);
+            trail.last = Some(body.position);
+            if velocity.magnitude() > MIN_SPEED {
+                let forward = velocity.normalize();
+                trail.emitter.position = body.position - forward * body.radius * NOZZLE;
+                trail.emitter.velocity = velocity * INHERITED - forward * EXHAUST_SPEED;
+                trail.emitter.update(&mut trail.particles, dt, rng);
+            }
+            trail.particles.update(dt);
+        }
+    }
+
+    pub fn draw(&self, framebuffer: &mut Framebuffer, uniforms: &Uniforms, origin: DVec3) {
+        for trail in &self.trails {
+            trail.particles.draw(framebuffer, uniforms, origin);
+        }
+    }
+}
+
+impl MemoryUsage for Exhaust {
+    fn memory_bytes(&self) -> usize {
+        self.trails.iter().map(|trail| trail.particles.memory_bytes()).sum()
+    }
+}
@@ -0,0 +1,107 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use image::{ImageBuffer, Luma, Rgb, RgbImage};
+use crate::framebuffer::Framebuffer;
+
+pub const CAPTURE_DIR: &str = "captures";
+
+// Planos de la proyección, para recuperar la distancia a partir del z-buffer
+#[derive(Debug, Clone, Copy)]
+pub struct DepthRange {
+    pub near: f32,
+    pub far: f32,
+}
+
+impl DepthRange {
+    // El z-buffer guarda z en NDC (-1..1); esto lo lleva a distancia a lo largo de la vista
+    pub fn linearize(&self, ndc_z: f32) -> f32 {
+        let (n, f) = (self.near, self.far);
+        2.0 * n * f / ((f + n) - ndc_z * (f - n))
+    }
+}
+
+// Guarda el color del frame y, si `auxiliary`, también profundidad e IDs de objeto.
+// `ids[i]` es el nombre del objeto con ID i + 1 (0 es el fondo). Devuelve las rutas escritas.
+pub fn save_frame(
+    framebuffer: &Framebuffer,
+    name: &str,
+    auxiliary: bool,
+    depth_range: DepthRange,
+    ids: &[&str],
+) -> Result<Vec<PathBuf>, String> {
+    let dir = Path::new(CAPTURE_DIR);
+    fs::create_dir_all(dir).map_err(|err| format!("No se pudo crear {}: {}", CAPTURE_DIR, err))?;
+
+    let mut written = Vec::new();
+    let mut save = |suffix: &str, result: Result<(), String>| -> Result<(), String> {
+        result?;
+        written.push(dir.join(format!("{}{}", name, suffix)));
+        Ok(())
+    };
+
+    let color_path = dir.join(format!("{}.png", name));
+    save(".png", save_image(color_image(framebuffer), &color_path))?;
+    if auxiliary {
+        let depth_path = dir.join(format!("{}_depth.png", name));
+        save("_depth.png", depth_image(framebuffer, depth_range).save(&depth_path).map_err(|err| error(&depth_path, err)))?;
+        let ids_path = dir.join(format!("{}_ids.png", name));
+        save("_ids.png", save_image(id_image(framebuffer), &ids_path))?;
+        let legend_path = dir.join(format!("{}_ids.txt", name));
+        save("_ids.txt", fs::write(&legend_path, id_legend(ids)).map_err(|err| error(&legend_path, err)))?;
+    }
+    Ok(written)
+}
+
+fn error(path: &Path, err: impl std::fmt::Display) -> String {
+    format!("No se pudo guardar {}: {}", path.display(), err)
+}
+
+fn save_image(image: RgbImage, path: &Path) -> Result<(), String> {
+    image.save(path).map_err(|err| error(path, err))
+}
+
+pub fn color_image(framebuffer: &Framebuffer) -> RgbImage {
+    RgbImage::from_fn(framebuffer.width as u32, framebuffer.height as u32, |x, y| {
+        let pixel = framebuffer.buffer[y as usize * framebuffer.width + x as usize];
+        Rgb([(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8])
+    })
+}
+
+// Distancia lineal en 16 bits normalizada a la más lejana del frame: blanco es cerca,
+// negro es el fondo (nada dibujado)
+fn depth_image(framebuffer: &Framebuffer, range: DepthRange) -> ImageBuffer<Luma<u16>, Vec<u16>> {
+    let distances: Vec<Option<f32>> = framebuffer
+        .zbuffer
+        .iter()
+        .map(|&z| z.is_finite().then(|| range.linearize(z)))
+        .collect();
+    let farthest = distances.iter().flatten().fold(range.near, |max, &d| max.max(d));
+
+    ImageBuffer::from_fn(framebuffer.width as u32, framebuffer.height as u32, |x, y| {
+        let value = match distances[y as usize * framebuffer.width + x as usize] {
+            Some(distance) => 1.0 - (distance / farthest).clamp(0.0, 1.0) * 0.95,
+            None => 0.0,
+        };
+        Luma([(value * u16::MAX as f32) as u16])
+    })
+}
+
+// Cada ID con un color distinto y estable (el fondo queda negro)
+fn id_image(framebuffer: &Framebuffer) -> RgbImage {
+    RgbImage::from_fn(framebuffer.width as u32, framebuffer.height as u32, |x, y| {
+        let id = framebuffer.ids[y as usize * framebuffer.width + x as usize];
+        if id == 0 {
+            return Rgb([0, 0, 0]);
+        }
+        let hash = id.wrapping_mul(0x9E37_79B9);
+        Rgb([(hash >> 24) as u8 | 0x40, (hash >> 16) as u8 | 0x40, (hash >> 8) as u8 | 0x40])
+    })
+}
+
+fn id_legend(ids: &[&str]) -> String {
+    let mut legend = String::from("0\tfondo\n");
+    for (i, name) in ids.iter().enumerate() {
+        legend.push_str(&format!("{}\t{}\n", i + 1, name));
+    }
+    legend
+}
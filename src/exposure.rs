@@ -0,0 +1,101 @@
+use crate::framebuffer::Framebuffer;
+
+// Luminancia lineal a la que se lleva el promedio medido (gris medio)
+const TARGET_LUMINANCE: f32 = 0.18;
+const MIN_EXPOSURE: f32 = 0.5;
+const MAX_EXPOSURE: f32 = 8.0;
+// Fracción del camino hacia la exposición objetivo que se recorre por frame
+const ADAPTATION_RATE: f32 = 0.08;
+
+// Qué pixeles se midieron en el último frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Metering {
+    // Toda la geometría visible
+    Frame,
+    // Solo los pixeles del cuerpo seleccionado
+    Spot(&'static str),
+}
+
+// Exposición automática: mide la luminancia promedio (logarítmica) del color HDR y
+// se adapta suavemente para llevarla al gris medio. Se suma a la exposición manual
+// del mapeo de tonos (ver tonemap.rs), que escala toda la imagen. Con un cuerpo seleccionado mide solo
+// sus pixeles (buffer de IDs), así los planetas exteriores se aclaran solos; el
+// Sol nunca se sobreexpone por encima de su color original.
+pub struct AutoExposure {
+    pub enabled: bool,
+    pub exposure: f32,
+    pub metering: Metering,
+}
+
+impl AutoExposure {
+    pub fn new(enabled: bool) -> Self {
+        AutoExposure { enabled, exposure: 1.0, metering: Metering::Frame }
+    }
+
+    // `object_ids` traduce IDs de objeto (ID - 1) a cuerpos, como en el resto del pipeline.
+    // Si el cuerpo seleccionado no se ve (fuera de cuadro u oculto tras otro) se mide el
+    // frame completo sin el Sol.
+    pub fn update(&mut self, framebuffer: &mut Framebuffer, object_ids: &[&str], selected: &'static str) {
+        if !self.enabled {
+            return;
+        }
+        let body_at = |id: u32| (id as usize).checked_sub(1).and_then(|i| object_ids.get(i)).copied();
+
+        let spot = average_log_luminance(framebuffer, |id| body_at(id) == Some(selected));
+        let (average, metering) = match spot {
+            Some(average) if selected != "sun" => (Some(average), Metering::Spot(selected)),
+            _ => (
+                average_log_luminance(framebuffer, |id| id != 0 && body_at(id) != Some("sun")),
+                Metering::Frame,
+            ),
+        };
+        self.metering = metering;
+
+        if let Some(average) = average {
+            let target = (TARGET_LUMINANCE / average.max(1e-4)).clamp(MIN_EXPOSURE, MAX_EXPOSURE);
+            // Adaptación en escala logarítmica: subir y bajar se sienten igual de rápido
+            let log_exposure = self.exposure.ln() + (target.ln() - self.exposure.ln()) * ADAPTATION_RATE;
+            self.exposure = log_exposure.exp();
+        }
+
+        self.apply(framebuffer, |id| body_at(id) == Some("sun"));
+    }
+
+    // Escala la geometría (no el fondo ni lo que no tiene ID); al Sol solo se le permite oscurecer
+    fn apply(&self, framebuffer: &mut Framebuffer, is_sun: impl Fn(u32) -> bool) {
+        if (self.exposure - 1.0).abs() < 1e-3 {
+            return;
+        }
+        for (pixel, id) in framebuffer.hdr.iter_mut().zip(&framebuffer.ids) {
+            if *id == 0 {
+                continue;
+            }
+            let exposure = if is_sun(*id) { self.exposure.min(1.0) } else { self.exposure };
+            *pixel = pixel.map(|channel| channel * exposure);
+        }
+    }
+
+    pub fn hud_line(&self) -> Option<String> {
+        if !self.enabled {
+            return None;
+        }
+        let metering = match self.metering {
+            Metering::Frame => String::from("frame"),
+            Metering::Spot(body) => format!("puntual en {}", body),
+        };
+        Some(format!("Exposición: x{:.2} ({})", self.exposure, metering))
+    }
+}
+
+// Media geométrica de la luminancia de los pixeles aceptados; None si no hay ninguno
+fn average_log_luminance(framebuffer: &Framebuffer, accept: impl Fn(u32) -> bool) -> Option<f32> {
+    let mut sum = 0.0;
+    let mut count = 0usize;
+    for (pixel, id) in framebuffer.hdr.iter().zip(&framebuffer.ids) {
+        if accept(*id) {
+            sum += (0.2126 * pixel[0] + 0.7152 * pixel[1] + 0.0722 * pixel[2] + 1e-4).ln();
+            count += 1;
+        }
+    }
+    (count > 0).then(|| (sum / count as f32).exp())
+}
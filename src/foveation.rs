@@ -0,0 +1,119 @@
+use crate::framebuffer::Framebuffer;
+
+// Renderizado foveado: el centro de la pantalla (donde suele estar el planeta
+// seguido) se sombrea a resolución completa y la periferia a media resolución.
+// En la periferia solo se sombrea un pixel de cada bloque de 2x2 (el "ancla", en
+// coordenadas pares); `upscale` completa el resto a partir de las anclas y mezcla
+// ambas imágenes en una franja para que no se note el borde.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Foveation {
+    // Radio de la zona a resolución completa, como fracción de la media diagonal
+    pub radius: f32,
+    // Ancho de la franja de mezcla, en la misma unidad
+    pub blend: f32,
+}
+
+impl Default for Foveation {
+    fn default() -> Self {
+        Foveation { radius: 0.45, blend: 0.1 }
+    }
+}
+
+impl Foveation {
+    // 0 en la zona central, 1 en la periferia y una rampa en la franja de mezcla
+    pub fn falloff(&self, x: usize, y: usize, width: usize, height: usize) -> f32 {
+        let dx = x as f32 + 0.5 - width as f32 * 0.5;
+        let dy = y as f32 + 0.5 - height as f32 * 0.5;
+        let half_diagonal = (width as f32).hypot(height as f32) * 0.5;
+        let distance = dx.hypot(dy) / half_diagonal;
+        if self.blend <= 0.0 {
+            return if distance < self.radius { 0.0 } else { 1.0 };
+        }
+        ((distance - self.radius) / self.blend).clamp(0.0, 1.0)
+    }
+
+    // ¿Hay que sombrear este pixel en el pase de geometría?
+    pub fn shades(&self, x: usize, y: usize, width: usize, height: usize) -> bool {
+        is_anchor(x, y) || self.falloff(x, y, width, height) < 1.0
+    }
+}
+
+fn is_anchor(x: usize, y: usize) -> bool {
+    (x | y) & 1 == 0
+}
+
+// Pase de escalado: los pixeles de la periferia que no se sombrearon toman color,
+// profundidad e ID de las anclas vecinas (interpolación bilineal del color), y los
+// de la franja de mezcla se funden con esa versión escalada. En el camino forward
+// va antes de los overlays, que se dibujan siempre a resolución completa; en el
+// diferido, después del pase de luces.
+pub fn upscale(framebuffer: &mut Framebuffer) {
+    // El tablero de ajedrez tiene prioridad: con él activo no hay anclas que escalar
+    let Some(foveation) = framebuffer.foveation.filter(|_| framebuffer.checkerboard.is_none()) else {
+        return;
+    };
+    let (width, height) = (framebuffer.width, framebuffer.height);
+
+    for y in 0..height {
+        for x in 0..width {
+            if is_anchor(x, y) {
+                continue;
+            }
+            let falloff = foveation.falloff(x, y, width, height);
+            if falloff <= 0.0 {
+                continue;
+            }
+
+            let index = y * width + x;
+            let anchor = (y & !1) * width + (x & !1);
+            let upscaled = bilinear(framebuffer, x, y);
+
+            if falloff >= 1.0 {
+                // No se sombreó: hereda la superficie del ancla si está más cerca que lo
+                // que ya hubiera (fondo, líneas)
+                if framebuffer.zbuffer[anchor] < framebuffer.zbuffer[index] {
+                    framebuffer.hdr[index] = upscaled;
+                    framebuffer.zbuffer[index] = framebuffer.zbuffer[anchor];
+                    framebuffer.ids[index] = framebuffer.ids[anchor];
+                }
+            } else if framebuffer.ids[index] == framebuffer.ids[anchor] {
+                // Franja de mezcla: solo dentro de la misma superficie, para no difuminar bordes
+                let own = &mut framebuffer.hdr[index];
+                (0..3).for_each(|channel| own[channel] += (upscaled[channel] - own[channel]) * falloff);
+            }
+        }
+    }
+}
+
+// Color bilineal entre las cuatro anclas que rodean al pixel; solo cuentan las que
+// pertenecen al mismo objeto que el ancla de su bloque
+fn bilinear(framebuffer: &Framebuffer, x: usize, y: usize) -> [f32; 3] {
+    let width = framebuffer.width;
+    let (x0, y0) = (x & !1, y & !1);
+    let x1 = if x0 + 2 < width { x0 + 2 } else { x0 };
+    let y1 = if y0 + 2 < framebuffer.height { y0 + 2 } else { y0 };
+    let fx = (x - x0) as f32 * 0.5;
+    let fy = (y - y0) as f32 * 0.5;
+    let id = framebuffer.ids[y0 * width + x0];
+
+    let samples = [
+        (x0, y0, (1.0 - fx) * (1.0 - fy)),
+        (x1, y0, fx * (1.0 - fy)),
+        (x0, y1, (1.0 - fx) * fy),
+        (x1, y1, fx * fy),
+    ];
+    let (mut sum, mut total) = ([0.0; 3], 0.0);
+    for (sx, sy, weight) in samples {
+        let index = sy * width + sx;
+        if weight <= 0.0 || framebuffer.ids[index] != id {
+            continue;
+        }
+        let color = framebuffer.hdr[index];
+        (0..3).for_each(|channel| sum[channel] += color[channel] * weight);
+        total += weight;
+    }
+    if total <= 0.0 {
+        return framebuffer.hdr[y0 * width + x0];
+    }
+    sum.map(|channel| channel / total)
+}
@@ -1,25 +1,67 @@
-
 use nalgebra_glm::{Vec2, Vec3};
 use crate::color::Color;
 
-pub struct Fragment {
+// `varyings`: los datos extra de los vértices ya interpolados (ver varyings.rs)
+pub struct Fragment<V = ()> {
     pub position: Vec2,
     pub color: Color,
     pub depth: f32,
     pub normal: Vec3,
     pub intensity: f32,
     pub vertex_position: Vec3,
+    pub world_position: Vec3,
+    // Coordenadas de textura del mesh, interpoladas con corrección de perspectiva
+    pub tex_coords: Vec2,
+    // Opacidad en [0, 1]; 1 salvo en los objetos translúcidos (ver transparency.rs)
+    pub alpha: f32,
+    // Fracción de la luz principal que llega (0 en la sombra de otro cuerpo, ver shadow.rs)
+    pub sunlit: f32,
+    pub varyings: V,
 }
 
 impl Fragment {
-    pub fn new(x: f32, y: f32, color: Color, depth: f32, normal: Vec3, intensity: f32, vertex_position: Vec3,) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(x: f32, y: f32, color: Color, depth: f32, normal: Vec3, intensity: f32, vertex_position: Vec3, world_position: Vec3) -> Self {
         Fragment {
             position: Vec2::new(x, y),
             color,
             depth,
             normal,
             intensity,
-            vertex_position
+            vertex_position,
+            world_position,
+            tex_coords: Vec2::zeros(),
+            alpha: 1.0,
+            sunlit: 1.0,
+            varyings: (),
         }
     }
-}
\ No newline at end of file
+
+    pub fn with_tex_coords(mut self, tex_coords: Vec2) -> Self {
+        self.tex_coords = tex_coords;
+        self
+    }
+
+    pub fn with_sunlit(mut self, sunlit: f32) -> Self {
+        self.sunlit = sunlit;
+        self
+    }
+}
+
+impl<V> Fragment<V> {
+    pub fn with_varyings<W>(self, varyings: W) -> Fragment<W> {
+        Fragment {
+            position: self.position,
+            color: self.color,
+            depth: self.depth,
+            normal: self.normal,
+            intensity: self.intensity,
+            vertex_position: self.vertex_position,
+            world_position: self.world_position,
+            tex_coords: self.tex_coords,
+            alpha: self.alpha,
+            sunlit: self.sunlit,
+            varyings,
+        }
+    }
+}
@@ -1,13 +1,53 @@
+use crate::color::Color;
+use crate::material::BlendMode;
+use crate::foveation::Foveation;
+use crate::transparency::Transparency;
+use crate::memory::{vec_bytes, MemoryUsage};
+use crate::tonemap::{self, ToneMap};
+#[cfg(feature = "deferred")]
+use crate::deferred::{GBuffer, Surface};
 
 pub struct Framebuffer {
     pub width: usize,
     pub height: usize,
+    // Lo que presenta minifb (sRGB, 0xRRGGBB): apply_tone_map lo llena desde `hdr` y después
+    // se dibujan encima las etiquetas, el post-proceso de pantalla y el HUD
     pub buffer: Vec<u32>,
+    // Color de la escena en espacio lineal y sin tope: la geometría, la luz y lo que
+    // se suma con mezcla aditiva pueden pasar de 1 hasta el mapeo de tonos
+    pub hdr: Vec<[f32; 3]>,
     pub zbuffer: Vec<f32>,
-    pub emission_buffer: Vec<u32>, // Nuevo buffer para la emisión
+    // Emisión de cada pixel en espacio lineal, sin tope (HDR); la lee el bloom
+    pub emission_buffer: Vec<[f32; 3]>,
+    // ID del objeto que dejó cada pixel (0: fondo)
+    pub ids: Vec<u32>,
+    // Solo existe tras el primer frame diferido
+    #[cfg(feature = "deferred")]
+    pub gbuffer: Option<GBuffer>,
+    // Periferia a media resolución (ver foveation.rs)
+    pub foveation: Option<Foveation>,
+    // Paridad del tablero de ajedrez de este frame (ver checkerboard.rs)
+    pub checkerboard: Option<usize>,
+    // Rasterización por bloques en paralelo (ver tiles.rs)
+    pub tiled: bool,
+    pub transparency: Transparency,
+    // Número del frame que se dibuja: mueve el ruido de la transparencia estocástica
+    // aunque la simulación esté en pausa (ver transparency::threshold)
+    pub frame: u32,
+    // Pixeles cubiertos por un material estocástico este frame (ver transparency.rs)
+    pub translucent: Vec<bool>,
+    // Mapeo de tonos y exposición con que `hdr` pasa a `buffer` (ver tonemap.rs)
+    pub tone_map: ToneMap,
+    // Supermuestreo: se dibuja a `supersampling` veces la resolución de la ventana en
+    // cada eje y resolve_supersampling lo promedia antes de presentar
+    supersampling: usize,
+    display_width: usize,
+    display_height: usize,
     background_color: u32,
-    current_color: u32,
-    current_emission_color: u32, // Color de emisión actual
+    current_linear: [f32; 3],
+    // Intensidad de emisión de lo que se dibuja (la del material de la llamada)
+    current_emission: f32,
+    current_id: u32,
 }
 
 impl Framebuffer {
@@ -16,70 +56,291 @@ impl Framebuffer {
             width,
             height,
             buffer: vec![0; width * height],
+            hdr: vec![[0.0; 3]; width * height],
             zbuffer: vec![f32::INFINITY; width * height],
-            emission_buffer: vec![0; width * height], // Inicializamos el buffer de emisión
+            emission_buffer: vec![[0.0; 3]; width * height],
+            ids: vec![0; width * height],
+            #[cfg(feature = "deferred")]
+            gbuffer: None,
+            foveation: None,
+            checkerboard: None,
+            tiled: false,
+            transparency: Transparency::default(),
+            frame: 0,
+            translucent: vec![false; width * height],
+            tone_map: ToneMap::default(),
+            supersampling: 1,
+            display_width: width,
+            display_height: height,
             background_color: 0x000000,
-            current_color: 0xFFFFFF,
-            current_emission_color: 0x000000, 
+            current_linear: [1.0; 3],
+            current_emission: 0.0,
+            current_id: 0,
+        }
+    }
+
+    // Nuevo tamaño (ventana redimensionada o pantalla completa); conserva los ajustes
+    pub fn resize(&mut self, width: usize, height: usize) {
+        (self.display_width, self.display_height) = (width, height);
+        let (width, height) = (width * self.supersampling, height * self.supersampling);
+        self.width = width;
+        self.height = height;
+        self.buffer = vec![self.background_color; width * height];
+        self.hdr = vec![Color::from_hex(self.background_color).to_linear(); width * height];
+        self.zbuffer = vec![f32::INFINITY; width * height];
+        self.emission_buffer = vec![[0.0; 3]; width * height];
+        self.ids = vec![0; width * height];
+        self.translucent = vec![false; width * height];
+        #[cfg(feature = "deferred")]
+        {
+            self.gbuffer = None;
         }
     }
 
     pub fn clear(&mut self) {
-        for pixel in self.buffer.iter_mut() {
-            *pixel = self.background_color;
+        // Tras resolver el supermuestreo del frame anterior se vuelve al tamaño interno
+        let (width, height) = (self.display_width * self.supersampling, self.display_height * self.supersampling);
+        if (self.width, self.height) != (width, height) {
+            (self.width, self.height) = (width, height);
+            self.buffer.resize(width * height, self.background_color);
+            self.hdr.resize(width * height, [0.0; 3]);
+            self.zbuffer.resize(width * height, f32::INFINITY);
+            self.emission_buffer.resize(width * height, [0.0; 3]);
+            self.ids.resize(width * height, 0);
+            self.translucent.resize(width * height, false);
         }
+        self.hdr.fill(Color::from_hex(self.background_color).to_linear());
         for depth in self.zbuffer.iter_mut() {
             *depth = f32::INFINITY;
         }
+        self.emission_buffer.fill([0.0; 3]);
+        self.ids.fill(0);
+        self.translucent.fill(false);
+    }
+
+    pub fn supersampling(&self) -> usize {
+        self.supersampling
+    }
+
+    // 1 (sin supermuestreo), 2 o 4 muestras por eje; reserva los buffers de nuevo
+    pub fn set_supersampling(&mut self, factor: usize) {
+        self.supersampling = factor.max(1);
+        self.resize(self.display_width, self.display_height);
+    }
+
+    // Lleva el frame a la resolución de la ventana: cada pixel promedia su bloque de
+    // muestras (filtro de caja, en HDR), se queda con la profundidad más cercana y con
+    // el ID de esa muestra. Se hace en el lugar: el bloque de un pixel siempre está en
+    // índices iguales o mayores al suyo. Lo que se dibuje después (etiquetas, HUD) va
+    // a la resolución de la ventana, hasta el próximo clear.
+    pub fn resolve_supersampling(&mut self) {
+        let factor = self.supersampling;
+        let (width, height) = (self.display_width, self.display_height);
+        if self.width == width && self.height == height {
+            return;
+        }
+        let samples = (factor * factor) as f32;
+        for y in 0..height {
+            for x in 0..width {
+                let (mut color, mut emission) = ([0.0f32; 3], [0.0f32; 3]);
+                let first = y * factor * self.width + x * factor;
+                let (mut depth, mut id, mut translucent) = (self.zbuffer[first], self.ids[first], false);
+                for sy in y * factor..(y + 1) * factor {
+                    for sx in x * factor..(x + 1) * factor {
+                        let index = sy * self.width + sx;
+                        for channel in 0..3 {
+                            color[channel] += self.hdr[index][channel];
+                            emission[channel] += self.emission_buffer[index][channel];
+                        }
+                        if self.zbuffer[index] < depth {
+                            depth = self.zbuffer[index];
+                            id = self.ids[index];
+                        }
+                        translucent |= self.translucent[index];
+                    }
+                }
+                let index = y * width + x;
+                self.hdr[index] = color.map(|channel| channel / samples);
+                self.emission_buffer[index] = emission.map(|channel| channel / samples);
+                self.zbuffer[index] = depth;
+                self.ids[index] = id;
+                self.translucent[index] = translucent;
+            }
+        }
+        (self.width, self.height) = (width, height);
+        self.buffer.truncate(width * height);
+        self.hdr.truncate(width * height);
+        self.zbuffer.truncate(width * height);
+        self.emission_buffer.truncate(width * height);
+        self.ids.truncate(width * height);
+        self.translucent.truncate(width * height);
     }
 
     pub fn point(&mut self, x: usize, y: usize, depth: f32) {
+        self.point_linear(x, y, depth, self.current_linear);
+    }
+
+    // Como `point`, con un color ya en lineal y sin tope (la geometría iluminada)
+    pub fn point_linear(&mut self, x: usize, y: usize, depth: f32, color: [f32; 3]) {
         if x < self.width && y < self.height {
             let index = y * self.width + x;
 
             if self.zbuffer[index] > depth {
-                self.buffer[index] = self.current_color;
+                self.hdr[index] = color;
                 self.zbuffer[index] = depth;
+                self.ids[index] = self.current_id;
+                self.emission_buffer[index] = self.emission(color);
+                // Un pixel escrito directamente (líneas, rastros) ya no es superficie a iluminar
+                #[cfg(feature = "deferred")]
+                if let Some(gbuffer) = self.gbuffer.as_mut().filter(|g| g.active) {
+                    gbuffer.material_id[index] = 0;
+                }
             }
         }
     }
 
-    pub fn set_background_color(&mut self, color: u32) {
-        self.background_color = color;
+    // Pase de geometría diferido: guarda la superficie en el G-buffer en vez de un color
+    #[cfg(feature = "deferred")]
+    pub fn surface_point(&mut self, x: usize, y: usize, depth: f32, surface: Surface) {
+        if x < self.width && y < self.height {
+            let index = y * self.width + x;
+            if self.zbuffer[index] > depth {
+                self.zbuffer[index] = depth;
+                self.ids[index] = self.current_id;
+                self.emission_buffer[index] = self.emission(surface.albedo.to_linear());
+                if let Some(gbuffer) = self.gbuffer.as_mut() {
+                    gbuffer.write(index, surface);
+                }
+            }
+        }
     }
 
-    pub fn set_current_color(&mut self, color: u32) {
-        self.current_color = color;
+    // Activa el G-buffer para el frame; se reserva la primera vez
+    #[cfg(feature = "deferred")]
+    pub fn begin_deferred(&mut self) {
+        let (width, height) = (self.width, self.height);
+        self.gbuffer.get_or_insert_with(|| GBuffer::new(width, height)).begin();
+    }
+
+    #[cfg(feature = "deferred")]
+    pub fn deferred_active(&self) -> bool {
+        self.gbuffer.as_ref().is_some_and(|gbuffer| gbuffer.active)
+    }
+
+    // Con el tablero de ajedrez se sombrea una paridad por frame; si no, con foveado,
+    // los fragmentos de la periferia fuera de las anclas no se sombrean
+    pub fn shades(&self, x: usize, y: usize) -> bool {
+        if let Some(parity) = self.checkerboard {
+            return (x + y + parity) & 1 == 0;
+        }
+        self.foveation.is_none_or(|foveation| foveation.shades(x, y, self.width, self.height))
+    }
+
+    // Mezcla aditiva con prueba de profundidad pero sin escribirla (líneas y brillos)
+    pub fn add_point(&mut self, x: usize, y: usize, depth: f32, color: Color) {
+        self.add_point_linear(x, y, depth, color.to_linear());
+    }
+
+    fn add_point_linear(&mut self, x: usize, y: usize, depth: f32, color: [f32; 3]) {
+        if x < self.width && y < self.height {
+            let index = y * self.width + x;
+            if self.zbuffer[index] > depth {
+                // Sobre una superficie aún sin iluminar se acumula aparte y se suma al resolver
+                #[cfg(feature = "deferred")]
+                if let Some(gbuffer) = self.gbuffer.as_mut().filter(|g| g.active && g.material_id[index] != 0) {
+                    gbuffer.glow[index] = gbuffer.glow[index].blend_add(&Color::from_linear(color));
+                    return;
+                }
+                (0..3).for_each(|channel| self.hdr[index][channel] += color[channel]);
+            }
+        }
+    }
+
+    // Etapa de mezcla de los translúcidos: prueba de profundidad sin escribirla, y el
+    // color (lineal) encima según `alpha` o sumado (en el diferido, como brillo; ver
+    // add_point)
+    pub fn blend_point(&mut self, x: usize, y: usize, depth: f32, color: [f32; 3], alpha: f32, mode: BlendMode) {
+        if mode == BlendMode::Additive {
+            self.add_point_linear(x, y, depth, color.map(|channel| channel * alpha));
+            return;
+        }
+        if x < self.width && y < self.height {
+            let index = y * self.width + x;
+            if self.zbuffer[index] > depth {
+                let alpha = alpha.clamp(0.0, 1.0);
+                let base = &mut self.hdr[index];
+                (0..3).for_each(|channel| base[channel] += (color[channel] - base[channel]) * alpha);
+            }
+        }
     }
 
-    pub fn set_emission_color(&mut self, color: u32) { // Método para configurar el color de emisión
-        self.current_emission_color = color;
+    pub fn mark_translucent(&mut self, x: usize, y: usize, depth: f32) {
+        if x < self.width && y < self.height {
+            let index = y * self.width + x;
+            if self.zbuffer[index] > depth {
+                self.translucent[index] = true;
+            }
+        }
     }
 
-    pub fn apply_emission(&mut self) { // Método de postprocesamiento
-        for i in 0..self.buffer.len() {
-            let base_color = self.buffer[i];
-            let emission_color = self.emission_buffer[i];
+    // Escribe un pixel con prueba de profundidad pero sin escribirla (texto en la escena)
+    pub fn depth_tested_pixel(&mut self, x: usize, y: usize, depth: f32, color: u32) {
+        if x < self.width && y < self.height {
+            let index = y * self.width + x;
+            if self.zbuffer[index] > depth {
+                self.buffer[index] = color;
+            }
+        }
+    }
 
-            // Mezclamos el color base y el color de emisión
-            self.buffer[i] = self.mix_colors(base_color, emission_color);
+    pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: u32) {
+        let x_end = (x + width).min(self.width);
+        let y_end = (y + height).min(self.height);
+        for py in y.min(y_end)..y_end {
+            let row = py * self.width;
+            for px in x.min(x_end)..x_end {
+                self.buffer[row + px] = color;
+            }
         }
     }
 
-    fn mix_colors(&self, color1: u32, color2: u32) -> u32 {
-        // Método simple para mezclar dos colores (simulación de brillo)
-        let r1 = (color1 >> 16) & 0xFF;
-        let g1 = (color1 >> 8) & 0xFF;
-        let b1 = color1 & 0xFF;
+    pub fn set_background_color(&mut self, color: u32) {
+        self.background_color = color;
+    }
+
+    pub fn set_current_color(&mut self, color: u32) {
+        self.current_linear = Color::from_hex(color).to_linear();
+    }
+
+    pub fn set_current_id(&mut self, id: u32) {
+        self.current_id = id;
+    }
 
-        let r2 = (color2 >> 16) & 0xFF;
-        let g2 = (color2 >> 8) & 0xFF;
-        let b2 = color2 & 0xFF;
+    // Lo que se dibuje desde ahora emite su color por `intensity` (0: nada, y tapa
+    // la emisión de lo que quede detrás)
+    pub fn set_emission(&mut self, intensity: f32) {
+        self.current_emission = intensity;
+    }
 
-        let r = (r1 + r2).min(255);
-        let g = (g1 + g2).min(255);
-        let b = (b1 + b2).min(255);
+    // Lleva el color de la escena al buffer que se presenta; lo que se dibuje después
+    // va directo a `buffer`
+    pub fn apply_tone_map(&mut self) {
+        tonemap::apply(&self.hdr, &mut self.buffer, self.tone_map);
+    }
+
+    fn emission(&self, color: [f32; 3]) -> [f32; 3] {
+        if self.current_emission <= 0.0 {
+            return [0.0; 3];
+        }
+        color.map(|channel| channel * self.current_emission)
+    }
+}
 
-        (r << 16) | (g << 8) | b
+impl MemoryUsage for Framebuffer {
+    fn memory_bytes(&self) -> usize {
+        let bytes = vec_bytes(&self.buffer) + vec_bytes(&self.hdr) + vec_bytes(&self.zbuffer) + vec_bytes(&self.emission_buffer) + vec_bytes(&self.ids) + vec_bytes(&self.translucent);
+        #[cfg(feature = "deferred")]
+        let bytes = bytes + self.gbuffer.as_ref().map_or(0, |gbuffer| gbuffer.memory_bytes());
+        bytes
     }
 }
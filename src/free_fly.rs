@@ -0,0 +1,56 @@
+use minifb::{Key, Window};
+use crate::camera::Camera;
+use crate::mouse::MouseMotion;
+
+// Unidades por frame al entrar al modo y límites de la rueda; cada paso de la rueda
+// multiplica o divide la velocidad por 1 + zoom_sensitivity de [mouse]
+const START_SPEED: f32 = 0.05;
+const MIN_SPEED: f32 = 0.001;
+const MAX_SPEED: f32 = 2.0;
+// Radianes por frame al mirar o alabear con las teclas
+const LOOK_SPEED: f32 = 0.03;
+
+// Cámara de espectador (Enter): se mueve por su cuenta en los ejes de la vista, sin
+// orbitar un punto. W / S avanzan y retroceden, A / D van de costado y Q / E suben y
+// bajan; el mouse (arrastrando con el botón derecho) o las flechas giran la mirada y
+// [ / ] alabean. El punto de mira queda siempre a la misma distancia delante del
+// ojo, así al volver a la cámara libre se orbita alrededor de él.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FreeFly {
+    pub speed: f32,
+}
+
+impl Default for FreeFly {
+    fn default() -> Self {
+        FreeFly { speed: START_SPEED }
+    }
+}
+
+impl FreeFly {
+    pub fn handle_input(&mut self, window: &Window, camera: &mut Camera, mouse: MouseMotion) {
+        let axis = |positive: Key, negative: Key| window.is_key_down(positive) as i32 as f32 - window.is_key_down(negative) as i32 as f32;
+        let yaw = axis(Key::Right, Key::Left) * LOOK_SPEED + mouse.yaw;
+        let pitch = axis(Key::Up, Key::Down) * LOOK_SPEED + mouse.pitch;
+        if yaw != 0.0 || pitch != 0.0 {
+            camera.look(yaw, pitch);
+        }
+        let roll = axis(Key::RightBracket, Key::LeftBracket);
+        if roll != 0.0 {
+            camera.roll(roll * LOOK_SPEED);
+        }
+        if mouse.zoom != 0.0 {
+            let factor = if mouse.zoom > 0.0 { 1.0 + mouse.zoom } else { 1.0 / (1.0 - mouse.zoom) };
+            self.speed = (self.speed * factor).clamp(MIN_SPEED, MAX_SPEED);
+        }
+
+        let (forward, right, up) = camera.axes();
+        let movement = forward * axis(Key::W, Key::S) + right * axis(Key::D, Key::A) + up * axis(Key::Q, Key::E);
+        if movement.magnitude() > 0.0 {
+            camera.translate(movement.normalize() * self.speed);
+        }
+    }
+
+    pub fn hud_line(&self) -> String {
+        format!("Vuelo libre: velocidad {:.3} por frame (rueda: más o menos rápido)", self.speed)
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::HashSet;
+use fastnoise_lite::{FastNoiseLite, NoiseType};
+use nalgebra_glm::DVec3;
+use crate::events::{EventQueue, SimEvent};
+use crate::framebuffer::Framebuffer;
+use crate::hud;
+use crate::solar_system::SolarSystem;
+use crate::text;
+
+// Temperatura efectiva de la fotosfera, en K
+const SUN_TEMPERATURE: f64 = 5772.0;
+// Distancias al centro del Sol en radios solares: la distorsión empieza en HAZE_START
+// y llega al máximo en HAZE_FULL; la alerta se enciende bajo WARNING y se apaga
+// recién al salir de CLEAR, para no repetirla en el borde
+const HAZE_START: f64 = 3.0;
+const HAZE_FULL: f64 = 1.3;
+const WARNING: f64 = 2.0;
+const CLEAR: f64 = 2.2;
+// Desplazamiento máximo en pixeles, separación de la grilla donde se evalúa el
+// ruido, escala del ruido y velocidad a la que sube el aire caliente
+const MAX_SHIFT: f32 = 5.0;
+const GRID: usize = 8;
+const FREQUENCY: f32 = 0.045;
+const RISE: f32 = 1.5;
+const FLICKER: f32 = 0.8;
+const WARNING_COLOR: u32 = 0xFF5533;
+
+// Temperatura de equilibrio de un cuerpo negro a `distance` radios solares del centro
+pub fn temperature(distance: f64) -> f64 {
+    SUN_TEMPERATURE * (1.0 / (2.0 * distance.max(1.0))).sqrt()
+}
+
+// Modo de roce solar: con la cámara cerca del Sol la imagen ondula como aire
+// caliente (un pase de post-proceso que desplaza la lectura de cada pixel con
+// ruido animado) y, para la cámara o la escolta bajo WARNING radios solares, se
+// emite una alerta térmica y el HUD muestra la temperatura.
+pub struct HeatHaze {
+    noise: FastNoiseLite,
+    // Intensidad de la distorsión de este frame, de 0 a 1
+    strength: f32,
+    // Observadores en alerta y el más caliente (nombre, radios solares, K)
+    warned: HashSet<&'static str>,
+    hottest: Option<(&'static str, f64, f64)>,
+}
+
+impl Default for HeatHaze {
+    fn default() -> Self {
+        let mut noise = FastNoiseLite::with_seed(5772);
+        noise.set_noise_type(Some(NoiseType::OpenSimplex2));
+        HeatHaze { noise, strength: 0.0, warned: HashSet::new(), hottest: None }
+    }
+}
+
+impl HeatHaze {
+    // `watched`: nombre y posición en el mundo; el primero es la cámara y es el que
+    // decide la distorsión de la pantalla
+    pub fn update(&mut self, system: &SolarSystem, watched: &[(&'static str, DVec3)], events: &mut EventQueue) {
+        let sun = system.position("sun");
+        let sun_radius = system.radius("sun");
+        self.strength = 0.0;
+        self.hottest = None;
+
+        for (i, &(name, position)) in watched.iter().enumerate() {
+            let distance = (position - sun).magnitude() / sun_radius;
+            if i == 0 {
+                self.strength = ((HAZE_START - distance) / (HAZE_START - HAZE_FULL)).clamp(0.0, 1.0) as f32;
+            }
+            let temperature = temperature(distance);
+            if distance < WARNING {
+                if self.warned.insert(name) {
+                    events.emit(SimEvent::HeatWarning { body: name, temperature });
+                }
+            } else if distance > CLEAR && self.warned.remove(name) {
+                events.emit(SimEvent::HeatCleared { body: name });
+            }
+            if self.warned.contains(name) && self.hottest.is_none_or(|(_, closest, _)| distance < closest) {
+                self.hottest = Some((name, distance, temperature));
+            }
+        }
+    }
+
+    // Desplaza cada pixel según dos canales de ruido que suben con el tiempo. El
+    // ruido se evalúa en una grilla de GRID pixeles y se interpola; la profundidad y
+    // los IDs no se tocan, así que la selección sigue la geometría real.
+    pub fn distort(&self, framebuffer: &mut Framebuffer, time: f32) {
+        if self.strength <= 0.0 {
+            return;
+        }
+        let (width, height) = (framebuffer.width, framebuffer.height);
+        let columns = width / GRID + 2;
+        let rows = height / GRID + 2;
+        let t = time * 0.05;
+        let mut offsets = Vec::with_capacity(columns * rows);
+        for row in 0..rows {
+            for column in 0..columns {
+                let (x, y) = ((column * GRID) as f32 * FREQUENCY, (row * GRID) as f32 * FREQUENCY + t * RISE);
+                let dx = self.noise.get_noise_3d(x, y, t * FLICKER);
+                let dy = self.noise.get_noise_3d(x + 97.0, y, t * FLICKER);
+                offsets.push((dx, dy));
+            }
+        }
+
+        let shift = MAX_SHIFT * self.strength;
+        let source = framebuffer.buffer.clone();
+        for y in 0..height {
+            let (row, fy) = (y / GRID, (y % GRID) as f32 / GRID as f32);
+            for x in 0..width {
+                let (column, fx) = (x / GRID, (x % GRID) as f32 / GRID as f32);
+                let at = |c: usize, r: usize| offsets[r * columns + c];
+                let lerp = |a: (f32, f32), b: (f32, f32), f: f32| (a.0 + (b.0 - a.0) * f, a.1 + (b.1 - a.1) * f);
+                let top = lerp(at(column, row), at(column + 1, row), fx);
+                let bottom = lerp(at(column, row + 1), at(column + 1, row + 1), fx);
+                let (dx, dy) = lerp(top, bottom, fy);
+
+                let sx = (x as f32 + dx * shift).round().clamp(0.0, (width - 1) as f32) as usize;
+                let sy = (y as f32 + dy * shift).round().clamp(0.0, (height - 1) as f32) as usize;
+                framebuffer.buffer[y * width + x] = source[sy * width + sx];
+            }
+        }
+    }
+
+    // Cartel rojo centrado arriba mientras alguien esté en alerta
+    pub fn draw_warning(&self, framebuffer: &mut Framebuffer) {
+        let Some((name, distance, temperature)) = self.hottest else {
+            return;
+        };
+        let line = format!("ALERTA TERMICA: {} a {:.2} radios solares, {:.0} K", name, distance, temperature);
+        let width = (text::text_width(&line, 1) + 12).min(framebuffer.width);
+        let x = (framebuffer.width - width) / 2;
+        hud::dim_rect(framebuffer, x, 4, width, text::LINE_HEIGHT + 6);
+        text::draw_text_shadowed(framebuffer, x + 6, 7, &line, WARNING_COLOR, 1);
+    }
+}
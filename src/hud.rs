@@ -0,0 +1,30 @@
+use crate::framebuffer::Framebuffer;
+use crate::text;
+
+const MARGIN: usize = 12;
+
+// Panel de información en la esquina superior izquierda, una línea por entrada
+pub fn draw_info(framebuffer: &mut Framebuffer, lines: &[String]) {
+    let mut y = MARGIN;
+    for line in lines {
+        text::draw_text_shadowed(framebuffer, MARGIN, y, line, 0xFFFFFF, 1);
+        y += text::LINE_HEIGHT;
+    }
+}
+
+// Línea del panel de información bajo el pixel (x, y), si hay una
+pub fn info_line_at(lines: &[String], x: usize, y: usize) -> Option<usize> {
+    let index = y.checked_sub(MARGIN)? / text::LINE_HEIGHT;
+    let line = lines.get(index)?;
+    (x >= MARGIN && x < MARGIN + text::text_width(line, 1)).then_some(index)
+}
+
+// Oscurece un rectángulo para que el texto de un panel resalte
+pub fn dim_rect(framebuffer: &mut Framebuffer, x: usize, y: usize, width: usize, height: usize) {
+    for py in y..(y + height).min(framebuffer.height) {
+        for px in x..(x + width).min(framebuffer.width) {
+            let index = py * framebuffer.width + px;
+            framebuffer.buffer[index] = (framebuffer.buffer[index] >> 1) & 0x7F7F7F;
+        }
+    }
+}
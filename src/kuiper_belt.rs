@@ -0,0 +1,88 @@
+use nalgebra_glm::{DVec3, Mat4, Vec4};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::color::Color;
+use crate::effects_budget;
+use crate::framebuffer::Framebuffer;
+use crate::memory::{vec_bytes, MemoryUsage};
+use crate::orbit::Orbit;
+use crate::solar_system::{self, SolarSystem};
+
+// El cinturón va de INNER a OUTER veces la órbita de Neptuno (30 a 50 UA en el real);
+// la semilla fija deja siempre los mismos objetos
+const INNER: f64 = 1.15;
+const OUTER: f64 = 1.6;
+const SEED: u64 = 47;
+// Más grueso y desordenado que el de asteroides: excentricidad e inclinación máximas
+const MAX_ECCENTRICITY: f64 = 0.15;
+const MAX_INCLINATION: f64 = 0.3;
+// Hielo apenas iluminado, tan lejos del Sol
+const ICE_COLOR: Color = Color::new(120, 130, 150);
+
+#[derive(Debug, Clone, Copy)]
+struct Object {
+    orbit: Orbit,
+    // Variación del brillo de cada objeto
+    shade: f32,
+}
+
+// Cinturón de Kuiper: un anillo ralo de objetos helados pasado Neptuno, en órbitas
+// keplerianas con la velocidad que da la tercera ley a partir de la de Neptuno. A
+// esa distancia ninguno llega a ocupar un pixel, así que se dibujan solo como puntos.
+pub struct KuiperBelt {
+    objects: Vec<Object>,
+    // Objetos que se dibujan con el presupuesto de efectos al 100% (ver AsteroidBelt)
+    count: usize,
+}
+
+impl KuiperBelt {
+    // None si el sistema no tiene a Neptuno en órbita
+    pub fn new(system: &SolarSystem, count: usize) -> Option<Self> {
+        let neptune = system.orbit("neptune")?;
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let objects = (0..effects_budget::scaled(count, effects_budget::MAX_SCALE))
+            .map(|_| {
+                let radius = neptune.radius * rng.gen_range(INNER..OUTER);
+                let speed = neptune.angular_speed * (neptune.radius / radius).powf(1.5);
+                let tau = std::f64::consts::TAU;
+                let orbit = Orbit::circular(radius, speed, 0.0)
+                    .with_eccentricity(rng.gen_range(0.0..MAX_ECCENTRICITY), rng.gen_range(0.0..tau))
+                    .with_inclination(rng.gen_range(0.0..MAX_INCLINATION), rng.gen_range(0.0..tau))
+                    .with_mean_anomaly(rng.gen_range(0.0..tau));
+                Object { orbit, shade: rng.gen_range(0.4..1.0) }
+            })
+            .collect();
+        Some(KuiperBelt { objects, count })
+    }
+
+    // `effects_scale` recorta cuántos objetos se dibujan (ver effects_budget.rs)
+    pub fn draw(&self, framebuffer: &mut Framebuffer, (view, projection): (&Mat4, &Mat4), viewport: &Mat4, system: &SolarSystem, origin: DVec3, effects_scale: f32) {
+        let view_projection = projection * view;
+        let sun = system.position("sun");
+        let (width, height) = (framebuffer.width as f32, framebuffer.height as f32);
+        let point_size = framebuffer.supersampling();
+        for object in self.objects.iter().take(effects_budget::scaled(self.count, effects_scale)) {
+            let center = solar_system::to_render(sun + object.orbit.position_at(system.time), origin);
+            let clip = view_projection * Vec4::new(center.x, center.y, center.z, 1.0);
+            if clip.w <= 0.0 {
+                continue;
+            }
+            let screen = viewport * (clip / clip.w);
+            if screen.x < 0.0 || screen.y < 0.0 || screen.x >= width || screen.y >= height {
+                continue;
+            }
+            let color = ICE_COLOR.to_linear().map(|channel| channel * object.shade);
+            for py in screen.y as usize..screen.y as usize + point_size {
+                for px in screen.x as usize..screen.x as usize + point_size {
+                    framebuffer.point_linear(px, py, screen.z, color);
+                }
+            }
+        }
+    }
+}
+
+impl MemoryUsage for KuiperBelt {
+    fn memory_bytes(&self) -> usize {
+        vec_bytes(&self.objects)
+    }
+}
@@ -0,0 +1,147 @@
+use std::time::{Duration, Instant};
+use crate::framebuffer::Framebuffer;
+use crate::hud;
+use crate::text;
+
+pub const LAYER_COUNT: usize = 6;
+
+// Capas de render: cada llamada de dibujo, hook y pase superpuesto pertenece a una
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layer {
+    // Cuerpos, naves, anillos y atmósferas
+    Bodies,
+    // Líneas de órbita con su regla
+    Orbits,
+    // Rastros de Mercurio y Venus
+    Trails,
+    // Luz zodiacal, polvo, líneas de campo y distorsión por calor
+    Effects,
+    // Paneles y texto del HUD
+    Ui,
+    // Volúmenes envolventes y mediciones
+    Debug,
+}
+
+pub const LAYERS: [Layer; LAYER_COUNT] = [Layer::Bodies, Layer::Orbits, Layer::Trails, Layer::Effects, Layer::Ui, Layer::Debug];
+
+impl Layer {
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    // Nombre para la consola y las estadísticas
+    pub fn name(self) -> &'static str {
+        match self {
+            Layer::Bodies => "bodies",
+            Layer::Orbits => "orbits",
+            Layer::Trails => "trails",
+            Layer::Effects => "effects",
+            Layer::Ui => "ui",
+            Layer::Debug => "debug",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Layer::Bodies => "Cuerpos",
+            Layer::Orbits => "Órbitas",
+            Layer::Trails => "Rastros",
+            Layer::Effects => "Efectos",
+            Layer::Ui => "Interfaz",
+            Layer::Debug => "Depuración",
+        }
+    }
+
+    // Acepta el nombre en inglés o la etiqueta en español, sin acentos ni mayúsculas
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.to_lowercase().replace('ó', "o");
+        LAYERS.into_iter().find(|layer| layer.name() == text || layer.label().to_lowercase().replace('ó', "o") == text)
+    }
+}
+
+// Visibilidad de cada capa (F1-F6 o el comando `capa` de la consola) y el tiempo
+// que tomó dibujarla en el frame, para el panel (F7) y las estadísticas
+pub struct RenderLayers {
+    visible: [bool; LAYER_COUNT],
+    timings: [Duration; LAYER_COUNT],
+    pub panel_visible: bool,
+}
+
+impl Default for RenderLayers {
+    fn default() -> Self {
+        RenderLayers { visible: [true; LAYER_COUNT], timings: [Duration::ZERO; LAYER_COUNT], panel_visible: false }
+    }
+}
+
+impl RenderLayers {
+    pub fn is_visible(&self, layer: Layer) -> bool {
+        self.visible[layer.index()]
+    }
+
+    pub fn set_visible(&mut self, layer: Layer, visible: bool) {
+        self.visible[layer.index()] = visible;
+    }
+
+    pub fn toggle(&mut self, layer: Layer) -> bool {
+        self.visible[layer.index()] = !self.visible[layer.index()];
+        self.visible[layer.index()]
+    }
+
+    pub fn begin_frame(&mut self) {
+        self.timings = [Duration::ZERO; LAYER_COUNT];
+    }
+
+    // Corre `draw` si la capa está visible y suma su duración al tiempo de la capa
+    pub fn draw<R>(&mut self, layer: Layer, draw: impl FnOnce() -> R) -> Option<R> {
+        if !self.is_visible(layer) {
+            return None;
+        }
+        let start = Instant::now();
+        let result = draw();
+        self.timings[layer.index()] += start.elapsed();
+        Some(result)
+    }
+
+    pub fn timings_ms(&self) -> [f32; LAYER_COUNT] {
+        self.timings.map(|timing| timing.as_secs_f32() * 1000.0)
+    }
+
+    // Comando de consola: `capa <nombre> [si|no]` alterna o fija la visibilidad
+    pub fn command(&mut self, args: &[&str]) -> Result<String, String> {
+        let (name, value) = match args {
+            [name] => (name, None),
+            [name, "si" | "on"] => (name, Some(true)),
+            [name, "no" | "off"] => (name, Some(false)),
+            _ => return Err(String::from("Uso: capa <cuerpos|orbitas|rastros|efectos|interfaz|depuracion> [si|no]")),
+        };
+        let layer = Layer::parse(name).ok_or_else(|| format!("Capa desconocida: {}", name))?;
+        let visible = match value {
+            Some(visible) => {
+                self.set_visible(layer, visible);
+                visible
+            }
+            None => self.toggle(layer),
+        };
+        Ok(format!("Capa {}: {}", layer.label(), if visible { "visible" } else { "oculta" }))
+    }
+
+    // Panel a la derecha, a media altura entre la telemetría y la memoria
+    pub fn draw_panel(&self, framebuffer: &mut Framebuffer) {
+        if !self.panel_visible {
+            return;
+        }
+        let width = 220.min(framebuffer.width);
+        let height = (LAYER_COUNT + 1) * text::LINE_HEIGHT + 8;
+        let x = framebuffer.width - width;
+        let y = framebuffer.height.saturating_sub(height) / 2;
+        hud::dim_rect(framebuffer, x, y, width, height);
+
+        text::draw_text_shadowed(framebuffer, x + 6, y + 4, "Capas (F7)", 0x88CCFF, 1);
+        let timings = self.timings_ms();
+        for (i, layer) in LAYERS.into_iter().enumerate() {
+            let line = format!("F{} {:<10} {:>6.2} ms", i + 1, layer.label(), timings[i]);
+            let color = if self.is_visible(layer) { 0xFFFFFF } else { 0x777777 };
+            text::draw_text_shadowed(framebuffer, x + 6, y + 4 + (i + 1) * text::LINE_HEIGHT, &line, color, 1);
+        }
+    }
+}
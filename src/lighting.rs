@@ -0,0 +1,151 @@
+use nalgebra_glm::{Vec3, dot};
+use crate::color::Color;
+use crate::material::Material;
+
+// Luz mínima del lado nocturno, en espacio lineal (un 6 % en sRGB); baja para que
+// las fases se distingan bien
+pub const AMBIENT: f32 = 0.005;
+
+// Iluminación difusa de un fragmento. Con `two_sided`, las caras que se ven
+// desde atrás (N·V < 0) usan la normal invertida, así las superficies abiertas
+// de un modelo no se ven negras por dentro.
+pub fn diffuse(normal: &Vec3, light_dir: &Vec3, view_dir: &Vec3, two_sided: bool) -> f32 {
+    let normal = if two_sided && dot(normal, view_dir) < 0.0 {
+        -normal
+    } else {
+        *normal
+    };
+    dot(&normal, light_dir).max(0.0)
+}
+
+// Brillo especular de Blinn-Phong: N·H elevado a `shininess`, con H entre la luz y
+// la cámara. Nada del lado de noche (N·L <= 0), así el reflejo no se cuela por detrás.
+pub fn specular(normal: &Vec3, light_dir: &Vec3, view_dir: &Vec3, shininess: f32, two_sided: bool) -> f32 {
+    let normal = if two_sided && dot(normal, view_dir) < 0.0 {
+        -normal
+    } else {
+        *normal
+    };
+    if dot(&normal, light_dir) <= 0.0 {
+        return 0.0;
+    }
+    let halfway = (light_dir + view_dir).normalize();
+    dot(&normal, &halfway).max(0.0).powf(shininess)
+}
+
+// Dónde está una luz de la escena
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LightKind {
+    // En un punto del espacio de render, sin atenuación: el Sol o una estrella lejana
+    // (los cuerpos están tan lejos entre sí que la distancia casi no cambiaría nada)
+    Point(Vec3),
+    // Rayos paralelos que viajan en esa dirección (normalizada)
+    Directional(Vec3),
+}
+
+// Luz que ilumina toda la escena; la primera de la lista es la principal (el Sol) y
+// es la que da `Fragment::intensity`. Las luces cercanas de alcance limitado son
+// PointLight.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Light {
+    pub kind: LightKind,
+    pub color: Color,
+    pub intensity: f32,
+}
+
+impl Light {
+    pub fn sun(position: Vec3) -> Self {
+        Light { kind: LightKind::Point(position), color: Color::WHITE, intensity: 1.0 }
+    }
+
+    // Dirección hacia la luz desde un punto de la superficie
+    pub fn direction(&self, position: &Vec3) -> Vec3 {
+        match self.kind {
+            LightKind::Point(light) => (light - position).normalize(),
+            LightKind::Directional(direction) => -direction,
+        }
+    }
+
+    // Difuso y reflejo especular de esta luz sobre `albedo`, en lineal
+    fn radiance(&self, albedo: [f32; 3], position: &Vec3, normal: &Vec3, view_dir: &Vec3, material: &Material) -> [f32; 3] {
+        let light_dir = self.direction(position);
+        let diffuse = (1.0 - AMBIENT) * diffuse(normal, &light_dir, view_dir, material.two_sided);
+        let highlight = if material.specular > 0.0 {
+            material.specular * specular(normal, &light_dir, view_dir, material.shininess.max(1.0), material.two_sided)
+        } else {
+            0.0
+        };
+        let color = self.color.to_linear();
+        [0, 1, 2].map(|channel| color[channel] * self.intensity * (albedo[channel] * diffuse + highlight))
+    }
+}
+
+// Color de `albedo` bajo todas las luces de la escena: ambiente más el difuso y el
+// reflejo de cada una, sumados en lineal y sin tope (va al buffer HDR). `sunlit`
+// atenúa la luz principal (sombras)
+pub fn shade(lights: &[Light], sunlit: f32, albedo: Color, position: &Vec3, normal: &Vec3, view_dir: &Vec3, material: &Material) -> [f32; 3] {
+    let albedo = albedo.to_linear();
+    lights.iter().enumerate().fold(albedo.map(|channel| channel * AMBIENT), |sum, (index, light)| {
+        let radiance = light.radiance(albedo, position, normal, view_dir, material);
+        let visible = if index == 0 { sunlit } else { 1.0 };
+        [0, 1, 2].map(|channel| sum[channel] + radiance[channel] * visible)
+    })
+}
+
+// Luces por objeto del camino forward; el diferido usa todas
+pub const MAX_FORWARD_LIGHTS: usize = 4;
+
+// Luz puntual pequeña (motores, lava) que solo alcanza lo que está dentro de `radius`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointLight {
+    // Cuerpo que emite la luz; no se ilumina a sí mismo
+    pub owner: &'static str,
+    // Espacio de render
+    pub position: Vec3,
+    pub color: Color,
+    pub radius: f32,
+}
+
+impl PointLight {
+    pub fn contribution(&self, position: &Vec3, normal: &Vec3, view_dir: &Vec3, two_sided: bool) -> Color {
+        let offset = self.position - position;
+        let distance = offset.magnitude();
+        if distance >= self.radius || distance <= 0.0 {
+            return Color::BLACK;
+        }
+        let falloff = (1.0 - distance / self.radius).powi(2);
+        self.color.scale_linear(falloff * diffuse(normal, &(offset / distance), view_dir, two_sided))
+    }
+}
+
+// Suma de las luces puntuales sobre un punto de la superficie
+pub fn point_lighting(lights: &[PointLight], position: &Vec3, normal: &Vec3, view_dir: &Vec3, two_sided: bool) -> Color {
+    lights
+        .iter()
+        .fold(Color::BLACK, |sum, light| sum.add_linear(light.contribution(position, normal, view_dir, two_sided)))
+}
+
+// Las `max` luces más cercanas que alcanzan una esfera, sin las del propio cuerpo
+pub fn nearest_lights(lights: &[PointLight], center: Vec3, radius: f32, owner: &str, max: usize) -> Vec<PointLight> {
+    let mut reaching: Vec<(f32, PointLight)> = lights
+        .iter()
+        .filter(|light| light.owner != owner)
+        .map(|light| ((light.position - center).magnitude() - radius, *light))
+        .filter(|(gap, light)| *gap < light.radius)
+        .collect();
+    reaching.sort_by(|a, b| a.0.total_cmp(&b.0));
+    reaching.into_iter().take(max).map(|(_, light)| light).collect()
+}
+
+// Ángulo de fase (grados) de un cuerpo visto desde `observer`: 0° es lleno,
+// 180° es nuevo
+pub fn phase_angle(body: &Vec3, sun: &Vec3, observer: &Vec3) -> f32 {
+    let to_sun = (sun - body).normalize();
+    let to_observer = (observer - body).normalize();
+    dot(&to_sun, &to_observer).clamp(-1.0, 1.0).acos().to_degrees()
+}
+
+// Fracción iluminada del disco para un ángulo de fase
+pub fn illuminated_fraction(phase_angle: f32) -> f32 {
+    (1.0 + phase_angle.to_radians().cos()) * 0.5
+}
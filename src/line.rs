@@ -1,7 +1,7 @@
 use crate::fragment::Fragment;
 use crate::vertex::Vertex;
-use crate::color::Color;
 
+// Bresenham entre las posiciones de pantalla de dos vértices, interpolando profundidad
 pub fn line(a: &Vertex, b: &Vertex) -> Vec<Fragment> {
     let mut fragments = Vec::new();
 
@@ -20,10 +20,24 @@ pub fn line(a: &Vertex, b: &Vertex) -> Vec<Fragment> {
     let sy = if y0 < y1 { 1 } else { -1 };
 
     let mut err = if dx > dy { dx / 2 } else { -dy / 2 };
+    let steps = dx.max(dy).max(1) as f32;
+    let mut step = 0;
 
     loop {
-        let z = start.z + (end.z - start.z) * (x0 - start.x as i32) as f32 / (end.x - start.x) as f32;
-        fragments.push(Fragment::new(x0 as f32, y0 as f32, Color::new(255, 255, 255), z));
+        let t = step as f32 / steps;
+        let z = start.z + (end.z - start.z) * t;
+        let color = a.color.lerp(&b.color, t);
+        let world_position = a.world_position + (b.world_position - a.world_position) * t;
+        fragments.push(Fragment::new(
+            x0 as f32,
+            y0 as f32,
+            color,
+            z,
+            a.transformed_normal,
+            1.0,
+            a.position,
+            world_position,
+        ));
 
         if x0 == x1 && y0 == y1 { break; }
 
@@ -36,6 +50,7 @@ pub fn line(a: &Vertex, b: &Vertex) -> Vec<Fragment> {
             err += dx;
             y0 += sy;
         }
+        step += 1;
     }
 
     fragments
@@ -0,0 +1,134 @@
+use std::fs;
+
+// Días desde el 1/1/1970 hasta la época de la simulación (1/1/2000, tiempo 0)
+const EPOCH_DAYS: i64 = 10_957;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateOrder {
+    DayMonthYear,
+    MonthDayYear,
+    Iso,
+}
+
+// Convenciones de formato de las lecturas del HUD según el idioma y la región de
+// la sección [locale] del archivo de escena. Los textos siguen en español; esto
+// solo cambia los separadores de miles y decimales y el orden de las fechas.
+//
+//   [locale]
+//   language = "es-GT"   # es-GT, es-MX, es-AR, es-ES, en-US, en-GB o "iso"
+#[derive(Debug, Clone, PartialEq)]
+pub struct Locale {
+    pub language: String,
+    pub thousands: char,
+    pub decimal: char,
+    pub dates: DateOrder,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale { language: String::from("es-GT"), thousands: ',', decimal: '.', dates: DateOrder::DayMonthYear }
+    }
+}
+
+impl Locale {
+    // Las regiones de habla hispana que usan coma decimal separan los miles con punto
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let (thousands, decimal, dates) = match tag {
+            "es" | "es-GT" | "es-MX" | "es-US" | "es-PR" | "es-DO" | "es-PA" => (',', '.', DateOrder::DayMonthYear),
+            "es-ES" | "es-AR" | "es-CO" | "es-CL" | "es-UY" | "es-VE" | "es-PE" | "es-EC" => ('.', ',', DateOrder::DayMonthYear),
+            "en" | "en-US" => (',', '.', DateOrder::MonthDayYear),
+            "en-GB" => (',', '.', DateOrder::DayMonthYear),
+            "iso" => (' ', '.', DateOrder::Iso),
+            _ => return None,
+        };
+        Some(Locale { language: tag.to_string(), thousands, decimal, dates })
+    }
+
+    pub fn load(path: &str) -> Self {
+        let parsed = fs::read_to_string(path).map_err(|err| err.to_string()).and_then(|text| Locale::parse(&text));
+        parsed.unwrap_or_else(|err| {
+            eprintln!("Formato regional en {}: {}; se usa es-GT", path, err);
+            Locale::default()
+        })
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let document: toml::Table = text.parse().map_err(|e: toml::de::Error| e.to_string())?;
+        let table = match document.get("locale") {
+            Some(toml::Value::Table(table)) => table,
+            Some(_) => return Err(String::from("'locale' debe ser una tabla")),
+            None => return Ok(Locale::default()),
+        };
+        match table.get("language") {
+            None => Ok(Locale::default()),
+            Some(toml::Value::String(tag)) => Locale::from_tag(tag).ok_or_else(|| format!("idioma desconocido: {}", tag)),
+            Some(_) => Err(String::from("locale.language debe ser un texto")),
+        }
+    }
+
+    // Número con separador de miles y `decimals` decimales
+    pub fn number(&self, value: f64, decimals: usize) -> String {
+        let text = format!("{:.*}", decimals, value.abs());
+        let (integer, fraction) = text.split_once('.').unwrap_or((&text, ""));
+        let mut grouped = String::new();
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i).is_multiple_of(3) {
+                grouped.push(self.thousands);
+            }
+            grouped.push(digit);
+        }
+        let sign = if value < 0.0 && text.chars().any(|c| c.is_ascii_digit() && c != '0') { "-" } else { "" };
+        if fraction.is_empty() {
+            format!("{}{}", sign, grouped)
+        } else {
+            format!("{}{}{}{}", sign, grouped, self.decimal, fraction)
+        }
+    }
+
+    pub fn kilometers(&self, km: f64) -> String {
+        format!("{} km", self.number(km, 0))
+    }
+
+    // Duración legible: horas y minutos bajo un día, días y horas bajo dos años,
+    // y años (con los días entre paréntesis) por encima
+    pub fn duration(&self, days: f64) -> String {
+        let days = days.abs();
+        if days < 1.0 {
+            let minutes = (days * 24.0 * 60.0).round() as u64;
+            format!("{} h {} min", minutes / 60, minutes % 60)
+        } else if days < 730.0 {
+            let hours = (days * 24.0).round() as u64;
+            format!("{} d {} h", self.number((hours / 24) as f64, 0), hours % 24)
+        } else {
+            format!("{} años ({} d)", self.number(days / 365.25, 2), self.number(days, 0))
+        }
+    }
+
+    // Fecha y hora simuladas a `days` días de la época
+    pub fn date(&self, days: f64) -> String {
+        let whole = days.floor();
+        let minutes = ((days - whole) * 24.0 * 60.0).floor() as u32;
+        let (year, month, day) = civil_from_days(EPOCH_DAYS + whole as i64);
+        let date = match self.dates {
+            DateOrder::DayMonthYear => format!("{:02}/{:02}/{}", day, month, year),
+            DateOrder::MonthDayYear => format!("{:02}/{:02}/{}", month, day, year),
+            DateOrder::Iso => format!("{}-{:02}-{:02}", year, month, day),
+        };
+        format!("{} {:02}:{:02}", date, minutes / 60, minutes % 60)
+    }
+}
+
+// Año, mes y día del calendario gregoriano para `days` días desde el 1/1/1970
+// (algoritmo de Howard Hinnant)
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
@@ -0,0 +1,66 @@
+use std::f32::consts::PI;
+use nalgebra_glm::{Vec3, rotate_vec3};
+use crate::color::Color;
+use crate::debug_draw;
+use crate::framebuffer::Framebuffer;
+use crate::render_hooks::HookContext;
+
+// Inclinación del eje magnético respecto al de rotación, en grados
+pub const DIPOLES: [(&str, f32); 5] = [
+    ("earth", 11.0),
+    ("jupiter", 9.6),
+    ("saturn", 0.0),
+    ("uranus", 59.0),
+    ("neptune", 47.0),
+];
+
+// Capas de líneas (parámetro L de McIlwain, en radios del planeta)
+const SHELLS: [f32; 3] = [1.8, 2.7, 4.0];
+const MERIDIANS: usize = 8;
+const SEGMENTS: usize = 40;
+const FIELD_COLOR: Color = Color::new(90, 150, 255);
+
+// Línea de campo de un dipolo en espacio de modelo (radio del planeta = 1):
+// r = L sen²θ, desde la superficie en un hemisferio hasta el otro
+fn field_line(shell: f32, azimuth: f32, tilt: f32) -> Vec<Vec3> {
+    let start = (1.0 / shell).sqrt().asin();
+    let end = PI - start;
+    let axis = Vec3::new(0.0, 0.0, 1.0);
+    (0..=SEGMENTS)
+        .map(|i| {
+            let theta = start + (end - start) * i as f32 / SEGMENTS as f32;
+            let r = shell * theta.sin().powi(2);
+            let point = Vec3::new(
+                r * theta.sin() * azimuth.cos(),
+                r * theta.cos(),
+                r * theta.sin() * azimuth.sin(),
+            );
+            rotate_vec3(&point, tilt, &axis)
+        })
+        .collect()
+}
+
+// Hook de post-render: dibuja las líneas del cuerpo si la visualización está activa.
+// Las capas exteriores son más tenues y cada línea se apaga hacia los polos.
+pub fn draw_field_lines(framebuffer: &mut Framebuffer, context: &HookContext) {
+    if !context.debug.show_field_lines {
+        return;
+    }
+    let Some(&(_, tilt)) = DIPOLES.iter().find(|(body, _)| *body == context.body) else {
+        return;
+    };
+
+    for shell in SHELLS {
+        let shell_fade = 1.5 / shell;
+        for m in 0..MERIDIANS {
+            let azimuth = 2.0 * PI * m as f32 / MERIDIANS as f32;
+            let points = field_line(shell, azimuth, tilt.to_radians());
+            for (i, pair) in points.windows(2).enumerate() {
+                let along = i as f32 / SEGMENTS as f32;
+                let pole_fade = (along * PI).sin();
+                let color = FIELD_COLOR * (0.6 * shell_fade * (0.3 + 0.7 * pole_fade));
+                debug_draw::draw_segment_additive(framebuffer, context.uniforms, pair[0], pair[1], color);
+            }
+        }
+    }
+}
@@ -1,32 +1,228 @@
-use nalgebra_glm::{Vec3, Mat4, look_at, perspective};
-use minifb::{Key, Window, WindowOptions};
-use std::time::Duration;
+
+use nalgebra_glm::{Vec3, DVec3, Mat4, perspective};
+use minifb::{Key, Window};
+use std::time::Instant;
 use std::f32::consts::PI;
+use std::sync::Arc;
+use std::io::Write;
+use std::panic::{self, AssertUnwindSafe};
+use std::collections::HashMap;
+use rayon::prelude::*;
 
 mod framebuffer;
 mod triangle;
+mod clipping;
 mod vertex;
+mod varyings;
+mod tangents;
 mod obj;
+mod primitives;
 mod color;
 mod fragment;
 mod shaders;
+mod shader_registry;
 mod camera;
+mod palettes;
+mod text;
+mod billboard;
+mod visualization;
+mod cli;
+mod stats;
+mod crash;
+mod material;
+mod lighting;
+mod line;
+mod bounds;
+mod debug_draw;
+mod solar_system;
+mod orbit;
+mod render_hooks;
+mod render_queue;
+mod compare;
+mod scene_diff;
+mod hud;
+mod ship;
+mod collision;
+mod escort;
+mod console;
+mod traffic;
+mod events;
+mod magnetosphere;
+mod particles;
+mod roche;
+mod stress;
+mod scene;
+mod scene_graph;
+mod system_file;
+mod transfer;
+mod measure;
+mod export;
+mod script;
+mod plugin;
+mod memory;
+mod foveation;
+mod tiles;
+mod postprocess;
+mod tonemap;
+mod scene_lights;
+mod shadow;
+mod texture;
+mod normal_map;
+mod skybox;
+mod starfield;
+mod asteroid_belt;
+mod kuiper_belt;
+mod comet;
+mod exhaust;
+mod trail;
+mod render_settings;
+mod benchmark;
+mod checkerboard;
+mod exposure;
+mod orbit_ruler;
+mod pacing;
+mod transparency;
+mod observer;
+mod mouse;
+mod free_fly;
+mod pilot;
+mod clock;
+mod warp;
+mod zodiacal;
+mod quiz;
+mod recorder;
+mod platform;
+mod effects_budget;
+mod heat_haze;
+mod layers;
+mod depth_of_field;
+mod onion_skin;
+mod locale;
+#[cfg(feature = "deferred")]
+mod deferred;
 
 use framebuffer::Framebuffer;
 use vertex::Vertex;
-use obj::Obj;
-use camera::Camera;
-use triangle::triangle;
-use shaders::{vertex_shader, fragment_shader, switch_shader, fragment_shader2, venus_shader, jupiter_shader, saturn_shader, mars_shader, earth_shader, uranus_shader, neptune_shader};
-use fastnoise_lite::{FastNoiseLite, NoiseType, FractalType};
+use varyings::Varyings;
+use normal_map::NormalMapping;
+use obj::{Obj, LoadOptions};
+use camera::{Camera, CameraAnimator, CameraMode};
+use solar_system::SolarSystem;
+use escort::Escort;
+use console::Console;
+use traffic::Traffic;
+use roche::RocheScenario;
+use stress::{Detail, Impostor, StressScene};
+use asteroid_belt::AsteroidBelt;
+use kuiper_belt::KuiperBelt;
+use scene::{Scene, SceneMeshes};
+use system_file::SystemFile;
+use transfer::TransferPanel;
+use measure::{Measurement, Target};
+use events::{EclipseMonitor, EventLog, EventQueue, ProximityMonitor, SimEvent};
+use render_hooks::{HookContext, RenderHooks, ALL_BODIES};
+use debug_draw::DebugFlags;
+use render_queue::{DrawCall, RenderFn, RenderQueue};
+use palettes::{Palette, PaletteSet};
+use color::Color;
+use visualization::VisualizationMode;
+use cli::Options;
+use stats::{FrameStats, StatsSink};
+use crash::CrashContext;
+use lighting::{Light, PointLight};
+use script::{ScriptLibrary, ScriptShader};
+use plugin::{PluginLibrary, PluginShader};
+use shader_registry::{Shader, ShaderRegistry};
+use memory::{MemoryBudgets, MemoryTracker, MemoryUsage, Subsystem};
+use render_settings::RenderSettings;
+use scene_lights::SceneLights;
+use shadow::ShadowMap;
+use skybox::Skybox;
+use comet::Comet;
+use exhaust::Exhaust;
+use trail::Trails;
+use orbit_ruler::OrbitView;
+use texture::{Texture, TextureLibrary};
+use benchmark::Benchmark;
+use checkerboard::Checkerboard;
+use exposure::AutoExposure;
+use pacing::{FramePacing, PacingMonitor};
+use transparency::TemporalResolve;
+use observer::SurfaceObserver;
+use mouse::{MouseLook, MouseMotion};
+use pilot::Pilot;
+use free_fly::FreeFly;
+use clock::SimulationClock;
+use warp::Warp;
+use quiz::Quiz;
+use recorder::Recorder;
+use platform::WindowSettings;
+use effects_budget::EffectsBudget;
+use heat_haze::HeatHaze;
+use depth_of_field::DepthOfField;
+use onion_skin::OnionSkin;
+use locale::Locale;
+use layers::{Layer, RenderLayers, LAYERS};
+use material::{BlendMode, DebugView, Material, MaterialTable, NormalMode, ShaderParams};
+use triangle::{triangle, assemble_triangles};
+use shaders::{write_fragment, script_shader, plugin_shader, textured_shader, tangent_vertex_shader, ring_shader, ring_opacity, uranus_ring_shader, uranus_ring_opacity, atmosphere_shader, atmosphere_opacity, ghost_past_shader, ghost_future_shader, ghost_opacity, engine_glow_shader, engine_glow_opacity, vertex_shader, cellular_shader, fragment_shader2, venus_shader, jupiter_shader, saturn_shader, mars_shader, earth_shader, uranus_shader, neptune_shader, moon_shader};
+use fastnoise_lite::{FastNoiseLite, NoiseType};
 
 pub struct Uniforms {
     model_matrix: Mat4,
     view_matrix: Mat4,
     projection_matrix: Mat4,
     viewport_matrix: Mat4,
-    time: u32,
+    // Tiempo de las animaciones (ver SimulationClock)
+    time: f32,
     noise: FastNoiseLite,
+    palette: Arc<Palette>,
+    false_color: Option<Color>,
+    material: Material,
+    // Posición de la cámara en el espacio de render (relativo al origen flotante)
+    camera_position: Vec3,
+    // Luces de la escena en el espacio de render; la primera es el Sol
+    lights: Vec<Light>,
+    // Sombras del Sol (ver shadow.rs); None si están apagadas
+    shadow_map: Option<Arc<ShadowMap>>,
+    // Luces puntuales cercanas (como mucho lighting::MAX_FORWARD_LIGHTS), las asigna la cola
+    point_lights: Vec<PointLight>,
+    // Shader genérico del objeto (ver shader_registry.rs); None usa el celular
+    shader: Option<Arc<dyn Shader>>,
+    // Shader de expresiones del cuerpo, si tiene uno en assets/shaders
+    script: Option<Arc<ScriptShader>>,
+    // Shader de una biblioteca dinámica registrada en [plugins] (feature "plugins")
+    plugin: Option<Arc<PluginShader>>,
+    // Mapa del cuerpo, si tiene uno en assets/textures
+    texture: Option<Arc<Texture>>,
+    // Mapa de normales de los cuerpos con relieve (material.relief > 0)
+    normal_map: Option<NormalMapping>,
+}
+
+impl Uniforms {
+    // Mismos parámetros con otra matriz de modelo
+    fn with_model_matrix(&self, model_matrix: Mat4) -> Uniforms {
+        Uniforms {
+            model_matrix,
+            view_matrix: self.view_matrix,
+            projection_matrix: self.projection_matrix,
+            viewport_matrix: self.viewport_matrix,
+            time: self.time,
+            noise: create_noise(),
+            palette: Arc::clone(&self.palette),
+            false_color: self.false_color,
+            material: self.material,
+            camera_position: self.camera_position,
+            lights: self.lights.clone(),
+            shadow_map: self.shadow_map.clone(),
+            point_lights: self.point_lights.clone(),
+            shader: self.shader.clone(),
+            script: self.script.clone(),
+            plugin: self.plugin.clone(),
+            texture: self.texture.clone(),
+            normal_map: self.normal_map.clone(),
+        }
+    }
 }
 
 fn create_noise() -> FastNoiseLite {
@@ -78,17 +274,30 @@ fn create_model_matrix(translation: Vec3, scale: f32, rotation: Vec3) -> Mat4 {
 }
 
 
-fn create_view_matrix(eye: Vec3, center: Vec3, up: Vec3) -> Mat4 {
-    look_at(&eye, &center, &up)
-}
+// PSNR mínimo para que --deferred-parity pase; difieren en el redondeo al
+// aplicar el material antes o después de la luz
+#[cfg(feature = "deferred")]
+const DEFERRED_PARITY_PSNR: f64 = 35.0;
+
+const NEAR_PLANE: f32 = 0.1;
+const FAR_PLANE: f32 = 1000.0;
+
+// Cada toque de +/- multiplica la exposición por raíz de 2 (medio paso)
+const EXPOSURE_STEP: f32 = std::f32::consts::SQRT_2;
+const MIN_EXPOSURE: f32 = 1.0 / 16.0;
+const MAX_EXPOSURE: f32 = 16.0;
+// Frames que tarda la cámara en pasar de un modo a otro (libre, nave, observador)
+const CAMERA_TRANSITION: f32 = 40.0;
+// Lo más que avanzan por frame las naves y las partículas con la simulación acelerada
+const MAX_LOCAL_DT: f64 = 1.0;
+// La rueda del mouse no acerca la cámara más que esto al punto de enfoque
+const MIN_ZOOM_DISTANCE: f32 = NEAR_PLANE;
 
 fn create_perspective_matrix(window_width: f32, window_height: f32) -> Mat4 {
     let fov = 45.0 * PI / 180.0;
     let aspect_ratio = window_width / window_height;
-    let near = 0.1;
-    let far = 1000.0;
 
-    perspective(fov, aspect_ratio, near, far)
+    perspective(fov, aspect_ratio, NEAR_PLANE, FAR_PLANE)
 }
 
 fn create_viewport_matrix(width: f32, height: f32) -> Mat4 {
@@ -100,71 +309,57 @@ fn create_viewport_matrix(width: f32, height: f32) -> Mat4 {
     )
 }
 
-fn render(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex]) {
-    // Vertex Shader
-    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
-    for vertex in vertex_array {
-        let transformed = vertex_shader(vertex, uniforms);
-        transformed_vertices.push(transformed);
-    }
+// Etapas de vértices y ensamblado; los vértices se reparten entre los hilos de rayon
+fn assemble<V: Varyings + Send + Sync>(
+    uniforms: &Uniforms,
+    vertex_array: &[Vertex<V>],
+    indices: &[u32],
+    vertex_stage: impl Fn(&Vertex<V>, &Uniforms) -> Vertex<V> + Sync,
+) -> Vec<[Vertex<V>; 3]> {
+    // Vertex Shader, una vez por vértice compartido
+    let transformed_vertices: Vec<Vertex<V>> = vertex_array.par_iter().map(|vertex| vertex_stage(vertex, uniforms)).collect();
 
     // Primitive Assembly
-    let mut triangles = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            triangles.push([
-                transformed_vertices[i].clone(),
-                transformed_vertices[i + 1].clone(),
-                transformed_vertices[i + 2].clone(),
-            ]);
-        }
-    }
-
-    // Rasterization
-    let mut fragments = Vec::new();
-    for tri in &triangles {
-        fragments.extend(triangle(&tri[0], &tri[1], &tri[2]));
-    }
-
-    // Fragment Processing
-    for fragment in fragments {
-        let x = fragment.position.x as usize;
-        let y = fragment.position.y as usize;
+    let triangles = assemble_triangles(&transformed_vertices, indices, uniforms);
+    stats::add_draw_call();
+    stats::add_triangles(triangles.len());
+    triangles
+}
 
-        if x < framebuffer.width && y < framebuffer.height {
-            let shaded_color = fragment_shader(&fragment, &uniforms);
-            let color = shaded_color.to_hex();
-            framebuffer.set_current_color(color);
-            framebuffer.point(x, y, fragment.depth);
-        }
-    }
+// Rasterización en paralelo. Cada triángulo genera sus fragmentos por separado y se
+// juntan en el orden de los triángulos, así que la escritura (en un solo hilo, con
+// la prueba de profundidad) ve exactamente lo mismo que antes.
+fn rasterize<V: Varyings + Send + Sync>(uniforms: &Uniforms, triangles: &[[Vertex<V>; 3]]) -> Vec<fragment::Fragment<V>> {
+    let fragments: Vec<fragment::Fragment<V>> = triangles
+        .par_iter()
+        .flat_map_iter(|tri| triangle(&tri[0], &tri[1], &tri[2], uniforms))
+        .collect();
+    stats::add_fragments(fragments.len());
+    fragments
 }
 
-fn render_sol(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex]) {
-    // Vertex Shader
-    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
-    for vertex in vertex_array {
-        let transformed = vertex_shader(vertex, uniforms);
-        transformed_vertices.push(transformed);
+// Pipeline completo de un cuerpo opaco: vértices, ensamblado, rasterización y el
+// shader de fragmentos que se pase. Las funciones render_* de abajo solo eligen el
+// shader, para poder usarse como RenderFn en la cola de dibujo.
+fn render_with_shader<S: Shader + ?Sized>(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex], indices: &[u32], shader: &S) {
+    if let Some(normal_map) = &uniforms.normal_map {
+        render_normal_mapped(framebuffer, uniforms, indices, shader, normal_map);
+        return;
     }
-
-    // Primitive Assembly
-    let mut triangles = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            triangles.push([
-                transformed_vertices[i].clone(),
-                transformed_vertices[i + 1].clone(),
-                transformed_vertices[i + 2].clone(),
-            ]);
-        }
+    let triangles = assemble(uniforms, vertex_array, indices, |vertex, uniforms| shader.vertex(vertex, uniforms));
+
+    // El G-buffer del camino diferido se sigue llenando fragmento a fragmento
+    #[cfg(feature = "deferred")]
+    let tiled = framebuffer.tiled && !framebuffer.deferred_active();
+    #[cfg(not(feature = "deferred"))]
+    let tiled = framebuffer.tiled;
+    if tiled {
+        tiles::render_tiled(framebuffer, uniforms, &triangles, |fragment, uniforms| shader.fragment(fragment, uniforms));
+        return;
     }
 
     // Rasterization
-    let mut fragments = Vec::new();
-    for tri in &triangles {
-        fragments.extend(triangle(&tri[0], &tri[1], &tri[2]));
-    }
+    let fragments = rasterize(uniforms, &triangles);
 
     // Fragment Processing
     for fragment in fragments {
@@ -172,324 +367,240 @@ fn render_sol(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array:
         let y = fragment.position.y as usize;
 
         if x < framebuffer.width && y < framebuffer.height {
-            let shaded_color = fragment_shader2(&fragment, &uniforms);
-            let color = shaded_color.to_hex();
-            framebuffer.set_current_color(color);
-
-            framebuffer.set_emission_color(0xFFFF00); // Emisión amarilla brillante
-
-            framebuffer.point(x, y, fragment.depth);
+            write_fragment(framebuffer, &fragment, uniforms, |fragment, uniforms| shader.fragment(fragment, uniforms));
         }
     }
 }
 
-fn render_venus(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex]) {
-    // Vertex Shader
-    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
-    for vertex in vertex_array {
-        let transformed = vertex_shader(vertex, uniforms);
-        transformed_vertices.push(transformed);
-    }
-
-    // Primitive Assembly
-    let mut triangles = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            triangles.push([
-                transformed_vertices[i].clone(),
-                transformed_vertices[i + 1].clone(),
-                transformed_vertices[i + 2].clone(),
-            ]);
+// Pipeline con mapa de normales: la base tangente de cada vértice (calculada de sus
+// coordenadas de textura al cargar la malla, ver tangents.rs) viaja hasta el fragmento, que tuerce su
+// normal con el mapa antes de sombrearse. Siempre por el camino inmediato: con
+// `tiled` la imagen sería la misma.
+fn render_normal_mapped<S: Shader + ?Sized>(framebuffer: &mut Framebuffer, uniforms: &Uniforms, indices: &[u32], shader: &S, normal_map: &NormalMapping) {
+    let triangles = assemble(uniforms, &normal_map.vertices, indices, tangent_vertex_shader);
+    for fragment in rasterize(uniforms, &triangles) {
+        let fragment = normal_map::perturb(fragment, &normal_map.texture, uniforms.material.relief, uniforms);
+        let (x, y) = (fragment.position.x as usize, fragment.position.y as usize);
+        if x < framebuffer.width && y < framebuffer.height {
+            write_fragment(framebuffer, &fragment, uniforms, |fragment, uniforms| shader.fragment(fragment, uniforms));
         }
     }
+}
 
-    // Rasterización y procesamiento de fragmentos
-    let mut fragments = Vec::new();
-    for tri in &triangles {
-        fragments.extend(triangle(&tri[0], &tri[1], &tri[2]));
+// Shader asignado al objeto en sus uniforms (ver ShaderRegistry)
+fn render(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex], indices: &[u32]) {
+    match &uniforms.shader {
+        Some(shader) => render_with_shader(framebuffer, uniforms, vertex_array, indices, shader.as_ref()),
+        None => render_with_shader(framebuffer, uniforms, vertex_array, indices, &cellular_shader),
     }
+}
 
-    // Fragment Shader específico de Venus
-    for fragment in fragments {
-        let x = fragment.position.x as usize;
-        let y = fragment.position.y as usize;
+// Shader de expresiones del cuerpo
+fn render_script(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex], indices: &[u32]) {
+    render_with_shader(framebuffer, uniforms, vertex_array, indices, &script_shader);
+}
 
-        if x < framebuffer.width && y < framebuffer.height {
-            // Aplicar el shader específico para Venus
-            let shaded_color = venus_shader(&fragment, uniforms);
-            let color = shaded_color.to_hex();
-            framebuffer.set_current_color(color);
-            framebuffer.point(x, y, fragment.depth);
-        }
-    }
+// Mapa del cuerpo (ver texture.rs)
+fn render_textured(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex], indices: &[u32]) {
+    render_with_shader(framebuffer, uniforms, vertex_array, indices, &textured_shader);
 }
 
-fn render_jupiter(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex]) {
-    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
-    for vertex in vertex_array {
-        let transformed = vertex_shader(vertex, uniforms);
-        transformed_vertices.push(transformed);
-    }
+// Shader del plug-in del cuerpo
+fn render_plugin(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex], indices: &[u32]) {
+    render_with_shader(framebuffer, uniforms, vertex_array, indices, &plugin_shader);
+}
 
-    let mut triangles = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            triangles.push([
-                transformed_vertices[i].clone(),
-                transformed_vertices[i + 1].clone(),
-                transformed_vertices[i + 2].clone(),
-            ]);
-        }
-    }
+// Anillos y atmósferas: mismo pipeline, con la opacidad de cada fragmento
+fn render_ring(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex], indices: &[u32]) {
+    render_translucent(framebuffer, uniforms, vertex_array, indices, ring_shader, ring_opacity);
+}
 
-    let mut fragments = Vec::new();
-    for tri in &triangles {
-        fragments.extend(triangle(&tri[0], &tri[1], &tri[2]));
-    }
+fn render_uranus_ring(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex], indices: &[u32]) {
+    render_translucent(framebuffer, uniforms, vertex_array, indices, uranus_ring_shader, uranus_ring_opacity);
+}
 
-    for fragment in fragments {
-        let x = fragment.position.x as usize;
-        let y = fragment.position.y as usize;
+fn render_atmosphere(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex], indices: &[u32]) {
+    render_translucent(framebuffer, uniforms, vertex_array, indices, atmosphere_shader, atmosphere_opacity);
+}
 
-        if x < framebuffer.width && y < framebuffer.height {
-            let shaded_color = jupiter_shader(&fragment, uniforms);
-            let color = shaded_color.to_hex();
-            framebuffer.set_current_color(color);
-            framebuffer.point(x, y, fragment.depth);
-        }
-    }
+// Fantasmas del cuerpo seleccionado, del pasado y del futuro
+fn render_ghost_past(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex], indices: &[u32]) {
+    render_translucent(framebuffer, uniforms, vertex_array, indices, ghost_past_shader, ghost_opacity);
 }
 
-fn render_saturn(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex]) {
-    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
-    for vertex in vertex_array {
-        let transformed = vertex_shader(vertex, uniforms);
-        transformed_vertices.push(transformed);
-    }
+fn render_ghost_future(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex], indices: &[u32]) {
+    render_translucent(framebuffer, uniforms, vertex_array, indices, ghost_future_shader, ghost_opacity);
+}
 
-    let mut triangles = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            triangles.push([
-                transformed_vertices[i].clone(),
-                transformed_vertices[i + 1].clone(),
-                transformed_vertices[i + 2].clone(),
-            ]);
-        }
-    }
+fn render_engine_glow(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex], indices: &[u32]) {
+    render_translucent(framebuffer, uniforms, vertex_array, indices, engine_glow_shader, engine_glow_opacity);
+}
 
-    let mut fragments = Vec::new();
-    for tri in &triangles {
-        fragments.extend(triangle(&tri[0], &tri[1], &tri[2]));
+// Objetos translúcidos: `opacity` da el alfa de cada fragmento y el material, cómo
+// se mezcla (ver BlendMode)
+fn render_translucent(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    vertex_array: &[Vertex],
+    indices: &[u32],
+    shader: fn(&fragment::Fragment, &Uniforms) -> Color,
+    opacity: fn(&fragment::Fragment, &Uniforms) -> f32,
+) {
+    // La mezcla depende del orden de los fragmentos: siempre por el camino inmediato
+    let mut triangles = assemble(uniforms, vertex_array, indices, vertex_shader);
+    if uniforms.material.blend == BlendMode::Over {
+        transparency::sort_back_to_front(&mut triangles);
+    }
+    let mut fragments = rasterize(uniforms, &triangles);
+    for fragment in &mut fragments {
+        fragment.alpha = opacity(fragment, uniforms);
     }
 
     for fragment in fragments {
-        let x = fragment.position.x as usize;
-        let y = fragment.position.y as usize;
-
-        if x < framebuffer.width && y < framebuffer.height {
-            let shaded_color = saturn_shader(&fragment, uniforms);
-            let color = shaded_color.to_hex();
-            framebuffer.set_current_color(color);
-            framebuffer.point(x, y, fragment.depth);
-        }
+        transparency::write_translucent_fragment(framebuffer, &fragment, uniforms, shader);
     }
 }
 
-fn render_mars(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex]) {
-    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
-    for vertex in vertex_array {
-        let transformed = vertex_shader(vertex, uniforms);
-        transformed_vertices.push(transformed);
-    }
-
-    let mut triangles = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            triangles.push([
-                transformed_vertices[i].clone(),
-                transformed_vertices[i + 1].clone(),
-                transformed_vertices[i + 2].clone(),
-            ]);
-        }
-    }
+fn render_sol(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex], indices: &[u32]) {
+    render_with_shader(framebuffer, uniforms, vertex_array, indices, &fragment_shader2);
+}
 
-    let mut fragments = Vec::new();
-    for tri in &triangles {
-        fragments.extend(triangle(&tri[0], &tri[1], &tri[2]));
-    }
+fn render_venus(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex], indices: &[u32]) {
+    render_with_shader(framebuffer, uniforms, vertex_array, indices, &venus_shader);
+}
 
-    for fragment in fragments {
-        let x = fragment.position.x as usize;
-        let y = fragment.position.y as usize;
+fn render_jupiter(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex], indices: &[u32]) {
+    render_with_shader(framebuffer, uniforms, vertex_array, indices, &jupiter_shader);
+}
 
-        if x < framebuffer.width && y < framebuffer.height {
-            let shaded_color = mars_shader(&fragment, uniforms);
-            let color = shaded_color.to_hex();
-            framebuffer.set_current_color(color);
-            framebuffer.point(x, y, fragment.depth);
-        }
-    }
+fn render_saturn(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex], indices: &[u32]) {
+    render_with_shader(framebuffer, uniforms, vertex_array, indices, &saturn_shader);
 }
 
-fn render_earth(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex]) {
-    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
-    for vertex in vertex_array {
-        let transformed = vertex_shader(vertex, uniforms);
-        transformed_vertices.push(transformed);
-    }
+fn render_mars(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex], indices: &[u32]) {
+    render_with_shader(framebuffer, uniforms, vertex_array, indices, &mars_shader);
+}
 
-    let mut triangles = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            triangles.push([
-                transformed_vertices[i].clone(),
-                transformed_vertices[i + 1].clone(),
-                transformed_vertices[i + 2].clone(),
-            ]);
-        }
-    }
+fn render_earth(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex], indices: &[u32]) {
+    render_with_shader(framebuffer, uniforms, vertex_array, indices, &earth_shader);
+}
 
-    let mut fragments = Vec::new();
-    for tri in &triangles {
-        fragments.extend(triangle(&tri[0], &tri[1], &tri[2]));
-    }
+fn render_moon(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex], indices: &[u32]) {
+    render_with_shader(framebuffer, uniforms, vertex_array, indices, &moon_shader);
+}
 
-    for fragment in fragments {
-        let x = fragment.position.x as usize;
-        let y = fragment.position.y as usize;
+fn render_uranus(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex], indices: &[u32]) {
+    render_with_shader(framebuffer, uniforms, vertex_array, indices, &uranus_shader);
+}
 
-        if x < framebuffer.width && y < framebuffer.height {
-            let shaded_color = earth_shader(&fragment, uniforms);
-            let color = shaded_color.to_hex();
-            framebuffer.set_current_color(color);
-            framebuffer.point(x, y, fragment.depth);
-        }
-    }
+fn render_neptune(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex], indices: &[u32]) {
+    render_with_shader(framebuffer, uniforms, vertex_array, indices, &neptune_shader);
 }
 
-fn render_uranus(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex]) {
-    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
-    for vertex in vertex_array {
-        let transformed = vertex_shader(vertex, uniforms);
-        transformed_vertices.push(transformed);
+// Lo que se dibuja alrededor de cada cuerpo además de su mesh
+fn register_hooks(hooks: &mut RenderHooks) {
+    for (body, _) in magnetosphere::DIPOLES {
+        hooks.add_post(Layer::Effects, body, magnetosphere::draw_field_lines);
     }
-
-    let mut triangles = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            triangles.push([
-                transformed_vertices[i].clone(),
-                transformed_vertices[i + 1].clone(),
-                transformed_vertices[i + 2].clone(),
-            ]);
+    hooks.add_post(Layer::Debug, ALL_BODIES, |framebuffer, context| {
+        if context.debug.show_bounds {
+            debug_draw::draw_bounds(framebuffer, context.uniforms, context.obj);
         }
-    }
+    });
+}
 
-    let mut fragments = Vec::new();
-    for tri in &triangles {
-        fragments.extend(triangle(&tri[0], &tri[1], &tri[2]));
-    }
+// Cuerpos que dejan rastro y su color
+const TRAILS: [(&str, Color); 2] = [("mercury", Color::new(176, 168, 156)), ("venus", Color::new(232, 194, 122))];
 
-    for fragment in fragments {
-        let x = fragment.position.x as usize;
-        let y = fragment.position.y as usize;
+// Capas de atmósfera translúcidas: cuerpo, nombre de la capa y escala sobre el radio
+const ATMOSPHERE_SHELLS: [(&str, &str, f32); 2] = [("earth", "earth_atmosphere", 1.06), ("venus", "venus_atmosphere", 1.05)];
 
-        if x < framebuffer.width && y < framebuffer.height {
-            let shaded_color = uranus_shader(&fragment, uniforms);
-            let color = shaded_color.to_hex();
-            framebuffer.set_current_color(color);
-            framebuffer.point(x, y, fragment.depth);
-        }
-    }
-}
+// Resplandor de los motores: nave, material y escala del halo respecto de la nave
+const ENGINE_GLOWS: [(&str, &str, f32); 2] = [("spaceship", "spaceship_glow", 1.4), ("navecita", "navecita_glow", 1.4)];
 
+// Las combinaciones exploradas se acumulan aquí para copiarlas al archivo de escena
+const EXPLORE_LOG: &str = "explored_materials.toml";
 
-fn render_neptune(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex]) {
-    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
-    for vertex in vertex_array {
-        let transformed = vertex_shader(vertex, uniforms);
-        transformed_vertices.push(transformed);
+fn log_explored(snippet: &str) {
+    println!("{}", snippet);
+    let result = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(EXPLORE_LOG)
+        .and_then(|mut file| writeln!(file, "{}", snippet));
+    if let Err(err) = result {
+        eprintln!("No se pudo escribir {}: {}", EXPLORE_LOG, err);
     }
+}
 
-    let mut triangles = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            triangles.push([
-                transformed_vertices[i].clone(),
-                transformed_vertices[i + 1].clone(),
-                transformed_vertices[i + 2].clone(),
-            ]);
+fn main() {
+    let options = Options::from_env();
+
+    // Modo utilitario: compara dos capturas y sale; el código de salida indica si difieren
+    if let Some((a, b)) = &options.compare {
+        let output = options.diff_output.as_deref().unwrap_or("diff.png");
+        match compare::run(a, b, output) {
+            Ok(metrics) => std::process::exit(if metrics.identical() { 0 } else { 1 }),
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(2);
+            }
         }
     }
 
-    let mut fragments = Vec::new();
-    for tri in &triangles {
-        fragments.extend(triangle(&tri[0], &tri[1], &tri[2]));
-    }
-
-    for fragment in fragments {
-        let x = fragment.position.x as usize;
-        let y = fragment.position.y as usize;
-
-        if x < framebuffer.width && y < framebuffer.height {
-            let shaded_color = neptune_shader(&fragment, uniforms);
-            let color = shaded_color.to_hex();
-            framebuffer.set_current_color(color);
-            framebuffer.point(x, y, fragment.depth);
+    // Modo utilitario: cambios entre dos escenas; sale con 0 si son equivalentes
+    if let Some((a, b)) = &options.scene_diff {
+        match scene_diff::run_diff(a, b) {
+            Ok(changed) => std::process::exit(if changed { 1 } else { 0 }),
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(2);
+            }
         }
     }
-}
-
-fn render_point(framebuffer: &mut Framebuffer, position: Vec3, radius: usize) {
-    let x = position.x as isize;
-    let y = position.y as isize;
-
-    let radius_squared = (radius as isize).pow(2);
 
-    for dx in -(radius as isize)..=(radius as isize) {
-        for dy in -(radius as isize)..=(radius as isize) {
-            if dx * dx + dy * dy <= radius_squared {
-                let px = x + dx;
-                let py = y + dy;
-
-                if px >= 0 && py >= 0 && (px as usize) < framebuffer.width && (py as usize) < framebuffer.height {
-                    framebuffer.set_current_color(0xFFFFFF); // Blanco
-                    framebuffer.point(px as usize, py as usize, position.z);
-                }
+    // Modo utilitario: mezcla de tres vías de escenas; sale con 1 si hubo conflictos
+    if let Some((base, ours, theirs)) = &options.scene_merge {
+        let output = options.merge_output.as_deref().unwrap_or("scene.merged.toml");
+        match scene_diff::run_merge(base, ours, theirs, output) {
+            Ok(conflicts) => std::process::exit(if conflicts.is_empty() { 0 } else { 1 }),
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(2);
             }
         }
     }
-}
-
-
-
-
 
+    // Posición, tamaño y pantalla completa de la última ejecución
+    let mut window_settings = WindowSettings::load(platform::SETTINGS_FILE);
+    let mut window = platform::open_window(&window_settings).unwrap();
+    window.update();
 
-fn main() {
-    let window_width = 800;
-    let window_height = 600;
-    let framebuffer_width = 800;
-    let framebuffer_height = 600;
-    let frame_delay = Duration::from_millis(16);
-
+    // El framebuffer sigue el tamaño de la ventana
+    let (framebuffer_width, framebuffer_height) = window.get_size();
     let mut framebuffer = Framebuffer::new(framebuffer_width, framebuffer_height);
-    let mut window = Window::new(
-        "Sistema solar",
-        window_width,
-        window_height,
-        WindowOptions::default(),
-    )
-    .unwrap();
 
-    window.set_position(500, 500);
-    window.update();
+    framebuffer.set_background_color(0x000000);
+
+    // Si algo falla se guarda un reporte y la ventana sigue abierta mostrando el
+    // error; al cerrarla se sale con el mismo código que un pánico sin atrapar
+    crash::install_panic_hook();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| run(&mut window, &mut framebuffer, &options, &mut window_settings)));
+    if result.is_err() {
+        crash::show_overlay(&mut window, &mut framebuffer);
+        std::process::exit(101);
+    }
+    window_settings.remember(&window);
+    if let Err(err) = window_settings.save(platform::SETTINGS_FILE) {
+        eprintln!("No se pudo guardar {}: {}", platform::SETTINGS_FILE, err);
+    }
+}
 
-    framebuffer.set_background_color(000000);
+fn run(window: &mut Window, framebuffer: &mut Framebuffer, options: &Options, window_settings: &mut WindowSettings) {
+    let (mut window_width, mut window_height) = window.get_size();
+    let mut suspended = false;
+    let (mut framebuffer_width, mut framebuffer_height) = (framebuffer.width, framebuffer.height);
 
-    let sun_translation = Vec3::new(0.0, 0.0, 0.0);
-    let sun_scale = 2.0; // Escala del sol
 
     let mut camera = Camera::new(
         Vec3::new(0.0, 0.0, 20.0),
@@ -497,348 +608,1311 @@ fn main() {
         Vec3::new(0.0, 3.0, 0.0)
     );
 
-    let planet_obj = Obj::load("assets/models/sphere.obj").expect("Failed to load obj");
-    let nave_obj = Obj::load("assets/models/Nave.obj").expect("Failed to load obj");
-    let sol_obj = Obj::load("assets/models/sol.obj").expect("Failed to load obj");
-    let mut time = 0;
+    // Los modelos se centran y normalizan a radio 1: la escala de cada cuerpo es su radio.
+    // Los planetas son una esfera generada (unos 1000 triángulos, ver primitives.rs),
+    // con normales y coordenadas equirectangulares exactas
+    let planet_obj = primitives::uv_sphere(32, 16);
+    let nave_obj = Obj::load_with_options("assets/models/Nave.obj", &LoadOptions::normalized()).expect("Failed to load obj");
+    let ring_obj = primitives::annulus(shaders::RING_INNER, shaders::RING_OUTER, 96);
+    let uranus_ring_obj = primitives::annulus(shaders::URANUS_RING_INNER, shaders::URANUS_RING_OUTER, 96);
+    // Malla de pocos triángulos para los cuerpos lejanos de --stress
+    let low_detail_obj = primitives::uv_sphere(16, 8);
+    // Los cuerpos con mapa usan una más fina, para que el mapa no se vea facetado
+    let textured_obj = primitives::uv_sphere(96, 48);
+    let textured_tangents = Arc::new(textured_obj.tangent_vertices());
+    // Esfera con que los planetas proyectan sombra (ver shadow.rs)
+    let shadow_casters = primitives::uv_sphere(24, 12).get_vertex_array();
+    let mut shadow_map = Arc::new(ShadowMap::new(shadow::SHADOW_MAP_SIZE));
+    let mut palettes = PaletteSet::load_dir("assets/palettes");
+    palettes.select("realistic");
+    let mut visualization = VisualizationMode::Natural;
+    let mut materials = MaterialTable::load("assets/scene.toml");
+    let mut rng = rand::thread_rng();
+    let mut normals_override: Option<NormalMode> = None;
+    let mut debug_view = DebugView::Off;
+    let mut debug = DebugFlags::default();
+    let mut scripts = ScriptLibrary::load_dir(script::SCRIPT_DIR);
+    let plugins = PluginLibrary::load("assets/scene.toml");
+    let mut shader_registry = ShaderRegistry::load("assets/scene.toml");
+    let mut memory = MemoryTracker::new(MemoryBudgets::load("assets/scene.toml"));
+    let mut render_settings = RenderSettings::load("assets/scene.toml");
+    let extra_lights = SceneLights::load("assets/scene.toml");
+    let mut textures = TextureLibrary::load_dir(texture::TEXTURE_DIR, render_settings.texture_filter);
+    let skybox = Skybox::new(&render_settings.skybox);
+    if options.foveated && render_settings.foveation.is_none() {
+        render_settings.foveation = Some(foveation::Foveation::default());
+    }
+    framebuffer.foveation = render_settings.foveation;
+    framebuffer.tiled = render_settings.tiled || options.tiled;
+    framebuffer.set_supersampling(options.ssaa.unwrap_or(render_settings.ssaa));
+    let mut checkerboard = Checkerboard::new(render_settings.checkerboard);
+    let mut auto_exposure = AutoExposure::new(render_settings.auto_exposure);
+    let mut depth_of_field = render_settings.depth_of_field;
+    let mut fxaa = render_settings.fxaa;
+    let bloom = render_settings.bloom;
+    framebuffer.transparency = render_settings.transparency;
+    framebuffer.tone_map = render_settings.tone_map;
+    let mut temporal_resolve = TemporalResolve::default();
+    let mut benchmark = options.benchmark.map(Benchmark::new);
+    // El benchmark mide sin esperas; si no, manda la línea de comandos sobre la escena
+    let frame_pacing = if benchmark.is_some() {
+        FramePacing::Uncapped
+    } else {
+        options.frame_pacing.unwrap_or(render_settings.frame_pacing)
+    };
+    frame_pacing.configure(window);
+    println!("Ritmo de frames: {}", frame_pacing.label());
+    let mut pacing_monitor = PacingMonitor::default();
+    // El benchmark compara siempre la misma carga: sin presupuesto adaptativo
+    let mut effects = EffectsBudget::new(render_settings.adaptive_effects && benchmark.is_none());
+    let mut hooks = RenderHooks::default();
+    register_hooks(&mut hooks);
+    let mut layers = RenderLayers::default();
+
+    // Canales de estadísticas por frame para herramientas externas
+    let mut stats_sinks = Vec::new();
+    if let Some(path) = &options.stats_file {
+        match StatsSink::file(path) {
+            Ok(sink) => stats_sinks.push(sink),
+            Err(err) => eprintln!("No se pudo crear {}: {}", path, err),
+        }
+    }
+    if let Some(port) = options.stats_port {
+        match StatsSink::tcp(port) {
+            Ok(sink) => stats_sinks.push(sink),
+            Err(err) => eprintln!("No se pudo abrir el puerto {}: {}", port, err),
+        }
+    }
+    let mut recorder = options.record.as_deref().and_then(|directory| match Recorder::create(directory) {
+        Ok(recorder) => {
+            println!("Registrando la simulación en {}", directory);
+            Some(recorder)
+        }
+        Err(err) => {
+            eprintln!("No se pudo crear el registro en {}: {}", directory, err);
+            None
+        }
+    });
+    let mut frame_count: u64 = 0;
+    let mut clock = SimulationClock::default();
+    // Estado orbital en f64; avanza una unidad de tiempo por frame
+    // Sistema de un archivo (--system) o, si no se pasa o no se puede leer, el incorporado
+    let mut scene = Scene::default();
+    let mut system = match options.system.as_deref().map(|path| (path, SystemFile::load(path))) {
+        Some((path, Ok(file))) => {
+            println!("Sistema de {}: {} cuerpos", path, file.system.bodies().len());
+            scene = Scene::from_bodies(file.scene);
+            for (body, shader) in file.shader_assignments {
+                if let Err(err) = shader_registry.assign(body, &shader) {
+                    eprintln!("Sistema en {}: {}.shader: {}", path, body, err);
+                }
+            }
+            if let Some((eye, target)) = file.camera {
+                camera = Camera::new(eye, target, Vec3::new(0.0, 1.0, 0.0));
+            }
+            file.system
+        }
+        Some((path, Err(err))) => {
+            eprintln!("Sistema en {}: {}; se usa el incorporado", path, err);
+            SolarSystem::new()
+        }
+        None => SolarSystem::new(),
+    };
+    let mut selected = if system.body("earth").is_some() { "earth" } else { system.bodies()[0].name };
+    let mut escort = Escort::new("navecita", "spaceship", system.position("navecita"));
+    let console = Console::spawn();
+    let mut traffic = Traffic::new(options.traffic);
+    let mut events = EventQueue::default();
+    let mut event_log = EventLog::new(10);
+    let mut eclipses = EclipseMonitor::default();
+    let mut proximity = ProximityMonitor::default();
+    let mut heat_haze = HeatHaze::default();
+    let mut transfer_panel = TransferPanel::default();
+    let mut measurement: Option<Measurement> = None;
+    let mut orbit_view = OrbitView::Hidden;
+    let mut show_labels = false;
+    // Modo observador en la superficie; guarda la cámara libre para volver a ella
+    let observer_start = SurfaceObserver::load("assets/scene.toml");
+    let locale = Locale::load("assets/scene.toml");
+    let mut mouse = MouseLook::load("assets/scene.toml");
+    let mut observer: Option<(SurfaceObserver, Camera)> = None;
+    // Nave del jugador, desde que toma los mandos; mientras la cámara la sigue se
+    // guarda la libre para volver a ella
+    let mut pilot: Option<Pilot> = None;
+    let mut camera_mode = CameraMode::Orbit;
+    let mut free_fly = FreeFly::default();
+    let mut free_camera: Option<Camera> = None;
+    // Viaje animado de la cámara libre hacia un planeta (teclas 0 a 8)
+    let mut warp: Option<Warp> = None;
+    // Cambio de modo de cámara en curso: hacia una cámara fija (la libre, al volver a
+    // ella) o, con None, hacia la que pide el modo nuevo en cada frame
+    let mut transition: Option<(CameraAnimator, Option<Camera>)> = None;
+    let mut quiz = Quiz::default();
+    let mut mouse_was_down = false;
+    let mut onion_skin = OnionSkin::default();
+    // Líneas del panel de información del frame anterior y cuál es la de los
+    // fantasmas, para saber qué se pulsa con el mouse
+    let mut info_lines: Vec<String> = Vec::new();
+    let mut ghost_line: Option<usize> = None;
+
+    // Prueba de fases: geometría Sol-Luna-cámara fija con un ángulo de fase conocido
+    if let Some(angle) = options.phase_test {
+        // Cerca de cuadratura, para que la Tierra no quede entre la Luna y la cámara
+        system.step(10.0);
+        selected = "moon";
+        camera = phase_test_camera(&system, selected, angle);
+    }
+
+    // Escena de estrés: la cámara arranca lejos y arriba para ver todas las órbitas
+    let stress = options.stress.map(|count| StressScene::new(&mut system, count));
+    if let Some(stress) = &stress {
+        println!("Escena de estrés: {} cuerpos generados ({} en total)", stress.bodies.len(), system.bodies().len());
+        for body in &stress.bodies {
+            scene.hide(body.name);
+        }
+        camera = Camera::new(Vec3::new(0.0, 40.0, 90.0), Vec3::zeros(), Vec3::new(0.0, 1.0, 0.0));
+    }
+
+    // Escenario del límite de Roche: la cámara arranca mirando a Júpiter y lo acompaña
+    let mut roche = options.roche.then(|| RocheScenario::new(&system));
+    let mut exhaust = Exhaust::new(&system, &["spaceship", "navecita"]);
+    let mut comet = Comet::new(&system, comet::COMET);
+    let mut trails = Trails::new(&TRAILS);
+    let asteroid_belt = (render_settings.asteroids > 0).then(|| AsteroidBelt::new(&system, render_settings.asteroids)).flatten();
+    let kuiper_belt = (render_settings.kuiper_belt > 0).then(|| KuiperBelt::new(&system, render_settings.kuiper_belt)).flatten();
+    if roche.is_some() {
+        selected = roche::HOST;
+        let host = system.position(roche::HOST).cast::<f32>();
+        camera = Camera::new(host + Vec3::new(0.0, 6.0, 12.0), host, Vec3::new(0.0, 1.0, 0.0));
+    }
 
     while window.is_open() {
+        let frame_start = Instant::now();
+        stats::begin_frame();
+        layers.begin_frame();
+
         if window.is_key_down(Key::Escape) {
             break;
         }
 
-        // Cambia el shader cuando se presiona la tecla "Space"
-        if window.is_key_pressed(Key::Space, minifb::KeyRepeat::No) {
-            switch_shader();
+        // Pantalla completa (ventana sin bordes) con Alt+Enter: se recrea la ventana
+        let alt = window.is_key_down(Key::LeftAlt) || window.is_key_down(Key::RightAlt);
+        if alt && window.is_key_pressed(Key::Enter, minifb::KeyRepeat::No) {
+            window_settings.remember(window);
+            window_settings.fullscreen = !window_settings.fullscreen;
+            match platform::open_window(window_settings) {
+                Ok(new_window) => {
+                    *window = new_window;
+                    frame_pacing.configure(window);
+                }
+                Err(err) => {
+                    eprintln!("No se pudo recrear la ventana: {}", err);
+                    window_settings.fullscreen = !window_settings.fullscreen;
+                }
+            }
+        }
+        // Ventana redimensionada o recreada: el framebuffer toma el nuevo tamaño
+        let size = window.get_size();
+        if size != (window_width, window_height) && size.0 > 0 && size.1 > 0 {
+            (window_width, window_height) = size;
+            framebuffer.resize(window_width, window_height);
+            (framebuffer_width, framebuffer_height) = (window_width, window_height);
+        }
+        // Ventana minimizada: no se dibuja ni se presenta nada hasta que se restaure.
+        // La simulación sigue (background_simulation) o queda en pausa aquí mismo.
+        if platform::is_minimized(window) != suspended {
+            suspended = !suspended;
+            if suspended {
+                println!("Ventana minimizada: render en pausa{}", if render_settings.background_simulation { ", la simulación sigue" } else { "" });
+            } else {
+                println!("Ventana restaurada");
+            }
+        }
+        if suspended && !render_settings.background_simulation {
+            window.update();
+            frame_pacing.wait_suspended(frame_start);
+            continue;
         }
 
-        time += 1;
-        handle_input(&window, &mut camera);
-
-        framebuffer.clear();
-
-        // Renderizar el Sol
-        let sun_model_matrix = create_model_matrix(sun_translation, sun_scale, Vec3::new(0.0, 0.0, 0.0));
-        let view_matrix = create_view_matrix(camera.eye, camera.center, camera.up);
-        let projection_matrix = create_perspective_matrix(window_width as f32, window_height as f32);
-        let viewport_matrix = create_viewport_matrix(framebuffer_width as f32, framebuffer_height as f32);
-
-        let sun_uniforms = Uniforms {
-            model_matrix: sun_model_matrix,
-            view_matrix,
-            projection_matrix,
-            viewport_matrix,
-            time,
-            noise: create_noise(),
-        };
+        // Cambia el shader genérico cuando se presiona la tecla "Space"
+        if window.is_key_pressed(Key::Space, minifb::KeyRepeat::No) {
+            println!("Shader: {}", shader_registry.cycle());
+        }
 
+        // Cambia la paleta de materiales de todos los planetas con "M"
+        if window.is_key_pressed(Key::M, minifb::KeyRepeat::No) {
+            println!("Paleta activa: {}", palettes.cycle().name);
+        }
 
+        // Alterna entre el color natural y las visualizaciones en falso color con "V"
+        if window.is_key_pressed(Key::V, minifb::KeyRepeat::No) {
+            visualization = visualization.next();
+            println!("Visualización: {}", visualization.label());
+        }
 
+        // Fuerza normales planas/suaves en toda la escena (depuración) con "N"
+        if window.is_key_pressed(Key::N, minifb::KeyRepeat::No) {
+            normals_override = match normals_override {
+                None => Some(NormalMode::Flat),
+                Some(NormalMode::Flat) => Some(NormalMode::Smooth),
+                Some(NormalMode::Smooth) => None,
+            };
+            println!("Normales: {:?}", normals_override.map_or(String::from("según escena"), |n| format!("{:?}", n)));
+        }
 
-        framebuffer.set_current_color(0xFFDD44); // Color para el Sol
-        render_sol(&mut framebuffer, &sun_uniforms, &planet_obj.get_vertex_array());
-        framebuffer.apply_emission();
+        // Inspector de materiales con "U": albedo, normales, profundidad, luz, ruido y UV
+        if window.is_key_pressed(Key::U, minifb::KeyRepeat::No) {
+            debug_view = debug_view.next();
+            println!("Vista de depuración: {}", debug_view.label());
+        }
 
-        // Planeta Mercurio orbitando alrededor del Sol
-        let planet1_distance = 2.1;
-        let planet1_translation = Vec3::new(
-            planet1_distance * (time as f32 * 0.08).cos(),
-            0.0,
-            planet1_distance * (time as f32 * 0.08).sin(),
-        );
+        // Capas de render: F1-F6 alternan cada una y F7 muestra el panel con sus tiempos
+        for (i, key) in [Key::F1, Key::F2, Key::F3, Key::F4, Key::F5, Key::F6].into_iter().enumerate() {
+            if window.is_key_pressed(key, minifb::KeyRepeat::No) {
+                let visible = layers.toggle(LAYERS[i]);
+                println!("Capa {}: {}", LAYERS[i].label(), if visible { "visible" } else { "oculta" });
+            }
+        }
+        if window.is_key_pressed(Key::F7, minifb::KeyRepeat::No) {
+            layers.panel_visible = !layers.panel_visible;
+        }
 
-        let planet1_scale = 0.7;
-        let planet1_model_matrix = create_model_matrix(planet1_translation, planet1_scale, Vec3::new(0.0, 0.0, 0.0));
+        // Muestra las esferas y cajas envolventes de cada cuerpo con "B"
+        if window.is_key_pressed(Key::B, minifb::KeyRepeat::No) {
+            debug.show_bounds = !debug.show_bounds;
+            println!("Volúmenes envolventes: {}", if debug.show_bounds { "visibles" } else { "ocultos" });
+        }
 
-        let planet1_uniforms = Uniforms {
-            model_matrix: planet1_model_matrix,
-            view_matrix,
-            projection_matrix,
-            viewport_matrix,
-            time,
-            noise: create_noise(),
-        };
+        // Líneas del campo magnético de la Tierra y los gigantes con "F"
+        if window.is_key_pressed(Key::F, minifb::KeyRepeat::No) {
+            debug.show_field_lines = !debug.show_field_lines;
+            println!("Campo magnético: {}", if debug.show_field_lines { "visible" } else { "oculto" });
+        }
 
-        render(&mut framebuffer, &planet1_uniforms, &planet_obj.get_vertex_array());
+        // Panel de memoria por subsistema con "K" (también la imprime)
+        if window.is_key_pressed(Key::K, minifb::KeyRepeat::No) {
+            memory.visible = !memory.visible;
+            if memory.visible {
+                memory.print();
+            }
+        }
 
-    // Crear rastros para el planeta
-    let trail_length = 50; // Número de puntos en el rastro
+        // Tablero de ajedrez (media pantalla sombreada por frame) con "H"
+        if window.is_key_pressed(Key::H, minifb::KeyRepeat::No) {
+            checkerboard.toggle();
+            println!("Tablero de ajedrez: {}", if checkerboard.enabled { "activo" } else { "inactivo" });
+        }
 
-    for i in 0..trail_length {
-        // Calcula un desfase temporal
-        let trail_time = time as f32 - (i as f32 * 0.2);
+        // Exposición automática (medición puntual sobre el cuerpo seleccionado) con "X"
+        if window.is_key_pressed(Key::X, minifb::KeyRepeat::No) {
+            auto_exposure.enabled = !auto_exposure.enabled;
+            println!("Exposición automática: {}", if auto_exposure.enabled { "activa" } else { "inactiva" });
+        }
 
-        // Posición del punto basado en el tiempo desfaseado
-        let trail_translation = Vec3::new(
-            planet1_distance * (trail_time * 0.08).cos(),
-            0.0,
-            planet1_distance * (trail_time * 0.08).sin() - 0.05 * i as f32, // Desfase gradual en Z
-        );
+        // Reloj de la simulación: "," y "." la hacen más lenta o más rápida, "P" la
+        // pausa y ";" avanza un paso mientras está en pausa
+        if window.is_key_pressed(Key::Comma, minifb::KeyRepeat::No) {
+            clock.slower();
+            println!("Velocidad de la simulación: x{}", clock.speed());
+        }
+        if window.is_key_pressed(Key::Period, minifb::KeyRepeat::No) {
+            clock.faster();
+            println!("Velocidad de la simulación: x{}", clock.speed());
+        }
+        if window.is_key_pressed(Key::P, minifb::KeyRepeat::No) {
+            clock.toggle_pause();
+            println!("Simulación {}", if clock.paused { "en pausa" } else { "en marcha" });
+        }
+        if window.is_key_pressed(Key::Semicolon, minifb::KeyRepeat::Yes) {
+            clock.step();
+        }
 
-        // Escala pequeña para los puntos
-        let trail_scale = 0.1;
+        // Supermuestreo (SSAA) con "F8": sin, 2x2 y 4x4 muestras por pixel
+        if window.is_key_pressed(Key::F8, minifb::KeyRepeat::No) {
+            let factor = match framebuffer.supersampling() {
+                1 => 2,
+                2 => 4,
+                _ => 1,
+            };
+            framebuffer.set_supersampling(factor);
+            println!("Supermuestreo: {}", if factor == 1 { String::from("inactivo") } else { format!("{}x{}", factor, factor) });
+        }
 
-        // Matriz de transformación para el "mini-planeta"
-        let trail_model_matrix = create_model_matrix(trail_translation, trail_scale, Vec3::new(0.0, 0.0, 0.0));
+        // Antialiasing en pantalla (FXAA) con "C"
+        if window.is_key_pressed(Key::C, minifb::KeyRepeat::No) {
+            fxaa = !fxaa;
+            println!("FXAA: {}", if fxaa { "activo" } else { "inactivo" });
+        }
 
-        // Uniforms para el rastro
-        let trail_uniforms = Uniforms {
-            model_matrix: trail_model_matrix,
-            view_matrix,
-            projection_matrix,
-            viewport_matrix,
-            time,
-            noise: create_noise(),
+        // Exposición del mapeo de tonos con "+" y "-", de a medio paso
+        let exposure_step = if window.is_key_pressed(Key::Equal, minifb::KeyRepeat::Yes) || window.is_key_pressed(Key::NumPadPlus, minifb::KeyRepeat::Yes) {
+            Some(EXPOSURE_STEP)
+        } else if window.is_key_pressed(Key::Minus, minifb::KeyRepeat::Yes) || window.is_key_pressed(Key::NumPadMinus, minifb::KeyRepeat::Yes) {
+            Some(1.0 / EXPOSURE_STEP)
+        } else {
+            None
         };
+        if let Some(step) = exposure_step {
+            let tone_map = &mut framebuffer.tone_map;
+            tone_map.exposure = (tone_map.exposure * step).clamp(MIN_EXPOSURE, MAX_EXPOSURE);
+            println!("Exposición: x{:.2} ({})", tone_map.exposure, tone_map.mapping.label());
+        }
 
-        // Renderiza el punto como un mini-planeta
-        render(&mut framebuffer, &trail_uniforms, &planet_obj.get_vertex_array());
-    }
-
+        // Profundidad de campo enfocada en el cuerpo seleccionado con "Z"
+        if window.is_key_pressed(Key::Z, minifb::KeyRepeat::No) {
+            depth_of_field.enabled = !depth_of_field.enabled;
+            println!("Profundidad de campo: {}", if depth_of_field.enabled { "activa" } else { "inactiva" });
+        }
 
+        // Órbitas con "O": ocultas, guías o con la regla en UA
+        if window.is_key_pressed(Key::O, minifb::KeyRepeat::No) {
+            orbit_view = orbit_view.next();
+            println!("Órbitas: {}", orbit_view.label());
+        }
 
-        // Planeta Venus orbitando alrededor del Sol
-        let planet2_distance = 3.3;
-        let planet2_translation = Vec3::new(
-            planet2_distance * (time as f32 * 0.05).cos(),
-            0.0,
-            planet2_distance * (time as f32 * 0.05).sin(),
-        );
-        let planet2_scale = 0.85;
-        let planet2_model_matrix = create_model_matrix(planet2_translation, planet2_scale, Vec3::new(0.0, 0.0, 0.0));
+        // Nombres de los cuerpos escritos en la escena con "Y"
+        if window.is_key_pressed(Key::Y, minifb::KeyRepeat::No) {
+            show_labels = !show_labels;
+            println!("Etiquetas en la escena: {}", if show_labels { "visibles" } else { "ocultas" });
+        }
 
-        let planet2_uniforms = Uniforms {
-            model_matrix: planet2_model_matrix,
-            view_matrix,
-            projection_matrix,
-            viewport_matrix,
-            time,
-            noise: create_noise(),
-        };
+        // Observador en la superficie del cuerpo seleccionado con "G" (o de la Tierra,
+        // si el seleccionado no rota)
+        if window.is_key_pressed(Key::G, minifb::KeyRepeat::No) {
+            match observer.take() {
+                Some((_, free_camera)) => {
+                    transition = Some((CameraAnimator::new(&camera, CAMERA_TRANSITION), Some(free_camera)));
+                    println!("Cámara libre");
+                }
+                None => {
+                    let body = if SurfaceObserver::can_stand_on(&system, selected) { selected } else { observer_start.body };
+                    let standing = SurfaceObserver { body, ..observer_start };
+                    println!("Observador en la superficie de {}", body);
+                    observer = Some((standing, Camera::new(camera.eye, camera.center, camera.up)));
+                    transition = Some((CameraAnimator::new(&camera, CAMERA_TRANSITION), None));
+                }
+            }
+        }
 
-        render_venus(&mut framebuffer, &planet2_uniforms, &planet_obj.get_vertex_array());
+        // Cámara con "Enter": libre, vuelo libre, persecución y cabina. Con las dos de
+        // la nave se toman sus mandos (la primera vez deja su órbita); en las otras la
+        // nave sigue con su inercia
+        if !alt && window.is_key_pressed(Key::Enter, minifb::KeyRepeat::No) {
+            camera_mode = camera_mode.next();
+            warp = None;
+            let animator = CameraAnimator::new(&camera, CAMERA_TRANSITION);
+            match camera_mode {
+                CameraMode::Orbit => {
+                    transition = free_camera.take().map(|free| (animator, Some(free)));
+                    println!("Cámara libre; la nave sigue su rumbo");
+                }
+                // Sigue desde donde estaba la cámara libre, sin transición
+                CameraMode::Fly => println!("Vuelo libre: W / S avanzan, A / D de costado, Q / E suben y bajan"),
+                CameraMode::Chase | CameraMode::Cockpit => {
+                    let pilot = pilot.get_or_insert_with(|| Pilot::take(&mut system, pilot::PLAYER_SHIP));
+                    println!("Cámara de {}: pilotando {}", camera_mode.label(), pilot.name);
+                    if free_camera.is_none() {
+                        free_camera = Some(Camera::new(camera.eye, camera.center, camera.up));
+                    }
+                    transition = Some((animator, None));
+                }
+            }
+        }
 
+        // Quiz de astronomía con "I"; se responde con 1-4 o con un clic sobre el cuerpo
+        if window.is_key_pressed(Key::I, minifb::KeyRepeat::No) {
+            quiz.toggle(&system, &mut rng);
+            println!("Quiz: {}", if quiz.active { "activo" } else { "inactivo" });
+        }
+        if quiz.active {
+            for (index, key) in [Key::Key1, Key::Key2, Key::Key3, Key::Key4].into_iter().enumerate() {
+                if window.is_key_pressed(key, minifb::KeyRepeat::No) {
+                    if let Some(body) = quiz.answer_option(index, &system, &mut rng) {
+                        selected = body;
+                    }
+                }
+            }
+        }
 
-            // Crear rastros para el planeta
-    let trail_length = 50; // Número de puntos en el rastro
+        // Viaje al Sol con "0" o a cada planeta con "1" a "8" (fuera del quiz y del
+        // observador): la cámara vuelve a ser libre y el cuerpo queda seleccionado
+        let warp_keys = [Key::Key0, Key::Key1, Key::Key2, Key::Key3, Key::Key4, Key::Key5, Key::Key6, Key::Key7, Key::Key8];
+        if !quiz.active && observer.is_none() {
+            for (key, body) in warp_keys.into_iter().zip(warp::WARP_TARGETS) {
+                if window.is_key_pressed(key, minifb::KeyRepeat::No) && system.body(body).is_some() {
+                    camera_mode = CameraMode::Orbit;
+                    free_camera = None;
+                    transition = None;
+                    selected = body;
+                    warp = Some(Warp::new(&camera, body));
+                    println!("Viajando a {}", body);
+                }
+            }
+        }
 
-    for i in 0..trail_length {
-        // Calcula un desfase temporal
-        let trail_time = time as f32 - (i as f32 * 0.2);
+        // Transparencia de anillos y atmósferas: mezcla ordenada o estocástica con "J"
+        if window.is_key_pressed(Key::J, minifb::KeyRepeat::No) {
+            framebuffer.transparency = framebuffer.transparency.next();
+            println!("Transparencia: {}", framebuffer.transparency.label());
+        }
 
-        // Posición del punto basado en el tiempo desfaseado
-        let trail_translation = Vec3::new(
-            planet2_distance * (trail_time * 0.05).cos(),
-            0.0,
-            planet2_distance * (trail_time * 0.05).sin() - 0.05 * i as f32, // Desfase gradual en Z
-        );
+        // Muestra u oculta el registro de telemetría con "T"
+        if window.is_key_pressed(Key::T, minifb::KeyRepeat::No) {
+            event_log.visible = !event_log.visible;
+        }
 
-        // Escala pequeña para los puntos
-        let trail_scale = 0.1;
+        // Cambia el cuerpo seleccionado con "Tab"
+        if window.is_key_pressed(Key::Tab, minifb::KeyRepeat::No) {
+            selected = system.next_body(selected);
+            println!("Seleccionado: {}", selected);
+            if let Some(measurement) = &mut measurement {
+                measurement.retarget(Target::Body(selected));
+            }
+        }
 
-        // Matriz de transformación para el "mini-planeta"
-        let trail_model_matrix = create_model_matrix(trail_translation, trail_scale, Vec3::new(0.0, 0.0, 0.0));
+        // Medir: "L" fija el cuerpo seleccionado como primer extremo y Tab elige el otro;
+        // otra vez "L" termina la medición
+        if window.is_key_pressed(Key::L, minifb::KeyRepeat::No) {
+            measurement = match measurement {
+                Some(_) => None,
+                None => {
+                    println!("Medición desde {}: elige el otro cuerpo con Tab", selected);
+                    Some(Measurement::new(Target::Body(selected), Target::Body(selected)))
+                }
+            };
+        }
 
-        // Uniforms para el rastro
-        let trail_uniforms = Uniforms {
-            model_matrix: trail_model_matrix,
-            view_matrix,
-            projection_matrix,
-            viewport_matrix,
-            time,
-            noise: create_noise(),
-        };
+        // Explorar: parámetros aleatorios para el material del cuerpo seleccionado con "R"
+        if window.is_key_pressed(Key::R, minifb::KeyRepeat::No) {
+            let mut explored = materials.get(selected);
+            explored.params = ShaderParams::random(&mut rng);
+            materials.set(selected, explored);
+            log_explored(&explored.params.to_toml(selected));
+        }
 
-        // Renderiza el punto como un mini-planeta
-        render(&mut framebuffer, &trail_uniforms, &planet_obj.get_vertex_array());
-    }
+        // Captura del frame (y de los buffers auxiliares con --export-aux) con "F12"
+        let capture_requested = window.is_key_pressed(Key::F12, minifb::KeyRepeat::No);
 
-        // Planeta Tierra orbitando alrededor del Sol
-        let planet3_distance = 5.1;
-        let planet3_translation = Vec3::new(
-            planet3_distance * (time as f32 * 0.045).cos(),
-            0.0,
-            planet3_distance * (time as f32 * 0.045).sin(),
-        );
-        let planet3_scale = 1.0;
-        let planet3_model_matrix = create_model_matrix(planet3_translation, planet3_scale, Vec3::new(0.0, 0.0, 0.0));
+        // Lo que avanza la simulación en este frame y el tiempo de las animaciones
+        let dt = clock.advance();
+        let time = clock.time as f32;
+        // Recarga en caliente de los shaders de expresiones, dos veces por segundo
+        if frame_count.is_multiple_of(30) {
+            scripts.reload();
+        }
+        let material = |body: &str| materials.get(body).with_normals_override(normals_override).with_debug_view(debug_view);
+        for line in console.poll() {
+            run_command(&line, &mut escort, &mut transfer_panel, &mut measurement, &mut layers, &mut onion_skin, &mut palettes, &system, &mut events);
+        }
 
-        let planet3_uniforms = Uniforms {
-            model_matrix: planet3_model_matrix,
-            view_matrix,
-            projection_matrix,
-            viewport_matrix,
-            time,
-            noise: create_noise(),
+        // Las órbitas salen de fórmulas cerradas y avanzan `dt` entero; las naves y las
+        // partículas se integran paso a paso y no pasan de MAX_LOCAL_DT por frame, así
+        // a x1000 no se vuelven inestables (se quedan atrás de los planetas)
+        let local_dt = dt.min(MAX_LOCAL_DT);
+        if options.phase_test.is_none() && dt > 0.0 {
+            let host_before = system.position(roche::HOST);
+            system.step(dt);
+            if let Some(scenario) = &mut roche {
+                scenario.dust.set_capacity(effects.count(roche::DUST_CAPACITY));
+                scenario.update(&system, local_dt, &mut rng, &mut events);
+                let shift = (system.position(roche::HOST) - host_before).cast::<f32>();
+                camera.eye += shift;
+                camera.center += shift;
+            }
+            if let Some(pilot) = &mut pilot {
+                pilot.update(&mut system, local_dt);
+            }
+            let transfer_to = escort.transfer_destination();
+            escort.update(&system, local_dt);
+            if let Some(body) = transfer_to.filter(|_| escort.transfer_destination().is_none()) {
+                let status = format!("llegó a {} y queda en órbita", solar_system::display_name(body));
+                events.emit(SimEvent::MissionUpdated { ship: escort.name, status });
+            }
+            system.set_position(escort.name, escort.ship.position);
+            exhaust.update(&system, local_dt, effects.count(exhaust::EXHAUST_CAPACITY), &mut rng);
+            if let Some(comet) = &mut comet {
+                comet.update(&system, local_dt, effects.count(comet::DUST_CAPACITY), &mut rng);
+            }
+            trails.record(&system);
+            for arrival in traffic.update(&system, local_dt, &mut rng) {
+                events.emit(SimEvent::ShipArrived { from: arrival.from, to: arrival.to });
+            }
+            let ships = ["spaceship", "navecita"];
+            eclipses.update(&system, &ships, &mut events);
+            proximity.update(&system, &ships, 0.5, &mut events);
+            if let Some(writer) = &mut recorder {
+                if let Err(err) = writer.record(&system, &escort, &traffic, dt) {
+                    eprintln!("Se detiene el registro CSV: {}", err);
+                    recorder = None;
+                }
+            }
+        }
+        // El benchmark recorre siempre la misma escena, sin mover la cámara
+        if options.phase_test.is_none() && benchmark.is_none() {
+            let motion = mouse.update(window);
+            match (&mut observer, &mut pilot) {
+                (Some((standing, _)), _) => standing.handle_input(window),
+                (None, Some(pilot)) if camera_mode.follows_ship() => pilot.handle_input(window),
+                (None, _) if warp.is_some() || transition.is_some() => {}
+                (None, _) if camera_mode == CameraMode::Fly => free_fly.handle_input(window, &mut camera, motion),
+                _ => handle_input(window, &mut camera, motion),
+            }
+        }
+        // Cámara que pide el modo: la del observador gira con el cuerpo y las de la nave
+        // la siguen
+        let mode_camera = match (&observer, &pilot, camera_mode) {
+            (Some((standing, _)), _, _) => Some(standing.camera(&system)),
+            (None, Some(pilot), CameraMode::Chase) => Some(pilot.chase_camera(&system)),
+            (None, Some(pilot), CameraMode::Cockpit) => Some(pilot.cockpit_camera(&system)),
+            _ => None,
         };
+        if let Some((animator, fixed)) = &mut transition {
+            let done = match fixed.as_ref().or(mode_camera.as_ref()) {
+                Some(target) => animator.update(&mut camera, target, 1.0),
+                None => true,
+            };
+            if done {
+                transition = None;
+            }
+        } else if let Some(target) = mode_camera {
+            if observer.is_none() && camera_mode == CameraMode::Chase {
+                camera.follow(&target, pilot::CHASE_SMOOTHING);
+            } else {
+                camera = target;
+            }
+        }
+        if let Some(active) = &mut warp {
+            if active.update(&mut camera, &system, 1.0) {
+                events.emit(SimEvent::WarpCompleted { body: active.body });
+                warp = None;
+            }
+        }
+        // Roce solar: la cámara decide la distorsión; la escolta también recibe alertas
+        heat_haze.update(&system, &[("cámara", camera.eye.cast()), (escort.name, escort.ship.position)], &mut events);
+        if let Some(measurement) = &mut measurement {
+            measurement.update(&system, camera.eye.cast(), 1.0);
+        }
+        if suspended {
+            window.update();
+            frame_pacing.wait_suspended(frame_start);
+            continue;
+        }
 
-        render_earth(&mut framebuffer, &planet3_uniforms, &planet_obj.get_vertex_array());
+        framebuffer.clear();
+        checkerboard.begin_frame(framebuffer);
+        framebuffer.frame = frame_count as u32;
 
-        // Planeta Marte orbitando alrededor del Sol
-        let planet4_distance = 6.4;
-        let planet4_translation = Vec3::new(
-            planet4_distance * (time as f32 * 0.04).cos(),
-            0.0,
-            planet4_distance * (time as f32 * 0.04).sin(),
-        );
-        let planet4_scale = 0.7;
-        let planet4_model_matrix = create_model_matrix(planet4_translation, planet4_scale, Vec3::new(0.0, 0.0, 0.0));
+        // Renderizar el Sol
+        // Origen flotante: todo se traslada por -origin antes de armar las matrices
+        let origin = camera.render_origin().cast::<f64>();
+        let camera_position = solar_system::to_render(camera.eye.cast(), origin);
+        let light_position = system.render_position("sun", origin);
+        let scene_lights = extra_lights.place(Light::sun(light_position), &system, origin);
+        let shadows = render_settings.shadows.then(|| {
+            let map = Arc::make_mut(&mut shadow_map);
+            map.clear(light_position);
+            scene.draw_shadow_casters(map, &system, origin, &shadow_casters);
+            Arc::clone(&shadow_map)
+        });
+        let point_lights = collect_point_lights(&system, &traffic, origin, shader_registry.for_body("mercury").emissive());
+        let view_matrix = camera.relative_view_matrix();
+        let projection_matrix = create_perspective_matrix(window_width as f32, window_height as f32);
+        // Con supermuestreo el framebuffer es más grande que la ventana hasta resolverlo
+        let viewport_matrix = create_viewport_matrix(framebuffer.width as f32, framebuffer.height as f32);
+        skybox.draw(framebuffer, &view_matrix, &projection_matrix, time);
 
-        let planet4_uniforms = Uniforms {
-            model_matrix: planet4_model_matrix,
+        // Lo común a todo el cuadro; cada dibujo cambia la matriz de modelo y su material
+        let base = Uniforms {
+            model_matrix: Mat4::identity(),
             view_matrix,
             projection_matrix,
             viewport_matrix,
             time,
             noise: create_noise(),
+            palette: palettes.active(),
+            false_color: None,
+            material: Material::default(),
+            camera_position,
+            lights: scene_lights.clone(),
+            shadow_map: shadows.clone(),
+            point_lights: Vec::new(),
+            shader: None,
+            script: None,
+            plugin: None,
+            texture: None,
+            normal_map: None,
         };
+        let mut queue = RenderQueue::default();
+
+        // Sol, planetas, lunas y naves según la descripción de la escena (scene.rs)
+        let meshes = SceneMeshes { planet: &planet_obj, ship: &nave_obj };
+        scene.queue_draw_calls(&mut queue, &system, origin, &meshes, |body, model_matrix| Uniforms {
+            false_color: visualization.false_color(body, system.position(body).cast::<f32>()),
+            material: material(body),
+            ..base.with_model_matrix(model_matrix)
+        });
+
+        // Anillos de Saturno y de Urano, translúcidos, en el ecuador de su planeta: se
+        // inclinan con su eje (Urano gira casi acostado y los suyos quedan casi de canto)
+        let rings: [(&str, &'static str, RenderFn, &Obj); 2] = [
+            ("saturn", "saturn_ring", render_ring, &ring_obj),
+            ("uranus", "uranus_ring", render_uranus_ring, &uranus_ring_obj),
+        ];
+        for (planet, ring, render_fn, obj) in rings {
+            if system.body(planet).is_none() {
+                continue;
+            }
+            let ring_uniforms = Uniforms {
+                false_color: visualization.false_color(planet, system.position(planet).cast::<f32>()),
+                material: material(ring),
+                ..base.with_model_matrix(system.axis_matrix(planet, origin) * create_model_matrix(Vec3::zeros(), system.radius(planet) as f32, Vec3::zeros()))
+            };
+            queue.push(DrawCall::new(ring, "ring", render_fn, obj, ring_uniforms).translucent());
+        }
 
-        render_mars(&mut framebuffer, &planet4_uniforms, &planet_obj.get_vertex_array());
+        // Tráfico: todas las naves comparten mesh y shader, la cola las agrupa
+        for npc in &traffic.ships {
+            let npc_model_matrix = create_model_matrix(solar_system::to_render(npc.position, origin), traffic::NPC_SCALE, Vec3::new(0.0, 0.0, 0.0));
+            let npc_uniforms = Uniforms {
+                material: material("npc"),
+                ..base.with_model_matrix(npc_model_matrix)
+            };
+            queue.push(DrawCall::new("npc", "default", render, &nave_obj, npc_uniforms));
+        }
 
-        // Planeta Júpiter orbitando alrededor del Sol
-        let planet5_distance = 7.9;
-        let planet5_translation = Vec3::new(
-            planet5_distance * (time as f32 * 0.035).cos(),
-            0.0,
-            planet5_distance * (time as f32 * 0.035).sin(),
-        );
-        let planet5_scale = 2.1;
-        let planet5_model_matrix = create_model_matrix(planet5_translation, planet5_scale, Vec3::new(0.0, 0.0, 0.0));
+        // Capas de atmósfera sobre algunos planetas
+        for (body, shell, scale) in ATMOSPHERE_SHELLS {
+            let shell_uniforms = Uniforms {
+                material: material(shell),
+                ..base.with_model_matrix(create_model_matrix(system.render_position(body, origin), system.radius(body) as f32 * scale, Vec3::new(0.0, 0.0, 0.0)))
+            };
+            queue.push(DrawCall::new(shell, "atmosphere", render_atmosphere, &planet_obj, shell_uniforms).translucent());
+        }
 
-        let planet5_uniforms = Uniforms {
-            model_matrix: planet5_model_matrix,
-            view_matrix,
-            projection_matrix,
-            viewport_matrix,
-            time,
-            noise: create_noise(),
-        };
+        // Resplandor aditivo alrededor de las naves
+        for (ship, glow, scale) in ENGINE_GLOWS {
+            if system.body(ship).is_none() {
+                continue;
+            }
+            let glow_uniforms = Uniforms {
+                material: material(glow),
+                ..base.with_model_matrix(create_model_matrix(system.render_position(ship, origin), system.radius(ship) as f32 * scale, Vec3::new(0.0, 0.0, 0.0)))
+            };
+            queue.push(DrawCall::new(glow, "engine_glow", render_engine_glow, &planet_obj, glow_uniforms).translucent());
+        }
 
-        render_jupiter(&mut framebuffer, &planet5_uniforms, &planet_obj.get_vertex_array());
+        // Fantasmas del cuerpo seleccionado en la capa de rastros
+        for (offset, position) in onion_skin.ghosts(&system, selected) {
+            let ghost_uniforms = Uniforms {
+                material: Material::default().with_debug_view(debug_view),
+                ..base.with_model_matrix(create_model_matrix(solar_system::to_render(position, origin), system.radius(selected) as f32, Vec3::new(0.0, 0.0, 0.0)))
+            };
+            let render_ghost: RenderFn = if offset < 0.0 { render_ghost_past } else { render_ghost_future };
+            queue.push(DrawCall::new("ghost", "ghost", render_ghost, &planet_obj, ghost_uniforms).translucent().in_layer(Layer::Trails));
+        }
 
-        // Planeta Saturno orbitando alrededor del Sol
-        let planet6_distance = 9.9;
-        let planet6_translation = Vec3::new(
-            planet6_distance * (time as f32 * 0.03).cos(),
-            0.0,
-            planet6_distance * (time as f32 * 0.03).sin(),
-        );
-        let planet6_scale = 1.8;
-        let planet6_model_matrix = create_model_matrix(planet6_translation, planet6_scale, Vec3::new(0.0, 0.0, 0.0));
+        // Cometa del escenario de Roche (o sus fragmentos), con el shader de la Luna
+        if let Some(scenario) = &roche {
+            for (position, radius) in scenario.positions(&system) {
+                let piece_uniforms = Uniforms {
+                    material: material(roche::COMET),
+                    ..base.with_model_matrix(create_model_matrix(solar_system::to_render(position, origin), radius as f32, Vec3::new(0.0, 0.0, 0.0)))
+                };
+                queue.push(DrawCall::new(roche::COMET, "moon", render_moon, &planet_obj, piece_uniforms));
+            }
+        }
 
-        let planet6_uniforms = Uniforms {
-            model_matrix: planet6_model_matrix,
-            view_matrix,
-            projection_matrix,
-            viewport_matrix,
-            time,
-            noise: create_noise(),
-        };
+        // Cuerpos de --stress con el shader de su tipo y el nivel de detalle según su
+        // tamaño en pantalla; los más chicos no pasan por la cola y se dibujan como impostores
+        let mut impostors = Vec::new();
+        for body in stress.iter().flat_map(|scene| &scene.bodies) {
+            let center = system.render_position(body.name, origin);
+            let radius = system.radius(body.name) as f32;
+            let pixels = stress::projected_radius(&view_matrix, &projection_matrix, framebuffer_height, center, radius);
+            let obj = match stress::detail(pixels) {
+                Detail::Impostor => {
+                    impostors.push(Impostor::new(center, pixels * framebuffer.supersampling() as f32, body.kind));
+                    continue;
+                }
+                Detail::Low => &low_detail_obj,
+                Detail::Full => &planet_obj,
+            };
+            let body_uniforms = Uniforms {
+                false_color: visualization.false_color(body.name, system.position(body.name).cast::<f32>()),
+                material: body.material(material(body.kind.shader())),
+                ..base.with_model_matrix(create_model_matrix(center, radius, Vec3::new(0.0, system.rotation(body.name) as f32, 0.0)))
+            };
+            let render: RenderFn = match body.kind {
+                stress::StressKind::Rocky => render_mars,
+                stress::StressKind::Gas => render_neptune,
+                stress::StressKind::Moon => render_moon,
+            };
+            let queued = queue.len();
+            queue.push(DrawCall::new(body.name, body.kind.shader(), render, obj, body_uniforms));
+            if queue.len() > queued && std::ptr::eq(obj, &low_detail_obj) {
+                stats::add_low_detail_body();
+            }
+        }
 
-        render_saturn(&mut framebuffer, &planet6_uniforms, &planet_obj.get_vertex_array());
+        // Las llamadas con el shader genérico reciben el que les toca en el registro
+        for call in queue.calls_mut().filter(|call| call.shader == "default") {
+            call.uniforms.shader = Some(shader_registry.for_body(call.body));
+        }
+        // Los cuerpos con mapa se dibujan con él sobre una esfera con coordenadas
+        // equirectangulares; un script o un plug-in del mismo cuerpo tiene prioridad
+        if !textures.is_empty() {
+            for call in queue.calls_mut() {
+                if let Some(texture) = textures.get(call.body) {
+                    call.uniforms.texture = Some(texture);
+                    call.render = render_textured;
+                    call.shader = "textured";
+                    call.obj = &textured_obj;
+                }
+            }
+        }
+        // Relieve: mapa de normales sobre la esfera UV, que sí tiene una base tangente
+        // continua (la del .obj no)
+        for call in queue.calls_mut().filter(|call| call.uniforms.material.relief > 0.0) {
+            call.uniforms.normal_map = Some(NormalMapping { texture: textures.normal_map(call.body), vertices: Arc::clone(&textured_tangents) });
+            call.obj = &textured_obj;
+        }
+        // Los cuerpos con un shader de expresiones en assets/shaders lo usan en vez del suyo
+        if !scripts.is_empty() {
+            for call in queue.calls_mut() {
+                if let Some(script) = scripts.get(call.body) {
+                    call.uniforms.script = Some(script);
+                    call.render = render_script;
+                    call.shader = "script";
+                }
+            }
+        }
+        // Un plug-in compilado tiene prioridad sobre el script del mismo cuerpo
+        if !plugins.is_empty() {
+            for call in queue.calls_mut() {
+                if let Some(plugin) = plugins.get(call.body) {
+                    call.uniforms.plugin = Some(plugin);
+                    call.render = render_plugin;
+                    call.shader = "plugin";
+                }
+            }
+        }
+        queue.assign_lights(&point_lights, lighting::MAX_FORWARD_LIGHTS);
+        queue.sort();
+        // Mientras la cámara baja a la superficie el cuerpo se sigue viendo entero
+        let standing = observer.as_ref().filter(|_| transition.is_none()).map(|(standing, _)| standing);
+        let calls: Vec<DrawCall> = queue
+            .drain()
+            .filter(|call| standing.is_none_or(|standing| !standing.hides(call.body)))
+            .filter(|call| camera_mode != CameraMode::Cockpit || pilot.as_ref().is_none_or(|pilot| !pilot.hides(call.body)))
+            .collect();
+        let velocities = checkerboard.object_velocities(&calls);
+
+        // Paridad: el mismo frame por el camino forward y por el diferido, comparados
+        #[cfg(feature = "deferred")]
+        if options.deferred_parity {
+            draw_calls(framebuffer, &calls, &hooks, debug, &mut layers);
+            framebuffer.apply_tone_map();
+            let forward = export::color_image(framebuffer);
+            framebuffer.clear();
+            skybox.draw(framebuffer, &view_matrix, &projection_matrix, time);
+            framebuffer.begin_deferred();
+            let object_ids = draw_calls(framebuffer, &calls, &hooks, debug, &mut layers);
+            deferred::resolve(framebuffer, &scene_lights, shadows.as_deref(), camera_position, &point_lights, &object_ids);
+            framebuffer.apply_tone_map();
+            let metrics = compare::compare(&forward, &export::color_image(framebuffer));
+            println!("Paridad forward/diferido: máxima diferencia {}, PSNR {:.2} dB, SSIM {:.5}", metrics.max_delta, metrics.psnr, metrics.ssim);
+            std::process::exit(if metrics.psnr >= DEFERRED_PARITY_PSNR { 0 } else { 1 });
+        }
+        #[cfg(feature = "deferred")]
+        if options.deferred {
+            framebuffer.begin_deferred();
+        }
 
-        // Planeta Urano orbitando alrededor del Sol
-        let planet7_distance = 12.1;
-        let planet7_translation = Vec3::new(
-            planet7_distance * (time as f32 * 0.025).cos(),
-            0.0,
-            planet7_distance * (time as f32 * 0.025).sin(),
-        );
-        let planet7_scale = 1.6;
-        let planet7_model_matrix = create_model_matrix(planet7_translation, planet7_scale, Vec3::new(0.0, 0.0, 0.0));
+        let object_ids = draw_calls(framebuffer, &calls, &hooks, debug, &mut layers);
+        if !impostors.is_empty() {
+            framebuffer.set_current_id(0);
+            layers.draw(Layer::Bodies, || {
+                for impostor in &impostors {
+                    impostor.draw(framebuffer, (&view_matrix, &projection_matrix), &viewport_matrix, light_position);
+                }
+            });
+        }
+        // Cinturón de asteroides: una malla para todas las rocas, o un punto las lejanas
+        if let Some(belt) = &asteroid_belt {
+            let belt_uniforms = Uniforms {
+                material: material("asteroids"),
+                ..base.with_model_matrix(Mat4::identity())
+            };
+            framebuffer.set_current_id(0);
+            layers.draw(Layer::Bodies, || belt.draw(framebuffer, belt_uniforms, &system, origin, render_moon, effects.scale()));
+        }
+        // Cinturón de Kuiper: solo puntos
+        if let Some(belt) = &kuiper_belt {
+            framebuffer.set_current_id(0);
+            layers.draw(Layer::Bodies, || belt.draw(framebuffer, (&view_matrix, &projection_matrix), &viewport_matrix, &system, origin, effects.scale()));
+        }
+        // Clic del quiz: el buffer de IDs todavía tiene solo la geometría del frame
+        let mouse_down = window.get_mouse_down(minifb::MouseButton::Left);
+        let click = (mouse_down && !mouse_was_down)
+            .then(|| window.get_mouse_pos(minifb::MouseMode::Discard))
+            .flatten()
+            .map(|(mouse_x, mouse_y)| {
+                let x = (mouse_x * framebuffer_width as f32 / window_width as f32) as usize;
+                let y = (mouse_y * framebuffer_height as f32 / window_height as f32) as usize;
+                (x, y)
+            });
+        mouse_was_down = mouse_down;
+        if let Some((x, y)) = click {
+            // Un clic en la línea de los fantasmas del panel de información los alterna
+            if ghost_line.is_some() && hud::info_line_at(&info_lines, x, y) == ghost_line {
+                onion_skin.toggle();
+                println!("Fantasmas: {}", if onion_skin.enabled { "visibles" } else { "ocultos" });
+            } else if quiz.active {
+                let samples = framebuffer.supersampling();
+                if let Some(body) = quiz::pick(framebuffer, &object_ids, &system, x * samples, y * samples) {
+                    quiz.answer_pick(body, &system, &mut rng);
+                    selected = body;
+                }
+            }
+        }
+        // Los pixeles sin sombrear (tablero o periferia foveada) se completan antes de los
+        // overlays; en el camino diferido, después del pase de luces
+        #[cfg(feature = "deferred")]
+        let deferred_frame = framebuffer.deferred_active();
+        #[cfg(not(feature = "deferred"))]
+        let deferred_frame = false;
+        if !deferred_frame {
+            checkerboard.reconstruct(framebuffer, &velocities);
+            foveation::upscale(framebuffer);
+            temporal_resolve.resolve(framebuffer);
+            auto_exposure.update(framebuffer, &object_ids, selected);
+        }
 
-        let planet7_uniforms = Uniforms {
-            model_matrix: planet7_model_matrix,
-            view_matrix,
-            projection_matrix,
-            viewport_matrix,
-            time,
-            noise: create_noise(),
+        // Lo que se dibuja directamente en espacio de render (matriz de modelo identidad)
+        let overlay_uniforms = base.with_model_matrix(Mat4::identity());
+        layers.draw(Layer::Effects, || {
+            zodiacal::draw(framebuffer, &overlay_uniforms, &system, origin, render_settings.zodiacal_light, effects.scale());
+        });
+        if let Some(standing) = standing {
+            let atmosphere = ATMOSPHERE_SHELLS.iter().any(|(body, _, _)| *body == standing.body);
+            layers.draw(Layer::Bodies, || standing.draw_horizon(framebuffer, &overlay_uniforms, &system, origin, atmosphere));
+        }
+        let ruler_labels =
+            layers.draw(Layer::Orbits, || orbit_ruler::draw(framebuffer, &overlay_uniforms, &system, origin, orbit_view)).unwrap_or_default();
+        if let Some(scenario) = &roche {
+            layers.draw(Layer::Effects, || scenario.dust.draw(framebuffer, &overlay_uniforms, origin));
+        }
+        layers.draw(Layer::Effects, || exhaust.draw(framebuffer, &overlay_uniforms, origin));
+        if let Some(comet) = &comet {
+            layers.draw(Layer::Effects, || comet.draw(framebuffer, &overlay_uniforms, &system, origin));
+        }
+        // Los rastros tienen su propio material ([bodies.trail]), no el de su cuerpo
+        let trail_uniforms = Uniforms {
+            material: material("trail"),
+            lights: Vec::new(),
+            shadow_map: None,
+            ..base.with_model_matrix(Mat4::identity())
         };
+        layers.draw(Layer::Trails, || trails.draw(framebuffer, &trail_uniforms, origin, effects.scale()));
+        if let Some(measurement) = &measurement {
+            layers.draw(Layer::Debug, || measurement.draw(framebuffer, &overlay_uniforms, &system, origin));
+        }
 
-        render_uranus(&mut framebuffer, &planet7_uniforms, &planet_obj.get_vertex_array());
-
-        // Planeta Neptuno orbitando alrededor del Sol
-        let planet8_distance = 15.2;
-        let planet8_translation = Vec3::new(
-            planet8_distance * (time as f32 * 0.02).cos(),
-            0.0,
-            planet8_distance * (time as f32 * 0.02).sin(),
-        );
-        let planet8_scale = 1.6;
-        let planet8_model_matrix = create_model_matrix(planet8_translation, planet8_scale, Vec3::new(0.0, 0.0, 0.0));
-
-        let planet8_uniforms = Uniforms {
-            model_matrix: planet8_model_matrix,
-            view_matrix,
-            projection_matrix,
-            viewport_matrix,
-            time,
-            noise: create_noise(),
+        #[cfg(feature = "deferred")]
+        if framebuffer.deferred_active() {
+            deferred::resolve(framebuffer, &scene_lights, shadows.as_deref(), camera_position, &point_lights, &object_ids);
+            deferred::atmosphere(framebuffer, &object_ids, light_position, camera_position);
+            checkerboard.reconstruct(framebuffer, &velocities);
+            foveation::upscale(framebuffer);
+            temporal_resolve.resolve(framebuffer);
+            auto_exposure.update(framebuffer, &object_ids, selected);
+        }
+        // Supermuestreo: el resto del frame (texto, post-proceso, HUD) va a la
+        // resolución de la ventana
+        framebuffer.resolve_supersampling();
+        layers.draw(Layer::Effects, || bloom.apply(framebuffer));
+        // De acá en adelante se dibuja sobre la imagen ya mapeada a la pantalla
+        framebuffer.apply_tone_map();
+        let overlay_uniforms = Uniforms {
+            viewport_matrix: create_viewport_matrix(framebuffer.width as f32, framebuffer.height as f32),
+            ..overlay_uniforms
         };
 
-        render_neptune(&mut framebuffer, &planet8_uniforms, &planet_obj.get_vertex_array());
-
-
-        // Movimiento orbital de la nave espacial
-        let spaceship_distance = 3.0; 
-        let spaceship_translation = Vec3::new(
-            spaceship_distance * (time as f32 * -0.016).cos(), // Movimiento en X
-            -5.0, // Movimiento en Y 
-            spaceship_distance * (time as f32 * -0.016).sin(), // Movimiento en Z
+        // Texto anclado en la escena, después de resolver la luz para que no se pierda
+        if !ruler_labels.is_empty() {
+            layers.draw(Layer::Orbits, || orbit_ruler::draw_labels(framebuffer, &overlay_uniforms, &ruler_labels));
+        }
+        if show_labels {
+            layers.draw(Layer::Ui, || {
+                for label in billboard::body_labels(&system, origin, &overlay_uniforms) {
+                    label.draw(framebuffer, &overlay_uniforms);
+                }
+            });
+        }
+        if let Some(measurement) = &measurement {
+            layers.draw(Layer::Debug, || measurement.billboard(&system, origin, &locale).draw(framebuffer, &overlay_uniforms));
+        }
+        // Foco en la cara visible del cuerpo seleccionado
+        let depth_range = export::DepthRange { near: NEAR_PLANE, far: FAR_PLANE };
+        let focus = DepthOfField::focus_distance(
+            &view_matrix,
+            system.render_position(selected, origin),
+            system.radius(selected) as f32,
+            &depth_range,
         );
+        layers.draw(Layer::Effects, || depth_of_field.apply(framebuffer, focus, &depth_range));
+        layers.draw(Layer::Effects, || heat_haze.distort(framebuffer, time));
+        // Sobre la imagen ya compuesta pero antes del HUD, que tiene que quedar nítido
+        if fxaa {
+            layers.draw(Layer::Effects, || postprocess::fxaa(framebuffer));
+        }
+        framebuffer.set_current_id(0);
+
+        // Antes del HUD, para que las capturas sirvan para componer
+        if capture_requested {
+            let name = format!("frame_{:06}", frame_count);
+            match export::save_frame(framebuffer, &name, options.export_aux, depth_range, &object_ids) {
+                Ok(paths) => {
+                    for path in paths {
+                        println!("Captura guardada: {}", path.display());
+                    }
+                }
+                Err(err) => eprintln!("{}", err),
+            }
+        }
 
-        // Escala de la nave 
-        let spaceship_scale = 0.6;
-        let spaceship_model_matrix = create_model_matrix(spaceship_translation, spaceship_scale, Vec3::new(0.0, 0.0, 0.0));
+        // Contabilidad de memoria del frame
+        memory.begin_frame();
+        memory.add(Subsystem::Meshes, planet_obj.memory_bytes() + nave_obj.memory_bytes() + ring_obj.memory_bytes() + uranus_ring_obj.memory_bytes() + textured_obj.memory_bytes() + asteroid_belt.as_ref().map_or(0, |belt| belt.memory_bytes()) + kuiper_belt.as_ref().map_or(0, |belt| belt.memory_bytes()));
+        memory.add(Subsystem::Textures, textures.memory_bytes() + skybox.memory_bytes());
+        memory.add(Subsystem::Framebuffers, framebuffer.memory_bytes() + checkerboard.memory_bytes() + temporal_resolve.memory_bytes() + shadow_map.memory_bytes());
+        if let Some(scenario) = &roche {
+            memory.add(Subsystem::Particles, scenario.dust.memory_bytes());
+        }
+        memory.add(Subsystem::Particles, exhaust.memory_bytes());
+        if let Some(comet) = &comet {
+            memory.add(Subsystem::Particles, comet.memory_bytes());
+        }
+        memory.add(Subsystem::Trails, trails.memory_bytes());
+        memory.check_budgets();
 
-        let spaceship_uniforms = Uniforms {
-            model_matrix: spaceship_model_matrix, // Matriz de modelo actualizada con movimiento orbital
-            view_matrix: create_view_matrix(camera.eye, camera.center, camera.up), // Matriz de vista
-            projection_matrix: create_perspective_matrix(window_width as f32, window_height as f32), 
-            viewport_matrix: create_viewport_matrix(framebuffer_width as f32, framebuffer_height as f32), 
-            time,
-            noise: create_noise(),
-        };
+        for event in events.drain() {
+            event_log.record(system.time, &event);
+        }
+        layers.draw(Layer::Ui, || {
+            event_log.draw(framebuffer);
+            memory.draw(framebuffer);
+            transfer_panel.draw(framebuffer, system.time);
+            quiz.draw(framebuffer);
+            heat_haze.draw_warning(framebuffer);
+
+            let mut info = selection_info(&system, selected, origin, camera_position, options.phase_test, &locale);
+            if let Some(measurement) = &measurement {
+                info.extend(measurement.hud_lines(&system, &locale));
+            }
+            info.extend(auto_exposure.hud_line());
+            info.extend(effects.hud_line());
+            info.extend(depth_of_field.hud_line(selected, focus));
+            if debug_view != DebugView::Off {
+                info.push(format!("Vista de depuración: {} (U)", debug_view.label()));
+            }
+            if let Some((standing, _)) = &observer {
+                info.push(standing.hud_line(&system));
+            }
+            if let Some(pilot) = pilot.as_ref().filter(|_| camera_mode.follows_ship()) {
+                info.push(pilot.hud_line());
+            }
+            if camera_mode == CameraMode::Fly && observer.is_none() {
+                info.push(free_fly.hud_line());
+            }
+            if let Some(active) = &warp {
+                info.push(active.hud_line());
+            }
+            info.extend(clock.hud_line());
+            ghost_line = system.orbit(selected).map(|_| {
+                info.push(onion_skin.hud_line());
+                info.len() - 1
+            });
+            hud::draw_info(framebuffer, &info);
+            visualization::draw_legend(framebuffer, visualization);
+            info_lines = info;
+        });
+        if !layers.is_visible(Layer::Ui) {
+            ghost_line = None;
+        }
+        // El panel de capas va fuera de la medición de la interfaz
+        if layers.is_visible(Layer::Ui) {
+            layers.draw_panel(framebuffer);
+        }
 
-        render(&mut framebuffer, &spaceship_uniforms, &nave_obj.get_vertex_array());
+        // Actualizar la ventana y dormir un poco
+        window
+            .update_with_buffer(&framebuffer.buffer, framebuffer_width, framebuffer_height)
+            .unwrap();
 
+        // El margen del frame se mide sin la espera del ritmo
+        effects.update(frame_start.elapsed(), frame_pacing.target_fps());
+        frame_pacing.wait(frame_start);
+
+        frame_count += 1;
+        let frame_time = frame_start.elapsed();
+        pacing_monitor.record(frame_time);
+        let frame_stats = FrameStats::collect(frame_count, frame_time).with_pacing(
+            frame_pacing.target_fps(),
+            pacing_monitor.achieved_fps(),
+            pacing_monitor.jitter_ms(),
+        )
+        .with_effects(effects.scale())
+        .with_layers(layers.timings_ms());
+        for sink in &mut stats_sinks {
+            sink.publish(&frame_stats);
+        }
+        if let Some(bench) = &mut benchmark {
+            bench.record(&frame_stats);
+            if bench.done() {
+                let label = if checkerboard.enabled {
+                    "tablero de ajedrez"
+                } else if framebuffer.foveation.is_some() {
+                    "foveado"
+                } else {
+                    "resolución completa"
+                };
+                println!("{}", bench.report(label, system.bodies().len()));
+                break;
+            }
+        }
 
-        // Nave espacial mas pequeña.
-        let navecita_distance = 3.0; 
-        let navecita_translation = Vec3::new(
-            navecita_distance * (time as f32 * -0.016).cos(), // Movimiento en X
-            5.0, // Movimiento en Y 
-            navecita_distance * (time as f32 * -0.016).sin(), // Movimiento en Z
-        );
+        // Estado para el reporte de fallos
+        crash::update_context(CrashContext {
+            frame: frame_count,
+            time,
+            stats: Some(frame_stats),
+            camera_eye: camera.eye,
+            camera_center: camera.center,
+            camera_up: camera.up,
+            scene: format!(
+                "{}; paleta: {}; visualización: {}",
+                scene_summary(&system),
+                palettes.active().name,
+                visualization.label()
+            ),
+            settings: format!("{:?}", options),
+        });
+    }
 
-        let navecita_scale = 0.3;
-        let navecita_model_matrix = create_model_matrix(navecita_translation, navecita_scale, Vec3::new(0.0, 0.0, 0.0));
 
-        let navecita_uniforms = Uniforms {
-            model_matrix: navecita_model_matrix, // Matriz de modelo actualizada con movimiento orbital
-            view_matrix: create_view_matrix(camera.eye, camera.center, camera.up), // Matriz de vista
-            projection_matrix: create_perspective_matrix(window_width as f32, window_height as f32), 
-            viewport_matrix: create_viewport_matrix(framebuffer_width as f32, framebuffer_height as f32), 
-            time,
-            noise: create_noise(),
-        };
+}
 
-        render(&mut framebuffer, &navecita_uniforms, &nave_obj.get_vertex_array());
+// Luces puntuales del frame: motores de las naves y Mercurio cuando su shader es
+// emisivo (el de lava). No hay estaciones todavía.
+fn collect_point_lights(system: &SolarSystem, traffic: &Traffic, origin: DVec3, mercury_glows: bool) -> Vec<PointLight> {
+    let engine = Color::new(150, 190, 255);
+    let mut lights = vec![
+        PointLight { owner: "spaceship", position: system.render_position("spaceship", origin), color: engine, radius: 8.0 },
+        PointLight { owner: "navecita", position: system.render_position("navecita", origin), color: engine, radius: 5.0 },
+    ];
+    for npc in &traffic.ships {
+        let position = solar_system::to_render(npc.position, origin);
+        lights.push(PointLight { owner: "npc", position, color: Color::new(255, 200, 140), radius: 2.0 });
+    }
+    if mercury_glows {
+        let position = system.render_position("mercury", origin);
+        lights.push(PointLight { owner: "mercury", position, color: Color::new(255, 110, 30), radius: 2.5 });
+    }
+    lights
+}
 
+// Cuerpos de la simulación para el reporte de fallos; con --stress se cortan los nombres
+fn scene_summary(system: &SolarSystem) -> String {
+    const SHOWN_NAMES: usize = 24;
+    let bodies = system.bodies();
+    let mut names: Vec<&str> = bodies.iter().take(SHOWN_NAMES).map(|body| body.name).collect();
+    if bodies.len() > SHOWN_NAMES {
+        names.push("...");
+    }
+    format!("{} cuerpos: {}", bodies.len(), names.join(", "))
+}
 
-        // Actualizar la ventana y dormir un poco
-        window
-            .update_with_buffer(&framebuffer.buffer, framebuffer_width, framebuffer_height)
-            .unwrap();
+// Cada cuerpo se dibuja y después corren sus hooks. Devuelve los nombres por ID de
+// objeto (el ID i + 1 es `ids[i]`).
+fn draw_calls(
+    framebuffer: &mut Framebuffer,
+    calls: &[DrawCall],
+    hooks: &RenderHooks,
+    debug: DebugFlags,
+    layers: &mut RenderLayers,
+) -> Vec<&'static str> {
+    let mut object_ids = Vec::with_capacity(calls.len());
+    // Las llamadas que comparten malla (planetas, tráfico, cuerpos de --stress) arman su
+    // arreglo de vértices e índices una sola vez por frame; cada una solo aporta sus uniforms
+    let mut vertex_arrays: HashMap<*const Obj, (Vec<Vertex>, Vec<u32>)> = HashMap::new();
+    for call in calls {
+        object_ids.push(call.body);
+        framebuffer.set_current_id(object_ids.len() as u32);
+        framebuffer.set_emission(call.uniforms.material.emission);
+        let context = HookContext { body: call.body, uniforms: &call.uniforms, obj: call.obj, debug };
+        let (vertex_array, indices) = vertex_arrays.entry(std::ptr::from_ref(call.obj)).or_insert_with(|| (call.obj.vertices(), call.obj.indices()));
+        layers.draw(call.layer, || (call.render)(framebuffer, &call.uniforms, vertex_array, indices));
+        hooks.run_post(framebuffer, &context, layers);
+    }
+    framebuffer.set_emission(0.0);
+    object_ids
+}
 
-        std::thread::sleep(frame_delay);
+// Comandos de la consola (stdin)
+#[allow(clippy::too_many_arguments)]
+fn run_command(
+    line: &str,
+    escort: &mut Escort,
+    transfer_panel: &mut TransferPanel,
+    measurement: &mut Option<Measurement>,
+    layers: &mut RenderLayers,
+    onion_skin: &mut OnionSkin,
+    palettes: &mut PaletteSet,
+    system: &SolarSystem,
+    events: &mut EventQueue,
+) {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let result = match words.as_slice() {
+        [] => return,
+        ["escolta", args @ ..] => escort.command(args, system).inspect(|message| {
+            events.emit(SimEvent::OrderChanged(message.clone()));
+        }),
+        ["transferencia", "cerrar"] => {
+            transfer_panel.visible = false;
+            Ok(String::from("Planificador cerrado"))
+        }
+        ["transferencia", "ejecutar", rest @ ..] => {
+            let index = match rest {
+                [] => Ok(1),
+                [n] => n.parse::<usize>().map_err(|_| format!("Ventana inválida: {}", n)),
+                _ => Err(String::from("Uso: transferencia ejecutar [n]")),
+            };
+            index.and_then(|index| {
+                let plan = index.checked_sub(1).and_then(|i| transfer_panel.plans.get(i)).cloned();
+                plan.ok_or_else(|| format!("No hay ventana {}; calcula una con 'transferencia <origen> <destino>'", index))
+            })
+            .map(|plan| escort.start_transfer(plan, system.time))
+            .inspect(|message| events.emit(SimEvent::OrderChanged(message.clone())))
+        }
+        ["transferencia", from, to, rest @ ..] => {
+            let days = match rest {
+                [] => Ok(200),
+                [days] => days.parse().map_err(|_| format!("Días inválidos: {}", days)),
+                _ => Err(String::from("Uso: transferencia <origen> <destino> [días]")),
+            };
+            days.and_then(|days| transfer::plan(system, from, to, days)).map(|plans| {
+                let message = match plans.first() {
+                    Some(best) => format!(
+                        "Mejor ventana {} -> {}: sale en {:.0} días, delta-v {:.3}",
+                        best.from, best.to, best.departure - system.time, best.delta_v
+                    ),
+                    None => String::from("Sin ventanas en ese intervalo"),
+                };
+                transfer_panel.show(plans);
+                message
+            })
+        }
+        ["medir", "quitar"] => {
+            *measurement = None;
+            Ok(String::from("Medición terminada"))
+        }
+        ["medir", a, rest @ ..] => Target::parse(&[a], system).and_then(|a| {
+            let b = Target::parse(rest, system)?;
+            let message = format!("Midiendo {} - {}", a.label(), b.label());
+            *measurement = Some(Measurement::new(a, b));
+            Ok(message)
+        }),
+        ["capa", args @ ..] => layers.command(args),
+        ["fantasmas", args @ ..] => onion_skin.command(args),
+        // Las mismas paletas que recorre M, por nombre
+        ["paleta", name] => {
+            if palettes.select(name) {
+                Ok(format!("Paleta activa: {}", name))
+            } else {
+                Err(format!("No hay una paleta \"{}\"; disponibles: {}", name, palettes.names().join(", ")))
+            }
+        }
+        ["ayuda"] => Ok(String::from(
+            "Comandos: escolta seguir | escolta orbitar <cuerpo> [radio] | transferencia <origen> <destino> [días] | transferencia ejecutar [n] | transferencia cerrar | medir <cuerpo> <cuerpo | x y z> | medir quitar | capa <nombre> [si|no] | fantasmas [n] [paso] | paleta <nombre>",
+        )),
+        [other, ..] => Err(format!("Comando desconocido: {} (escribe 'ayuda')", other)),
+    };
+    match result {
+        Ok(message) => println!("{}", message),
+        Err(err) => eprintln!("{}", err),
     }
+}
 
+// Ángulo de fase e iluminación del cuerpo seleccionado vistos desde la cámara
+fn selection_info(
+    system: &SolarSystem,
+    selected: &str,
+    origin: DVec3,
+    camera_position: Vec3,
+    phase_test: Option<f32>,
+    locale: &Locale,
+) -> Vec<String> {
+    let days_per_unit = orbit_ruler::days_per_time_unit(system);
+    let mut lines = vec![
+        format!("Fecha simulada: {}", locale.date(system.time * days_per_unit)),
+        format!("Seleccionado: {}", selected),
+    ];
+    // Distancia al Sol y período orbital con la equivalencia de la UA y del año terrestre
+    if let Some(orbit) = system.orbit(selected).filter(|orbit| orbit.angular_speed != 0.0) {
+        let distance = (system.position(selected) - system.position("sun")).magnitude();
+        lines.push(format!(
+            "Distancia al Sol: {} ({} UA)",
+            locale.kilometers(orbit_ruler::kilometers(system, distance)),
+            locale.number(distance / orbit_ruler::astronomical_unit(system), 2)
+        ));
+        lines.push(format!("Período orbital: {}", locale.duration(std::f64::consts::TAU / orbit.angular_speed.abs() * days_per_unit)));
+    }
+    if selected != "sun" {
+        let body = system.render_position(selected, origin);
+        let sun = system.render_position("sun", origin);
+        let phase = lighting::phase_angle(&body, &sun, &camera_position);
+        lines.push(format!(
+            "Fase: {:.1} grados, {:.0}% iluminado",
+            phase,
+            lighting::illuminated_fraction(phase) * 100.0
+        ));
+    }
+    if let Some(expected) = phase_test {
+        lines.push(format!("Prueba de fases: esperado {:.1} grados", expected));
+    }
+    lines
+}
 
+// Cámara frente a `body` de modo que el ángulo Sol-cuerpo-cámara sea `phase_angle`.
+// Gira alrededor del eje hacia el padre para no atravesarlo.
+fn phase_test_camera(system: &SolarSystem, body: &str, phase_angle: f32) -> Camera {
+    let distance = 0.6;
+    let position = system.position(body);
+    let up = DVec3::new(0.0, 1.0, 0.0);
+    // El Sol mismo (o un cuerpo en su centro) no tiene dirección hacia el Sol
+    let to_sun = (system.position("sun") - position).try_normalize(f64::EPSILON).unwrap_or(DVec3::new(1.0, 0.0, 0.0));
+    let towards_parent = system
+        .body(body)
+        .and_then(|b| b.parent)
+        .and_then(|parent| (system.position(parent) - position).try_normalize(f64::EPSILON))
+        .unwrap_or(up);
+    // Eje perpendicular a la dirección del Sol para que el ángulo sea exacto; si el
+    // padre queda en la misma línea que el Sol sirve cualquier perpendicular
+    let axis = (towards_parent - to_sun * towards_parent.dot(&to_sun))
+        .try_normalize(1e-9)
+        .or_else(|| to_sun.cross(&up).try_normalize(1e-9))
+        .unwrap_or(DVec3::new(0.0, 0.0, 1.0));
+    let to_camera = nalgebra_glm::rotate_vec3(&to_sun, (phase_angle as f64).to_radians(), &axis);
+
+    Camera::new(
+        (position + to_camera * distance).cast(),
+        position.cast(),
+        axis.cast(),
+    )
 }
 
-fn handle_input(window: &Window, camera: &mut Camera) {
+fn handle_input(window: &Window, camera: &mut Camera, mouse: MouseMotion) {
     let movement_speed = 1.0;
     let rotation_speed = PI/50.0;
     let zoom_speed = 0.1;
@@ -856,6 +1930,12 @@ fn handle_input(window: &Window, camera: &mut Camera) {
     if window.is_key_down(Key::S) {
       camera.orbit(0.0, rotation_speed);
     }
+    if window.is_key_down(Key::LeftBracket) {
+      camera.roll(-rotation_speed);
+    }
+    if window.is_key_down(Key::RightBracket) {
+      camera.roll(rotation_speed);
+    }
 
     // Camera movement controls
     let mut movement = Vec3::new(0.0, 0.0, 0.0);
@@ -882,4 +1962,67 @@ fn handle_input(window: &Window, camera: &mut Camera) {
     if window.is_key_down(Key::Down) {
       camera.zoom(-zoom_speed);
     }
+
+    // Arrastre con el botón derecho y rueda del mouse
+    if mouse.yaw != 0.0 || mouse.pitch != 0.0 {
+      camera.orbit(mouse.yaw, mouse.pitch);
+    }
+    if mouse.zoom != 0.0 {
+      let distance = (camera.center - camera.eye).magnitude();
+      camera.zoom((distance * mouse.zoom).min(distance - MIN_ZOOM_DISTANCE));
+    }
+}
+
+#[cfg(all(test, feature = "deferred"))]
+mod deferred_parity_tests {
+    use super::*;
+
+    // Una esfera iluminada por el Sol en un buffer chico, sin ventana
+    fn render_sphere(framebuffer: &mut Framebuffer, deferred: bool) -> image::RgbImage {
+        let sphere = primitives::uv_sphere(32, 16);
+        let camera = Camera::new(Vec3::new(0.0, 0.0, 3.0), Vec3::zeros(), Vec3::new(0.0, 1.0, 0.0));
+        let (width, height) = (framebuffer.width as f32, framebuffer.height as f32);
+        let lights = vec![Light::sun(Vec3::new(-10.0, 5.0, 10.0))];
+        let uniforms = Uniforms {
+            model_matrix: create_model_matrix(Vec3::zeros(), 1.0, Vec3::zeros()),
+            view_matrix: camera.view_matrix(),
+            projection_matrix: create_perspective_matrix(width, height),
+            viewport_matrix: create_viewport_matrix(width, height),
+            time: 0.0,
+            noise: create_noise(),
+            palette: Arc::new(Palette::builtin()),
+            false_color: None,
+            material: Material::default(),
+            camera_position: camera.eye,
+            lights: lights.clone(),
+            shadow_map: None,
+            point_lights: Vec::new(),
+            shader: None,
+            script: None,
+            plugin: None,
+            texture: None,
+            normal_map: None,
+        };
+
+        framebuffer.clear();
+        if deferred {
+            framebuffer.begin_deferred();
+        }
+        framebuffer.set_current_id(1);
+        render_with_shader(framebuffer, &uniforms, &sphere.vertices(), &sphere.indices(), &cellular_shader);
+        if deferred {
+            deferred::resolve(framebuffer, &lights, None, camera.eye, &[], &["earth"]);
+        }
+        framebuffer.apply_tone_map();
+        export::color_image(framebuffer)
+    }
+
+    #[test]
+    fn forward_and_deferred_match() {
+        let mut framebuffer = Framebuffer::new(64, 64);
+        let forward = render_sphere(&mut framebuffer, false);
+        let deferred = render_sphere(&mut framebuffer, true);
+        let metrics = compare::compare(&forward, &deferred);
+        assert!(metrics.psnr >= DEFERRED_PARITY_PSNR, "PSNR {:.2} dB por debajo de {}", metrics.psnr, DEFERRED_PARITY_PSNR);
+    }
 }
@@ -0,0 +1,365 @@
+use std::collections::HashMap;
+use std::fs;
+use rand::Rng;
+use crate::color::Color;
+use crate::fragment::Fragment;
+
+// Cómo se obtienen las normales de un mesh en el ensamblado de primitivas
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NormalMode {
+    // Normales de vértice interpoladas (planetas)
+    #[default]
+    Smooth,
+    // Una normal por cara (modelos facetados como la nave)
+    Flat,
+}
+
+impl NormalMode {
+    fn parse(text: &str) -> Option<Self> {
+        match text {
+            "smooth" => Some(NormalMode::Smooth),
+            "flat" => Some(NormalMode::Flat),
+            _ => None,
+        }
+    }
+}
+
+// Caras que se descartan antes de rasterizar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CullMode {
+    // Las que le dan la espalda a la cámara (esferas y mallas cerradas)
+    #[default]
+    Back,
+    // Ninguna: superficies abiertas, anillos o modelos con el orden mezclado
+    None,
+}
+
+impl CullMode {
+    fn parse(text: &str) -> Option<Self> {
+        match text {
+            "back" => Some(CullMode::Back),
+            "none" => Some(CullMode::None),
+            _ => None,
+        }
+    }
+}
+
+// Orden de los vértices de las caras delanteras, visto desde afuera del modelo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FrontFace {
+    // La convención de OBJ
+    #[default]
+    CounterClockwise,
+    Clockwise,
+}
+
+impl FrontFace {
+    fn parse(text: &str) -> Option<Self> {
+        match text {
+            "ccw" => Some(FrontFace::CounterClockwise),
+            "cw" => Some(FrontFace::Clockwise),
+            _ => None,
+        }
+    }
+}
+
+// Cómo se combina un material translúcido con lo que ya hay en el pixel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlendMode {
+    // Encima, según su opacidad (anillos, atmósferas): depende del orden
+    #[default]
+    Over,
+    // Suma su color por la opacidad (brillos): no depende del orden
+    Additive,
+}
+
+impl BlendMode {
+    fn parse(text: &str) -> Option<Self> {
+        match text {
+            "over" => Some(BlendMode::Over),
+            "additive" => Some(BlendMode::Additive),
+            _ => None,
+        }
+    }
+}
+
+// Inspector de materiales: reemplaza el sombreado de toda la escena por uno de sus
+// componentes, para aislar en qué etapa falla un shader
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DebugView {
+    #[default]
+    Off,
+    // Salida del shader sin luz
+    Albedo,
+    // Normal en el mundo, de [-1, 1] a [0, 1] por canal
+    Normals,
+    // Distancia a la cámara en escala logarítmica (blanco cerca)
+    Depth,
+    // Solo la iluminación, sobre un material blanco
+    Light,
+    // El ruido que leen los shaders, sin paleta
+    Noise,
+    // Coordenadas esféricas sobre el modelo (u en rojo, v en verde)
+    Uvs,
+}
+
+impl DebugView {
+    pub fn next(self) -> Self {
+        match self {
+            DebugView::Off => DebugView::Albedo,
+            DebugView::Albedo => DebugView::Normals,
+            DebugView::Normals => DebugView::Depth,
+            DebugView::Depth => DebugView::Light,
+            DebugView::Light => DebugView::Noise,
+            DebugView::Noise => DebugView::Uvs,
+            DebugView::Uvs => DebugView::Off,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DebugView::Off => "sombreado completo",
+            DebugView::Albedo => "albedo",
+            DebugView::Normals => "normales",
+            DebugView::Depth => "profundidad",
+            DebugView::Light => "solo luz",
+            DebugView::Noise => "ruido",
+            DebugView::Uvs => "UV",
+        }
+    }
+}
+
+// Parámetros que los shaders procedurales leen en vez de constantes fijas
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShaderParams {
+    // Multiplica la frecuencia de las bandas
+    pub band_frequency: f32,
+    // Multiplica el zoom de los patrones de ruido
+    pub noise_scale: f32,
+    // Rotación del tono de la paleta, en grados
+    pub hue_shift: f32,
+}
+
+impl Default for ShaderParams {
+    fn default() -> Self {
+        ShaderParams { band_frequency: 1.0, noise_scale: 1.0, hue_shift: 0.0 }
+    }
+}
+
+impl ShaderParams {
+    // Valores aleatorios dentro de rangos que siguen viéndose como un planeta
+    pub fn random(rng: &mut impl Rng) -> Self {
+        ShaderParams {
+            band_frequency: rng.gen_range(0.3..3.0),
+            noise_scale: rng.gen_range(0.25..4.0),
+            hue_shift: rng.gen_range(-180.0..180.0),
+        }
+    }
+
+    // Fragmento listo para pegar en el archivo de escena
+    pub fn to_toml(self, body: &str) -> String {
+        format!(
+            "[bodies.{}]\nband_frequency = {:.3}\nnoise_scale = {:.3}\nhue_shift = {:.1}\n",
+            body, self.band_frequency, self.noise_scale, self.hue_shift
+        )
+    }
+}
+
+// Opciones de material por objeto, independientes del shader que lo pinta
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Material {
+    // Multiplica la salida del shader por el color interpolado de los vértices
+    // (p. ej. oclusión ambiental horneada en el modelo)
+    pub vertex_colors: bool,
+    pub normals: NormalMode,
+    // Ilumina ambas caras de los triángulos (modelos con superficies abiertas)
+    pub two_sided: bool,
+    // No recibe la luz del Sol (cuerpos emisivos)
+    pub unlit: bool,
+    // Cuánto brilla en el buffer de emisión (HDR, multiplica su color); lo que pasa
+    // del umbral del bloom se derrama sobre los pixeles vecinos. 0: no emite
+    pub emission: f32,
+    // Reflejo del Sol (Blinn-Phong): `specular` es su fuerza (0: mate) y `shininess`
+    // el exponente, más alto cuanto más chico y nítido es el brillo
+    pub specular: f32,
+    pub shininess: f32,
+    // Relieve del mapa de normales (ver normal_map.rs): multiplica sus pendientes;
+    // 0 deja la superficie lisa
+    pub relief: f32,
+    pub cull: CullMode,
+    pub front_face: FrontFace,
+    // Solo cuenta en los objetos translúcidos (ver transparency.rs)
+    pub blend: BlendMode,
+    pub params: ShaderParams,
+    // Canal del inspector de materiales (no se lee del archivo de escena)
+    pub debug_view: DebugView,
+}
+
+impl Material {
+    // Permite forzar el modo de normales de toda la escena para depurar
+    pub fn with_normals_override(mut self, normals: Option<NormalMode>) -> Self {
+        if let Some(normals) = normals {
+            self.normals = normals;
+        }
+        self
+    }
+
+    pub fn with_debug_view(mut self, debug_view: DebugView) -> Self {
+        self.debug_view = debug_view;
+        self
+    }
+
+    // Modulaciones del material sobre el color que produjo el shader
+    pub fn apply<V>(&self, color: Color, fragment: &Fragment<V>) -> Color {
+        let color = if self.params.hue_shift != 0.0 {
+            color.shift_hue(self.params.hue_shift)
+        } else {
+            color
+        };
+        if self.vertex_colors {
+            color.blend_multiply(&fragment.color)
+        } else {
+            color
+        }
+    }
+}
+
+// Materiales por cuerpo leídos de la sección [bodies.<nombre>] del archivo de escena
+//
+//   [bodies.spaceship]
+//   normals = "flat"
+//   vertex_colors = true
+//   two_sided = true
+//   unlit = false
+//   emission = 0.0         # brillo HDR para el bloom (ver postprocess.rs)
+//   specular = 0.0         # reflejo del Sol; 0 es mate
+//   shininess = 16.0       # exponente del reflejo (>= 1)
+//   relief = 0.0           # relieve con mapa de normales; 0 es liso
+//   cull = "back"          # o "none"
+//   front_face = "ccw"     # o "cw"
+//   blend = "over"         # o "additive"; solo en objetos translúcidos
+//   band_frequency = 1.0
+//   noise_scale = 1.0
+//   hue_shift = 0.0
+#[derive(Debug, Default)]
+pub struct MaterialTable {
+    materials: HashMap<String, Material>,
+}
+
+impl MaterialTable {
+    pub fn load(path: &str) -> Self {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) => {
+                eprintln!("No se pudo leer {} ({}); se usan materiales por defecto", path, err);
+                return MaterialTable::default();
+            }
+        };
+
+        match MaterialTable::parse(&text) {
+            Ok(table) => table,
+            Err(err) => {
+                eprintln!("Error en {}: {}", path, err);
+                MaterialTable::default()
+            }
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let document: toml::Table = text.parse().map_err(|e: toml::de::Error| e.to_string())?;
+        let mut materials = HashMap::new();
+
+        let bodies = match document.get("bodies") {
+            Some(toml::Value::Table(bodies)) => bodies,
+            Some(_) => return Err(String::from("'bodies' debe ser una tabla")),
+            None => return Ok(MaterialTable { materials }),
+        };
+
+        for (name, entry) in bodies {
+            let entry = entry.as_table().ok_or_else(|| format!("bodies.{} debe ser una tabla", name))?;
+            let mut material = Material::default();
+
+            if let Some(value) = entry.get("vertex_colors") {
+                material.vertex_colors = value
+                    .as_bool()
+                    .ok_or_else(|| format!("bodies.{}.vertex_colors debe ser booleano", name))?;
+            }
+            if let Some(value) = entry.get("two_sided") {
+                material.two_sided = value
+                    .as_bool()
+                    .ok_or_else(|| format!("bodies.{}.two_sided debe ser booleano", name))?;
+            }
+            if let Some(value) = entry.get("unlit") {
+                material.unlit = value
+                    .as_bool()
+                    .ok_or_else(|| format!("bodies.{}.unlit debe ser booleano", name))?;
+            }
+            if let Some(value) = entry.get("normals") {
+                material.normals = value
+                    .as_str()
+                    .and_then(NormalMode::parse)
+                    .ok_or_else(|| format!("bodies.{}.normals debe ser \"smooth\" o \"flat\"", name))?;
+            }
+            if let Some(value) = entry.get("cull") {
+                material.cull = value
+                    .as_str()
+                    .and_then(CullMode::parse)
+                    .ok_or_else(|| format!("bodies.{}.cull debe ser \"back\" o \"none\"", name))?;
+            }
+            if let Some(value) = entry.get("blend") {
+                material.blend = value
+                    .as_str()
+                    .and_then(BlendMode::parse)
+                    .ok_or_else(|| format!("bodies.{}.blend debe ser \"over\" o \"additive\"", name))?;
+            }
+            if let Some(value) = entry.get("front_face") {
+                material.front_face = value
+                    .as_str()
+                    .and_then(FrontFace::parse)
+                    .ok_or_else(|| format!("bodies.{}.front_face debe ser \"ccw\" o \"cw\"", name))?;
+            }
+
+            let number = |key: &str| -> Result<Option<f32>, String> {
+                match entry.get(key) {
+                    None => Ok(None),
+                    Some(toml::Value::Float(value)) => Ok(Some(*value as f32)),
+                    Some(toml::Value::Integer(value)) => Ok(Some(*value as f32)),
+                    Some(_) => Err(format!("bodies.{}.{} debe ser un número", name, key)),
+                }
+            };
+            if let Some(value) = number("emission")? {
+                material.emission = value.max(0.0);
+            }
+            if let Some(value) = number("specular")? {
+                material.specular = value.max(0.0);
+            }
+            if let Some(value) = number("shininess")? {
+                material.shininess = value.max(1.0);
+            }
+            if let Some(value) = number("relief")? {
+                material.relief = value.max(0.0);
+            }
+            if let Some(value) = number("band_frequency")? {
+                material.params.band_frequency = value;
+            }
+            if let Some(value) = number("noise_scale")? {
+                material.params.noise_scale = value;
+            }
+            if let Some(value) = number("hue_shift")? {
+                material.params.hue_shift = value;
+            }
+
+            materials.insert(name.clone(), material);
+        }
+
+        Ok(MaterialTable { materials })
+    }
+
+    pub fn get(&self, body: &str) -> Material {
+        self.materials.get(body).copied().unwrap_or_default()
+    }
+
+    pub fn set(&mut self, body: &str, material: Material) {
+        self.materials.insert(body.to_string(), material);
+    }
+}
@@ -0,0 +1,139 @@
+use nalgebra_glm::DVec3;
+use crate::billboard::Billboard;
+use crate::debug_draw;
+use crate::framebuffer::Framebuffer;
+use crate::locale::Locale;
+use crate::orbit_ruler;
+use crate::solar_system::{self, SolarSystem};
+use crate::Uniforms;
+
+const LINE_COLOR: u32 = 0x66FFFF;
+// Alto de la lectura en la escena, como fracción del largo del segmento
+const READOUT_HEIGHT: f32 = 0.04;
+
+// Extremo de una medición: un cuerpo (se mueve con él) o un punto fijo de la escena
+#[derive(Debug, Clone, PartialEq)]
+pub enum Target {
+    Body(&'static str),
+    Point(DVec3),
+}
+
+impl Target {
+    pub fn position(&self, system: &SolarSystem) -> DVec3 {
+        match self {
+            Target::Body(name) => system.position(name),
+            Target::Point(point) => *point,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Target::Body(name) => name.to_string(),
+            Target::Point(p) => format!("({:.1}, {:.1}, {:.1})", p.x, p.y, p.z),
+        }
+    }
+
+    // Un nombre de cuerpo o tres coordenadas
+    pub fn parse(args: &[&str], system: &SolarSystem) -> Result<Self, String> {
+        match args {
+            [name] => system
+                .body(name)
+                .map(|body| Target::Body(body.name))
+                .ok_or_else(|| format!("Cuerpo desconocido: {}", name)),
+            [x, y, z] => {
+                let coordinate = |text: &str| text.parse::<f64>().map_err(|_| format!("Coordenada inválida: {}", text));
+                Ok(Target::Point(DVec3::new(coordinate(x)?, coordinate(y)?, coordinate(z)?)))
+            }
+            _ => Err(String::from("Se espera un cuerpo o tres coordenadas x y z")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Reading {
+    pub distance: f64,
+    // Separación angular vista desde la cámara, en grados
+    pub angle: f64,
+    // Rapidez con la que cambia la distancia (positiva: se alejan), por unidad de tiempo
+    pub closing_speed: f64,
+    // Módulo de la velocidad relativa, por unidad de tiempo
+    pub relative_speed: f64,
+}
+
+// Medición en vivo entre dos objetivos; la velocidad relativa se deriva de las
+// posiciones del frame anterior
+#[derive(Debug, Clone)]
+pub struct Measurement {
+    pub a: Target,
+    pub b: Target,
+    previous: Option<(DVec3, DVec3)>,
+    pub reading: Reading,
+}
+
+impl Measurement {
+    pub fn new(a: Target, b: Target) -> Self {
+        Measurement { a, b, previous: None, reading: Reading::default() }
+    }
+
+    // Cambia el segundo extremo; la velocidad relativa vuelve a empezar
+    pub fn retarget(&mut self, b: Target) {
+        self.b = b;
+        self.previous = None;
+        self.reading = Reading::default();
+    }
+
+    pub fn update(&mut self, system: &SolarSystem, camera: DVec3, dt: f64) {
+        let a = self.a.position(system);
+        let b = self.b.position(system);
+        let offset = b - a;
+        let distance = offset.magnitude();
+
+        let (relative_speed, closing_speed) = match self.previous {
+            Some((previous_a, previous_b)) if dt > 0.0 => {
+                let relative_velocity = ((b - previous_b) - (a - previous_a)) / dt;
+                let closing = if distance > 1e-9 { relative_velocity.dot(&offset) / distance } else { 0.0 };
+                (relative_velocity.magnitude(), closing)
+            }
+            _ => (0.0, 0.0),
+        };
+        self.previous = Some((a, b));
+
+        let angle = match ((a - camera).try_normalize(1e-9), (b - camera).try_normalize(1e-9)) {
+            (Some(to_a), Some(to_b)) => to_a.dot(&to_b).clamp(-1.0, 1.0).acos().to_degrees(),
+            _ => 0.0,
+        };
+
+        self.reading = Reading { distance, angle, closing_speed, relative_speed };
+    }
+
+    pub fn hud_lines(&self, system: &SolarSystem, locale: &Locale) -> Vec<String> {
+        vec![
+            format!("Medición: {} - {}", self.a.label(), self.b.label()),
+            format!(
+                "  Distancia: {} ({})",
+                locale.number(self.reading.distance, 3),
+                locale.kilometers(orbit_ruler::kilometers(system, self.reading.distance))
+            ),
+            format!("  Separación angular: {:.2} grados", self.reading.angle),
+            format!(
+                "  Velocidad relativa: {:.4} ({:+.4} radial)",
+                self.reading.relative_speed, self.reading.closing_speed
+            ),
+        ]
+    }
+
+    // `uniforms` con matriz de modelo identidad: los extremos ya están en espacio de render
+    pub fn draw(&self, framebuffer: &mut Framebuffer, uniforms: &Uniforms, system: &SolarSystem, origin: DVec3) {
+        let a = solar_system::to_render(self.a.position(system), origin);
+        let b = solar_system::to_render(self.b.position(system), origin);
+        debug_draw::draw_segment(framebuffer, uniforms, a, b, LINE_COLOR);
+    }
+
+    // Distancia escrita en la escena, a mitad del segmento
+    pub fn billboard(&self, system: &SolarSystem, origin: DVec3, locale: &Locale) -> Billboard {
+        let a = solar_system::to_render(self.a.position(system), origin);
+        let b = solar_system::to_render(self.b.position(system), origin);
+        let text = locale.kilometers(orbit_ruler::kilometers(system, self.reading.distance));
+        Billboard::new((a + b) * 0.5, text, LINE_COLOR, (b - a).magnitude() * READOUT_HEIGHT)
+    }
+}
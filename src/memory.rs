@@ -0,0 +1,207 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use crate::framebuffer::Framebuffer;
+use crate::hud;
+use crate::text;
+
+// Partes del programa cuya memoria se contabiliza por separado
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Subsystem {
+    Meshes,
+    // Mapas de los cuerpos (assets/textures) y del cielo
+    Textures,
+    Framebuffers,
+    Particles,
+    // Posiciones guardadas de los rastros de los planetas (ver trail.rs)
+    Trails,
+}
+
+impl Subsystem {
+    pub const ALL: [Subsystem; 5] = [
+        Subsystem::Meshes,
+        Subsystem::Textures,
+        Subsystem::Framebuffers,
+        Subsystem::Particles,
+        Subsystem::Trails,
+    ];
+
+    // Clave en la sección [memory_budget] del archivo de escena
+    pub fn key(self) -> &'static str {
+        match self {
+            Subsystem::Meshes => "meshes",
+            Subsystem::Textures => "textures",
+            Subsystem::Framebuffers => "framebuffers",
+            Subsystem::Particles => "particles",
+            Subsystem::Trails => "trails",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Subsystem::Meshes => "Mallas",
+            Subsystem::Textures => "Texturas",
+            Subsystem::Framebuffers => "Framebuffers",
+            Subsystem::Particles => "Partículas",
+            Subsystem::Trails => "Rastros",
+        }
+    }
+
+    fn index(self) -> usize {
+        Subsystem::ALL.iter().position(|s| *s == self).unwrap_or(0)
+    }
+}
+
+// Bytes reservados por una estructura (capacidad de sus buffers, no solo lo usado)
+pub trait MemoryUsage {
+    fn memory_bytes(&self) -> usize;
+}
+
+pub fn vec_bytes<T>(vec: &Vec<T>) -> usize {
+    vec.capacity() * std::mem::size_of::<T>()
+}
+
+pub fn deque_bytes<T>(deque: &VecDeque<T>) -> usize {
+    deque.capacity() * std::mem::size_of::<T>()
+}
+
+// Límites por subsistema leídos del archivo de escena, en KiB
+//
+//   [memory_budget]
+//   meshes = 4096
+//   framebuffers = 16384
+#[derive(Debug, Default)]
+pub struct MemoryBudgets {
+    limits: HashMap<Subsystem, usize>,
+}
+
+impl MemoryBudgets {
+    pub fn load(path: &str) -> Self {
+        let parsed = fs::read_to_string(path).map_err(|err| err.to_string()).and_then(|text| MemoryBudgets::parse(&text));
+        parsed.unwrap_or_else(|err| {
+            eprintln!("Presupuestos de memoria en {}: {}; no se aplican límites", path, err);
+            MemoryBudgets::default()
+        })
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let document: toml::Table = text.parse().map_err(|e: toml::de::Error| e.to_string())?;
+        let mut limits = HashMap::new();
+        let table = match document.get("memory_budget") {
+            Some(toml::Value::Table(table)) => table,
+            Some(_) => return Err(String::from("'memory_budget' debe ser una tabla")),
+            None => return Ok(MemoryBudgets { limits }),
+        };
+        for (key, value) in table {
+            let subsystem = Subsystem::ALL
+                .into_iter()
+                .find(|s| s.key() == key)
+                .ok_or_else(|| format!("subsistema desconocido: memory_budget.{}", key))?;
+            let kib = value
+                .as_integer()
+                .filter(|kib| *kib >= 0)
+                .ok_or_else(|| format!("memory_budget.{} debe ser un entero de KiB", key))?;
+            limits.insert(subsystem, kib as usize * 1024);
+        }
+        Ok(MemoryBudgets { limits })
+    }
+
+    pub fn limit(&self, subsystem: Subsystem) -> Option<usize> {
+        self.limits.get(&subsystem).copied()
+    }
+}
+
+// Contabilidad del frame: cada subsistema suma sus bytes y al final se comparan
+// con los presupuestos
+pub struct MemoryTracker {
+    usage: [usize; Subsystem::ALL.len()],
+    budgets: MemoryBudgets,
+    // Avisa una sola vez cada vez que un subsistema cruza su límite
+    over_budget: HashSet<Subsystem>,
+    pub visible: bool,
+}
+
+impl MemoryTracker {
+    pub fn new(budgets: MemoryBudgets) -> Self {
+        MemoryTracker { usage: [0; Subsystem::ALL.len()], budgets, over_budget: HashSet::new(), visible: false }
+    }
+
+    pub fn begin_frame(&mut self) {
+        self.usage = [0; Subsystem::ALL.len()];
+    }
+
+    pub fn add(&mut self, subsystem: Subsystem, bytes: usize) {
+        self.usage[subsystem.index()] += bytes;
+    }
+
+    pub fn total(&self) -> usize {
+        self.usage.iter().sum()
+    }
+
+    pub fn check_budgets(&mut self) {
+        for subsystem in Subsystem::ALL {
+            let used = self.usage[subsystem.index()];
+            match self.budgets.limit(subsystem) {
+                Some(limit) if used > limit => {
+                    if self.over_budget.insert(subsystem) {
+                        eprintln!(
+                            "Aviso: {} usa {} y supera su presupuesto de {}",
+                            subsystem.label(),
+                            format_bytes(used),
+                            format_bytes(limit)
+                        );
+                    }
+                }
+                _ => {
+                    self.over_budget.remove(&subsystem);
+                }
+            }
+        }
+    }
+
+    pub fn lines(&self) -> Vec<(String, bool)> {
+        let mut lines: Vec<(String, bool)> = Subsystem::ALL
+            .iter()
+            .map(|subsystem| {
+                let used = self.usage[subsystem.index()];
+                let budget = self.budgets.limit(*subsystem).map_or(String::from("sin límite"), format_bytes);
+                (format!("{:<13}{:>10} / {}", subsystem.label(), format_bytes(used), budget), self.over_budget.contains(subsystem))
+            })
+            .collect();
+        lines.push((format!("{:<13}{:>10}", "Total", format_bytes(self.total())), false));
+        lines
+    }
+
+    pub fn print(&self) {
+        println!("Memoria por subsistema:");
+        for (line, over) in self.lines() {
+            println!("  {}{}", line, if over { "  (excedido)" } else { "" });
+        }
+    }
+
+    // Panel en la esquina inferior derecha; los subsistemas excedidos en rojo
+    pub fn draw(&self, framebuffer: &mut Framebuffer) {
+        if !self.visible {
+            return;
+        }
+        let lines = self.lines();
+        let width = 300.min(framebuffer.width);
+        let height = (lines.len() + 1) * text::LINE_HEIGHT + 8;
+        let x = framebuffer.width - width;
+        let y = framebuffer.height.saturating_sub(height);
+        hud::dim_rect(framebuffer, x, y, width, height);
+
+        text::draw_text_shadowed(framebuffer, x + 6, y + 4, "Memoria (K)", 0x88CCFF, 1);
+        for (i, (line, over)) in lines.iter().enumerate() {
+            let color = if *over { 0xFF5555 } else { 0xFFFFFF };
+            text::draw_text_shadowed(framebuffer, x + 6, y + 4 + (i + 1) * text::LINE_HEIGHT, line, color, 1);
+        }
+    }
+}
+
+pub fn format_bytes(bytes: usize) -> String {
+    match bytes {
+        b if b >= 1024 * 1024 => format!("{:.1} MiB", b as f64 / (1024.0 * 1024.0)),
+        b if b >= 1024 => format!("{:.1} KiB", b as f64 / 1024.0),
+        b => format!("{} B", b),
+    }
+}
@@ -0,0 +1,95 @@
+use std::fs;
+use minifb::{MouseButton, MouseMode, Window};
+
+// Lo que pide el mouse en un frame: giros en radianes (arrastrar a la derecha da
+// `yaw` positivo y hacia abajo `pitch` negativo) y pasos de la rueda por la
+// sensibilidad; la cámara libre acerca esa fracción de la distancia al punto de
+// enfoque (negativo aleja) y el vuelo libre la usa para cambiar de velocidad
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MouseMotion {
+    pub yaw: f32,
+    pub pitch: f32,
+    pub zoom: f32,
+}
+
+// Orbitar arrastrando con el botón derecho y acercar con la rueda, como en un visor
+// 3D. Arrastrar "agarra" la escena: hacia la derecha la gira hacia la derecha y
+// hacia abajo la baja (la cámara sube). La rueda acerca en proporción a la
+// distancia, así cada paso se siente igual de cerca de un planeta que desde lejos.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MouseLook {
+    // Radianes por pixel arrastrado
+    pub orbit_sensitivity: f32,
+    // Fracción de la distancia por paso de la rueda
+    pub zoom_sensitivity: f32,
+    // Arrastrar hacia abajo baja la cámara en vez de subirla
+    pub invert_y: bool,
+    // Posición del mouse en el frame anterior mientras se arrastra
+    last: Option<(f32, f32)>,
+}
+
+impl Default for MouseLook {
+    fn default() -> Self {
+        MouseLook { orbit_sensitivity: 0.005, zoom_sensitivity: 0.1, invert_y: false, last: None }
+    }
+}
+
+impl MouseLook {
+    // Sección [mouse] del archivo de escena; 0 apaga el arrastre o la rueda
+    //
+    //   [mouse]
+    //   orbit_sensitivity = 0.005   # radianes por pixel
+    //   zoom_sensitivity = 0.1      # fracción de la distancia por paso de la rueda
+    //   invert_y = false
+    pub fn load(path: &str) -> Self {
+        let parsed = fs::read_to_string(path).map_err(|err| err.to_string()).and_then(|text| MouseLook::parse(&text));
+        parsed.unwrap_or_else(|err| {
+            eprintln!("Mouse en {}: {}; se usan los valores por defecto", path, err);
+            MouseLook::default()
+        })
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let document: toml::Table = text.parse().map_err(|e: toml::de::Error| e.to_string())?;
+        let mut mouse = MouseLook::default();
+        let table = match document.get("mouse") {
+            Some(toml::Value::Table(table)) => table,
+            Some(_) => return Err(String::from("'mouse' debe ser una tabla")),
+            None => return Ok(mouse),
+        };
+
+        let fields = [("orbit_sensitivity", &mut mouse.orbit_sensitivity), ("zoom_sensitivity", &mut mouse.zoom_sensitivity)];
+        for (key, field) in fields {
+            match table.get(key) {
+                None => {}
+                Some(toml::Value::Float(value)) => *field = *value as f32,
+                Some(toml::Value::Integer(value)) => *field = *value as f32,
+                Some(_) => return Err(format!("mouse.{} debe ser un número", key)),
+            }
+        }
+        match table.get("invert_y") {
+            None => {}
+            Some(toml::Value::Boolean(value)) => mouse.invert_y = *value,
+            Some(_) => return Err(String::from("mouse.invert_y debe ser true o false")),
+        }
+        // Más de un paso completo acercaría hasta pasar el punto de enfoque
+        mouse.zoom_sensitivity = mouse.zoom_sensitivity.clamp(0.0, 0.9);
+        Ok(mouse)
+    }
+
+    // Una vez por frame, aunque la cámara libre no esté activa: así un arrastre que
+    // empezó en otro modo no da un salto al volver
+    pub fn update(&mut self, window: &Window) -> MouseMotion {
+        let position = window.get_mouse_pos(MouseMode::Discard).filter(|_| window.get_mouse_down(MouseButton::Right));
+        let (yaw, pitch) = match (self.last, position) {
+            (Some((last_x, last_y)), Some((x, y))) => {
+                let pitch = (y - last_y) * self.orbit_sensitivity;
+                ((x - last_x) * self.orbit_sensitivity, if self.invert_y { pitch } else { -pitch })
+            }
+            _ => (0.0, 0.0),
+        };
+        self.last = position;
+        let scroll = window.get_scroll_wheel().map_or(0.0, |(_, y)| y);
+        MouseMotion { yaw, pitch, zoom: scroll * self.zoom_sensitivity }
+    }
+}
@@ -0,0 +1,98 @@
+use std::sync::Arc;
+use fastnoise_lite::{FastNoiseLite, FractalType, NoiseType};
+use nalgebra_glm::Vec3;
+use rayon::prelude::*;
+use crate::color::Color;
+use crate::fragment::Fragment;
+use crate::lighting;
+use crate::tangents::TangentFrame;
+use crate::texture::Texture;
+use crate::vertex::Vertex;
+use crate::Uniforms;
+
+// Tamaño de los mapas de relieve que se generan cuando un cuerpo no trae el suyo
+const RELIEF_WIDTH: usize = 1024;
+const RELIEF_HEIGHT: usize = 512;
+// Altura de las montañas, en radios del cuerpo, y tamaño de los rasgos (frecuencia
+// del ruido sobre la esfera unitaria)
+const RELIEF_AMPLITUDE: f32 = 0.02;
+const RELIEF_FREQUENCY: f32 = 6.0;
+// Cerca de los polos los texels se juntan; el paso en longitud no baja de esto
+const MIN_PARALLEL: f32 = 0.05;
+
+// Lo que necesita un cuerpo con relieve: su mapa y los vértices de la malla con su
+// base tangente (los de Obj::tangent_vertices, en el orden de sus índices), armados
+// una sola vez al cargarla
+#[derive(Clone)]
+pub struct NormalMapping {
+    pub texture: Arc<Texture>,
+    pub vertices: Arc<Vec<Vertex<TangentFrame>>>,
+}
+
+// Mapa de normales en espacio tangente (x hacia +u, y hacia +v, z afuera) a partir
+// de un relieve de ruido fractal. El ruido se lee en 3D sobre la esfera, así el mapa
+// no tiene costura en la longitud 180 ni se estira en los polos.
+pub fn generate(seed: i32) -> Texture {
+    let mut noise = FastNoiseLite::with_seed(seed);
+    noise.set_noise_type(Some(NoiseType::OpenSimplex2));
+    noise.set_fractal_type(Some(FractalType::FBm));
+    noise.set_fractal_octaves(Some(5));
+    noise.set_frequency(Some(RELIEF_FREQUENCY));
+
+    let (width, height) = (RELIEF_WIDTH, RELIEF_HEIGHT);
+    let heights: Vec<f32> = (0..width * height)
+        .into_par_iter()
+        .map(|index| {
+            let p = sphere_point(index % width, index / width, width, height);
+            noise.get_noise_3d(p.x, p.y, p.z) * RELIEF_AMPLITUDE
+        })
+        .collect();
+
+    let texels = (0..width * height)
+        .into_par_iter()
+        .map(|index| {
+            let (x, y) = (index % width, index / width);
+            let height_at = |x: usize, y: usize| heights[y * width + x];
+            let (west, east) = ((x + width - 1) % width, (x + 1) % width);
+            let (north, south) = (y.saturating_sub(1), (y + 1).min(height - 1));
+            // Distancia sobre la superficie entre los vecinos, en radios
+            let latitude = (0.5 - (y as f32 + 0.5) / height as f32) * std::f32::consts::PI;
+            let step_u = 2.0 * std::f32::consts::TAU / width as f32 * latitude.cos().max(MIN_PARALLEL);
+            let step_v = (south - north).max(1) as f32 * std::f32::consts::PI / height as f32;
+            let slope_u = (height_at(east, y) - height_at(west, y)) / step_u;
+            // Las filas bajan hacia el sur y v crece hacia el norte
+            let slope_v = (height_at(x, north) - height_at(x, south)) / step_v;
+            let normal = Vec3::new(-slope_u, -slope_v, 1.0).normalize();
+            let encode = |value: f32| ((value * 0.5 + 0.5) * 255.0).round() as u8;
+            Color::new(encode(normal.x), encode(normal.y), encode(normal.z))
+        })
+        .collect();
+    Texture::from_texels(width, height, texels)
+}
+
+// Centro del texel (x, y) de un mapa equirectangular, sobre la esfera unitaria (con
+// la orientación de primitives::uv_sphere)
+fn sphere_point(x: usize, y: usize, width: usize, height: usize) -> Vec3 {
+    let u = (x as f32 + 0.5) / width as f32;
+    let polar = (y as f32 + 0.5) / height as f32 * std::f32::consts::PI;
+    let (sin, cos) = ((1.0 - u) * std::f32::consts::TAU).sin_cos();
+    Vec3::new(cos * polar.sin(), polar.cos(), sin * polar.sin())
+}
+
+// Reemplaza la normal del fragmento por la del mapa, llevada al mundo con su base
+// tangente, y rehace la luz difusa del Sol con ella. `strength` exagera (> 1) o
+// suaviza (< 1) la pendiente del mapa.
+pub fn perturb(fragment: Fragment<TangentFrame>, map: &Texture, strength: f32, uniforms: &Uniforms) -> Fragment {
+    let sampled = map.sample_normal(fragment.tex_coords);
+    let local = Vec3::new(sampled.x * strength, sampled.y * strength, sampled.z.max(0.0)).normalize();
+    let normal = (fragment.varyings.tbn(&fragment.normal) * local).normalize();
+
+    let view_dir = (uniforms.camera_position - fragment.world_position).normalize();
+    let intensity = uniforms.lights.first().map_or(0.0, |light| {
+        lighting::diffuse(&normal, &light.direction(&fragment.world_position), &view_dir, uniforms.material.two_sided)
+    });
+    let mut fragment = fragment.with_varyings(());
+    fragment.normal = normal;
+    fragment.intensity = intensity * fragment.sunlit;
+    fragment
+}
@@ -1,20 +1,64 @@
-use tobj;
 use nalgebra_glm::{Vec2, Vec3};
 use crate::vertex::Vertex;
+use crate::color::Color;
+use crate::bounds::{Aabb, BoundingSphere};
+use crate::memory::{vec_bytes, MemoryUsage};
+use crate::tangents::{self, TangentFrame};
 
 pub struct Obj {
     meshes: Vec<Mesh>,
+    // Volúmenes envolventes en espacio de modelo, ya con recentrado y escala
+    aabb: Aabb,
+    bounding_sphere: BoundingSphere,
+}
+
+// Punto que se usa como nuevo origen (pivote) del modelo al cargarlo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Recenter {
+    // Se conserva el origen del archivo
+    #[default]
+    None,
+    // Centro de la caja envolvente
+    BoundingBox,
+}
+
+// Ajustes aplicados al cargar un modelo, para que la escala de la escena
+// signifique lo mismo para cualquier asset
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LoadOptions {
+    pub recenter: Recenter,
+    // Escala el modelo para que su vértice más lejano quede a distancia 1 del pivote
+    pub normalize: bool,
+}
+
+impl LoadOptions {
+    // Centrado en la caja envolvente y con radio unitario
+    pub fn normalized() -> Self {
+        LoadOptions {
+            recenter: Recenter::BoundingBox,
+            normalize: true,
+        }
+    }
 }
 
 struct Mesh {
     vertices: Vec<Vec3>,
     normals: Vec<Vec3>,
     texcoords: Vec<Vec2>,
+    colors: Vec<Color>,
     indices: Vec<u32>,
+    // Base tangente por vértice, calculada al cargar (ver tangents.rs)
+    tangents: Vec<TangentFrame>,
+}
+
+impl Mesh {
+    fn compute_tangents(&mut self) {
+        self.tangents = tangents::compute(&self.vertices, &self.normals, &self.texcoords, &self.indices);
+    }
 }
 
 impl Obj {
-    pub fn load(filename: &str) -> Result<Self, tobj::LoadError> {
+    pub fn load_with_options(filename: &str, options: &LoadOptions) -> Result<Self, tobj::LoadError> {
         let (models, _) = tobj::load_obj(filename, &tobj::LoadOptions {
             single_index: true,
             triangulate: true,
@@ -33,30 +77,146 @@ impl Obj {
                 texcoords: mesh.texcoords.chunks(2)
                     .map(|t| Vec2::new(t[0], 1.0 - t[1]))
                     .collect(),
+                // Colores por vértice ("v x y z r g b"), si el exportador los incluye
+                colors: mesh.vertex_color.chunks(3)
+                    .map(|c| Color::from_f32(c[0], c[1], c[2]))
+                    .collect(),
                 indices: mesh.indices,
+                tangents: Vec::new(),
             }
         }).collect();
 
-        Ok(Obj { meshes })
+        let mut obj = Obj {
+            meshes,
+            aabb: Aabb { min: Vec3::zeros(), max: Vec3::zeros() },
+            bounding_sphere: BoundingSphere { center: Vec3::zeros(), radius: 0.0 },
+        };
+        obj.apply_options(options);
+        for mesh in &mut obj.meshes {
+            mesh.compute_tangents();
+        }
+        obj.aabb = Aabb::from_points(obj.positions());
+        obj.bounding_sphere = BoundingSphere::from_points(obj.positions());
+        Ok(obj)
     }
 
-    pub fn get_vertex_array(&self) -> Vec<Vertex> {
-        let mut vertices = Vec::new();
+    // Malla generada en código (ver primitives.rs), sin colores por vértice
+    pub fn from_arrays(vertices: Vec<Vec3>, normals: Vec<Vec3>, texcoords: Vec<Vec2>, indices: Vec<u32>) -> Self {
+        let mut mesh = Mesh { vertices, normals, texcoords, colors: Vec::new(), indices, tangents: Vec::new() };
+        mesh.compute_tangents();
+
+        let mut obj = Obj {
+            meshes: vec![mesh],
+            aabb: Aabb { min: Vec3::zeros(), max: Vec3::zeros() },
+            bounding_sphere: BoundingSphere { center: Vec3::zeros(), radius: 0.0 },
+        };
+        obj.aabb = Aabb::from_points(obj.positions());
+        obj.bounding_sphere = BoundingSphere::from_points(obj.positions());
+        obj
+    }
+
+    pub fn aabb(&self) -> Aabb {
+        self.aabb
+    }
+
+    pub fn bounding_sphere(&self) -> BoundingSphere {
+        self.bounding_sphere
+    }
+
+    fn positions(&self) -> impl Iterator<Item = &Vec3> + Clone {
+        self.meshes.iter().flat_map(|mesh| mesh.vertices.iter())
+    }
+
+    fn pivot(&self, recenter: Recenter) -> Vec3 {
+        match recenter {
+            Recenter::None => Vec3::zeros(),
+            Recenter::BoundingBox => Aabb::from_points(self.positions()).center(),
+        }
+    }
+
+    fn apply_options(&mut self, options: &LoadOptions) {
+        let pivot = self.pivot(options.recenter);
+
+        let scale = if options.normalize {
+            let radius = self
+                .positions()
+                .map(|p| (p - pivot).magnitude())
+                .fold(0.0, f32::max);
+            if radius > 0.0 { 1.0 / radius } else { 1.0 }
+        } else {
+            1.0
+        };
+
+        // Traslación y escala uniforme: las normales no cambian
+        for mesh in &mut self.meshes {
+            for position in &mut mesh.vertices {
+                *position = (*position - pivot) * scale;
+            }
+        }
+    }
+
+    // Vértices sin repetir de todas las mallas; `indices` los agrupa en triángulos.
+    // Así el shader de vértices corre una vez por vértice y no una por cara.
+    pub fn vertices(&self) -> Vec<Vertex> {
+        let mut vertices = Vec::with_capacity(self.meshes.iter().map(|mesh| mesh.vertices.len()).sum());
 
         for mesh in &self.meshes {
-            for &index in &mesh.indices {
-                let position = mesh.vertices[index as usize];
-                let normal = mesh.normals.get(index as usize)
+            for (index, &position) in mesh.vertices.iter().enumerate() {
+                let normal = mesh.normals.get(index)
                     .cloned()
                     .unwrap_or(Vec3::new(0.0, 1.0, 0.0));
-                let tex_coords = mesh.texcoords.get(index as usize)
+                let tex_coords = mesh.texcoords.get(index)
                     .cloned()
                     .unwrap_or(Vec2::new(0.0, 0.0));
+                let color = mesh.colors.get(index)
+                    .cloned()
+                    .unwrap_or(Color::WHITE);
 
-                vertices.push(Vertex::new(position, normal, tex_coords));
+                let mut vertex = Vertex::new(position, normal, tex_coords);
+                vertex.color = color;
+                vertices.push(vertex);
             }
         }
 
         vertices
     }
+
+    // Tres índices por triángulo sobre `vertices`, desplazados por las mallas anteriores
+    pub fn indices(&self) -> Vec<u32> {
+        let mut indices = Vec::with_capacity(self.meshes.iter().map(|mesh| mesh.indices.len()).sum());
+        let mut base = 0;
+        for mesh in &self.meshes {
+            indices.extend(mesh.indices.iter().map(|&index| base + index));
+            base += mesh.vertices.len() as u32;
+        }
+        indices
+    }
+
+    // Un vértice por esquina de cada triángulo (sin índices)
+    pub fn get_vertex_array(&self) -> Vec<Vertex> {
+        let vertices = self.vertices();
+        self.indices().into_iter().map(|index| vertices[index as usize].clone()).collect()
+    }
+
+    // Los de `vertices` con su base tangente, para materiales con mapa de normales
+    pub fn tangent_vertices(&self) -> Vec<Vertex<TangentFrame>> {
+        let frames = self.meshes.iter().flat_map(|mesh| (0..mesh.vertices.len()).map(|index| mesh.tangents.get(index).copied().unwrap_or_default()));
+        self.vertices().into_iter().zip(frames).map(|(vertex, frame)| vertex.with_varyings(frame)).collect()
+    }
+}
+
+impl MemoryUsage for Obj {
+    fn memory_bytes(&self) -> usize {
+        self.meshes
+            .iter()
+            .map(|mesh| {
+                vec_bytes(&mesh.vertices)
+                    + vec_bytes(&mesh.normals)
+                    + vec_bytes(&mesh.texcoords)
+                    + vec_bytes(&mesh.colors)
+                    + vec_bytes(&mesh.indices)
+                    + vec_bytes(&mesh.tangents)
+            })
+            .sum()
+    }
 }
@@ -0,0 +1,214 @@
+use std::fs;
+use minifb::{Key, Window};
+use nalgebra_glm::{DVec3, Vec3};
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::debug_draw;
+use crate::framebuffer::Framebuffer;
+use crate::solar_system::{self, SolarSystem};
+use crate::Uniforms;
+
+// Altura del ojo sobre la superficie, como fracción del radio del cuerpo
+const EYE_HEIGHT: f64 = 0.01;
+// Grados por frame al mirar alrededor o caminar con las teclas
+const LOOK_SPEED: f32 = 1.5;
+const WALK_SPEED: f32 = 1.0;
+const MAX_LATITUDE: f32 = 89.0;
+const GROUND_COLOR: Color = Color::new(92, 78, 64);
+const SKY_COLOR: Color = Color::new(70, 130, 205);
+// Luz del suelo de noche, en espacio lineal (no llega a negro para que se vea el horizonte)
+const NIGHT_LIGHT: f32 = 0.008;
+
+// Observador parado sobre la superficie de un cuerpo que rota: la cámara gira
+// con el cuerpo, así el Sol y los planetas salen y se ponen en el horizonte.
+// Ángulos en grados; la longitud crece hacia el este (el sentido de la rotación,
+// por eso el Sol siempre sale por el este) y el rumbo se mide desde el norte.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SurfaceObserver {
+    pub body: &'static str,
+    pub latitude: f32,
+    pub longitude: f32,
+    pub heading: f32,
+    // Ángulo de la mirada sobre el horizonte
+    pub elevation: f32,
+}
+
+impl Default for SurfaceObserver {
+    fn default() -> Self {
+        SurfaceObserver { body: "earth", latitude: 20.0, longitude: 0.0, heading: 90.0, elevation: 10.0 }
+    }
+}
+
+impl SurfaceObserver {
+    // Sección [observer] del archivo de escena: latitud, longitud y hacia dónde
+    // se mira al entrar al modo
+    //
+    //   [observer]
+    //   latitude = 20.0
+    //   longitude = 0.0
+    //   heading = 90.0     # 0 norte, 90 este
+    //   elevation = 10.0
+    pub fn load(path: &str) -> Self {
+        let parsed = fs::read_to_string(path).map_err(|err| err.to_string()).and_then(|text| SurfaceObserver::parse(&text));
+        parsed.unwrap_or_else(|err| {
+            eprintln!("Observador en {}: {}; se usan los valores por defecto", path, err);
+            SurfaceObserver::default()
+        })
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let document: toml::Table = text.parse().map_err(|e: toml::de::Error| e.to_string())?;
+        let mut observer = SurfaceObserver::default();
+        let table = match document.get("observer") {
+            Some(toml::Value::Table(table)) => table,
+            Some(_) => return Err(String::from("'observer' debe ser una tabla")),
+            None => return Ok(observer),
+        };
+
+        let fields = [
+            ("latitude", &mut observer.latitude),
+            ("longitude", &mut observer.longitude),
+            ("heading", &mut observer.heading),
+            ("elevation", &mut observer.elevation),
+        ];
+        for (key, field) in fields {
+            match table.get(key) {
+                None => {}
+                Some(toml::Value::Float(value)) => *field = *value as f32,
+                Some(toml::Value::Integer(value)) => *field = *value as f32,
+                Some(_) => return Err(format!("observer.{} debe ser un número", key)),
+            }
+        }
+        observer.latitude = observer.latitude.clamp(-MAX_LATITUDE, MAX_LATITUDE);
+        Ok(observer)
+    }
+
+    // Solo tiene sentido sobre un cuerpo con rotación propia
+    pub fn can_stand_on(system: &SolarSystem, body: &str) -> bool {
+        system.spin(body) != 0.0
+    }
+
+    // Flechas: mirar alrededor; W/S y A/D: caminar en latitud y longitud
+    pub fn handle_input(&mut self, window: &Window) {
+        if window.is_key_down(Key::Left) {
+            self.heading -= LOOK_SPEED;
+        }
+        if window.is_key_down(Key::Right) {
+            self.heading += LOOK_SPEED;
+        }
+        if window.is_key_down(Key::Up) {
+            self.elevation += LOOK_SPEED;
+        }
+        if window.is_key_down(Key::Down) {
+            self.elevation -= LOOK_SPEED;
+        }
+        if window.is_key_down(Key::W) {
+            self.latitude += WALK_SPEED;
+        }
+        if window.is_key_down(Key::S) {
+            self.latitude -= WALK_SPEED;
+        }
+        if window.is_key_down(Key::D) {
+            self.longitude += WALK_SPEED;
+        }
+        if window.is_key_down(Key::A) {
+            self.longitude -= WALK_SPEED;
+        }
+        self.heading = self.heading.rem_euclid(360.0);
+        self.elevation = self.elevation.clamp(-85.0, 85.0);
+        self.latitude = self.latitude.clamp(-MAX_LATITUDE, MAX_LATITUDE);
+        self.longitude = (self.longitude + 180.0).rem_euclid(360.0) - 180.0;
+    }
+
+    // Cenit, este y norte locales en coordenadas del mundo
+    fn local_frame(&self, system: &SolarSystem) -> (DVec3, DVec3, DVec3) {
+        let latitude = (self.latitude as f64).to_radians();
+        let longitude = (self.longitude as f64).to_radians();
+        // Con rotación retrógrada el este queda del otro lado: se refleja el modelo
+        let sense = if system.spin(self.body) < 0.0 { -1.0 } else { 1.0 };
+        let model_up = DVec3::new(
+            latitude.cos() * longitude.cos(),
+            sense * latitude.sin(),
+            sense * latitude.cos() * longitude.sin(),
+        );
+        // Misma rotación que la matriz de modelo del cuerpo; el norte sigue a su eje
+        let up = system.orient(self.body, model_up);
+        let east = (up.cross(&system.orient(self.body, DVec3::new(0.0, sense, 0.0)))).normalize();
+        let north = east.cross(&up);
+        (up, east, north)
+    }
+
+    pub fn camera(&self, system: &SolarSystem) -> Camera {
+        let (up, east, north) = self.local_frame(system);
+        let eye = system.position(self.body) + up * system.radius(self.body) * (1.0 + EYE_HEIGHT);
+        let heading = (self.heading as f64).to_radians();
+        let elevation = (self.elevation as f64).to_radians();
+        let direction = (north * heading.cos() + east * heading.sin()) * elevation.cos() + up * elevation.sin();
+        Camera::new(eye.cast(), (eye + direction).cast(), up.cast())
+    }
+
+    // Altura del Sol sobre el horizonte, en grados
+    pub fn sun_elevation(&self, system: &SolarSystem) -> f32 {
+        let (up, _, _) = self.local_frame(system);
+        let eye = system.position(self.body) + up * system.radius(self.body) * (1.0 + EYE_HEIGHT);
+        let to_sun = (system.position("sun") - eye).normalize();
+        to_sun.dot(&up).clamp(-1.0, 1.0).asin().to_degrees() as f32
+    }
+
+    // El propio cuerpo, su atmósfera y sus anillos rodean a la cámara: no se dibujan
+    // (el rasterizador no recorta contra el plano cercano) y el horizonte se pinta aparte
+    pub fn hides(&self, body: &str) -> bool {
+        body == self.body || body.strip_prefix(self.body).is_some_and(|rest| rest.starts_with('_'))
+    }
+
+    // Suelo bajo el horizonte y, si el cuerpo tiene atmósfera, cielo de día sobre el
+    // fondo. `uniforms` con matriz de modelo identidad.
+    pub fn draw_horizon(&self, framebuffer: &mut Framebuffer, uniforms: &Uniforms, system: &SolarSystem, origin: DVec3, atmosphere: bool) {
+        let Some(inverse) = (uniforms.projection_matrix * uniforms.view_matrix).try_inverse() else {
+            return;
+        };
+        let (up, _, _) = self.local_frame(system);
+        let eye = solar_system::to_render(system.position(self.body) + up * system.radius(self.body) * (1.0 + EYE_HEIGHT), origin);
+        let up: Vec3 = up.cast();
+        // Desde la altura del ojo el horizonte de la esfera cae un poco bajo la horizontal
+        let horizon = -(1.0 - 1.0 / (1.0 + EYE_HEIGHT as f32).powi(2)).sqrt();
+
+        // La luz del día sube del crepúsculo (Sol 6° bajo el horizonte) a pleno día
+        let sun = self.sun_elevation(system).to_radians().sin();
+        let daylight = ((sun + 0.1) / 0.3).clamp(0.0, 1.0);
+        let ground = GROUND_COLOR.to_linear().map(|channel| channel * (NIGHT_LIGHT + (1.0 - NIGHT_LIGHT) * sun.max(0.0)));
+        let sky = SKY_COLOR.to_linear().map(|channel| channel * daylight);
+
+        framebuffer.set_current_id(0);
+        for y in 0..framebuffer.height {
+            for x in 0..framebuffer.width {
+                let ray = debug_draw::unproject_far(&inverse, framebuffer, x, y) - eye;
+                let height = ray.normalize().dot(&up);
+                if height < horizon {
+                    framebuffer.point_linear(x, y, -1.0, ground);
+                } else if atmosphere && daylight > 0.0 {
+                    let index = y * framebuffer.width + x;
+                    if framebuffer.zbuffer[index] == f32::INFINITY {
+                        // Más claro cerca del horizonte
+                        framebuffer.hdr[index] = sky.map(|channel| channel * (1.0 + 0.4 * (1.0 - height)));
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn hud_line(&self, system: &SolarSystem) -> String {
+        let latitude = if self.latitude >= 0.0 { 'N' } else { 'S' };
+        let longitude = if self.longitude >= 0.0 { 'E' } else { 'O' };
+        format!(
+            "Observador en {}: lat {:.0} {}, lon {:.0} {}, rumbo {:.0}, Sol a {:.0} grados",
+            self.body,
+            self.latitude.abs(),
+            latitude,
+            self.longitude.abs(),
+            longitude,
+            self.heading,
+            self.sun_elevation(system)
+        )
+    }
+}
@@ -0,0 +1,70 @@
+use nalgebra_glm::DVec3;
+use crate::solar_system::SolarSystem;
+
+// Fantasmas del cuerpo seleccionado ("papel cebolla"): copias translúcidas en su
+// posición `count` pasos antes y después, separadas `step` unidades de tiempo de
+// la simulación. Las del pasado y las del futuro se tiñen distinto, así se ven la
+// dirección y la velocidad del movimiento; solo para cuerpos con órbita.
+#[derive(Debug, Clone, Copy)]
+pub struct OnionSkin {
+    pub enabled: bool,
+    pub count: usize,
+    pub step: f64,
+}
+
+impl Default for OnionSkin {
+    fn default() -> Self {
+        OnionSkin { enabled: false, count: 3, step: 6.0 }
+    }
+}
+
+impl OnionSkin {
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+    }
+
+    // Desfase de tiempo (negativo en el pasado) y posición de cada fantasma
+    pub fn ghosts(&self, system: &SolarSystem, body: &str) -> Vec<(f64, DVec3)> {
+        if !self.enabled {
+            return Vec::new();
+        }
+        (1..=self.count)
+            .flat_map(|k| [-(k as f64), k as f64])
+            .filter_map(|k| {
+                let offset = k * self.step;
+                system.position_at(body, system.time + offset).map(|position| (offset, position))
+            })
+            .collect()
+    }
+
+    // Línea del panel de información; se alterna con un clic sobre ella
+    pub fn hud_line(&self) -> String {
+        if self.enabled {
+            format!("Fantasmas: +-{} pasos de {} (clic para ocultar)", self.count, self.step)
+        } else {
+            String::from("Fantasmas: ocultos (clic para ver)")
+        }
+    }
+
+    // Comando de consola: `fantasmas [n] [paso]`
+    pub fn command(&mut self, args: &[&str]) -> Result<String, String> {
+        let usage = || String::from("Uso: fantasmas [n] [paso]");
+        match args {
+            [] => self.toggle(),
+            [count, rest @ ..] => {
+                self.count = count.parse::<usize>().map_err(|_| usage())?.clamp(1, 12);
+                if let [step] = rest {
+                    self.step = step.parse::<f64>().ok().filter(|step| *step > 0.0).ok_or_else(usage)?;
+                } else if !rest.is_empty() {
+                    return Err(usage());
+                }
+                self.enabled = true;
+            }
+        }
+        Ok(if self.enabled {
+            format!("Fantasmas: {} antes y después, cada {} unidades de tiempo", self.count, self.step)
+        } else {
+            String::from("Fantasmas ocultos")
+        })
+    }
+}
@@ -0,0 +1,109 @@
+use nalgebra_glm::DVec3;
+
+// Iteraciones de Newton para la ecuación de Kepler; con e < 0.99 sobran
+const KEPLER_ITERATIONS: usize = 12;
+const KEPLER_TOLERANCE: f64 = 1e-12;
+// Excentricidad máxima: de 1 en adelante la órbita deja de ser cerrada
+pub const MAX_ECCENTRICITY: f64 = 0.99;
+
+// Órbita kepleriana alrededor del padre, a partir de sus elementos. Con
+// excentricidad e inclinación 0 es un círculo en el plano XZ recorrido de +X hacia
+// +Z. Los ángulos van en radianes; el nodo ascendente y el periapsis se miden
+// desde +X, y la inclinación levanta la órbita hacia +Y pasado el nodo ascendente.
+#[derive(Debug, Clone, Copy)]
+pub struct Orbit {
+    // Semieje mayor (el radio si es circular)
+    pub radius: f64,
+    // Movimiento medio: radianes de anomalía media por unidad de tiempo de
+    // simulación (negativo: sentido horario)
+    pub angular_speed: f64,
+    // Desplazamiento fijo sobre el plano orbital
+    pub height: f64,
+    pub eccentricity: f64,
+    pub inclination: f64,
+    // Longitud del nodo ascendente y argumento del periapsis
+    pub ascending_node: f64,
+    pub periapsis: f64,
+    // Anomalía media en el tiempo 0
+    pub mean_anomaly: f64,
+}
+
+impl Orbit {
+    pub const fn circular(radius: f64, angular_speed: f64, height: f64) -> Self {
+        Orbit { radius, angular_speed, height, eccentricity: 0.0, inclination: 0.0, ascending_node: 0.0, periapsis: 0.0, mean_anomaly: 0.0 }
+    }
+
+    pub fn with_eccentricity(mut self, eccentricity: f64, periapsis: f64) -> Self {
+        self.eccentricity = eccentricity.clamp(0.0, MAX_ECCENTRICITY);
+        self.periapsis = periapsis;
+        self
+    }
+
+    pub fn with_inclination(mut self, inclination: f64, ascending_node: f64) -> Self {
+        self.inclination = inclination;
+        self.ascending_node = ascending_node;
+        self
+    }
+
+    pub fn with_mean_anomaly(mut self, mean_anomaly: f64) -> Self {
+        self.mean_anomaly = mean_anomaly;
+        self
+    }
+
+    // Semieje menor
+    pub fn semi_minor_axis(&self) -> f64 {
+        self.radius * (1.0 - self.eccentricity * self.eccentricity).sqrt()
+    }
+
+    // Distancia mínima al padre
+    pub fn periapsis_distance(&self) -> f64 {
+        self.radius * (1.0 - self.eccentricity)
+    }
+
+    // Anomalía excéntrica E con M = E - e·sen(E), por Newton
+    pub fn eccentric_anomaly_at(&self, time: f64) -> f64 {
+        let mean = self.mean_anomaly + time * self.angular_speed;
+        let e = self.eccentricity;
+        if e == 0.0 {
+            return mean;
+        }
+        // Punto de partida de Danby: converge para cualquier e < 1
+        let mut anomaly = mean + 0.85 * e * mean.sin().signum();
+        for _ in 0..KEPLER_ITERATIONS {
+            let step = (anomaly - e * anomaly.sin() - mean) / (1.0 - e * anomaly.cos());
+            anomaly -= step;
+            if step.abs() < KEPLER_TOLERANCE {
+                break;
+            }
+        }
+        anomaly
+    }
+
+    // Ángulo de la posición sobre el plano XZ, de +X hacia +Z (hacia dónde queda el
+    // cuerpo visto desde el padre)
+    pub fn angle_at(&self, time: f64) -> f64 {
+        let position = self.position_at(time);
+        position.z.atan2(position.x)
+    }
+
+    // Posición relativa al cuerpo padre
+    pub fn position_at(&self, time: f64) -> DVec3 {
+        self.point_at(self.eccentric_anomaly_at(time))
+    }
+
+    // Punto de la elipse para una anomalía excéntrica: recorrerla de 0 a 2π dibuja la
+    // órbita entera, con puntos más parejos que un paso fijo en el tiempo (que deja
+    // pocos cerca del periapsis)
+    pub fn point_at(&self, eccentric_anomaly: f64) -> DVec3 {
+        // En el plano de la órbita, con el foco (el padre) en el origen y el periapsis en +X
+        let x = self.radius * (eccentric_anomaly.cos() - self.eccentricity);
+        let y = self.semi_minor_axis() * eccentric_anomaly.sin();
+        let (sin_w, cos_w) = self.periapsis.sin_cos();
+        let (sin_i, cos_i) = self.inclination.sin_cos();
+        let (sin_n, cos_n) = self.ascending_node.sin_cos();
+        // Girado por el periapsis, levantado por la inclinación y llevado al nodo
+        let (along, across) = (x * cos_w - y * sin_w, x * sin_w + y * cos_w);
+        let (across, up) = (across * cos_i, across * sin_i);
+        DVec3::new(along * cos_n - across * sin_n, self.height + up, along * sin_n + across * cos_n)
+    }
+}
@@ -0,0 +1,218 @@
+use std::f64::consts::TAU;
+use nalgebra_glm::{DVec3, Vec2, Vec3};
+use crate::color::Color;
+use crate::debug_draw;
+use crate::framebuffer::Framebuffer;
+use crate::orbit::Orbit;
+use crate::solar_system::{self, Body, SolarSystem};
+use crate::text;
+use crate::Uniforms;
+
+const ORBIT_COLOR: Color = Color::new(40, 70, 110);
+// Las guías solas son más tenues: acompañan la escena sin competir con ella
+const GUIDE_COLOR: Color = Color::new(22, 36, 56);
+const TICK_COLOR: Color = Color::new(110, 160, 220);
+const LABEL_COLOR: u32 = 0x88AACC;
+// Nivel de detalle de las órbitas: se parte de BASE_SEGMENTS arcos y cada uno se
+// divide en dos mientras su cuerda se aleje más de MAX_ERROR_PIXELS del arco en
+// pantalla, hasta MAX_DEPTH veces (como mucho BASE_SEGMENTS * 2^MAX_DEPTH por órbita)
+const BASE_SEGMENTS: usize = 16;
+const MAX_DEPTH: u32 = 7;
+const MAX_ERROR_PIXELS: f32 = 0.75;
+// Separación mínima en pantalla entre marcas y entre etiquetas, en pixeles
+const MIN_TICK_PIXELS: f32 = 24.0;
+const MIN_LABEL_PIXELS: f32 = 90.0;
+// Pasos "redondos" de la regla, en UA de arco
+const TICK_STEPS: [f64; 9] = [0.01, 0.02, 0.05, 0.1, 0.2, 0.5, 1.0, 2.0, 5.0];
+// Cuerpos con órbita que no son planetas ni lunas
+const SKIPPED: [&str; 1] = ["spaceship"];
+
+// Qué se muestra de las órbitas (se alterna con O)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OrbitView {
+    #[default]
+    Hidden,
+    // Solo el recorrido de cada órbita, como una línea tenue
+    Guides,
+    // El recorrido con la regla en UA y sus etiquetas
+    Ruler,
+}
+
+impl OrbitView {
+    pub fn next(self) -> Self {
+        match self {
+            OrbitView::Hidden => OrbitView::Guides,
+            OrbitView::Guides => OrbitView::Ruler,
+            OrbitView::Ruler => OrbitView::Hidden,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            OrbitView::Hidden => "ocultas",
+            OrbitView::Guides => "guías",
+            OrbitView::Ruler => "con regla en UA",
+        }
+    }
+}
+
+// La escena no está a escala real: la UA es el radio de la órbita de la Tierra
+pub fn astronomical_unit(system: &SolarSystem) -> f64 {
+    system.orbit("earth").map_or(1.0, |orbit| orbit.radius)
+}
+
+pub const KM_PER_AU: f64 = 149_597_870.7;
+const DAYS_PER_YEAR: f64 = 365.25;
+
+// Distancia de la escena en kilómetros, con la misma equivalencia de la UA
+pub fn kilometers(system: &SolarSystem, distance: f64) -> f64 {
+    distance / astronomical_unit(system) * KM_PER_AU
+}
+
+// Días reales por unidad de tiempo de la simulación: una vuelta de la Tierra es un año
+pub fn days_per_time_unit(system: &SolarSystem) -> f64 {
+    system.orbit("earth").map_or(1.0, |orbit| DAYS_PER_YEAR * orbit.angular_speed.abs() / TAU)
+}
+
+// Líneas de órbita y, con OrbitView::Ruler, una regla en UA: marcas a lo largo de
+// cada órbita cuya separación se adapta al zoom (la más fina que deje al menos
+// MIN_TICK_PIXELS entre marcas) y etiquetas con la distancia recorrida sobre la
+// órbita desde el periapsis (en una elíptica las marcas se reparten por anomalía
+// excéntrica, así que quedan un poco más juntas en los extremos del eje mayor). `uniforms` con matriz de modelo identidad. Las etiquetas se devuelven con
+// su punto en espacio de render, para escribirlas con draw_labels sobre la imagen ya
+// mapeada a la pantalla.
+pub fn draw(framebuffer: &mut Framebuffer, uniforms: &Uniforms, system: &SolarSystem, origin: DVec3, view: OrbitView) -> Vec<(Vec3, String)> {
+    if view == OrbitView::Hidden {
+        return Vec::new();
+    }
+    let color = if view == OrbitView::Ruler { ORBIT_COLOR } else { GUIDE_COLOR };
+    let au = astronomical_unit(system);
+    let mut labels = Vec::new();
+    for body in system.bodies() {
+        if SKIPPED.contains(&body.name) {
+            continue;
+        }
+        let Some(orbit) = body.orbit else {
+            continue;
+        };
+        let parent = body.parent.map_or(DVec3::zeros(), |parent| system.position(parent));
+        // Punto de la órbita para una anomalía excéntrica, corrido `offset` hacia
+        // afuera del padre (las marcas de la regla)
+        let point = |anomaly: f64, offset: f64| {
+            let local = orbit.point_at(anomaly);
+            let outward = (local - DVec3::new(0.0, orbit.height, 0.0)).normalize();
+            solar_system::to_render(parent + local + outward * offset, origin)
+        };
+
+        // La órbita como polilínea adaptativa; de paso se mide cuántos pixeles ocupa una UA
+        let mut pixels = 0.0;
+        let mut visible_arc = 0.0;
+        let mut arcs = Vec::new();
+        for i in 0..BASE_SEGMENTS {
+            let (start, end) = (TAU * i as f64 / BASE_SEGMENTS as f64, TAU * (i + 1) as f64 / BASE_SEGMENTS as f64);
+            subdivide(framebuffer, uniforms, &|angle| point(angle, 0.0), start, end, 0, &mut arcs);
+        }
+        for (start, end) in arcs {
+            let (a, b) = (point(start, 0.0), point(end, 0.0));
+            debug_draw::draw_segment_additive(framebuffer, uniforms, a, b, color);
+            if let (Some(pa), Some(pb)) = (on_screen(framebuffer, uniforms, a), on_screen(framebuffer, uniforms, b)) {
+                pixels += (pb - pa).magnitude();
+                visible_arc += (orbit.point_at(end) - orbit.point_at(start)).magnitude() / au;
+            }
+        }
+        if view != OrbitView::Ruler || visible_arc <= 0.0 {
+            continue;
+        }
+        let pixels_per_au = pixels / visible_arc as f32;
+        let Some(step) = TICK_STEPS.iter().copied().find(|step| *step as f32 * pixels_per_au >= MIN_TICK_PIXELS) else {
+            continue;
+        };
+        let label_every = (MIN_LABEL_PIXELS / (step as f32 * pixels_per_au)).ceil().max(1.0) as usize;
+
+        let circumference = perimeter(&orbit) / au;
+        let half_tick = 0.25 * step * au;
+        let ticks = (circumference / step).floor() as usize;
+        for k in 0..ticks {
+            let angle = TAU * k as f64 * step / circumference;
+            let inner = point(angle, -half_tick);
+            let outer = point(angle, half_tick);
+            debug_draw::draw_segment_additive(framebuffer, uniforms, inner, outer, TICK_COLOR);
+
+            if k % label_every == 0 {
+                let label = if k == 0 { orbit_label(body, au) } else { format_au(k as f64 * step) };
+                labels.push((outer, label));
+            }
+        }
+    }
+    labels
+}
+
+pub fn draw_labels(framebuffer: &mut Framebuffer, uniforms: &Uniforms, labels: &[(Vec3, String)]) {
+    for (point, label) in labels {
+        if let Some(position) = on_screen(framebuffer, uniforms, *point) {
+            draw_label(framebuffer, position, label);
+        }
+    }
+}
+
+// Agrega a `arcs` los tramos de [start, end] que se dibujan como una sola cuerda. El
+// error es la distancia en pantalla entre el punto medio del arco y el de la cuerda;
+// un tramo que cruza el plano de la cámara se sigue dividiendo para no perder la
+// parte visible, y uno que queda entero de un lado de la pantalla no se divide.
+fn subdivide(framebuffer: &Framebuffer, uniforms: &Uniforms, point: &dyn Fn(f64) -> Vec3, start: f64, end: f64, depth: u32, arcs: &mut Vec<(f64, f64)>) {
+    let middle = 0.5 * (start + end);
+    let projected = [start, middle, end].map(|angle| debug_draw::project(uniforms, point(angle)));
+    let error = match projected {
+        [Some(a), Some(m), Some(b)] => {
+            let (width, height) = (framebuffer.width as f32, framebuffer.height as f32);
+            let points = [a, m, b];
+            let off_screen = points.iter().all(|p| p.x < 0.0)
+                || points.iter().all(|p| p.y < 0.0)
+                || points.iter().all(|p| p.x >= width)
+                || points.iter().all(|p| p.y >= height);
+            if off_screen { 0.0 } else { (m - (a + b) * 0.5).magnitude() }
+        }
+        [None, None, None] => 0.0,
+        _ => f32::INFINITY,
+    };
+    if error > MAX_ERROR_PIXELS && depth < MAX_DEPTH {
+        subdivide(framebuffer, uniforms, point, start, middle, depth + 1, arcs);
+        subdivide(framebuffer, uniforms, point, middle, end, depth + 1, arcs);
+    } else {
+        arcs.push((start, end));
+    }
+}
+
+// Perímetro de la elipse (aproximación de Ramanujan; exacta en un círculo)
+fn perimeter(orbit: &Orbit) -> f64 {
+    let (a, b) = (orbit.radius, orbit.semi_minor_axis());
+    let h = ((a - b) / (a + b)).powi(2);
+    std::f64::consts::PI * (a + b) * (1.0 + 3.0 * h / (10.0 + (4.0 - 3.0 * h).sqrt()))
+}
+
+// El origen de la regla lleva el nombre y el semieje mayor de la órbita
+fn orbit_label(body: &Body, au: f64) -> String {
+    let radius = body.orbit.map_or(0.0, |orbit| orbit.radius) / au;
+    format!("{} {}", body.name, format_au(radius))
+}
+
+fn format_au(value: f64) -> String {
+    if value >= 1.0 {
+        format!("{:.1} UA", value)
+    } else {
+        format!("{:.2} UA", value)
+    }
+}
+
+fn on_screen(framebuffer: &Framebuffer, uniforms: &Uniforms, point: Vec3) -> Option<Vec2> {
+    debug_draw::project(uniforms, point)
+        .filter(|p| p.x >= 0.0 && p.y >= 0.0 && p.x < framebuffer.width as f32 && p.y < framebuffer.height as f32)
+}
+
+fn draw_label(framebuffer: &mut Framebuffer, position: Vec2, label: &str) {
+    let x = position.x as usize + 3;
+    let y = (position.y as usize).saturating_sub(text::LINE_HEIGHT);
+    if x + text::text_width(label, 1) < framebuffer.width {
+        text::draw_text_shadowed(framebuffer, x, y, label, LABEL_COLOR, 1);
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use minifb::Window;
+
+// minifb no informa la frecuencia del monitor; el modo "display" usa este valor
+// con el limitador propio de minifb, que espera dentro de update_with_buffer
+const DISPLAY_FALLBACK_HZ: u32 = 60;
+// Frames que promedia el monitor de ritmo
+const PACING_WINDOW: usize = 120;
+
+// Cómo se reparte el tiempo entre frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FramePacing {
+    // Sin esperas: tan rápido como se pueda dibujar
+    Uncapped,
+    // Tope de frames por segundo; se duerme lo que falta hasta el intervalo
+    Capped(u32),
+    // Sincronizado (en lo posible) con el refresco de la pantalla
+    Display,
+}
+
+impl Default for FramePacing {
+    fn default() -> Self {
+        FramePacing::Capped(60)
+    }
+}
+
+impl FramePacing {
+    pub const CAPS: [u32; 3] = [30, 60, 120];
+
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "uncapped" | "libre" => Some(FramePacing::Uncapped),
+            "display" | "pantalla" => Some(FramePacing::Display),
+            number => number
+                .parse::<u32>()
+                .ok()
+                .filter(|fps| FramePacing::CAPS.contains(fps))
+                .map(FramePacing::Capped),
+        }
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            FramePacing::Uncapped => String::from("sin límite"),
+            FramePacing::Capped(fps) => format!("{} fps", fps),
+            FramePacing::Display => format!("pantalla (~{} Hz)", DISPLAY_FALLBACK_HZ),
+        }
+    }
+
+    // Frames por segundo buscados; 0 sin límite
+    pub fn target_fps(self) -> u32 {
+        match self {
+            FramePacing::Uncapped => 0,
+            FramePacing::Capped(fps) => fps,
+            FramePacing::Display => DISPLAY_FALLBACK_HZ,
+        }
+    }
+
+    // Solo el modo "display" deja la espera al limitador de minifb
+    pub fn configure(self, window: &mut Window) {
+        match self {
+            FramePacing::Display => window.set_target_fps(DISPLAY_FALLBACK_HZ as usize),
+            _ => window.set_target_fps(0),
+        }
+    }
+
+    // Duerme lo que falte del intervalo del frame que empezó en `frame_start`
+    pub fn wait(self, frame_start: Instant) {
+        let FramePacing::Capped(fps) = self else {
+            return;
+        };
+        let interval = Duration::from_secs_f64(1.0 / fps as f64);
+        if let Some(remaining) = interval.checked_sub(frame_start.elapsed()) {
+            std::thread::sleep(remaining);
+        }
+    }
+
+    // Con la ventana minimizada no hay frame que presentar: se duerme el intervalo
+    // completo también sin tope, para que el bucle no gire en vacío
+    pub fn wait_suspended(self, frame_start: Instant) {
+        let fps = match self.target_fps() {
+            0 => DISPLAY_FALLBACK_HZ,
+            fps => fps,
+        };
+        let interval = Duration::from_secs_f64(1.0 / fps as f64);
+        if let Some(remaining) = interval.checked_sub(frame_start.elapsed()) {
+            std::thread::sleep(remaining);
+        }
+    }
+}
+
+// Ritmo logrado: frames por segundo promedio y variación entre intervalos
+#[derive(Default)]
+pub struct PacingMonitor {
+    intervals: VecDeque<Duration>,
+}
+
+impl PacingMonitor {
+    pub fn record(&mut self, interval: Duration) {
+        if self.intervals.len() == PACING_WINDOW {
+            self.intervals.pop_front();
+        }
+        self.intervals.push_back(interval);
+    }
+
+    pub fn achieved_fps(&self) -> f32 {
+        let total: Duration = self.intervals.iter().sum();
+        if total.is_zero() {
+            0.0
+        } else {
+            self.intervals.len() as f32 / total.as_secs_f32()
+        }
+    }
+
+    // Desviación estándar de los intervalos, en milisegundos
+    pub fn jitter_ms(&self) -> f32 {
+        let count = self.intervals.len();
+        if count < 2 {
+            return 0.0;
+        }
+        let millis = self.intervals.iter().map(|interval| interval.as_secs_f32() * 1000.0);
+        let mean = millis.clone().sum::<f32>() / count as f32;
+        let variance = millis.map(|ms| (ms - mean).powi(2)).sum::<f32>() / count as f32;
+        variance.sqrt()
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use crate::color::Color;
+
+// Paleta de materiales: colores por cuerpo celeste, cargada desde assets/palettes/*.toml
+//
+//   name = "realistic"
+//   earth = ["#0000FF", "#228B22", "#FFFFFF"]
+//
+// Cada shader define el orden de sus colores; las entradas que falten usan los
+// valores por defecto del shader (color::palette).
+#[derive(Debug, Clone, Default)]
+pub struct Palette {
+    pub name: String,
+    bodies: HashMap<String, Vec<Color>>,
+}
+
+impl Palette {
+    pub fn builtin() -> Self {
+        Palette {
+            name: String::from("integrada"),
+            bodies: HashMap::new(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Palette::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let table: toml::Table = text.parse().map_err(|e: toml::de::Error| e.to_string())?;
+
+        let mut palette = Palette::builtin();
+        for (key, value) in table {
+            match (key.as_str(), value) {
+                ("name", toml::Value::String(name)) => palette.name = name,
+                (_, toml::Value::Array(entries)) => {
+                    let colors = entries
+                        .iter()
+                        .map(|entry| {
+                            entry
+                                .as_str()
+                                .and_then(parse_hex_color)
+                                .ok_or_else(|| format!("color inválido en '{}': {}", key, entry))
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    palette.bodies.insert(key, colors);
+                }
+                (_, other) => return Err(format!("valor inesperado para '{}': {}", key, other)),
+            }
+        }
+
+        Ok(palette)
+    }
+
+    // Devuelve los colores del cuerpo, completando con `defaults` lo que la paleta no define
+    pub fn colors<const N: usize>(&self, body: &str, defaults: [Color; N]) -> [Color; N] {
+        let mut colors = defaults;
+        if let Some(entries) = self.bodies.get(body) {
+            for (slot, color) in colors.iter_mut().zip(entries) {
+                *slot = *color;
+            }
+        }
+        colors
+    }
+
+    // Un color suelto del cuerpo por índice (shaders de expresiones)
+    pub fn color(&self, body: &str, index: usize, default: Color) -> Color {
+        self.bodies.get(body).and_then(|entries| entries.get(index)).copied().unwrap_or(default)
+    }
+}
+
+pub fn parse_hex_color(text: &str) -> Option<Color> {
+    let hex = text.trim().trim_start_matches('#');
+    if hex.len() != 6 {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok().map(Color::from_hex)
+}
+
+// Conjunto de paletas disponibles y la activa, compartida por todos los shaders
+pub struct PaletteSet {
+    palettes: Vec<Arc<Palette>>,
+    active: usize,
+}
+
+impl PaletteSet {
+    pub fn load_dir(dir: &str) -> Self {
+        let mut paths: Vec<_> = fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok().map(|e| e.path()))
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+                    .collect()
+            })
+            .unwrap_or_default();
+        paths.sort();
+
+        let mut palettes = Vec::new();
+        for path in paths {
+            match Palette::load(&path) {
+                Ok(palette) => palettes.push(Arc::new(palette)),
+                Err(err) => eprintln!("No se pudo cargar la paleta {}", err),
+            }
+        }
+
+        if palettes.is_empty() {
+            palettes.push(Arc::new(Palette::builtin()));
+        }
+
+        PaletteSet { palettes, active: 0 }
+    }
+
+    pub fn active(&self) -> Arc<Palette> {
+        Arc::clone(&self.palettes[self.active])
+    }
+
+    pub fn cycle(&mut self) -> &Palette {
+        self.active = (self.active + 1) % self.palettes.len();
+        &self.palettes[self.active]
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.palettes.iter().map(|p| p.name.as_str()).collect()
+    }
+
+    pub fn select(&mut self, name: &str) -> bool {
+        match self.palettes.iter().position(|p| p.name == name) {
+            Some(index) => {
+                self.active = index;
+                true
+            }
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::VecDeque;
+use nalgebra_glm::{DVec3, Vec4};
+use rand::Rng;
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+use crate::memory::{deque_bytes, MemoryUsage};
+use crate::solar_system;
+use crate::Uniforms;
+
+#[derive(Debug, Clone)]
+pub struct Particle {
+    pub position: DVec3,
+    pub velocity: DVec3,
+    pub age: f64,
+    pub lifetime: f64,
+}
+
+// Color de una partícula a lo largo de su vida, de `start` al nacer a `end` al
+// morir (interpolado en luz lineal). Con mezcla aditiva el negro no suma nada, así
+// que terminar en negro es apagarse.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorOverLife {
+    pub start: Color,
+    pub end: Color,
+}
+
+impl ColorOverLife {
+    pub const fn new(start: Color, end: Color) -> Self {
+        ColorOverLife { start, end }
+    }
+
+    // `life` de 0 (recién emitida) a 1 (al final de su vida)
+    pub fn at(&self, life: f32) -> Color {
+        self.start.lerp_linear(&self.end, life)
+    }
+}
+
+// Partículas sueltas (polvo, escombros, gases) que se mueven en línea recta y
+// cambian de color con la edad. Se dibujan con mezcla aditiva y prueba de
+// profundidad sin escribirla: puntos de un pixel, o con `size` > 0 discos de
+// borde suave que miran a la cámara y se achican con la distancia.
+#[derive(Debug)]
+pub struct ParticleSystem {
+    // De la más vieja a la más nueva: descartar la más vieja no mueve el resto
+    pub particles: VecDeque<Particle>,
+    pub color: ColorOverLife,
+    // Radio de cada partícula en unidades de la escena (0: un pixel)
+    pub size: f32,
+    capacity: usize,
+}
+
+impl ParticleSystem {
+    pub fn new(capacity: usize, color: ColorOverLife) -> Self {
+        ParticleSystem { particles: VecDeque::with_capacity(capacity), color, size: 0.0, capacity }
+    }
+
+    pub fn with_size(mut self, size: f32) -> Self {
+        self.size = size.max(0.0);
+        self
+    }
+
+    // Si el sistema está lleno se descarta la partícula más vieja
+    pub fn emit(&mut self, position: DVec3, velocity: DVec3, lifetime: f64) {
+        if self.particles.len() >= self.capacity {
+            self.particles.pop_front();
+        }
+        self.particles.push_back(Particle { position, velocity, age: 0.0, lifetime });
+    }
+
+    // Cambia el máximo de partículas; al achicarlo se descartan las más viejas
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        let excess = self.particles.len().saturating_sub(self.capacity);
+        self.particles.drain(..excess);
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        for particle in &mut self.particles {
+            particle.position += particle.velocity * dt;
+            particle.age += dt;
+        }
+        self.particles.retain(|particle| particle.age < particle.lifetime);
+    }
+
+    // `uniforms` solo aporta vista, proyección y viewport
+    pub fn draw(&self, framebuffer: &mut Framebuffer, uniforms: &Uniforms, origin: DVec3) {
+        let transform = uniforms.projection_matrix * uniforms.view_matrix;
+        for particle in &self.particles {
+            let p = solar_system::to_render(particle.position, origin);
+            let clip = transform * Vec4::new(p.x, p.y, p.z, 1.0);
+            if clip.w <= 0.0 {
+                continue;
+            }
+            let screen = uniforms.viewport_matrix * (clip / clip.w);
+            if screen.x < 0.0 || screen.y < 0.0 {
+                continue;
+            }
+            let color = self.color.at((particle.age / particle.lifetime) as f32);
+            // Radio en pixeles; por debajo de uno queda el punto
+            let radius = uniforms.projection_matrix[(1, 1)] * self.size / clip.w * framebuffer.height as f32 / 2.0;
+            if radius <= 1.0 {
+                framebuffer.add_point(screen.x as usize, screen.y as usize, screen.z, color);
+                continue;
+            }
+            let reach = radius.ceil() as isize;
+            let (center_x, center_y) = (screen.x as isize, screen.y as isize);
+            for dy in -reach..=reach {
+                for dx in -reach..=reach {
+                    let (x, y) = (center_x + dx, center_y + dy);
+                    let distance = ((dx * dx + dy * dy) as f32).sqrt() / radius;
+                    if x < 0 || y < 0 || distance >= 1.0 {
+                        continue;
+                    }
+                    let falloff = (1.0 - distance * distance).powi(2);
+                    framebuffer.add_point(x as usize, y as usize, screen.z, color * falloff);
+                }
+            }
+        }
+    }
+}
+
+impl MemoryUsage for ParticleSystem {
+    fn memory_bytes(&self) -> usize {
+        deque_bytes(&self.particles)
+    }
+}
+
+// Fuente de partículas: cada paso suelta `rate` por unidad de tiempo (acumulando
+// las fracciones) desde `position`, con `velocity` más una dispersión al azar de
+// hasta `spread` por eje. El dueño mueve la fuente antes de cada update.
+#[derive(Debug, Clone)]
+pub struct Emitter {
+    pub position: DVec3,
+    pub velocity: DVec3,
+    pub rate: f64,
+    pub spread: f64,
+    pub lifetime: f64,
+    pending: f64,
+}
+
+impl Emitter {
+    pub fn new(rate: f64, spread: f64, lifetime: f64) -> Self {
+        Emitter { position: DVec3::zeros(), velocity: DVec3::zeros(), rate, spread, lifetime, pending: 0.0 }
+    }
+
+    pub fn update(&mut self, particles: &mut ParticleSystem, dt: f64, rng: &mut impl Rng) {
+        self.pending += self.rate * dt;
+        while self.pending >= 1.0 {
+            self.pending -= 1.0;
+            let jitter = DVec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0));
+            particles.emit(self.position, self.velocity + jitter * self.spread, self.lifetime);
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use minifb::{Key, Window};
+use nalgebra_glm::DVec3;
+use crate::camera::Camera;
+use crate::ship::Ship;
+use crate::solar_system::{self, SolarSystem};
+
+// Nave que se pilotea con Enter
+pub const PLAYER_SHIP: &str = "spaceship";
+// Velocidad máxima y aceleración de los motores por frame: cruza de la Tierra a
+// Marte en unos pocos segundos
+const MAX_SPEED: f64 = 0.5;
+const THRUST: f64 = 0.01;
+// Radianes por frame de cabeceo y guiñada, y de alabeo
+const TURN_SPEED: f64 = 0.03;
+const ROLL_SPEED: f64 = 0.05;
+// Por debajo de esta velocidad el freno la deja quieta
+const MIN_SPEED: f64 = 1e-4;
+// Cámara de persecución: distancia detrás de la nave, altura sobre ella y punto de
+// mira delante, en radios de la nave
+const CHASE_DISTANCE: f64 = 3.0;
+const CHASE_HEIGHT: f64 = 0.8;
+const CHASE_LOOK_AHEAD: f64 = 2.0;
+// Fracción de la distancia a su lugar que recorre por frame la cámara de persecución
+pub const CHASE_SMOOTHING: f32 = 0.15;
+// El ojo de la cabina va delante del centro, a esta fracción del radio, y un poco arriba
+const COCKPIT_FORWARD: f64 = 0.5;
+const COCKPIT_HEIGHT: f64 = 0.15;
+
+// Lo que pide el jugador en un frame, cada eje de -1 a 1. `thrust` negativo frena.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Controls {
+    pub thrust: f64,
+    pub pitch: f64,
+    pub yaw: f64,
+    pub roll: f64,
+}
+
+// Nave en manos del jugador: al tomar los mandos deja su órbita y desde ahí se
+// mueve solo con su inercia y el empuje de los motores (sin gravedad), orientada
+// con cabeceo, guiñada y alabeo. No entra en los cuerpos: se apoya en su
+// superficie y pierde la velocidad que llevaba hacia ellos.
+pub struct Pilot {
+    pub name: &'static str,
+    pub ship: Ship,
+    // Se consumen en cada update
+    controls: Controls,
+}
+
+impl Pilot {
+    // Arranca con la velocidad que traía en su órbita, con la nariz hacia donde iba
+    pub fn take(system: &mut SolarSystem, name: &'static str) -> Self {
+        let mut ship = Ship::new(system.position(name), MAX_SPEED, THRUST);
+        if let Some(orbit) = system.orbit(name) {
+            ship.velocity = orbit.position_at(system.time + 1.0) - orbit.position_at(system.time);
+        }
+        if ship.velocity.magnitude() > MIN_SPEED {
+            ship.face(ship.velocity, DVec3::y());
+        }
+        system.release_orbit(name);
+        Pilot { name, ship, controls: Controls::default() }
+    }
+
+    // ↑ / ↓ empujan y frenan, W / S cabecean (S levanta la nariz, como tirar de la
+    // palanca), A / D y ← / → giran y Q / E alabean
+    pub fn handle_input(&mut self, window: &Window) {
+        let axis = |positive: Key, negative: Key| window.is_key_down(positive) as i32 as f64 - window.is_key_down(negative) as i32 as f64;
+        self.controls = Controls {
+            thrust: axis(Key::Up, Key::Down),
+            pitch: axis(Key::S, Key::W),
+            yaw: (axis(Key::D, Key::A) + axis(Key::Right, Key::Left)).clamp(-1.0, 1.0),
+            roll: axis(Key::E, Key::Q),
+        };
+    }
+
+    pub fn update(&mut self, system: &mut SolarSystem, dt: f64) {
+        let controls = std::mem::take(&mut self.controls);
+        self.ship.rotate(controls.pitch * TURN_SPEED * dt, controls.yaw * TURN_SPEED * dt, controls.roll * ROLL_SPEED * dt);
+        let acceleration = if controls.thrust >= 0.0 {
+            self.ship.forward() * controls.thrust * THRUST
+        } else if self.ship.velocity.magnitude() > MIN_SPEED {
+            // Frena contra la velocidad sin pasarse
+            -self.ship.velocity.normalize() * (-controls.thrust * THRUST).min(self.ship.velocity.magnitude() / dt)
+        } else {
+            self.ship.velocity = DVec3::zeros();
+            DVec3::zeros()
+        };
+        self.ship.integrate(acceleration, dt);
+
+        let radius = system.radius(self.name);
+        for body in system.bodies() {
+            let offset = self.ship.position - body.position;
+            let distance = offset.magnitude();
+            let contact = radius + body.radius;
+            if body.name == self.name || distance >= contact || distance == 0.0 {
+                continue;
+            }
+            let normal = offset / distance;
+            self.ship.position = body.position + normal * contact;
+            self.ship.velocity -= normal * self.ship.velocity.dot(&normal).min(0.0);
+        }
+        system.set_pose(self.name, self.ship.position, &self.ship.rotation_matrix());
+    }
+
+    // Lugar de la cámara de persecución: detrás y un poco arriba de la nave, mirando
+    // hacia adelante, con su techo como arriba; la cámara lo sigue con Camera::follow
+    pub fn chase_camera(&self, system: &SolarSystem) -> Camera {
+        let radius = system.radius(self.name);
+        let (forward, up) = (self.ship.forward(), self.ship.up());
+        let eye = self.ship.position - forward * radius * CHASE_DISTANCE + up * radius * CHASE_HEIGHT;
+        let center = self.ship.position + forward * radius * CHASE_LOOK_AHEAD;
+        Camera::new(eye.cast(), center.cast(), up.cast())
+    }
+
+    // Desde la cabina, clavada a la orientación de la nave. La nave misma no se
+    // dibuja (ver `hides`).
+    pub fn cockpit_camera(&self, system: &SolarSystem) -> Camera {
+        let radius = system.radius(self.name);
+        let (forward, up) = (self.ship.forward(), self.ship.up());
+        let eye = self.ship.position + forward * radius * COCKPIT_FORWARD + up * radius * COCKPIT_HEIGHT;
+        Camera::new(eye.cast(), (eye + forward).cast(), up.cast())
+    }
+
+    // La nave y su resplandor rodean a la cámara de la cabina
+    pub fn hides(&self, body: &str) -> bool {
+        body == self.name || body.strip_prefix(self.name).is_some_and(|rest| rest.starts_with('_'))
+    }
+
+    pub fn hud_line(&self) -> String {
+        format!(
+            "Pilotando {}: velocidad {:.3} de {:.1} por frame (Enter: cambia la cámara)",
+            solar_system::display_name(self.name),
+            self.ship.velocity.magnitude(),
+            MAX_SPEED
+        )
+    }
+}
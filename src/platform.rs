@@ -0,0 +1,174 @@
+use std::fs;
+use std::io;
+use minifb::{Window, WindowOptions};
+
+// Preferencias de la ventana que se recuerdan entre ejecuciones (no se versiona)
+pub const SETTINGS_FILE: &str = "settings.toml";
+const TITLE: &str = "Sistema solar";
+const ICON_SIZE: usize = 32;
+
+// Posición y tamaño de la ventana y modo pantalla completa. minifb no informa la
+// resolución del monitor: la pantalla completa es una ventana sin bordes en (0, 0)
+// del tamaño `fullscreen_width` x `fullscreen_height`.
+//
+//   [window]
+//   x = 500
+//   y = 500
+//   width = 800
+//   height = 600
+//   fullscreen = false
+//   fullscreen_width = 1920
+//   fullscreen_height = 1080
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowSettings {
+    pub x: isize,
+    pub y: isize,
+    pub width: usize,
+    pub height: usize,
+    pub fullscreen: bool,
+    pub fullscreen_width: usize,
+    pub fullscreen_height: usize,
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        WindowSettings { x: 500, y: 500, width: 800, height: 600, fullscreen: false, fullscreen_width: 1920, fullscreen_height: 1080 }
+    }
+}
+
+impl WindowSettings {
+    // Sin archivo (la primera vez) se usan los valores por defecto sin avisar
+    pub fn load(path: &str) -> Self {
+        let Ok(text) = fs::read_to_string(path) else {
+            return WindowSettings::default();
+        };
+        WindowSettings::parse(&text).unwrap_or_else(|err| {
+            eprintln!("Ajustes de ventana en {}: {}; se usan los valores por defecto", path, err);
+            WindowSettings::default()
+        })
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let document: toml::Table = text.parse().map_err(|e: toml::de::Error| e.to_string())?;
+        let mut settings = WindowSettings::default();
+        let table = match document.get("window") {
+            Some(toml::Value::Table(table)) => table,
+            Some(_) => return Err(String::from("'window' debe ser una tabla")),
+            None => return Ok(settings),
+        };
+
+        let integer = |key: &str| -> Result<Option<i64>, String> {
+            match table.get(key) {
+                None => Ok(None),
+                Some(value) => value.as_integer().map(Some).ok_or_else(|| format!("window.{} debe ser un entero", key)),
+            }
+        };
+        let size = |key: &str| -> Result<Option<usize>, String> {
+            match integer(key)? {
+                Some(value) if value > 0 => Ok(Some(value as usize)),
+                Some(_) => Err(format!("window.{} debe ser positivo", key)),
+                None => Ok(None),
+            }
+        };
+
+        settings.x = integer("x")?.map_or(settings.x, |x| x as isize);
+        settings.y = integer("y")?.map_or(settings.y, |y| y as isize);
+        settings.width = size("width")?.unwrap_or(settings.width);
+        settings.height = size("height")?.unwrap_or(settings.height);
+        settings.fullscreen_width = size("fullscreen_width")?.unwrap_or(settings.fullscreen_width);
+        settings.fullscreen_height = size("fullscreen_height")?.unwrap_or(settings.fullscreen_height);
+        if let Some(value) = table.get("fullscreen") {
+            settings.fullscreen = value.as_bool().ok_or_else(|| String::from("window.fullscreen debe ser booleano"))?;
+        }
+        Ok(settings)
+    }
+
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let text = format!(
+            "# Lo escribe el programa al salir: última posición y tamaño de la ventana\n\
+             [window]\nx = {}\ny = {}\nwidth = {}\nheight = {}\nfullscreen = {}\nfullscreen_width = {}\nfullscreen_height = {}\n",
+            self.x, self.y, self.width, self.height, self.fullscreen, self.fullscreen_width, self.fullscreen_height
+        );
+        fs::write(path, text)
+    }
+
+    // Guarda la geometría actual si la ventana no está en pantalla completa
+    pub fn remember(&mut self, window: &Window) {
+        if self.fullscreen {
+            return;
+        }
+        let (x, y) = window.get_position();
+        let (width, height) = window.get_size();
+        if width > 0 && height > 0 {
+            (self.x, self.y, self.width, self.height) = (x, y, width, height);
+        }
+    }
+}
+
+// Minimizada, minifb informa un tamaño de cero (o un lado en cero, según el sistema)
+pub fn is_minimized(window: &Window) -> bool {
+    let (width, height) = window.get_size();
+    width == 0 || height == 0
+}
+
+// Crea la ventana en el modo de `settings`: con bordes y redimensionable, o sin
+// bordes y por encima de las demás en pantalla completa
+pub fn open_window(settings: &WindowSettings) -> Result<Window, minifb::Error> {
+    let (x, y, width, height) = if settings.fullscreen {
+        (0, 0, settings.fullscreen_width, settings.fullscreen_height)
+    } else {
+        (settings.x, settings.y, settings.width, settings.height)
+    };
+    let options = WindowOptions {
+        borderless: settings.fullscreen,
+        title: !settings.fullscreen,
+        resize: !settings.fullscreen,
+        topmost: settings.fullscreen,
+        ..WindowOptions::default()
+    };
+    let mut window = Window::new(TITLE, width, height, options)?;
+    window.set_position(x, y);
+    set_icon(&mut window);
+    Ok(window)
+}
+
+// X11 recibe el ícono como buffer ARGB; en Windows minifb pide un .ico en disco y
+// en macOS no tiene efecto, así que ahí se queda el ícono por defecto
+#[cfg(target_os = "linux")]
+fn set_icon(window: &mut Window) {
+    let icon = icon_argb();
+    if let Ok(icon) = minifb::Icon::try_from(icon.as_slice()) {
+        window.set_icon(icon);
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_icon(_window: &mut Window) {}
+
+// Planeta anillado dibujado en código: ancho, alto y los pixeles en ARGB
+fn icon_argb() -> Vec<u64> {
+    let mut pixels = vec![ICON_SIZE as u64, ICON_SIZE as u64];
+    let center = (ICON_SIZE as f32 - 1.0) / 2.0;
+    for y in 0..ICON_SIZE {
+        for x in 0..ICON_SIZE {
+            let (dx, dy) = (x as f32 - center, y as f32 - center);
+            let distance = (dx * dx + dy * dy).sqrt();
+            // Anillo: elipse achatada e inclinada alrededor del planeta
+            let (u, v) = (dx * 0.94 + dy * 0.34, (-dx * 0.34 + dy * 0.94) * 3.2);
+            let ring = (u * u + v * v).sqrt();
+            let in_front = v > 0.0 || distance > 9.0;
+            let argb: u64 = if (12.0..15.0).contains(&ring) && in_front {
+                0xFFD8C8A0
+            } else if distance < 9.0 {
+                // Sombreado simple con la luz arriba a la izquierda
+                let light = (1.0 - (dx + dy + 9.0) / 24.0).clamp(0.35, 1.0);
+                let (r, g, b) = ((235.0 * light) as u64, (170.0 * light) as u64, (90.0 * light) as u64);
+                0xFF000000 | (r << 16) | (g << 8) | b
+            } else {
+                0
+            };
+            pixels.push(argb);
+        }
+    }
+    pixels
+}
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Arc;
+use crate::color::Color;
+use crate::fragment::Fragment;
+use crate::Uniforms;
+
+// Shaders de cuerpos en bibliotecas dinámicas (feature "plugins"). Una biblioteca
+// compilada como cdylib exporta
+//
+//   #[no_mangle]
+//   pub extern "C" fn shade(fragment: *const PluginFragment, params: *const PluginParams) -> PluginColor
+//
+// con las mismas estructuras #[repr(C)] de este archivo, y se registra por nombre
+// en el archivo de escena junto con los cuerpos que pinta:
+//
+//   [plugins.rocoso]
+//   library = "plugins/librocoso.so"
+//   bodies = ["mars", "mercury"]
+//
+// El color que devuelve es el albedo: la luz del Sol, las luces puntuales y el
+// material se aplican después, igual que con los shaders propios. Hay un ejemplo
+// en plugins/example_shader.rs.
+
+// Datos del fragmento en el ABI de C
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct PluginFragment {
+    // Posición en espacio de modelo (la esfera de radio 1)
+    pub position: [f32; 3],
+    pub world_position: [f32; 3],
+    pub normal: [f32; 3],
+    // Iluminación difusa ya calculada, por si el shader la quiere usar
+    pub intensity: f32,
+}
+
+// Parámetros del material del cuerpo y el tiempo de las animaciones (frames a x1,
+// con fracción; ver SimulationClock)
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct PluginParams {
+    pub time: f32,
+    pub band_frequency: f32,
+    pub noise_scale: f32,
+    pub hue_shift: f32,
+}
+
+// Componentes en [0, 1] (sRGB)
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct PluginColor {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+}
+
+#[cfg_attr(not(feature = "plugins"), allow(dead_code))]
+pub const SHADE_SYMBOL: &[u8] = b"shade";
+
+pub type ShadeFn = unsafe extern "C" fn(*const PluginFragment, *const PluginParams) -> PluginColor;
+
+pub struct PluginShader {
+    shade: ShadeFn,
+    // La biblioteca tiene que vivir mientras se use `shade`
+    #[cfg(feature = "plugins")]
+    _library: libloading::Library,
+}
+
+impl PluginShader {
+    #[cfg(feature = "plugins")]
+    fn load(path: &str) -> Result<Self, String> {
+        // SAFETY: cargar una biblioteca ejecuta sus inicializadores; es código del
+        // usuario que él mismo registró en la escena
+        let library = unsafe { libloading::Library::new(path) }.map_err(|err| err.to_string())?;
+        // SAFETY: el contrato del plug-in es exportar `shade` con la firma de ShadeFn
+        let shade = unsafe { library.get::<ShadeFn>(SHADE_SYMBOL) }.map(|symbol| *symbol).map_err(|err| err.to_string())?;
+        Ok(PluginShader { shade, _library: library })
+    }
+
+    pub fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        let input = PluginFragment {
+            position: fragment.vertex_position.into(),
+            world_position: fragment.world_position.into(),
+            normal: fragment.normal.into(),
+            intensity: fragment.intensity,
+        };
+        let params = PluginParams {
+            time: uniforms.time,
+            band_frequency: uniforms.material.params.band_frequency,
+            noise_scale: uniforms.material.params.noise_scale,
+            hue_shift: uniforms.material.params.hue_shift,
+        };
+        // SAFETY: los punteros apuntan a valores vivos durante la llamada
+        let color = unsafe { (self.shade)(&input, &params) };
+        Color::from_f32(color.r, color.g, color.b)
+    }
+}
+
+// Plug-ins de la escena, indexados por el cuerpo que pintan
+#[derive(Default)]
+pub struct PluginLibrary {
+    bodies: HashMap<String, Arc<PluginShader>>,
+}
+
+impl PluginLibrary {
+    pub fn load(path: &str) -> Self {
+        let Ok(text) = fs::read_to_string(path) else {
+            return PluginLibrary::default();
+        };
+        let entries = match parse(&text) {
+            Ok(entries) => entries,
+            Err(err) => {
+                eprintln!("Plug-ins en {}: {}", path, err);
+                return PluginLibrary::default();
+            }
+        };
+        PluginLibrary::from_entries(entries)
+    }
+
+    #[cfg(feature = "plugins")]
+    fn from_entries(entries: Vec<PluginEntry>) -> Self {
+        let mut library = PluginLibrary::default();
+        for entry in entries {
+            match PluginShader::load(&entry.library) {
+                Ok(shader) => {
+                    println!("Plug-in {} cargado de {} para {}", entry.name, entry.library, entry.bodies.join(", "));
+                    let shader = Arc::new(shader);
+                    for body in entry.bodies {
+                        library.bodies.insert(body, Arc::clone(&shader));
+                    }
+                }
+                Err(err) => eprintln!("No se pudo cargar el plug-in {} ({}): {}", entry.name, entry.library, err),
+            }
+        }
+        library
+    }
+
+    #[cfg(not(feature = "plugins"))]
+    fn from_entries(entries: Vec<PluginEntry>) -> Self {
+        if !entries.is_empty() {
+            eprintln!("La escena registra plug-ins de shaders; se ignoran porque requieren compilar con --features plugins");
+        }
+        PluginLibrary::default()
+    }
+
+    pub fn get(&self, body: &str) -> Option<Arc<PluginShader>> {
+        self.bodies.get(body).cloned()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bodies.is_empty()
+    }
+}
+
+// Sin la feature "plugins" la sección se lee pero no se carga nada
+#[cfg_attr(not(feature = "plugins"), allow(dead_code))]
+struct PluginEntry {
+    name: String,
+    library: String,
+    bodies: Vec<String>,
+}
+
+// Sección [plugins.<nombre>] del archivo de escena
+fn parse(text: &str) -> Result<Vec<PluginEntry>, String> {
+    let document: toml::Table = text.parse().map_err(|e: toml::de::Error| e.to_string())?;
+    let plugins = match document.get("plugins") {
+        Some(toml::Value::Table(plugins)) => plugins,
+        Some(_) => return Err(String::from("'plugins' debe ser una tabla")),
+        None => return Ok(Vec::new()),
+    };
+
+    let mut entries = Vec::new();
+    for (name, entry) in plugins {
+        let entry = entry.as_table().ok_or_else(|| format!("plugins.{} debe ser una tabla", name))?;
+        let library = entry
+            .get("library")
+            .and_then(|value| value.as_str())
+            .ok_or_else(|| format!("plugins.{}.library debe ser la ruta de la biblioteca", name))?;
+        let bodies = entry
+            .get("bodies")
+            .and_then(|value| value.as_array())
+            .and_then(|bodies| bodies.iter().map(|body| body.as_str().map(String::from)).collect::<Option<Vec<_>>>())
+            .ok_or_else(|| format!("plugins.{}.bodies debe ser una lista de cuerpos", name))?;
+        entries.push(PluginEntry { name: name.clone(), library: library.to_string(), bodies });
+    }
+    Ok(entries)
+}
@@ -0,0 +1,247 @@
+use rayon::prelude::*;
+use crate::framebuffer::Framebuffer;
+
+// Antialiasing en pantalla (FXAA) sobre el color final, antes de las capturas y del
+// HUD. Mucho más barato que el supermuestreo: no sombrea más fragmentos, sino que
+// busca los bordes por contraste de luminancia y mezcla cada pixel del borde con su
+// vecino del otro lado, en proporción a dónde cae el pixel a lo largo del borde.
+
+// Contraste mínimo para tratar un pixel como borde: absoluto (zonas oscuras) y
+// relativo a la luminancia más alta del vecindario
+const EDGE_THRESHOLD_MIN: f32 = 0.0312;
+const EDGE_THRESHOLD: f32 = 0.125;
+// Cuánto se suavizan los detalles de un pixel (0 lo desactiva)
+const SUBPIXEL_BLEND: f32 = 0.75;
+// Pasos de la búsqueda del final del borde, en pixeles; los últimos saltan más
+const SEARCH_STEPS: [f32; 10] = [1.0, 1.0, 1.0, 1.0, 1.0, 1.5, 2.0, 2.0, 4.0, 8.0];
+
+pub fn fxaa(framebuffer: &mut Framebuffer) {
+    let (width, height) = (framebuffer.width, framebuffer.height);
+    if width < 3 || height < 3 {
+        return;
+    }
+    let colors: Vec<[f32; 3]> = framebuffer.buffer.iter().map(|&color| unpack(color)).collect();
+    let lumas: Vec<f32> = colors.iter().map(luma).collect();
+    let image = Image { width, height, colors: &colors, lumas: &lumas };
+
+    for y in 0..height {
+        for x in 0..width {
+            if let Some(color) = image.antialias(x, y) {
+                framebuffer.buffer[y * width + x] = pack(color);
+            }
+        }
+    }
+}
+
+struct Image<'a> {
+    width: usize,
+    height: usize,
+    colors: &'a [[f32; 3]],
+    lumas: &'a [f32],
+}
+
+impl Image<'_> {
+    fn luma_at(&self, x: isize, y: isize) -> f32 {
+        let x = x.clamp(0, self.width as isize - 1) as usize;
+        let y = y.clamp(0, self.height as isize - 1) as usize;
+        self.lumas[y * self.width + x]
+    }
+
+    // Interpolación bilineal en coordenadas de pixel (el centro del pixel x está en x + 0.5)
+    fn sample<T: Copy>(&self, values: &[T], u: f32, v: f32, mix: impl Fn(T, T, f32) -> T) -> T {
+        let (fx, fy) = ((u - 0.5).max(0.0), (v - 0.5).max(0.0));
+        let (x0, y0) = ((fx as usize).min(self.width - 1), (fy as usize).min(self.height - 1));
+        let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+        let (tx, ty) = (fx - x0 as f32, fy - y0 as f32);
+        let at = |x: usize, y: usize| values[y * self.width + x];
+        mix(mix(at(x0, y0), at(x1, y0), tx), mix(at(x0, y1), at(x1, y1), tx), ty)
+    }
+
+    fn sample_luma(&self, u: f32, v: f32) -> f32 {
+        self.sample(self.lumas, u, v, |a, b, t| a + (b - a) * t)
+    }
+
+    fn sample_color(&self, u: f32, v: f32) -> [f32; 3] {
+        self.sample(self.colors, u, v, |a, b, t| [0, 1, 2].map(|i| a[i] + (b[i] - a[i]) * t))
+    }
+
+    // Color suavizado del pixel, o None si no está sobre un borde
+    fn antialias(&self, x: usize, y: usize) -> Option<[f32; 3]> {
+        let (xi, yi) = (x as isize, y as isize);
+        let m = self.luma_at(xi, yi);
+        // Norte es la fila de arriba (la Y de pantalla crece hacia abajo)
+        let (n, s, e, w) = (self.luma_at(xi, yi - 1), self.luma_at(xi, yi + 1), self.luma_at(xi + 1, yi), self.luma_at(xi - 1, yi));
+        let highest = m.max(n).max(s).max(e).max(w);
+        let lowest = m.min(n).min(s).min(e).min(w);
+        let range = highest - lowest;
+        if range < EDGE_THRESHOLD_MIN.max(EDGE_THRESHOLD * highest) {
+            return None;
+        }
+        let (ne, nw) = (self.luma_at(xi + 1, yi - 1), self.luma_at(xi - 1, yi - 1));
+        let (se, sw) = (self.luma_at(xi + 1, yi + 1), self.luma_at(xi - 1, yi + 1));
+
+        // Mezcla por detalle de un pixel: contraste con el promedio del vecindario
+        let average = (2.0 * (n + e + s + w) + ne + nw + se + sw) / 12.0;
+        let subpixel = ((average - m).abs() / range).clamp(0.0, 1.0);
+        let subpixel = subpixel * subpixel * (3.0 - 2.0 * subpixel);
+        let subpixel_blend = subpixel * subpixel * SUBPIXEL_BLEND;
+
+        // Un borde horizontal cambia de luminancia en vertical
+        let horizontal = 2.0 * (n + s - 2.0 * m).abs() + (ne + se - 2.0 * e).abs() + (nw + sw - 2.0 * w).abs()
+            >= 2.0 * (e + w - 2.0 * m).abs() + (ne + nw - 2.0 * n).abs() + (se + sw - 2.0 * s).abs();
+        let (positive, negative) = if horizontal { (s, n) } else { (e, w) };
+        let (positive_gradient, negative_gradient) = ((positive - m).abs(), (negative - m).abs());
+        // Hacia qué lado está el vecino del otro lado del borde
+        let (step, opposite, gradient) = if positive_gradient >= negative_gradient {
+            (1.0, positive, positive_gradient)
+        } else {
+            (-1.0, negative, negative_gradient)
+        };
+
+        // Se recorre el borde (a medio pixel, sobre la línea entre los dos lados) hasta
+        // que la luminancia deja de parecerse a la del borde en cada sentido
+        let (u, v) = (x as f32 + 0.5, y as f32 + 0.5);
+        let (edge_u, edge_v) = if horizontal { (u, v + step * 0.5) } else { (u + step * 0.5, v) };
+        let (along_u, along_v) = if horizontal { (1.0, 0.0) } else { (0.0, 1.0) };
+        let edge_luma = (m + opposite) * 0.5;
+        let threshold = gradient * 0.25;
+        let search = |direction: f32| {
+            let mut distance = 0.0;
+            let mut delta = 0.0;
+            for length in SEARCH_STEPS {
+                distance += length;
+                delta = self.sample_luma(edge_u + along_u * distance * direction, edge_v + along_v * distance * direction) - edge_luma;
+                if delta.abs() >= threshold {
+                    break;
+                }
+            }
+            (distance, delta)
+        };
+        let (positive_distance, positive_delta) = search(1.0);
+        let (negative_distance, negative_delta) = search(-1.0);
+        let (distance, delta) = if positive_distance <= negative_distance {
+            (positive_distance, positive_delta)
+        } else {
+            (negative_distance, negative_delta)
+        };
+        // Solo se mezcla si el final más cercano va hacia el lado contrario al pixel
+        let edge_blend = if (delta >= 0.0) == (m - edge_luma >= 0.0) {
+            0.0
+        } else {
+            0.5 - distance / (positive_distance + negative_distance)
+        };
+
+        let blend = edge_blend.max(subpixel_blend);
+        Some(if horizontal { self.sample_color(u, v + step * blend) } else { self.sample_color(u + step * blend, v) })
+    }
+}
+
+fn unpack(color: u32) -> [f32; 3] {
+    [(color >> 16) & 0xFF, (color >> 8) & 0xFF, color & 0xFF].map(|channel| channel as f32 / 255.0)
+}
+
+fn pack(color: [f32; 3]) -> u32 {
+    let [r, g, b] = color.map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u32);
+    (r << 16) | (g << 8) | b
+}
+
+// Luminancia perceptual del color ya en sRGB, como la que usa FXAA
+fn luma(color: &[f32; 3]) -> f32 {
+    0.299 * color[0] + 0.587 * color[1] + 0.114 * color[2]
+}
+
+// Bloom: lo que en el buffer de emisión (HDR) pasa del umbral de luminancia se
+// desenfoca con una gaussiana separable (una pasada horizontal y otra vertical, a
+// media resolución) y se suma al color en espacio lineal, así el Sol se derrama
+// sobre los pixeles de alrededor. Va sobre el color HDR, antes del mapeo de tonos.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bloom {
+    pub enabled: bool,
+    // Luminancia lineal desde la que un pixel empieza a brillar
+    pub threshold: f32,
+    // Cuánto del brillo desenfocado se suma
+    pub intensity: f32,
+    // Alcance del halo, en pixeles de pantalla
+    pub radius: f32,
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Bloom { enabled: true, threshold: 1.0, intensity: 0.6, radius: 32.0 }
+    }
+}
+
+impl Bloom {
+    pub fn apply(&self, framebuffer: &mut Framebuffer) {
+        if !self.enabled || self.intensity <= 0.0 {
+            return;
+        }
+        let (width, height) = (framebuffer.width, framebuffer.height);
+        let (half_width, half_height) = (width.div_ceil(2), height.div_ceil(2));
+
+        // Pasada de brillo: promedio de cada bloque de 2x2, con la parte bajo el umbral
+        // recortada sin cambiar el tono
+        let mut bright = vec![[0.0f32; 3]; half_width * half_height];
+        let mut any = false;
+        for (index, pixel) in bright.iter_mut().enumerate() {
+            let (x, y) = (index % half_width * 2, index / half_width * 2);
+            let mut sum = [0.0; 3];
+            for (sx, sy) in [(x, y), (x + 1, y), (x, y + 1), (x + 1, y + 1)] {
+                let emission = framebuffer.emission_buffer[sy.min(height - 1) * width + sx.min(width - 1)];
+                (0..3).for_each(|channel| sum[channel] += emission[channel] * 0.25);
+            }
+            let luminance = 0.2126 * sum[0] + 0.7152 * sum[1] + 0.0722 * sum[2];
+            if luminance > self.threshold {
+                let keep = (luminance - self.threshold) / luminance;
+                *pixel = sum.map(|channel| channel * keep);
+                any = true;
+            }
+        }
+        if !any {
+            return;
+        }
+
+        // El halo llega hasta `radius` (3 sigma) y se desenfoca a media resolución
+        let kernel = gaussian_kernel(self.radius / 6.0);
+        let blurred = blur_rows(&bright, half_width, &kernel);
+        let blurred = transpose(&blur_rows(&transpose(&blurred, half_width, half_height), half_height, &kernel), half_height, half_width);
+
+        // Composición aditiva, con interpolación bilineal desde la media resolución
+        let image = Image { width: half_width, height: half_height, colors: &blurred, lumas: &[] };
+        framebuffer.hdr.par_chunks_mut(width).enumerate().for_each(|(y, row)| {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                let glow = image.sample_color((x as f32 + 0.5) * 0.5, (y as f32 + 0.5) * 0.5);
+                (0..3).for_each(|channel| pixel[channel] += glow[channel] * self.intensity);
+            }
+        });
+    }
+}
+
+// Pesos normalizados de una gaussiana de desviación `sigma` (en pixeles), hasta 3 sigma
+fn gaussian_kernel(sigma: f32) -> Vec<f32> {
+    let sigma = sigma.max(0.5);
+    let reach = (sigma * 3.0).ceil() as isize;
+    let weights: Vec<f32> = (-reach..=reach).map(|offset| (-((offset * offset) as f32) / (2.0 * sigma * sigma)).exp()).collect();
+    let total: f32 = weights.iter().sum();
+    weights.into_iter().map(|weight| weight / total).collect()
+}
+
+// Una pasada de la gaussiana a lo largo de cada fila; los bordes repiten el último pixel
+fn blur_rows(pixels: &[[f32; 3]], width: usize, kernel: &[f32]) -> Vec<[f32; 3]> {
+    let reach = (kernel.len() / 2) as isize;
+    let mut output = vec![[0.0; 3]; pixels.len()];
+    output.par_chunks_mut(width).zip(pixels.par_chunks(width)).for_each(|(output, row)| {
+        for (x, pixel) in output.iter_mut().enumerate() {
+            for (tap, weight) in kernel.iter().enumerate() {
+                let source = row[(x as isize + tap as isize - reach).clamp(0, width as isize - 1) as usize];
+                (0..3).for_each(|channel| pixel[channel] += source[channel] * weight);
+            }
+        }
+    });
+    output
+}
+
+// La pasada vertical reusa la horizontal sobre la imagen traspuesta
+fn transpose(pixels: &[[f32; 3]], width: usize, height: usize) -> Vec<[f32; 3]> {
+    (0..width * height).map(|index| pixels[(index % height) * width + index / height]).collect()
+}
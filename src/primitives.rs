@@ -0,0 +1,52 @@
+use nalgebra_glm::{Vec2, Vec3};
+use crate::obj::Obj;
+
+// Anillo plano en el plano XZ (anillos planetarios), con radios en unidades del
+// modelo; la normal apunta a +Y y el material debería iluminar ambas caras
+pub fn annulus(inner: f32, outer: f32, segments: usize) -> Obj {
+    let segments = segments.max(3);
+    let (mut vertices, mut normals, mut texcoords, mut indices) = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    for i in 0..=segments {
+        let v = i as f32 / segments as f32;
+        let (sin, cos) = (v * std::f32::consts::TAU).sin_cos();
+        for (u, radius) in [(0.0, inner), (1.0, outer)] {
+            vertices.push(Vec3::new(cos * radius, 0.0, sin * radius));
+            normals.push(Vec3::new(0.0, 1.0, 0.0));
+            texcoords.push(Vec2::new(u, v));
+        }
+    }
+    for i in 0..segments as u32 {
+        let (a, b, c, d) = (2 * i, 2 * i + 1, 2 * i + 2, 2 * i + 3);
+        indices.extend_from_slice(&[a, b, c, b, d, c]);
+    }
+    Obj::from_arrays(vertices, normals, texcoords, indices)
+}
+
+// Esfera UV de radio 1 (la misma escala que los modelos normalizados), para
+// dibujar con pocos triángulos los cuerpos que ocupan pocos pixeles y los que
+// tienen un mapa equirectangular: u crece hacia el este (el sentido del giro,
+// antihorario visto desde +Y) y v vale 1 en el polo norte
+pub fn uv_sphere(segments: usize, rings: usize) -> Obj {
+    let (segments, rings) = (segments.max(3), rings.max(2));
+    let (mut vertices, mut normals, mut texcoords, mut indices) = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    for ring in 0..=rings {
+        let v = ring as f32 / rings as f32;
+        let (sin_polar, cos_polar) = (v * std::f32::consts::PI).sin_cos();
+        for segment in 0..=segments {
+            let u = segment as f32 / segments as f32;
+            let (sin, cos) = (u * std::f32::consts::TAU).sin_cos();
+            let normal = Vec3::new(cos * sin_polar, cos_polar, sin * sin_polar);
+            vertices.push(normal);
+            normals.push(normal);
+            texcoords.push(Vec2::new(1.0 - u, 1.0 - v));
+        }
+    }
+    let row = segments as u32 + 1;
+    for ring in 0..rings as u32 {
+        for segment in 0..segments as u32 {
+            let (a, b) = (ring * row + segment, (ring + 1) * row + segment);
+            indices.extend_from_slice(&[a, b, a + 1, a + 1, b, b + 1]);
+        }
+    }
+    Obj::from_arrays(vertices, normals, texcoords, indices)
+}
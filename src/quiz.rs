@@ -0,0 +1,230 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+use crate::framebuffer::Framebuffer;
+use crate::hud;
+use crate::solar_system::{display_name, SolarSystem};
+use crate::text;
+
+const PLANETS: [&str; 8] = ["mercury", "venus", "earth", "mars", "jupiter", "saturn", "uranus", "neptune"];
+// Cuerpos que se pueden pedir con un clic
+const PICKABLE: [&str; 10] = ["sun", "mercury", "venus", "earth", "moon", "mars", "jupiter", "saturn", "uranus", "neptune"];
+const OPTIONS: usize = 4;
+// Distancia máxima en pixeles entre el clic y el cuerpo, para los que se ven chicos
+const PICK_RADIUS: i32 = 6;
+const PANEL_WIDTH: usize = 560;
+const TITLE_COLOR: u32 = 0x88CCFF;
+const RIGHT_COLOR: u32 = 0x66DD66;
+const WRONG_COLOR: u32 = 0xFF6666;
+
+// Tipos de pregunta; las respuestas salen de los datos de los cuerpos en SolarSystem
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    LargestRadius,
+    SmallestRadius,
+    ClosestToSun,
+    LongestYear,
+    ShortestDay,
+    Retrograde,
+    Pick,
+}
+
+const KINDS: [Kind; 7] = [
+    Kind::LargestRadius,
+    Kind::SmallestRadius,
+    Kind::ClosestToSun,
+    Kind::LongestYear,
+    Kind::ShortestDay,
+    Kind::Retrograde,
+    Kind::Pick,
+];
+
+struct Question {
+    text: String,
+    // Vacío en las preguntas de clic
+    options: Vec<&'static str>,
+    correct: &'static str,
+    // Los datos de las opciones, para explicar la respuesta
+    explanation: String,
+}
+
+// Modo quiz: preguntas de opción múltiple (teclas 1-4 o clic sobre el cuerpo) y
+// de "haz clic en ...", con un panel de puntaje
+#[derive(Default)]
+pub struct Quiz {
+    pub active: bool,
+    question: Option<Question>,
+    score: u32,
+    asked: u32,
+    // Resultado de la última respuesta y su explicación
+    feedback: Option<(String, String, bool)>,
+}
+
+impl Quiz {
+    pub fn toggle(&mut self, system: &SolarSystem, rng: &mut impl Rng) {
+        self.active = !self.active;
+        if self.active {
+            self.score = 0;
+            self.asked = 0;
+            self.feedback = None;
+            self.next_question(system, rng);
+        }
+    }
+
+    fn next_question(&mut self, system: &SolarSystem, rng: &mut impl Rng) {
+        let kind = *KINDS.choose(rng).unwrap_or(&Kind::Pick);
+        self.question = Some(generate(kind, system, rng));
+    }
+
+    // Respuesta con las teclas numéricas (desde 0); devuelve el cuerpo elegido
+    pub fn answer_option(&mut self, index: usize, system: &SolarSystem, rng: &mut impl Rng) -> Option<&'static str> {
+        let body = self.question.as_ref()?.options.get(index).copied()?;
+        self.answer(body, system, rng);
+        Some(body)
+    }
+
+    // Respuesta con un clic sobre un cuerpo; en las preguntas de opción múltiple
+    // solo cuenta si el cuerpo es una de las opciones
+    pub fn answer_pick(&mut self, body: &'static str, system: &SolarSystem, rng: &mut impl Rng) {
+        let Some(question) = &self.question else {
+            return;
+        };
+        if question.options.is_empty() || question.options.contains(&body) {
+            self.answer(body, system, rng);
+        }
+    }
+
+    fn answer(&mut self, body: &'static str, system: &SolarSystem, rng: &mut impl Rng) {
+        let Some(question) = self.question.take() else {
+            return;
+        };
+        self.asked += 1;
+        let right = body == question.correct;
+        let verdict = if right {
+            self.score += 1;
+            String::from("Correcto!")
+        } else {
+            format!("Incorrecto: era {}", display_name(question.correct))
+        };
+        self.feedback = Some((verdict, question.explanation, right));
+        self.next_question(system, rng);
+    }
+
+    // Panel centrado abajo
+    pub fn draw(&self, framebuffer: &mut Framebuffer) {
+        if !self.active {
+            return;
+        }
+        let Some(question) = &self.question else {
+            return;
+        };
+        let options = if question.options.is_empty() {
+            String::from("(haz clic sobre el cuerpo)")
+        } else {
+            question
+                .options
+                .iter()
+                .enumerate()
+                .map(|(i, body)| format!("{} {}", i + 1, display_name(body)))
+                .collect::<Vec<_>>()
+                .join("   ")
+        };
+        let title = format!("Quiz (I)   Puntaje: {}/{}", self.score, self.asked);
+
+        let width = PANEL_WIDTH.min(framebuffer.width);
+        let x = (framebuffer.width - width) / 2;
+        let height = 5 * text::LINE_HEIGHT + 8;
+        let y = framebuffer.height.saturating_sub(height + 8);
+        hud::dim_rect(framebuffer, x, y, width, height);
+
+        text::draw_text_shadowed(framebuffer, x + 6, y + 4, &title, TITLE_COLOR, 1);
+        text::draw_text_shadowed(framebuffer, x + 6, y + 4 + text::LINE_HEIGHT, &question.text, 0xFFFFFF, 1);
+        text::draw_text_shadowed(framebuffer, x + 6, y + 4 + 2 * text::LINE_HEIGHT, &options, 0xFFFFFF, 1);
+        if let Some((verdict, explanation, right)) = &self.feedback {
+            let color = if *right { RIGHT_COLOR } else { WRONG_COLOR };
+            text::draw_text_shadowed(framebuffer, x + 6, y + 4 + 3 * text::LINE_HEIGHT, verdict, color, 1);
+            text::draw_text_shadowed(framebuffer, x + 6, y + 4 + 4 * text::LINE_HEIGHT, explanation, 0xAAAAAA, 1);
+        }
+    }
+}
+
+fn generate(kind: Kind, system: &SolarSystem, rng: &mut impl Rng) -> Question {
+    let radius = |body: &str| system.radius(body);
+    let orbit_radius = |body: &str| system.orbit(body).map_or(0.0, |orbit| orbit.radius);
+    let angular_speed = |body: &str| system.orbit(body).map_or(0.0, |orbit| orbit.angular_speed.abs());
+    let spin = |body: &str| system.spin(body);
+
+    match kind {
+        Kind::LargestRadius => choice("Cuál de estos planetas tiene el radio más grande?", "Radios", rng, radius, true),
+        Kind::SmallestRadius => choice("Cuál de estos planetas es el más pequeño?", "Radios", rng, radius, false),
+        Kind::ClosestToSun => choice("Cuál de estos planetas está más cerca del Sol?", "Órbitas", rng, orbit_radius, false),
+        // La vuelta más larga es la de menor velocidad angular
+        Kind::LongestYear => choice("Cuál de estos planetas tarda más en dar la vuelta al Sol?", "Velocidad orbital", rng, angular_speed, false),
+        Kind::ShortestDay => choice("Cuál de estos planetas tiene el día más corto?", "Rotación", rng, |body| spin(body).abs(), true),
+        Kind::Retrograde => {
+            // Una sola opción retrógrada entre planetas que giran hacia adelante
+            let retrograde: Vec<&'static str> = PLANETS.iter().copied().filter(|body| spin(body) < 0.0).collect();
+            let prograde: Vec<&'static str> = PLANETS.iter().copied().filter(|body| spin(body) > 0.0).collect();
+            let Some(&correct) = retrograde.choose(rng) else {
+                return generate(Kind::Pick, system, rng);
+            };
+            let mut options: Vec<&'static str> = prograde.choose_multiple(rng, OPTIONS - 1).copied().collect();
+            options.push(correct);
+            options.shuffle(rng);
+            Question {
+                text: String::from("Cuál de estos planetas gira al revés (rotación retrógrada)?"),
+                options,
+                correct,
+                explanation: String::new(),
+            }
+        }
+        Kind::Pick => {
+            let correct = *PICKABLE.choose(rng).unwrap_or(&"earth");
+            Question { text: format!("Haz clic en {}", display_name(correct)), options: Vec::new(), correct, explanation: String::new() }
+        }
+    }
+}
+
+// Opción múltiple: la respuesta es el máximo (o el mínimo) de `value` entre las opciones
+fn choice(
+    text: &str,
+    label: &str,
+    rng: &mut impl Rng,
+    value: impl Fn(&str) -> f64,
+    largest: bool,
+) -> Question {
+    let options: Vec<&'static str> = PLANETS.choose_multiple(rng, OPTIONS).copied().collect();
+    let key = |body: &&'static str| if largest { value(body) } else { -value(body) };
+    let correct = options.iter().copied().max_by(|a, b| key(a).total_cmp(&key(b))).unwrap_or(PLANETS[0]);
+    let values = options
+        .iter()
+        .map(|body| format!("{} {:.3}", display_name(body), value(body)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    Question { text: text.to_string(), options, correct, explanation: format!("{}: {}", label, values) }
+}
+
+// Cuerpo bajo el pixel (x, y) según el buffer de IDs, o el más cercano dentro de
+// PICK_RADIUS. Las capas de un cuerpo (atmósfera, anillos) cuentan como el cuerpo.
+pub fn pick(framebuffer: &Framebuffer, object_ids: &[&'static str], system: &SolarSystem, x: usize, y: usize) -> Option<&'static str> {
+    let mut best: Option<(i32, &'static str)> = None;
+    for dy in -PICK_RADIUS..=PICK_RADIUS {
+        for dx in -PICK_RADIUS..=PICK_RADIUS {
+            let (px, py) = (x as i32 + dx, y as i32 + dy);
+            if px < 0 || py < 0 || px as usize >= framebuffer.width || py as usize >= framebuffer.height {
+                continue;
+            }
+            let id = framebuffer.ids[py as usize * framebuffer.width + px as usize];
+            let Some(&call_body) = (id as usize).checked_sub(1).and_then(|i| object_ids.get(i)) else {
+                continue;
+            };
+            let Some(body) = system.bodies().iter().map(|body| body.name).find(|name| call_body.split('_').next() == Some(*name)) else {
+                continue;
+            };
+            let distance = dx * dx + dy * dy;
+            if best.is_none_or(|(closest, _)| distance < closest) {
+                best = Some((distance, body));
+            }
+        }
+    }
+    best.map(|(_, body)| body)
+}
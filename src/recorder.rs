@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use nalgebra_glm::DVec3;
+use crate::escort::{Escort, EscortOrder};
+use crate::solar_system::SolarSystem;
+use crate::traffic::Traffic;
+
+// Pasos entre vaciados del buffer a disco
+const FLUSH_EVERY: u64 = 60;
+
+// Registro de la simulación en CSV para analizarla afuera (pandas, numpy...):
+//
+//   bodies.csv  step,time,body,parent,x,y,z,vx,vy,vz,rotation
+//   ships.csv   step,time,ship,x,y,z,vx,vy,vz,speed,state
+//
+// Posiciones en coordenadas del mundo (f64, sin el origen flotante). La velocidad
+// de los cuerpos es la diferencia de posiciones entre pasos dividida por dt, así
+// se puede comparar con la velocidad orbital analítica (radio * velocidad angular);
+// en el primer paso queda vacía. La escolta guarda la velocidad de su integrador y
+// su orden; las naves de tráfico, su ruta y progreso (sin velocidad).
+pub struct Recorder {
+    bodies: BufWriter<File>,
+    ships: BufWriter<File>,
+    previous: HashMap<&'static str, DVec3>,
+    step: u64,
+}
+
+impl Recorder {
+    pub fn create(directory: &str) -> io::Result<Self> {
+        fs::create_dir_all(directory)?;
+        let mut bodies = BufWriter::new(File::create(Path::new(directory).join("bodies.csv"))?);
+        let mut ships = BufWriter::new(File::create(Path::new(directory).join("ships.csv"))?);
+        writeln!(bodies, "step,time,body,parent,x,y,z,vx,vy,vz,rotation")?;
+        writeln!(ships, "step,time,ship,x,y,z,vx,vy,vz,speed,state")?;
+        Ok(Recorder { bodies, ships, previous: HashMap::new(), step: 0 })
+    }
+
+    // Una fila por cuerpo y por nave, después de avanzar la simulación `dt`
+    pub fn record(&mut self, system: &SolarSystem, escort: &Escort, traffic: &Traffic, dt: f64) -> io::Result<()> {
+        let time = system.time;
+        for body in system.bodies() {
+            let velocity = match self.previous.insert(body.name, body.position) {
+                Some(previous) if dt > 0.0 => vector((body.position - previous) / dt),
+                _ => String::from(",,"),
+            };
+            writeln!(
+                self.bodies,
+                "{},{},{},{},{},{},{}",
+                self.step,
+                time,
+                body.name,
+                body.parent.unwrap_or(""),
+                vector(body.position),
+                velocity,
+                body.rotation
+            )?;
+        }
+
+        let ship = &escort.ship;
+        writeln!(
+            self.ships,
+            "{},{},{},{},{},{},{}",
+            self.step,
+            time,
+            escort.name,
+            vector(ship.position),
+            vector(ship.velocity),
+            ship.velocity.magnitude(),
+            order_label(&escort.order)
+        )?;
+        for (i, npc) in traffic.ships.iter().enumerate() {
+            writeln!(
+                self.ships,
+                "{},{},npc{},{},,,,,{}->{} {:.3}",
+                self.step,
+                time,
+                i,
+                vector(npc.position),
+                npc.from,
+                npc.to,
+                npc.progress
+            )?;
+        }
+
+        self.step += 1;
+        if self.step.is_multiple_of(FLUSH_EVERY) {
+            self.bodies.flush()?;
+            self.ships.flush()?;
+        }
+        Ok(())
+    }
+}
+
+fn vector(v: DVec3) -> String {
+    format!("{},{},{}", v.x, v.y, v.z)
+}
+
+fn order_label(order: &EscortOrder) -> String {
+    match order {
+        EscortOrder::Follow => String::from("formación"),
+        EscortOrder::Orbit { target, radius } => format!("orbita {} r={}", target, radius),
+        EscortOrder::Transfer(_) => String::from("transferencia"),
+    }
+}
@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+use crate::debug_draw::DebugFlags;
+use crate::framebuffer::Framebuffer;
+use crate::layers::{Layer, RenderLayers};
+use crate::obj::Obj;
+use crate::Uniforms;
+
+// Clave para hooks que se aplican a todos los cuerpos
+pub const ALL_BODIES: &str = "*";
+
+// Lo que un hook puede consultar del cuerpo que se está dibujando
+pub struct HookContext<'a> {
+    pub body: &'a str,
+    pub uniforms: &'a Uniforms,
+    pub obj: &'a Obj,
+    pub debug: DebugFlags,
+}
+
+pub type RenderHook = Box<dyn Fn(&mut Framebuffer, &HookContext)>;
+
+// Callbacks después del render de cada cuerpo (líneas de campo, volúmenes de
+// depuración...). El orden de registro es el orden de dibujo;
+// cada hook pertenece a una capa y solo corre si la capa está visible.
+#[derive(Default)]
+pub struct RenderHooks {
+    post: HashMap<String, Vec<(Layer, RenderHook)>>,
+}
+
+impl RenderHooks {
+    pub fn add_post(&mut self, layer: Layer, body: &str, hook: impl Fn(&mut Framebuffer, &HookContext) + 'static) {
+        self.post.entry(body.to_string()).or_default().push((layer, Box::new(hook)));
+    }
+
+    // Los del cuerpo primero y los globales después, para que lo global (depuración,
+    // selección) quede encima
+    pub fn run_post(&self, framebuffer: &mut Framebuffer, context: &HookContext, layers: &mut RenderLayers) {
+        run(&self.post, context.body, framebuffer, context, layers);
+        run(&self.post, ALL_BODIES, framebuffer, context, layers);
+    }
+}
+
+fn run(
+    hooks: &HashMap<String, Vec<(Layer, RenderHook)>>,
+    key: &str,
+    framebuffer: &mut Framebuffer,
+    context: &HookContext,
+    layers: &mut RenderLayers,
+) {
+    if let Some(hooks) = hooks.get(key) {
+        for (layer, hook) in hooks {
+            layers.draw(*layer, || hook(framebuffer, context));
+        }
+    }
+}
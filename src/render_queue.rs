@@ -0,0 +1,109 @@
+use nalgebra_glm::Vec4;
+use crate::framebuffer::Framebuffer;
+use crate::layers::Layer;
+use crate::lighting::{self, PointLight};
+use crate::obj::Obj;
+use crate::stats;
+use crate::vertex::Vertex;
+use crate::Uniforms;
+
+// Vértices sin repetir y tres índices por triángulo (ver Obj::vertices)
+pub type RenderFn = fn(&mut Framebuffer, &Uniforms, &[Vertex], &[u32]);
+
+// Distancia mínima frente a la cámara; coincide con el plano cercano de la proyección
+const NEAR: f32 = 0.1;
+
+// Pases del frame, en el orden en que se dibujan. Lo emisivo (el Sol) va después de
+// todo lo opaco sin importar cómo se llame su shader, y lo translúcido al final.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Pass {
+    Opaque,
+    Emissive,
+    Translucent,
+}
+
+pub struct DrawCall<'a> {
+    pub body: &'static str,
+    // Clave del material/shader; las llamadas con la misma clave se dibujan juntas
+    pub shader: &'static str,
+    pub render: RenderFn,
+    pub obj: &'a Obj,
+    pub uniforms: Uniforms,
+    // Distancia en espacio de vista al centro de la esfera envolvente
+    pub depth: f32,
+    // Anillos y atmósferas van en el pase translúcido, de atrás hacia adelante
+    pub pass: Pass,
+    pub layer: Layer,
+}
+
+impl<'a> DrawCall<'a> {
+    pub fn new(body: &'static str, shader: &'static str, render: RenderFn, obj: &'a Obj, uniforms: Uniforms) -> Self {
+        let pass = if uniforms.material.emission > 0.0 { Pass::Emissive } else { Pass::Opaque };
+        DrawCall { body, shader, render, obj, uniforms, depth: 0.0, pass, layer: Layer::Bodies }
+    }
+
+    pub fn translucent(mut self) -> Self {
+        self.pass = Pass::Translucent;
+        self
+    }
+
+    pub fn in_layer(mut self, layer: Layer) -> Self {
+        self.layer = layer;
+        self
+    }
+}
+
+// Lista de dibujo del frame. Se llena en cualquier orden y se recorre por pase (ver
+// Pass) y dentro de cada uno agrupada por shader y de adelante hacia atrás, para que
+// el z-buffer descarte pronto los fragmentos ocultos; lo translúcido va de atrás hacia adelante.
+#[derive(Default)]
+pub struct RenderQueue<'a> {
+    calls: Vec<DrawCall<'a>>,
+}
+
+impl<'a> RenderQueue<'a> {
+    // Descarta lo que queda completamente detrás de la cámara o fuera del frustum
+    pub fn push(&mut self, mut call: DrawCall<'a>) {
+        let sphere = call.obj.bounding_sphere().transformed(&call.uniforms.model_matrix);
+        let center = Vec4::new(sphere.center.x, sphere.center.y, sphere.center.z, 1.0);
+        call.depth = -(call.uniforms.view_matrix * center).z;
+
+        if call.depth + sphere.radius < NEAR || !sphere.in_frustum(&(call.uniforms.projection_matrix * call.uniforms.view_matrix)) {
+            stats::add_culled_body();
+            return;
+        }
+        self.calls.push(call);
+    }
+
+    // Cada llamada recibe las luces puntuales más cercanas que la alcanzan
+    pub fn assign_lights(&mut self, lights: &[PointLight], max: usize) {
+        for call in &mut self.calls {
+            let sphere = call.obj.bounding_sphere().transformed(&call.uniforms.model_matrix);
+            call.uniforms.point_lights = lighting::nearest_lights(lights, sphere.center, sphere.radius, call.body, max);
+        }
+    }
+
+    pub fn calls_mut(&mut self) -> impl Iterator<Item = &mut DrawCall<'a>> {
+        self.calls.iter_mut()
+    }
+
+    pub fn sort(&mut self) {
+        self.calls.sort_by(|a, b| {
+            a.pass.cmp(&b.pass).then_with(|| {
+                if a.pass == Pass::Translucent {
+                    b.depth.total_cmp(&a.depth)
+                } else {
+                    a.shader.cmp(b.shader).then(a.depth.total_cmp(&b.depth))
+                }
+            })
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.calls.len()
+    }
+
+    pub fn drain(&mut self) -> impl Iterator<Item = DrawCall<'a>> + '_ {
+        self.calls.drain(..)
+    }
+}
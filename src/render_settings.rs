@@ -0,0 +1,233 @@
+use std::fs;
+use crate::depth_of_field::DepthOfField;
+use crate::foveation::Foveation;
+use crate::pacing::FramePacing;
+use crate::postprocess::Bloom;
+use crate::skybox::{SkyboxSettings, SkyboxSource};
+use crate::texture::TextureFilter;
+use crate::tonemap::{ToneMap, ToneMapping};
+use crate::transparency::Transparency;
+
+// Ajustes de render leídos de la sección [render] del archivo de escena
+//
+//   [render]
+//   foveated = true
+//   fovea_radius = 0.45
+//   fovea_blend = 0.1
+//   checkerboard = false
+//   tiled = false            # rasterización por bloques en paralelo (ver tiles.rs)
+//   ssaa = 1                 # supermuestreo: 1 (sin), 2 o 4 muestras por eje
+//   fxaa = false             # antialiasing en pantalla (también con C)
+//   bloom = true             # halo de lo emisivo (ver postprocess.rs)
+//   bloom_threshold = 1.0    # luminancia lineal desde la que brilla
+//   bloom_intensity = 0.6
+//   bloom_radius = 32.0      # alcance del halo, en pixeles
+//   tone_mapping = "aces"    # "reinhard" o "none"; del color HDR a la pantalla
+//   exposure = 1.0           # exposición inicial (también con + y -)
+//   shadows = true           # los planetas se eclipsan entre sí (ver shadow.rs)
+//   texture_filter = "bilinear"  # o "nearest"; mapas de assets/textures
+//   skybox = "starfield"     # "points", "none" o la ruta de un mapa equirectangular
+//   skybox_brightness = 1.0
+//   auto_exposure = false
+//   frame_pacing = 60        # 30, 60, 120, "uncapped" o "display"
+//   transparency = "sorted"  # o "stochastic"
+//   zodiacal_light = 0.4     # 0 la desactiva
+//   asteroids = 3000         # rocas del cinturón entre Marte y Júpiter; 0 lo quita
+//   kuiper_belt = 1500       # objetos del cinturón de Kuiper; 0 lo quita
+//   adaptive_effects = true  # rastros y partículas según el margen del frame
+//   depth_of_field = false   # también con Z
+//   dof_aperture = 6.0       # desenfoque del fondo lejano, en pixeles
+//   dof_max_radius = 12.0
+//   background_simulation = true  # la simulación sigue con la ventana minimizada
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RenderSettings {
+    // Periferia a media resolución (modo de rendimiento)
+    pub foveation: Option<Foveation>,
+    // Media pantalla sombreada por frame, el resto reconstruido del anterior
+    pub checkerboard: bool,
+    // Pantalla en bloques de tiles::TILE_SIZE sombreados en paralelo
+    pub tiled: bool,
+    // Muestras por eje de cada pixel (1: sin supermuestreo)
+    pub ssaa: usize,
+    // Antialiasing en pantalla (ver postprocess.rs)
+    pub fxaa: bool,
+    // Halo de los materiales emisivos
+    pub bloom: Bloom,
+    // Del color HDR de la escena a la pantalla
+    pub tone_map: ToneMap,
+    // Mapa de sombras del Sol
+    pub shadows: bool,
+    // Lectura de los mapas de los cuerpos (ver texture.rs)
+    pub texture_filter: TextureFilter,
+    // Cielo de fondo (ver skybox.rs)
+    pub skybox: SkyboxSettings,
+    // Exposición automática, medida sobre el cuerpo seleccionado
+    pub auto_exposure: bool,
+    pub frame_pacing: FramePacing,
+    // Anillos y atmósferas
+    pub transparency: Transparency,
+    // Intensidad de la luz zodiacal (ver zodiacal.rs)
+    pub zodiacal_light: f32,
+    // Rocas del cinturón de asteroides con el presupuesto de efectos al 100%
+    pub asteroids: usize,
+    // Objetos del cinturón de Kuiper, igual
+    pub kuiper_belt: usize,
+    // Presupuesto de efectos decorativos ajustado al margen del frame
+    pub adaptive_effects: bool,
+    // Profundidad de campo enfocada en el cuerpo seleccionado
+    pub depth_of_field: DepthOfField,
+    // Con la ventana minimizada se deja de dibujar; esto decide si la simulación avanza
+    pub background_simulation: bool,
+}
+
+impl RenderSettings {
+    pub fn load(path: &str) -> Self {
+        let parsed = fs::read_to_string(path).map_err(|err| err.to_string()).and_then(|text| RenderSettings::parse(&text));
+        parsed.unwrap_or_else(|err| {
+            eprintln!("Ajustes de render en {}: {}; se usan los valores por defecto", path, err);
+            RenderSettings::default()
+        })
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let document: toml::Table = text.parse().map_err(|e: toml::de::Error| e.to_string())?;
+        let mut settings = RenderSettings::default();
+        let table = match document.get("render") {
+            Some(toml::Value::Table(table)) => table,
+            Some(_) => return Err(String::from("'render' debe ser una tabla")),
+            None => return Ok(settings),
+        };
+
+        let number = |key: &str| -> Result<Option<f32>, String> {
+            match table.get(key) {
+                None => Ok(None),
+                Some(toml::Value::Float(value)) => Ok(Some(*value as f32)),
+                Some(toml::Value::Integer(value)) => Ok(Some(*value as f32)),
+                Some(_) => Err(format!("render.{} debe ser un número", key)),
+            }
+        };
+
+        let foveated = match table.get("foveated") {
+            None => false,
+            Some(value) => value.as_bool().ok_or_else(|| String::from("render.foveated debe ser booleano"))?,
+        };
+        if let Some(value) = table.get("checkerboard") {
+            settings.checkerboard = value.as_bool().ok_or_else(|| String::from("render.checkerboard debe ser booleano"))?;
+        }
+        if let Some(value) = table.get("tiled") {
+            settings.tiled = value.as_bool().ok_or_else(|| String::from("render.tiled debe ser booleano"))?;
+        }
+        if let Some(value) = table.get("ssaa") {
+            settings.ssaa = value
+                .as_integer()
+                .filter(|factor| [1, 2, 4].contains(factor))
+                .ok_or_else(|| String::from("render.ssaa debe ser 1, 2 o 4"))? as usize;
+        }
+        if let Some(value) = table.get("fxaa") {
+            settings.fxaa = value.as_bool().ok_or_else(|| String::from("render.fxaa debe ser booleano"))?;
+        }
+        if let Some(value) = table.get("shadows") {
+            settings.shadows = value.as_bool().ok_or_else(|| String::from("render.shadows debe ser booleano"))?;
+        }
+        if let Some(value) = table.get("texture_filter") {
+            settings.texture_filter = value
+                .as_str()
+                .and_then(TextureFilter::parse)
+                .ok_or_else(|| String::from("render.texture_filter debe ser \"bilinear\" o \"nearest\""))?;
+        }
+        if let Some(value) = table.get("skybox") {
+            settings.skybox.source = value
+                .as_str()
+                .and_then(SkyboxSource::parse)
+                .ok_or_else(|| String::from("render.skybox debe ser \"starfield\", \"points\", \"none\" o la ruta de una imagen"))?;
+        }
+        if let Some(brightness) = number("skybox_brightness")? {
+            settings.skybox.brightness = brightness.max(0.0);
+        }
+        if let Some(value) = table.get("bloom") {
+            settings.bloom.enabled = value.as_bool().ok_or_else(|| String::from("render.bloom debe ser booleano"))?;
+        }
+        if let Some(threshold) = number("bloom_threshold")? {
+            settings.bloom.threshold = threshold.max(0.0);
+        }
+        if let Some(intensity) = number("bloom_intensity")? {
+            settings.bloom.intensity = intensity.max(0.0);
+        }
+        if let Some(radius) = number("bloom_radius")? {
+            settings.bloom.radius = radius.max(0.0);
+        }
+        if let Some(value) = table.get("tone_mapping") {
+            settings.tone_map.mapping = value
+                .as_str()
+                .and_then(ToneMapping::parse)
+                .ok_or_else(|| String::from("render.tone_mapping debe ser \"aces\", \"reinhard\" o \"none\""))?;
+        }
+        if let Some(exposure) = number("exposure")? {
+            if exposure <= 0.0 {
+                return Err(String::from("render.exposure debe ser mayor que 0"));
+            }
+            settings.tone_map.exposure = exposure;
+        }
+        if let Some(value) = table.get("auto_exposure") {
+            settings.auto_exposure = value.as_bool().ok_or_else(|| String::from("render.auto_exposure debe ser booleano"))?;
+        }
+        if let Some(value) = table.get("frame_pacing") {
+            let text = match value {
+                toml::Value::Integer(fps) => fps.to_string(),
+                toml::Value::String(mode) => mode.clone(),
+                _ => String::new(),
+            };
+            settings.frame_pacing = FramePacing::parse(&text)
+                .ok_or_else(|| String::from("render.frame_pacing debe ser 30, 60, 120, \"uncapped\" o \"display\""))?;
+        }
+        if let Some(value) = table.get("transparency") {
+            settings.transparency = value
+                .as_str()
+                .and_then(Transparency::parse)
+                .ok_or_else(|| String::from("render.transparency debe ser \"sorted\" o \"stochastic\""))?;
+        }
+        if let Some(intensity) = number("zodiacal_light")? {
+            settings.zodiacal_light = intensity.max(0.0);
+        }
+        if let Some(value) = table.get("asteroids") {
+            settings.asteroids = value
+                .as_integer()
+                .and_then(|count| usize::try_from(count).ok())
+                .ok_or_else(|| String::from("render.asteroids debe ser un entero no negativo"))?;
+        }
+        if let Some(value) = table.get("kuiper_belt") {
+            settings.kuiper_belt = value
+                .as_integer()
+                .and_then(|count| usize::try_from(count).ok())
+                .ok_or_else(|| String::from("render.kuiper_belt debe ser un entero no negativo"))?;
+        }
+        if let Some(value) = table.get("adaptive_effects") {
+            settings.adaptive_effects = value.as_bool().ok_or_else(|| String::from("render.adaptive_effects debe ser booleano"))?;
+        }
+        if let Some(value) = table.get("depth_of_field") {
+            settings.depth_of_field.enabled = value.as_bool().ok_or_else(|| String::from("render.depth_of_field debe ser booleano"))?;
+        }
+        if let Some(aperture) = number("dof_aperture")? {
+            settings.depth_of_field.aperture = aperture.max(0.0);
+        }
+        if let Some(radius) = number("dof_max_radius")? {
+            settings.depth_of_field.max_radius = radius.max(0.0);
+        }
+        if let Some(value) = table.get("background_simulation") {
+            settings.background_simulation =
+                value.as_bool().ok_or_else(|| String::from("render.background_simulation debe ser booleano"))?;
+        }
+        if foveated {
+            let mut foveation = Foveation::default();
+            if let Some(radius) = number("fovea_radius")? {
+                foveation.radius = radius.max(0.0);
+            }
+            if let Some(blend) = number("fovea_blend")? {
+                foveation.blend = blend.max(0.0);
+            }
+            settings.foveation = Some(foveation);
+        }
+
+        Ok(settings)
+    }
+}
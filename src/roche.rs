@@ -0,0 +1,142 @@
+use nalgebra_glm::DVec3;
+use rand::Rng;
+use crate::color::Color;
+use crate::events::{EventQueue, SimEvent};
+use crate::particles::{ColorOverLife, Emitter, ParticleSystem};
+use crate::solar_system::SolarSystem;
+
+// Escenario de demostración: un cometa en órbita cerrada alrededor de Júpiter
+// cruza el límite de Roche, se parte en una cadena de fragmentos que se separan
+// a lo largo de la órbita y cada fragmento deja una estela de polvo, empujada lejos
+// del Sol como la cola de un cometa
+pub const HOST: &str = "jupiter";
+pub const COMET: &str = "comet";
+
+// Parámetro gravitacional del anfitrión en unidades de la escena por frame²
+const HOST_GM: f64 = 0.02;
+// Densidades relativas (Júpiter ~1.33 g/cm³, núcleo de cometa ~0.6 g/cm³)
+const HOST_DENSITY: f64 = 1.33;
+const COMET_DENSITY: f64 = 0.6;
+const PERIAPSIS: f64 = 2.2;
+const APOAPSIS: f64 = 12.0;
+// Pasos antes del periapsis en los que arranca el escenario
+const APPROACH_STEPS: usize = 250;
+
+pub const COMET_RADIUS: f64 = 0.2;
+pub const FRAGMENT_RADIUS: f64 = 0.08;
+// Partículas de polvo con el presupuesto de efectos al 100%
+pub const DUST_CAPACITY: usize = 4000;
+const FRAGMENT_COUNT: usize = 14;
+
+// Polvo recién soltado casi blanco, que se enrojece y se apaga al alejarse
+const DUST_COLOR: ColorOverLife = ColorOverLife::new(Color::new(255, 236, 200), Color::new(90, 40, 20));
+const DUST_LIFETIME: f64 = 90.0;
+const DUST_SPEED: f64 = 0.004;
+// Partículas por frame de cada trozo, antes y después de partirse
+const DUST_RATE: f64 = 0.2;
+const FRAGMENT_DUST_RATE: f64 = 0.6;
+// Presión de la luz del Sol sobre el polvo: velocidad hacia afuera, en unidades por frame
+const DUST_PUSH: f64 = 0.006;
+
+// Límite de Roche para un satélite fluido: d = 2.44 R (ρ_planeta / ρ_satélite)^(1/3)
+pub fn roche_limit(host_radius: f64, host_density: f64, body_density: f64) -> f64 {
+    2.44 * host_radius * (host_density / body_density).cbrt()
+}
+
+// Trozo del cometa; posición y velocidad relativas al anfitrión
+#[derive(Debug, Clone)]
+pub struct Piece {
+    pub offset: DVec3,
+    pub velocity: DVec3,
+    pub radius: f64,
+    dust: Emitter,
+}
+
+pub struct RocheScenario {
+    pub pieces: Vec<Piece>,
+    pub broken: bool,
+    pub limit: f64,
+    pub dust: ParticleSystem,
+}
+
+impl RocheScenario {
+    pub fn new(system: &SolarSystem) -> Self {
+        // Estado en el periapsis (vis-viva) y se integra hacia atrás hasta el punto de partida;
+        // Verlet es reversible, así que al avanzar el cometa vuelve a pasar por ese periapsis
+        let semi_major = (PERIAPSIS + APOAPSIS) / 2.0;
+        let speed = (HOST_GM * (2.0 / PERIAPSIS - 1.0 / semi_major)).sqrt();
+        let inclination = 0.25_f64;
+        let mut comet = Piece {
+            offset: DVec3::new(PERIAPSIS, 0.0, 0.0),
+            velocity: DVec3::new(0.0, speed * inclination.sin(), speed * inclination.cos()),
+            radius: COMET_RADIUS,
+            dust: Emitter::new(DUST_RATE, DUST_SPEED, DUST_LIFETIME),
+        };
+        for _ in 0..APPROACH_STEPS {
+            integrate(&mut comet, -1.0);
+        }
+
+        RocheScenario {
+            pieces: vec![comet],
+            broken: false,
+            limit: roche_limit(system.radius(HOST), HOST_DENSITY, COMET_DENSITY),
+            dust: ParticleSystem::new(DUST_CAPACITY, DUST_COLOR),
+        }
+    }
+
+    pub fn update(&mut self, system: &SolarSystem, dt: f64, rng: &mut impl Rng, events: &mut EventQueue) {
+        for piece in &mut self.pieces {
+            integrate(piece, dt);
+        }
+
+        if !self.broken && self.pieces[0].offset.magnitude() < self.limit {
+            self.break_up(rng);
+            events.emit(SimEvent::TidalBreakup { body: COMET, host: HOST, fragments: self.pieces.len() });
+        }
+
+        // Polvo: sale de cada trozo con la mitad de su velocidad, una dispersión
+        // pequeña y el empuje de la luz en la dirección opuesta al Sol
+        let host = system.position(HOST);
+        let sun = system.position("sun");
+        for piece in &mut self.pieces {
+            let position = host + piece.offset;
+            let away = (position - sun).try_normalize(f64::EPSILON).unwrap_or_else(DVec3::zeros);
+            piece.dust.position = position;
+            piece.dust.velocity = piece.velocity * 0.5 + away * DUST_PUSH;
+            piece.dust.update(&mut self.dust, dt, rng);
+        }
+        self.dust.update(dt);
+    }
+
+    // Los fragmentos quedan alineados con el eje de marea (la dirección radial) y con la
+    // misma velocidad; la diferencia de energía orbital entre ellos los estira solos
+    fn break_up(&mut self, rng: &mut impl Rng) {
+        let comet = self.pieces.remove(0);
+        let radial = comet.offset.normalize();
+        for i in 0..FRAGMENT_COUNT {
+            let along = (i as f64 / (FRAGMENT_COUNT - 1) as f64 - 0.5) * 2.0 * comet.radius;
+            let jitter = DVec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)) * 0.0005;
+            self.pieces.push(Piece {
+                offset: comet.offset + radial * along,
+                velocity: comet.velocity + jitter,
+                radius: FRAGMENT_RADIUS * rng.gen_range(0.6..1.2),
+                dust: Emitter::new(FRAGMENT_DUST_RATE, DUST_SPEED, DUST_LIFETIME),
+            });
+        }
+        self.broken = true;
+    }
+
+    // Posiciones absolutas de los trozos para dibujarlos
+    pub fn positions<'a>(&'a self, system: &SolarSystem) -> impl Iterator<Item = (DVec3, f64)> + 'a {
+        let host = system.position(HOST);
+        self.pieces.iter().map(move |piece| (host + piece.offset, piece.radius))
+    }
+}
+
+// Velocity Verlet con la gravedad del anfitrión (se ignora la marea del Sol)
+fn integrate(piece: &mut Piece, dt: f64) {
+    let acceleration = |offset: DVec3| -offset * HOST_GM / offset.magnitude().powi(3);
+    let half = piece.velocity + acceleration(piece.offset) * (dt / 2.0);
+    piece.offset += half * dt;
+    piece.velocity = half + acceleration(piece.offset) * (dt / 2.0);
+}
@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+use nalgebra_glm::{DVec3, Mat4};
+use crate::obj::Obj;
+use crate::render_queue::{DrawCall, RenderFn, RenderQueue};
+use crate::shadow::ShadowMap;
+use crate::solar_system::SolarSystem;
+use crate::vertex::Vertex;
+use crate::Uniforms;
+
+// Malla con la que se dibuja un cuerpo; `Hidden` lo deja a otro sistema (p. ej. los
+// cuerpos de --stress, que eligen su nivel de detalle en stress.rs)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SceneMesh {
+    Planet,
+    Ship,
+    Hidden,
+}
+
+// Cómo se ve un cuerpo del sistema solar: clave del shader en la cola, función de
+// render y malla. La órbita, el radio y el padre siguen en solar_system.rs.
+#[derive(Clone, Copy)]
+pub struct SceneBody {
+    pub name: &'static str,
+    pub shader: &'static str,
+    pub render: RenderFn,
+    pub mesh: SceneMesh,
+}
+
+impl SceneBody {
+    pub fn new(name: &'static str, shader: &'static str, render: RenderFn, mesh: SceneMesh) -> Self {
+        SceneBody { name, shader, render, mesh }
+    }
+
+    // Cuerpo con uno de los shaders propios ("sun", "venus", ..., "neptune") o el
+    // genérico ("default"); None para los demás nombres
+    pub fn with_shader(name: &'static str, shader: &str, mesh: SceneMesh) -> Option<Self> {
+        let (shader, render): (&'static str, RenderFn) = match shader {
+            "default" => ("default", crate::render),
+            "sun" => ("sun", crate::render_sol),
+            "venus" => ("venus", crate::render_venus),
+            "earth" => ("earth", crate::render_earth),
+            "moon" => ("moon", crate::render_moon),
+            "mars" => ("mars", crate::render_mars),
+            "jupiter" => ("jupiter", crate::render_jupiter),
+            "saturn" => ("saturn", crate::render_saturn),
+            "uranus" => ("uranus", crate::render_uranus),
+            "neptune" => ("neptune", crate::render_neptune),
+            _ => return None,
+        };
+        Some(SceneBody::new(name, shader, render, mesh))
+    }
+}
+
+pub struct SceneMeshes<'a> {
+    pub planet: &'a Obj,
+    pub ship: &'a Obj,
+}
+
+// Descripción visual de la escena. Cada cuerpo de SolarSystem se dibuja con su
+// entrada o, si no tiene, como planeta con el shader genérico: un planeta enano o
+// una luna nueva solo necesita su línea en SolarSystem::new, y una entrada aquí si
+// quiere su propio shader.
+pub struct Scene {
+    bodies: HashMap<&'static str, SceneBody>,
+}
+
+impl Default for Scene {
+    fn default() -> Self {
+        let mut scene = Scene { bodies: HashMap::new() };
+        for body in [
+            SceneBody::new("sun", "sun", crate::render_sol, SceneMesh::Planet),
+            SceneBody::new("mercury", "default", crate::render, SceneMesh::Planet),
+            SceneBody::new("venus", "venus", crate::render_venus, SceneMesh::Planet),
+            SceneBody::new("earth", "earth", crate::render_earth, SceneMesh::Planet),
+            SceneBody::new("moon", "moon", crate::render_moon, SceneMesh::Planet),
+            SceneBody::new("mars", "mars", crate::render_mars, SceneMesh::Planet),
+            SceneBody::new("ceres", "moon", crate::render_moon, SceneMesh::Planet),
+            SceneBody::new("jupiter", "jupiter", crate::render_jupiter, SceneMesh::Planet),
+            SceneBody::new("io", "moon", crate::render_moon, SceneMesh::Planet),
+            SceneBody::new("europa", "moon", crate::render_moon, SceneMesh::Planet),
+            SceneBody::new("ganymede", "moon", crate::render_moon, SceneMesh::Planet),
+            SceneBody::new("callisto", "moon", crate::render_moon, SceneMesh::Planet),
+            SceneBody::new("saturn", "saturn", crate::render_saturn, SceneMesh::Planet),
+            SceneBody::new("uranus", "uranus", crate::render_uranus, SceneMesh::Planet),
+            SceneBody::new("neptune", "neptune", crate::render_neptune, SceneMesh::Planet),
+            SceneBody::new("pluto", "moon", crate::render_moon, SceneMesh::Planet),
+            SceneBody::new("eris", "moon", crate::render_moon, SceneMesh::Planet),
+            SceneBody::new("halley", "moon", crate::render_moon, SceneMesh::Planet),
+            SceneBody::new("spaceship", "default", crate::render, SceneMesh::Ship),
+            SceneBody::new("navecita", "default", crate::render, SceneMesh::Ship),
+        ] {
+            scene.push(body);
+        }
+        scene
+    }
+}
+
+impl Scene {
+    // Solo las entradas dadas (un sistema leído de archivo)
+    pub fn from_bodies(bodies: impl IntoIterator<Item = SceneBody>) -> Self {
+        let mut scene = Scene { bodies: HashMap::new() };
+        for body in bodies {
+            scene.push(body);
+        }
+        scene
+    }
+
+    // Agrega o reemplaza la entrada del cuerpo
+    pub fn push(&mut self, body: SceneBody) {
+        self.bodies.insert(body.name, body);
+    }
+
+    pub fn hide(&mut self, name: &'static str) {
+        self.push(SceneBody::new(name, "default", crate::render, SceneMesh::Hidden));
+    }
+
+    pub fn body(&self, name: &'static str) -> SceneBody {
+        self.bodies.get(name).copied().unwrap_or(SceneBody::new(name, "default", crate::render, SceneMesh::Planet))
+    }
+
+    // Una llamada de dibujo por cuerpo visible, en su posición, con su radio como
+    // escala y su giro; `uniforms` arma el resto de los uniforms del cuerpo
+    pub fn queue_draw_calls<'a>(
+        &self,
+        queue: &mut RenderQueue<'a>,
+        system: &SolarSystem,
+        origin: DVec3,
+        meshes: &SceneMeshes<'a>,
+        uniforms: impl Fn(&'static str, Mat4) -> Uniforms,
+    ) {
+        for body in system.bodies() {
+            let scene_body = self.body(body.name);
+            let obj = match scene_body.mesh {
+                SceneMesh::Planet => meshes.planet,
+                SceneMesh::Ship => meshes.ship,
+                SceneMesh::Hidden => continue,
+            };
+            let model_matrix = system.model_matrix(body.name, origin);
+            queue.push(DrawCall::new(body.name, scene_body.shader, scene_body.render, obj, uniforms(body.name, model_matrix)));
+        }
+    }
+
+    // Pase de sombras: los planetas y las lunas visibles, con `caster` (una esfera de
+    // pocos triángulos) en lugar de su malla. Ni el Sol ni las naves proyectan sombra.
+    pub fn draw_shadow_casters(&self, shadow_map: &mut ShadowMap, system: &SolarSystem, origin: DVec3, caster: &[Vertex]) {
+        for body in system.bodies() {
+            let scene_body = self.body(body.name);
+            if scene_body.mesh != SceneMesh::Planet || scene_body.shader == "sun" {
+                continue;
+            }
+            let model_matrix = system.model_matrix(body.name, origin);
+            shadow_map.draw(&model_matrix, caster);
+        }
+    }
+}
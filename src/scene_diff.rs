@@ -0,0 +1,292 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+
+// Claves de un archivo de escena aplanadas: (sección, clave) -> valor. La sección es
+// la ruta del encabezado ("bodies.sun", "render"; "" para las claves sueltas).
+type Entries = BTreeMap<(String, String), toml::Value>;
+
+// Archivo de escena aplanado, con las secciones aunque estén vacías
+#[derive(Debug, Default, PartialEq)]
+pub struct SceneEntries {
+    pub sections: BTreeSet<String>,
+    pub entries: Entries,
+}
+
+impl SceneEntries {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|err| format!("No se pudo leer {}: {}", path, err))?;
+        SceneEntries::parse(&text).map_err(|err| format!("{}: {}", path, err))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let document: toml::Table = text.parse().map_err(|e: toml::de::Error| e.to_string())?;
+        let mut scene = SceneEntries::default();
+        scene.flatten("", &document);
+        Ok(scene)
+    }
+
+    fn flatten(&mut self, section: &str, table: &toml::Table) {
+        for (key, value) in table {
+            match value {
+                toml::Value::Table(inner) => {
+                    let path = if section.is_empty() { key.clone() } else { format!("{}.{}", section, key) };
+                    self.sections.insert(path.clone());
+                    self.flatten(&path, inner);
+                }
+                value => {
+                    self.entries.insert((section.to_string(), key.clone()), value.clone());
+                }
+            }
+        }
+    }
+}
+
+// Un cambio entre dos escenas
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    BodyAdded(String),
+    BodyRemoved(String),
+    SectionAdded(String),
+    SectionRemoved(String),
+    Added { section: String, key: String, value: toml::Value },
+    Removed { section: String, key: String, value: toml::Value },
+    Changed { section: String, key: String, from: toml::Value, to: toml::Value },
+}
+
+impl Change {
+    pub fn describe(&self) -> String {
+        match self {
+            Change::BodyAdded(body) => format!("+ cuerpo {}", body),
+            Change::BodyRemoved(body) => format!("- cuerpo {}", body),
+            Change::SectionAdded(section) => format!("+ [{}]", section),
+            Change::SectionRemoved(section) => format!("- [{}]", section),
+            Change::Added { section, key, value } => format!("+ {} = {}", qualified(section, key), value),
+            Change::Removed { section, key, value } => format!("- {} = {}", qualified(section, key), value),
+            Change::Changed { section, key, from, to } => format!("~ {}: {} -> {}", qualified(section, key), from, to),
+        }
+    }
+}
+
+fn qualified(section: &str, key: &str) -> String {
+    if section.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", section, key)
+    }
+}
+
+// Cuerpo de una sección de material ([bodies.<nombre>])
+fn body_name(section: &str) -> Option<&str> {
+    section.strip_prefix("bodies.").filter(|name| !name.contains('.'))
+}
+
+// Cambios de `a` a `b`: primero las secciones agregadas y quitadas (los cuerpos por
+// nombre) y después cada parámetro, ordenados por sección
+pub fn diff(a: &SceneEntries, b: &SceneEntries) -> Vec<Change> {
+    let mut changes = Vec::new();
+    for section in b.sections.difference(&a.sections) {
+        changes.push(body_name(section).map_or_else(|| Change::SectionAdded(section.clone()), |body| Change::BodyAdded(body.to_string())));
+    }
+    for section in a.sections.difference(&b.sections) {
+        changes.push(body_name(section).map_or_else(|| Change::SectionRemoved(section.clone()), |body| Change::BodyRemoved(body.to_string())));
+    }
+
+    let keys: BTreeSet<&(String, String)> = a.entries.keys().chain(b.entries.keys()).collect();
+    for entry in keys {
+        let (section, key) = entry.clone();
+        match (a.entries.get(entry), b.entries.get(entry)) {
+            (None, Some(value)) => changes.push(Change::Added { section, key, value: value.clone() }),
+            (Some(value), None) => changes.push(Change::Removed { section, key, value: value.clone() }),
+            (Some(from), Some(to)) if from != to => changes.push(Change::Changed { section, key, from: from.clone(), to: to.clone() }),
+            _ => {}
+        }
+    }
+    changes
+}
+
+// Modo `--scene-diff a.toml b.toml`: imprime los cambios; devuelve si hay alguno
+pub fn run_diff(path_a: &str, path_b: &str) -> Result<bool, String> {
+    let a = SceneEntries::load(path_a)?;
+    let b = SceneEntries::load(path_b)?;
+    let changes = diff(&a, &b);
+    if changes.is_empty() {
+        println!("Las escenas {} y {} son equivalentes", path_a, path_b);
+    } else {
+        println!("Cambios de {} a {}:", path_a, path_b);
+        for change in &changes {
+            println!("  {}", change.describe());
+        }
+    }
+    Ok(!changes.is_empty())
+}
+
+// Clave que cambió distinto en los dos lados desde la base común
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub section: String,
+    pub key: String,
+    pub ours: Option<toml::Value>,
+    pub theirs: Option<toml::Value>,
+}
+
+impl Conflict {
+    pub fn describe(&self) -> String {
+        let show = |value: &Option<toml::Value>| value.as_ref().map_or(String::from("(quitada)"), |value| value.to_string());
+        format!("{}: nuestra {} / su {}", qualified(&self.section, &self.key), show(&self.ours), show(&self.theirs))
+    }
+}
+
+// Mezcla de tres vías: cada clave toma el lado que la cambió respecto de `base`; si
+// cambiaron las dos a valores distintos queda la nuestra y se informa el conflicto.
+// El resultado es el texto de `ours_text` con los cambios de `theirs` aplicados
+// línea por línea, así se conservan los comentarios y el orden del archivo.
+pub fn merge(base: &SceneEntries, ours_text: &str, theirs: &SceneEntries) -> Result<(String, Vec<Conflict>), String> {
+    let ours = SceneEntries::parse(ours_text)?;
+    let mut expected = ours.entries.clone();
+    let mut edits: Vec<(String, String, Option<toml::Value>)> = Vec::new();
+    let mut conflicts = Vec::new();
+
+    let keys: BTreeSet<&(String, String)> = base.entries.keys().chain(ours.entries.keys()).chain(theirs.entries.keys()).collect();
+    for entry in keys {
+        let (b, o, t) = (base.entries.get(entry), ours.entries.get(entry), theirs.entries.get(entry));
+        if o == t || t == b {
+            continue;
+        }
+        let (section, key) = entry.clone();
+        if o == b {
+            match t {
+                Some(value) => expected.insert(entry.clone(), value.clone()),
+                None => expected.remove(entry),
+            };
+            edits.push((section, key, t.cloned()));
+        } else {
+            conflicts.push(Conflict { section, key, ours: o.cloned(), theirs: t.cloned() });
+        }
+    }
+
+    // Secciones vacías que agregaron ellos y encabezados que quitaron sin tocarlos nosotros
+    let added: Vec<String> = theirs.sections.iter().filter(|s| !base.sections.contains(*s) && !ours.sections.contains(*s)).cloned().collect();
+    let removed: Vec<String> = base.sections.iter().filter(|s| ours.sections.contains(*s) && !theirs.sections.contains(*s)).cloned().collect();
+
+    let text = SceneText::new(ours_text).apply(&edits, &added, &removed, &expected);
+    let merged = SceneEntries::parse(&text)?;
+    if merged.entries != expected {
+        return Err(String::from("la escena mezclada no coincide con los cambios (¿valores de varias líneas?)"));
+    }
+    Ok((text, conflicts))
+}
+
+// Modo `--scene-merge base.toml nuestra.toml suya.toml`: escribe la mezcla en
+// `output` y devuelve los conflictos
+pub fn run_merge(base: &str, ours: &str, theirs: &str, output: &str) -> Result<Vec<Conflict>, String> {
+    let base = SceneEntries::load(base)?;
+    let theirs_entries = SceneEntries::load(theirs)?;
+    let ours_text = fs::read_to_string(ours).map_err(|err| format!("No se pudo leer {}: {}", ours, err))?;
+    let (text, conflicts) = merge(&base, &ours_text, &theirs_entries).map_err(|err| format!("No se pudo mezclar: {}", err))?;
+    fs::write(output, text).map_err(|err| format!("No se pudo guardar {}: {}", output, err))?;
+
+    println!("Escena mezclada: {}", output);
+    if conflicts.is_empty() {
+        println!("Sin conflictos");
+    } else {
+        println!("Conflictos (se conserva el valor de {}):", ours);
+        for conflict in &conflicts {
+            println!("  {}", conflict.describe());
+        }
+    }
+    Ok(conflicts)
+}
+
+// Texto de un archivo de escena indexado por sección y clave, para editarlo sin
+// perder los comentarios. Solo entiende encabezados `[sección]` y claves de una línea.
+struct SceneText<'a> {
+    lines: Vec<&'a str>,
+    // Línea de cada clave
+    keys: BTreeMap<(String, String), usize>,
+    // Última línea con contenido de cada sección ("" son las claves sueltas del principio)
+    section_end: BTreeMap<String, usize>,
+    headers: BTreeMap<String, usize>,
+}
+
+impl<'a> SceneText<'a> {
+    fn new(text: &'a str) -> Self {
+        let lines: Vec<&str> = text.lines().collect();
+        let mut keys = BTreeMap::new();
+        let mut section_end = BTreeMap::new();
+        let mut headers = BTreeMap::new();
+        let mut section = String::new();
+        for (i, line) in lines.iter().enumerate() {
+            let trimmed = line.trim();
+            if let Some(name) = trimmed.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')).filter(|name| !name.starts_with('[')) {
+                section = name.trim().to_string();
+                headers.insert(section.clone(), i);
+                section_end.insert(section.clone(), i);
+            } else if let Some((key, _)) = trimmed.split_once('=').filter(|_| !trimmed.starts_with('#')) {
+                keys.insert((section.clone(), key.trim().to_string()), i);
+                section_end.insert(section.clone(), i);
+            }
+        }
+        SceneText { lines, keys, section_end, headers }
+    }
+
+    fn apply(&self, edits: &[(String, String, Option<toml::Value>)], added: &[String], removed: &[String], expected: &Entries) -> String {
+        let mut lines: Vec<Option<String>> = self.lines.iter().map(|line| Some(line.to_string())).collect();
+        // Líneas nuevas después de cada línea, antes de todo y al final (secciones nuevas)
+        let mut after: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+        let mut before = Vec::new();
+        let mut new_sections: BTreeMap<String, Vec<String>> = added.iter().map(|section| (section.clone(), Vec::new())).collect();
+
+        for (section, key, value) in edits {
+            let line = value.as_ref().map(|value| format!("{} = {}", key, value));
+            match (self.keys.get(&(section.clone(), key.clone())), line) {
+                (Some(&i), Some(line)) => {
+                    let indent = &self.lines[i][..self.lines[i].len() - self.lines[i].trim_start().len()];
+                    lines[i] = Some(format!("{}{}", indent, line));
+                }
+                (Some(&i), None) => lines[i] = None,
+                (None, Some(line)) => match self.section_end.get(section) {
+                    Some(&end) => after.entry(end).or_default().push(line),
+                    None if section.is_empty() => before.push(line),
+                    None => new_sections.entry(section.clone()).or_default().push(line),
+                },
+                (None, None) => {}
+            }
+        }
+        // Un encabezado que ellos quitaron se va si ya no le queda ninguna clave
+        for section in removed {
+            let emptied = !expected.keys().any(|(s, _)| s == section || s.starts_with(&format!("{}.", section)));
+            if let (Some(&i), true) = (self.headers.get(section), emptied) {
+                lines[i] = None;
+            }
+        }
+
+        let mut text = String::new();
+        for line in &before {
+            text.push_str(line);
+            text.push('\n');
+        }
+        for (i, line) in lines.iter().enumerate() {
+            if let Some(line) = line {
+                text.push_str(line);
+                text.push('\n');
+            }
+            for line in after.get(&i).into_iter().flatten() {
+                text.push_str(line);
+                text.push('\n');
+            }
+        }
+        for (section, keys) in &new_sections {
+            // Una tabla que solo agrupa subtablas nuevas no necesita encabezado propio
+            let prefix = format!("{}.", section);
+            if keys.is_empty() && new_sections.keys().any(|other| other.starts_with(&prefix)) {
+                continue;
+            }
+            text.push_str(&format!("\n[{}]\n", section));
+            for line in keys {
+                text.push_str(line);
+                text.push('\n');
+            }
+        }
+        text
+    }
+}
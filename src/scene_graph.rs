@@ -0,0 +1,100 @@
+use nalgebra_glm::{self as glm, DMat3, DMat4, DVec3, Mat4};
+
+// Transformación de un nodo respecto de su padre: traslación y giros en radianes
+// alrededor de X, Y y Z (en el mismo orden que create_model_matrix)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub translation: DVec3,
+    pub rotation: DVec3,
+}
+
+impl Transform {
+    pub fn at(translation: DVec3) -> Self {
+        Transform { translation, rotation: DVec3::zeros() }
+    }
+
+    // Con la orientación de una matriz de rotación, descompuesta en los tres giros.
+    // Con la nariz justo hacia arriba o abajo (giro en Y de ±90°) X y Z se confunden,
+    // pero la matriz que arman sigue siendo la misma.
+    pub fn oriented(translation: DVec3, rotation: &DMat3) -> Self {
+        let y = (-rotation[(2, 0)]).clamp(-1.0, 1.0).asin();
+        let x = rotation[(2, 1)].atan2(rotation[(2, 2)]);
+        let z = rotation[(1, 0)].atan2(rotation[(0, 0)]);
+        Transform { translation, rotation: DVec3::new(x, y, z) }
+    }
+
+    pub fn matrix(&self) -> DMat4 {
+        glm::translation(&self.translation)
+            * glm::rotation(self.rotation.z, &DVec3::z())
+            * glm::rotation(self.rotation.y, &DVec3::y())
+            * glm::rotation(self.rotation.x, &DVec3::x())
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::at(DVec3::zeros())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub name: &'static str,
+    // Índice del padre en el grafo; siempre menor que el del hijo
+    pub parent: Option<usize>,
+    pub local: Transform,
+    // Matriz del nodo en el mundo, en doble precisión; la arma `update`
+    world: DMat4,
+}
+
+// Jerarquía de transformaciones: cada nodo se mueve con su padre (una luna con su
+// planeta, una cámara con su nave). Los padres van antes que sus hijos, así que
+// `update` compone todo el árbol en una sola pasada.
+#[derive(Debug, Clone, Default)]
+pub struct SceneGraph {
+    nodes: Vec<Node>,
+}
+
+impl SceneGraph {
+    // El padre tiene que existir ya
+    pub fn add(&mut self, name: &'static str, parent: Option<usize>, local: Transform) -> usize {
+        debug_assert!(parent.is_none_or(|parent| parent < self.nodes.len()));
+        let world = parent.map_or(DMat4::identity(), |parent| self.nodes[parent].world) * local.matrix();
+        self.nodes.push(Node { name, parent, local, world });
+        self.nodes.len() - 1
+    }
+
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.nodes.iter().position(|node| node.name == name)
+    }
+
+    pub fn node(&self, index: usize) -> &Node {
+        &self.nodes[index]
+    }
+
+    // Se ve en el mundo después del próximo `update`
+    pub fn set_local(&mut self, index: usize, local: Transform) {
+        self.nodes[index].local = local;
+    }
+
+    pub fn update(&mut self) {
+        for i in 0..self.nodes.len() {
+            let parent = self.nodes[i].parent.map_or(DMat4::identity(), |parent| self.nodes[parent].world);
+            self.nodes[i].world = parent * self.nodes[i].local.matrix();
+        }
+    }
+
+    pub fn world_position(&self, index: usize) -> DVec3 {
+        self.nodes[index].world.column(3).xyz()
+    }
+
+    // Matriz del nodo relativa al origen flotante, lista para componer con la del
+    // modelo: la resta se hace en f64 y solo el resultado pasa a f32 (ver to_render)
+    pub fn render_matrix(&self, index: usize, origin: DVec3) -> Mat4 {
+        let mut world = self.nodes[index].world;
+        for axis in 0..3 {
+            world[(axis, 3)] -= origin[axis];
+        }
+        world.cast::<f32>()
+    }
+}
@@ -0,0 +1,120 @@
+use std::fs;
+use nalgebra_glm::{DVec3, Vec3};
+use crate::color::Color;
+use crate::lighting::{Light, LightKind};
+use crate::palettes::parse_hex_color;
+use crate::solar_system::{self, SolarSystem};
+
+// Luces que se suman al Sol, leídas de la lista [[lights]] del archivo de escena:
+//
+//   [[lights]]
+//   kind = "point"                # o "directional"
+//   position = [40.0, 6.0, 0.0]   # puntual: en el mundo, o relativa a `body`
+//   body = "spaceship"            # opcional; la luz acompaña al cuerpo
+//   direction = [-1.0, 0.0, 0.0]  # direccional: hacia dónde viajan los rayos
+//   color = "#FFB08C"             # por defecto blanco
+//   intensity = 0.4               # por defecto 1
+#[derive(Debug, Default)]
+pub struct SceneLights {
+    lights: Vec<SceneLight>,
+}
+
+#[derive(Debug)]
+struct SceneLight {
+    kind: SceneLightKind,
+    color: Color,
+    intensity: f32,
+}
+
+#[derive(Debug)]
+enum SceneLightKind {
+    Point { position: DVec3, body: Option<String> },
+    Directional(Vec3),
+}
+
+impl SceneLights {
+    pub fn load(path: &str) -> Self {
+        let parsed = fs::read_to_string(path).map_err(|err| err.to_string()).and_then(|text| SceneLights::parse(&text));
+        parsed.unwrap_or_else(|err| {
+            eprintln!("Luces de la escena en {}: {}; solo ilumina el Sol", path, err);
+            SceneLights::default()
+        })
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let document: toml::Table = text.parse().map_err(|e: toml::de::Error| e.to_string())?;
+        let entries = match document.get("lights") {
+            Some(toml::Value::Array(entries)) => entries,
+            Some(_) => return Err(String::from("'lights' debe ser una lista de tablas ([[lights]])")),
+            None => return Ok(SceneLights::default()),
+        };
+
+        let mut lights = Vec::with_capacity(entries.len());
+        for (index, entry) in entries.iter().enumerate() {
+            let entry = entry.as_table().ok_or_else(|| format!("lights[{}] debe ser una tabla", index))?;
+            let field = |key: &str| format!("lights[{}].{}", index, key);
+
+            let kind = match entry.get("kind").map(|value| value.as_str()) {
+                None | Some(Some("point")) => {
+                    let position = entry
+                        .get("position")
+                        .map_or(Some(DVec3::zeros()), vector)
+                        .ok_or_else(|| format!("{} debe ser [x, y, z]", field("position")))?;
+                    let body = match entry.get("body") {
+                        None => None,
+                        Some(value) => Some(value.as_str().ok_or_else(|| format!("{} debe ser un texto", field("body")))?.to_string()),
+                    };
+                    SceneLightKind::Point { position, body }
+                }
+                Some(Some("directional")) => {
+                    let direction = entry
+                        .get("direction")
+                        .and_then(vector)
+                        .filter(|direction| direction.magnitude() > 0.0)
+                        .ok_or_else(|| format!("{} debe ser [x, y, z] y no nulo", field("direction")))?;
+                    SceneLightKind::Directional(direction.normalize().cast::<f32>())
+                }
+                _ => return Err(format!("{} debe ser \"point\" o \"directional\"", field("kind"))),
+            };
+            let color = match entry.get("color") {
+                None => Color::WHITE,
+                Some(value) => value
+                    .as_str()
+                    .and_then(parse_hex_color)
+                    .ok_or_else(|| format!("{} debe ser un color \"#RRGGBB\"", field("color")))?,
+            };
+            let intensity = match entry.get("intensity") {
+                None => 1.0,
+                Some(toml::Value::Float(value)) => *value as f32,
+                Some(toml::Value::Integer(value)) => *value as f32,
+                Some(_) => return Err(format!("{} debe ser un número", field("intensity"))),
+            };
+            lights.push(SceneLight { kind, color, intensity: intensity.max(0.0) });
+        }
+        Ok(SceneLights { lights })
+    }
+
+    // El Sol y las luces del archivo, ubicadas en el espacio de render de este frame.
+    // Las que siguen a un cuerpo que no está en el sistema no iluminan.
+    pub fn place(&self, sun: Light, system: &SolarSystem, origin: DVec3) -> Vec<Light> {
+        let mut lights = vec![sun];
+        for light in &self.lights {
+            let kind = match &light.kind {
+                SceneLightKind::Point { position, body: None } => LightKind::Point(solar_system::to_render(*position, origin)),
+                SceneLightKind::Point { position, body: Some(body) } => match system.body(body) {
+                    Some(body) => LightKind::Point(solar_system::to_render(body.position + position, origin)),
+                    None => continue,
+                },
+                SceneLightKind::Directional(direction) => LightKind::Directional(*direction),
+            };
+            lights.push(Light { kind, color: light.color, intensity: light.intensity });
+        }
+        lights
+    }
+}
+
+fn vector(value: &toml::Value) -> Option<DVec3> {
+    let array = value.as_array().filter(|array| array.len() == 3)?;
+    let component = |value: &toml::Value| value.as_float().or_else(|| value.as_integer().map(|value| value as f64));
+    Some(DVec3::new(component(&array[0])?, component(&array[1])?, component(&array[2])?))
+}
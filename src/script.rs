@@ -0,0 +1,563 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+use crate::color::Color;
+use crate::fragment::Fragment;
+use crate::palettes::parse_hex_color;
+use crate::Uniforms;
+
+// Shaders de expresiones: assets/shaders/<cuerpo>.expr reemplaza el shader
+// del cuerpo sin recompilar, y se recarga al guardar el archivo.
+//
+//   // Marte con bandas y rocas
+//   let bands = band(y * 10 * band_frequency + time * 0.05)
+//   let rock = noise(x * 4, y * 4, z * 4)
+//   color = gradient(bands * 0.7 + rock * 0.3, #B22222, #8B0000, palette(2, #A0522D))
+//
+// Cada línea es `let <nombre> = <expresión>` o la salida `color = <expresión>`.
+// Entradas: x, y, z (posición en el modelo), time, intensity, band_frequency, noise_scale.
+// Valores: números y colores (#RRGGBB); + y - entre iguales, * y / también con un número.
+// Funciones: sin cos abs fract floor sqrt min max pow step clamp smoothstep mix
+//   noise(x, y, z)          ruido de la escena, -1..1
+//   band(v)                 0.5 + 0.5 sen(v)
+//   gradient(t, c1, c2...)  paradas equiespaciadas en 0..1
+//   palette(i, defecto)     color i del cuerpo en la paleta activa
+//   rgb(r, g, b)            componentes 0..1
+pub const SCRIPT_DIR: &str = "assets/shaders";
+const EXTENSION: &str = "expr";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Number,
+    Color,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Value {
+    Number(f32),
+    Color([f32; 3]),
+}
+
+impl Value {
+    fn number(self) -> f32 {
+        match self {
+            Value::Number(n) => n,
+            Value::Color([r, g, b]) => (r + g + b) / 3.0,
+        }
+    }
+
+    fn color(self) -> [f32; 3] {
+        match self {
+            Value::Number(n) => [n; 3],
+            Value::Color(c) => c,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Input {
+    X,
+    Y,
+    Z,
+    Time,
+    Intensity,
+    BandFrequency,
+    NoiseScale,
+}
+
+impl Input {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "x" => Some(Input::X),
+            "y" => Some(Input::Y),
+            "z" => Some(Input::Z),
+            "time" => Some(Input::Time),
+            "intensity" => Some(Input::Intensity),
+            "band_frequency" => Some(Input::BandFrequency),
+            "noise_scale" => Some(Input::NoiseScale),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Function {
+    Sin,
+    Cos,
+    Abs,
+    Fract,
+    Floor,
+    Sqrt,
+    Min,
+    Max,
+    Pow,
+    Step,
+    Clamp,
+    Smoothstep,
+    Mix,
+    Noise,
+    Band,
+    Gradient,
+    Palette,
+    Rgb,
+}
+
+impl Function {
+    fn parse(name: &str) -> Option<Self> {
+        let function = match name {
+            "sin" => Function::Sin,
+            "cos" => Function::Cos,
+            "abs" => Function::Abs,
+            "fract" => Function::Fract,
+            "floor" => Function::Floor,
+            "sqrt" => Function::Sqrt,
+            "min" => Function::Min,
+            "max" => Function::Max,
+            "pow" => Function::Pow,
+            "step" => Function::Step,
+            "clamp" => Function::Clamp,
+            "smoothstep" => Function::Smoothstep,
+            "mix" => Function::Mix,
+            "noise" => Function::Noise,
+            "band" => Function::Band,
+            "gradient" => Function::Gradient,
+            "palette" => Function::Palette,
+            "rgb" => Function::Rgb,
+            _ => return None,
+        };
+        Some(function)
+    }
+
+    // Tipo del resultado a partir de los tipos de los argumentos
+    fn check(self, name: &str, args: &[Kind]) -> Result<Kind, String> {
+        use Kind::{Color, Number};
+        let numbers = |count: usize| -> Result<Kind, String> {
+            if args.len() == count && args.iter().all(|kind| *kind == Number) {
+                Ok(Number)
+            } else {
+                Err(format!("{} espera {} números", name, count))
+            }
+        };
+        match self {
+            Function::Sin | Function::Cos | Function::Abs | Function::Fract | Function::Floor | Function::Sqrt | Function::Band => numbers(1),
+            Function::Min | Function::Max | Function::Pow | Function::Step => numbers(2),
+            Function::Clamp | Function::Smoothstep | Function::Noise => numbers(3),
+            Function::Rgb => numbers(3).map(|_| Color),
+            Function::Mix => match args {
+                [a, b, Number] if a == b => Ok(*a),
+                _ => Err(String::from("mix espera (a, b, t) con a y b del mismo tipo y t número")),
+            },
+            Function::Gradient => match args {
+                [Number, stops @ ..] if stops.len() >= 2 && stops.iter().all(|kind| *kind == Color) => Ok(Color),
+                _ => Err(String::from("gradient espera (t, color, color, ...)")),
+            },
+            Function::Palette => match args {
+                [Number, Color] => Ok(Color),
+                _ => Err(String::from("palette espera (índice, color por defecto)")),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Number(f32),
+    Color([f32; 3]),
+    Input(Input),
+    Local(usize),
+    Neg(Box<Expr>),
+    Binary(char, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f32),
+    Color([f32; 3]),
+    Ident(String),
+    Symbol(char),
+}
+
+fn tokenize(line: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            tokens.push(Token::Number(text.parse().map_err(|_| format!("número inválido: {}", text))?));
+        } else if c == '#' {
+            let text: String = chars[i..(i + 7).min(chars.len())].iter().collect();
+            let color = parse_hex_color(&text).ok_or_else(|| format!("color inválido: {}", text))?;
+            let (r, g, b) = color.to_f32();
+            tokens.push(Token::Color([r, g, b]));
+            i += 7;
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else if "+-*/(),=".contains(c) {
+            tokens.push(Token::Symbol(c));
+            i += 1;
+        } else {
+            return Err(format!("carácter inesperado: '{}'", c));
+        }
+    }
+    Ok(tokens)
+}
+
+// Anidamiento máximo de paréntesis, llamadas y signos menos en una expresión; más
+// que eso agotaría la pila al analizarla o al evaluarla
+const MAX_DEPTH: usize = 64;
+
+// Descenso recursivo sobre los tokens de una línea; comprueba los tipos al construir
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    locals: &'a [(String, Kind)],
+    // Niveles de `unary` abiertos ahora mismo (ver MAX_DEPTH)
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<(), String> {
+        match self.next() {
+            Some(Token::Symbol(c)) if c == symbol => Ok(()),
+            _ => Err(format!("se esperaba '{}'", symbol)),
+        }
+    }
+
+    fn expression(&mut self) -> Result<(Expr, Kind), String> {
+        let (mut left, kind) = self.term()?;
+        while let Some(Token::Symbol(op @ ('+' | '-'))) = self.peek().cloned() {
+            self.position += 1;
+            let (right, right_kind) = self.term()?;
+            if kind != right_kind {
+                return Err(format!("'{}' entre un número y un color", op));
+            }
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok((left, kind))
+    }
+
+    fn term(&mut self) -> Result<(Expr, Kind), String> {
+        let (mut left, mut kind) = self.unary()?;
+        while let Some(Token::Symbol(op @ ('*' | '/'))) = self.peek().cloned() {
+            self.position += 1;
+            let (right, right_kind) = self.unary()?;
+            kind = match (op, kind, right_kind) {
+                (_, Kind::Number, Kind::Number) => Kind::Number,
+                ('/', _, Kind::Color) => return Err(String::from("no se puede dividir entre un color")),
+                _ => Kind::Color,
+            };
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok((left, kind))
+    }
+
+    // Toda la recursión (paréntesis, argumentos, signos) pasa por aquí
+    fn unary(&mut self) -> Result<(Expr, Kind), String> {
+        if self.depth >= MAX_DEPTH {
+            return Err(format!("expresión demasiado anidada (más de {} niveles)", MAX_DEPTH));
+        }
+        self.depth += 1;
+        let result = if self.peek() == Some(&Token::Symbol('-')) {
+            self.position += 1;
+            self.unary().map(|(inner, kind)| (Expr::Neg(Box::new(inner)), kind))
+        } else {
+            self.primary()
+        };
+        self.depth -= 1;
+        result
+    }
+
+    fn primary(&mut self) -> Result<(Expr, Kind), String> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok((Expr::Number(n), Kind::Number)),
+            Some(Token::Color(c)) => Ok((Expr::Color(c), Kind::Color)),
+            Some(Token::Symbol('(')) => {
+                let inner = self.expression()?;
+                self.expect(')')?;
+                Ok(inner)
+            }
+            Some(Token::Ident(name)) => {
+                if self.peek() == Some(&Token::Symbol('(')) {
+                    self.position += 1;
+                    let function = Function::parse(&name).ok_or_else(|| format!("función desconocida: {}", name))?;
+                    let mut args = Vec::new();
+                    let mut kinds = Vec::new();
+                    if self.peek() != Some(&Token::Symbol(')')) {
+                        loop {
+                            let (arg, kind) = self.expression()?;
+                            args.push(arg);
+                            kinds.push(kind);
+                            if self.peek() == Some(&Token::Symbol(',')) {
+                                self.position += 1;
+                            } else {
+                                break;
+                            }
+                        }
+                    }
+                    self.expect(')')?;
+                    let kind = function.check(&name, &kinds)?;
+                    Ok((Expr::Call(function, args), kind))
+                } else if let Some(index) = self.locals.iter().rposition(|(local, _)| *local == name) {
+                    Ok((Expr::Local(index), self.locals[index].1))
+                } else {
+                    let input = Input::parse(&name).ok_or_else(|| format!("variable desconocida: {}", name))?;
+                    Ok((Expr::Input(input), Kind::Number))
+                }
+            }
+            Some(Token::Symbol(c)) => Err(format!("símbolo inesperado: '{}'", c)),
+            None => Err(String::from("expresión incompleta")),
+        }
+    }
+}
+
+// Shader compilado de un archivo .expr
+#[derive(Debug, Clone)]
+pub struct ScriptShader {
+    pub body: String,
+    locals: Vec<Expr>,
+    output: Expr,
+}
+
+impl ScriptShader {
+    pub fn parse(body: &str, text: &str) -> Result<Self, String> {
+        let mut names: Vec<(String, Kind)> = Vec::new();
+        let mut locals = Vec::new();
+        let mut output = None;
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split("//").next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let at_line = |err: String| format!("línea {}: {}", number + 1, err);
+            let tokens = tokenize(line).map_err(at_line)?;
+            let (name, rest) = match tokens.as_slice() {
+                [Token::Ident(keyword), Token::Ident(name), Token::Symbol('='), rest @ ..] if keyword == "let" => (name.clone(), rest),
+                [Token::Ident(name), Token::Symbol('='), rest @ ..] if name == "color" => (name.clone(), rest),
+                _ => return Err(at_line(String::from("se esperaba 'let <nombre> = ...' o 'color = ...'"))),
+            };
+
+            let mut parser = Parser { tokens: rest, position: 0, locals: &names, depth: 0 };
+            let (expr, kind) = parser.expression().map_err(at_line)?;
+            if parser.position < rest.len() {
+                return Err(at_line(String::from("sobra texto al final de la línea")));
+            }
+
+            if name == "color" {
+                if kind != Kind::Color {
+                    return Err(at_line(String::from("'color' debe ser un color")));
+                }
+                output = Some(expr);
+            } else {
+                names.push((name, kind));
+                locals.push(expr);
+            }
+        }
+
+        let output = output.ok_or_else(|| String::from("falta la línea 'color = ...'"))?;
+        Ok(ScriptShader { body: body.to_string(), locals, output })
+    }
+
+    pub fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        let mut values = Vec::with_capacity(self.locals.len());
+        for expr in &self.locals {
+            let value = self.eval(expr, &values, fragment, uniforms);
+            values.push(value);
+        }
+        let [r, g, b] = self.eval(&self.output, &values, fragment, uniforms).color();
+        Color::from_f32(r, g, b)
+    }
+
+    fn eval(&self, expr: &Expr, locals: &[Value], fragment: &Fragment, uniforms: &Uniforms) -> Value {
+        let eval = |expr: &Expr| self.eval(expr, locals, fragment, uniforms);
+        match expr {
+            Expr::Number(n) => Value::Number(*n),
+            Expr::Color(c) => Value::Color(*c),
+            Expr::Local(index) => locals[*index],
+            Expr::Input(input) => Value::Number(match input {
+                Input::X => fragment.vertex_position.x,
+                Input::Y => fragment.vertex_position.y,
+                Input::Z => fragment.vertex_position.z,
+                Input::Time => uniforms.time,
+                Input::Intensity => fragment.intensity,
+                Input::BandFrequency => uniforms.material.params.band_frequency,
+                Input::NoiseScale => uniforms.material.params.noise_scale,
+            }),
+            Expr::Neg(inner) => match eval(inner) {
+                Value::Number(n) => Value::Number(-n),
+                Value::Color(c) => Value::Color(c.map(|v| -v)),
+            },
+            Expr::Binary(op, a, b) => binary(*op, eval(a), eval(b)),
+            Expr::Call(function, args) => {
+                let values: Vec<Value> = args.iter().map(eval).collect();
+                let n = |i: usize| values[i].number();
+                match function {
+                    Function::Sin => Value::Number(n(0).sin()),
+                    Function::Cos => Value::Number(n(0).cos()),
+                    Function::Abs => Value::Number(n(0).abs()),
+                    Function::Fract => Value::Number(n(0).fract()),
+                    Function::Floor => Value::Number(n(0).floor()),
+                    Function::Sqrt => Value::Number(n(0).max(0.0).sqrt()),
+                    Function::Min => Value::Number(n(0).min(n(1))),
+                    Function::Max => Value::Number(n(0).max(n(1))),
+                    Function::Pow => Value::Number(n(0).powf(n(1))),
+                    Function::Step => Value::Number(if n(1) < n(0) { 0.0 } else { 1.0 }),
+                    Function::Clamp => Value::Number(n(0).clamp(n(1), n(2).max(n(1)))),
+                    Function::Smoothstep => {
+                        let t = ((n(2) - n(0)) / (n(1) - n(0))).clamp(0.0, 1.0);
+                        Value::Number(t * t * (3.0 - 2.0 * t))
+                    }
+                    Function::Mix => {
+                        let t = n(2);
+                        match (values[0], values[1]) {
+                            (Value::Number(a), Value::Number(b)) => Value::Number(a + (b - a) * t),
+                            (a, b) => {
+                                let (a, b) = (a.color(), b.color());
+                                Value::Color([0, 1, 2].map(|i| a[i] + (b[i] - a[i]) * t))
+                            }
+                        }
+                    }
+                    Function::Noise => Value::Number(uniforms.noise.get_noise_3d(n(0), n(1), n(2))),
+                    Function::Band => Value::Number(0.5 + 0.5 * n(0).sin()),
+                    Function::Gradient => {
+                        let stops = &values[1..];
+                        let scaled = n(0).clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+                        let index = (scaled.floor() as usize).min(stops.len() - 2);
+                        let (a, b) = (stops[index].color(), stops[index + 1].color());
+                        let t = scaled - index as f32;
+                        Value::Color([0, 1, 2].map(|i| a[i] + (b[i] - a[i]) * t))
+                    }
+                    Function::Palette => {
+                        let [r, g, b] = values[1].color();
+                        let default = Color::from_f32(r, g, b);
+                        let (r, g, b) = uniforms.palette.color(&self.body, n(0).max(0.0) as usize, default).to_f32();
+                        Value::Color([r, g, b])
+                    }
+                    Function::Rgb => Value::Color([n(0), n(1), n(2)]),
+                }
+            }
+        }
+    }
+}
+
+fn binary(op: char, a: Value, b: Value) -> Value {
+    let apply = |x: f32, y: f32| match op {
+        '+' => x + y,
+        '-' => x - y,
+        '*' => x * y,
+        _ => if y != 0.0 { x / y } else { 0.0 },
+    };
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => Value::Number(apply(x, y)),
+        (a, b) => {
+            let (a, b) = (a.color(), b.color());
+            Value::Color([0, 1, 2].map(|i| apply(a[i], b[i])))
+        }
+    }
+}
+
+struct LoadedScript {
+    shader: Arc<ScriptShader>,
+    modified: Option<SystemTime>,
+}
+
+// Shaders de expresiones por cuerpo, recargados cuando cambia su archivo
+#[derive(Default)]
+pub struct ScriptLibrary {
+    dir: PathBuf,
+    scripts: HashMap<String, LoadedScript>,
+    // Último archivo con error, para no repetir el mensaje cada vez que se revisa
+    failed: HashMap<PathBuf, Option<SystemTime>>,
+}
+
+impl ScriptLibrary {
+    pub fn load_dir(dir: &str) -> Self {
+        let mut library = ScriptLibrary { dir: PathBuf::from(dir), ..Default::default() };
+        library.reload();
+        library
+    }
+
+    pub fn get(&self, body: &str) -> Option<Arc<ScriptShader>> {
+        self.scripts.get(body).map(|script| Arc::clone(&script.shader))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scripts.is_empty()
+    }
+
+    // Compila los archivos nuevos o modificados y olvida los borrados. Si un archivo
+    // tiene errores se conserva la versión anterior.
+    pub fn reload(&mut self) {
+        let paths: Vec<PathBuf> = fs::read_dir(&self.dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok().map(|e| e.path()))
+                    .filter(|path| path.extension().is_some_and(|ext| ext == EXTENSION))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let present: Vec<String> = paths.iter().filter_map(|path| body_name(path)).collect();
+        self.scripts.retain(|body, _| {
+            let keep = present.contains(body);
+            if !keep {
+                println!("Shader de expresiones quitado: {}", body);
+            }
+            keep
+        });
+
+        for path in paths {
+            let Some(body) = body_name(&path) else { continue };
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+            let unchanged = self.scripts.get(&body).is_some_and(|script| script.modified == modified)
+                || self.failed.get(&path) == Some(&modified);
+            if unchanged {
+                continue;
+            }
+
+            let result = fs::read_to_string(&path)
+                .map_err(|err| err.to_string())
+                .and_then(|text| ScriptShader::parse(&body, &text));
+            match result {
+                Ok(shader) => {
+                    println!("Shader de expresiones cargado: {}", path.display());
+                    self.failed.remove(&path);
+                    self.scripts.insert(body, LoadedScript { shader: Arc::new(shader), modified });
+                }
+                Err(err) => {
+                    eprintln!("Error en {}: {}", path.display(), err);
+                    self.failed.insert(path, modified);
+                }
+            }
+        }
+    }
+}
+
+fn body_name(path: &Path) -> Option<String> {
+    path.file_stem().and_then(|stem| stem.to_str()).map(str::to_string)
+}
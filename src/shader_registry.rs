@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Arc;
+use crate::color::Color;
+use crate::fragment::Fragment;
+use crate::shaders;
+use crate::vertex::Vertex;
+use crate::Uniforms;
+
+// Shader de un objeto: la etapa de vértices (por defecto la transformación común) y
+// la de fragmentos. Cualquier función `fn(&Fragment, &Uniforms) -> Color` ya es uno.
+pub trait Shader: Send + Sync {
+    fn vertex(&self, vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
+        shaders::vertex_shader(vertex, uniforms)
+    }
+
+    fn fragment(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color;
+
+    // Los cuerpos con un shader emisivo iluminan lo que tienen cerca (ver lighting.rs)
+    fn emissive(&self) -> bool {
+        false
+    }
+}
+
+impl<F: Fn(&Fragment, &Uniforms) -> Color + Send + Sync> Shader for F {
+    fn fragment(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        self(fragment, uniforms)
+    }
+}
+
+// Shader de fragmentos que además emite luz
+pub struct Emissive<F>(pub F);
+
+impl<F: Fn(&Fragment, &Uniforms) -> Color + Send + Sync> Shader for Emissive<F> {
+    fn fragment(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        (self.0)(fragment, uniforms)
+    }
+
+    fn emissive(&self) -> bool {
+        true
+    }
+}
+
+// Shaders por los que pasa la barra espaciadora, en orden
+const CYCLE: [&str; 6] = ["cellular", "dalmata", "cloud", "lava", "black_and_white", "moon"];
+
+// Shaders por nombre y el que usa cada objeto. Un cuerpo puede fijar el suyo con
+// `shader = "<nombre>"` en su sección [bodies.<nombre>] del archivo de escena; los
+// que se dibujan con el shader genérico y no fijan uno usan el activo del ciclo.
+pub struct ShaderRegistry {
+    shaders: HashMap<&'static str, Arc<dyn Shader>>,
+    assignments: HashMap<String, &'static str>,
+    active: usize,
+}
+
+impl Default for ShaderRegistry {
+    fn default() -> Self {
+        let mut registry = ShaderRegistry { shaders: HashMap::new(), assignments: HashMap::new(), active: 0 };
+        registry.register("cellular", Arc::new(shaders::cellular_shader));
+        registry.register("dalmata", Arc::new(shaders::dalmata_shader));
+        registry.register("cloud", Arc::new(shaders::cloud_shader));
+        registry.register("lava", Arc::new(Emissive(shaders::lava_shader)));
+        registry.register("black_and_white", Arc::new(shaders::black_and_white));
+        registry.register("moon", Arc::new(shaders::moon_shader));
+        registry.register("sun", Arc::new(Emissive(shaders::fragment_shader2)));
+        registry.register("venus", Arc::new(shaders::venus_shader));
+        registry.register("earth", Arc::new(shaders::earth_shader));
+        registry.register("mars", Arc::new(shaders::mars_shader));
+        registry.register("jupiter", Arc::new(shaders::jupiter_shader));
+        registry.register("saturn", Arc::new(shaders::saturn_shader));
+        registry.register("uranus", Arc::new(shaders::uranus_shader));
+        registry.register("neptune", Arc::new(shaders::neptune_shader));
+        registry
+    }
+}
+
+impl ShaderRegistry {
+    pub fn load(path: &str) -> Self {
+        let mut registry = ShaderRegistry::default();
+        let Ok(text) = fs::read_to_string(path) else {
+            return registry;
+        };
+        if let Err(err) = registry.parse_assignments(&text) {
+            eprintln!("Shaders en {}: {}", path, err);
+        }
+        registry
+    }
+
+    fn parse_assignments(&mut self, text: &str) -> Result<(), String> {
+        let document: toml::Table = text.parse().map_err(|e: toml::de::Error| e.to_string())?;
+        let Some(bodies) = document.get("bodies").and_then(toml::Value::as_table) else {
+            return Ok(());
+        };
+        for (body, entry) in bodies {
+            let Some(value) = entry.get("shader") else {
+                continue;
+            };
+            let name = value.as_str().ok_or_else(|| format!("bodies.{}.shader debe ser un texto", body))?;
+            let name = self.key(name).ok_or_else(|| format!("bodies.{}.shader: no hay un shader \"{}\"", body, name))?;
+            self.assignments.insert(body.clone(), name);
+        }
+        Ok(())
+    }
+
+    pub fn register(&mut self, name: &'static str, shader: Arc<dyn Shader>) {
+        self.shaders.insert(name, shader);
+    }
+
+    fn key(&self, name: &str) -> Option<&'static str> {
+        self.shaders.keys().copied().find(|key| *key == name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn Shader>> {
+        self.shaders.get(name).cloned()
+    }
+
+    pub fn assign(&mut self, body: &str, name: &str) -> Result<(), String> {
+        let name = self.key(name).ok_or_else(|| format!("no hay un shader \"{}\"", name))?;
+        self.assignments.insert(body.to_string(), name);
+        Ok(())
+    }
+
+    // Nombre del shader del cuerpo: el que fijó o el activo del ciclo
+    pub fn name_for(&self, body: &str) -> &'static str {
+        self.assignments.get(body).copied().unwrap_or(CYCLE[self.active])
+    }
+
+    pub fn for_body(&self, body: &str) -> Arc<dyn Shader> {
+        let name = self.name_for(body);
+        self.get(name).unwrap_or_else(|| panic!("shader \"{}\" sin registrar", name))
+    }
+
+    // Pasa al siguiente shader del ciclo y devuelve su nombre
+    pub fn cycle(&mut self) -> &'static str {
+        self.active = (self.active + 1) % CYCLE.len();
+        CYCLE[self.active]
+    }
+}
@@ -1,16 +1,23 @@
 
-use nalgebra_glm::{Vec3, Vec4, Mat3, dot, mat4_to_mat3};
+use nalgebra_glm::{Vec3, Vec4, Mat3, mat4_to_mat3};
 use crate::vertex::Vertex;
 use crate::Uniforms;
 use crate::fragment::Fragment;
-use crate::color::Color;
-use std::f32::consts::PI;
+use crate::color::{Color, palette};
+use crate::visualization;
+use crate::lighting;
+use crate::material::DebugView;
+use crate::tangents::TangentFrame;
+use crate::framebuffer::Framebuffer;
+use crate::stats;
+#[cfg(feature = "deferred")]
+use crate::deferred::Surface;
 use rand::Rng;
 use rand::SeedableRng;
 use rand::rngs::StdRng;
 
 
-pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
+pub fn vertex_shader<V: Clone>(vertex: &Vertex<V>, uniforms: &Uniforms) -> Vertex<V> {
     let position = Vec4::new(
         vertex.position.x,
         vertex.position.y,
@@ -18,7 +25,8 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
         1.0
     );
 
-    let transformed = uniforms.projection_matrix * uniforms.view_matrix * uniforms.model_matrix * position;
+    let world_position = uniforms.model_matrix * position;
+    let transformed = uniforms.projection_matrix * uniforms.view_matrix * world_position;
 
     let w = transformed.w;
     let transformed_position = Vec4::new(
@@ -41,107 +49,214 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
         tex_coords: vertex.tex_coords,
         color: vertex.color,
         transformed_position: Vec3::new(screen_position.x, screen_position.y, screen_position.z),
-        transformed_normal: transformed_normal
+        transformed_normal,
+        world_position: Vec3::new(world_position.x, world_position.y, world_position.z),
+        clip_position: transformed,
+        varyings: vertex.varyings.clone(),
     }
 }
 
-pub static mut SHADER_INDEX: u8 = 0;
+// `vertex_shader` que además lleva la base tangente al espacio del mundo
+pub fn tangent_vertex_shader(vertex: &Vertex<TangentFrame>, uniforms: &Uniforms) -> Vertex<TangentFrame> {
+    let mut transformed = vertex_shader(vertex, uniforms);
+    transformed.varyings = vertex.varyings.transformed(&mat4_to_mat3(&uniforms.model_matrix));
+    transformed
+}
 
-pub fn fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-  unsafe {
-    match SHADER_INDEX {
-        5 => black_and_white(fragment, uniforms),
-        1 => dalmata_shader(fragment, uniforms),
-        2 => cloud_shader(fragment, uniforms),
-        3 => cellular_shader(fragment, uniforms),
-        4 => lava_shader(fragment, uniforms),
-        6 => moon_shader(fragment, uniforms), 
-        _ => cellular_shader(fragment, uniforms), // Default
+// Etapa común a todos los shaders de fragmento: el color falso reemplaza al
+// shader natural, el material modula ese albedo y la luz del Sol se aplica al
+// final (salvo materiales sin iluminación), como en el pase diferido. Devuelve
+// el color en lineal y sin tope, para el buffer HDR.
+pub fn shade<V>(fragment: &Fragment<V>, uniforms: &Uniforms, shader: impl Fn(&Fragment<V>, &Uniforms) -> Color) -> [f32; 3] {
+    match uniforms.material.debug_view {
+        DebugView::Off => {}
+        DebugView::Light if uniforms.material.unlit => return Color::WHITE.to_linear(),
+        DebugView::Light => return illuminate(Color::WHITE, fragment, uniforms),
+        view => return debug_channel(view, fragment, uniforms, shader).to_linear(),
+    }
+    let material = &uniforms.material;
+    match uniforms.false_color {
+        Some(color) => material.apply(visualization::false_color_shader(fragment, color), fragment).to_linear(),
+        None if material.unlit => material.apply(shader(fragment, uniforms), fragment).to_linear(),
+        None => illuminate(material.apply(shader(fragment, uniforms), fragment), fragment, uniforms),
+    }
+}
+
+// Luces de la escena y luces puntuales sobre `albedo`, en lineal
+fn illuminate<V>(albedo: Color, fragment: &Fragment<V>, uniforms: &Uniforms) -> [f32; 3] {
+    let view_dir = (uniforms.camera_position - fragment.world_position).normalize();
+    let lit = lighting::shade(
+        &uniforms.lights,
+        fragment.sunlit,
+        albedo,
+        &fragment.world_position,
+        &fragment.normal,
+        &view_dir,
+        &uniforms.material,
+    );
+    if uniforms.point_lights.is_empty() {
+        return lit;
+    }
+    let local = lighting::point_lighting(
+        &uniforms.point_lights,
+        &fragment.world_position,
+        &fragment.normal,
+        &view_dir,
+        uniforms.material.two_sided,
+    );
+    let local = albedo.blend_multiply(&local).to_linear();
+    [0, 1, 2].map(|channel| lit[channel] + local[channel])
+}
+
+// Canales del inspector de materiales que no dependen de la luz (ver DebugView)
+pub fn debug_channel<V>(view: DebugView, fragment: &Fragment<V>, uniforms: &Uniforms, shader: impl Fn(&Fragment<V>, &Uniforms) -> Color) -> Color {
+    match view {
+        DebugView::Normals => {
+            let n = fragment.normal.normalize() * 0.5 + Vec3::new(0.5, 0.5, 0.5);
+            Color::from_f32(n.x, n.y, n.z)
+        }
+        DebugView::Depth => {
+            let distance = (uniforms.camera_position - fragment.world_position).magnitude();
+            let value = 1.0 - (1.0 + distance).ln() / (1.0 + DEBUG_DEPTH_RANGE).ln();
+            Color::from_f32(value, value, value)
+        }
+        DebugView::Noise => {
+            let p = fragment.vertex_position * DEBUG_NOISE_ZOOM * uniforms.material.params.noise_scale;
+            let value = uniforms.noise.get_noise_3d(p.x, p.y, p.z) * 0.5 + 0.5;
+            Color::from_f32(value, value, value)
+        }
+        DebugView::Uvs => {
+            let p = fragment.vertex_position.normalize();
+            let u = p.z.atan2(p.x) / std::f32::consts::TAU + 0.5;
+            let v = p.y.clamp(-1.0, 1.0).asin() / std::f32::consts::PI + 0.5;
+            Color::from_f32(u, v, 0.0)
+        }
+        DebugView::Off | DebugView::Albedo | DebugView::Light => uniforms.material.apply(shader(fragment, uniforms), fragment),
+    }
+}
+
+// Distancia que llega a negro en la vista de profundidad y zoom del ruido sobre el
+// modelo de radio 1 en la vista de ruido
+const DEBUG_DEPTH_RANGE: f32 = 200.0;
+const DEBUG_NOISE_ZOOM: f32 = 4.0;
+
+// Escribe un fragmento rasterizado: sombreado completo en el camino forward o, con
+// el G-buffer activo, la superficie sin iluminar para el pase de luces
+pub fn write_fragment<V>(framebuffer: &mut Framebuffer, fragment: &Fragment<V>, uniforms: &Uniforms, shader: impl Fn(&Fragment<V>, &Uniforms) -> Color) {
+    let x = fragment.position.x as usize;
+    let y = fragment.position.y as usize;
+
+    if !framebuffer.shades(x, y) {
+        stats::add_skipped_fragment();
+        return;
+    }
+
+    #[cfg(feature = "deferred")]
+    if framebuffer.deferred_active() {
+        framebuffer.surface_point(x, y, fragment.depth, Surface::shade(fragment, uniforms, shader));
+        return;
+    }
+
+    framebuffer.point_linear(x, y, fragment.depth, shade(fragment, uniforms, shader));
+}
+
+// Shader de expresiones del cuerpo (script.rs); magenta si falta, para que se note
+pub fn script_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    match &uniforms.script {
+        Some(script) => script.shade(fragment, uniforms),
+        None => Color::new(255, 0, 255),
+    }
+}
+
+// Shader de un plug-in dinámico (ver plugin.rs); magenta si el cuerpo no tiene uno
+pub fn plugin_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    match &uniforms.plugin {
+        Some(plugin) => plugin.shade(fragment, uniforms),
+        None => Color::new(255, 0, 255),
+    }
+}
+
+// Mapa equirectangular del cuerpo (ver texture.rs), leído en las coordenadas de
+// textura del mesh; magenta si el cuerpo no tiene uno
+pub fn textured_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    match &uniforms.texture {
+        Some(texture) => texture.sample(fragment.tex_coords),
+        None => Color::new(255, 0, 255),
     }
-  }
 }
+
 pub fn fragment_shader2(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    return emissive_shader(fragment, uniforms);  
+    emissive_shader(fragment, uniforms)
 }
 
 fn emissive_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
   let intensity = fragment.intensity;
 
   // Color base del material, un amarillo cálido
-  let base_color = Color::new(255, 223, 0); // Amarillo cálido
+  let [base_color, emission_color] = uniforms.palette.colors("sun", [palette::SUN_CORE, palette::SUN_EMISSION]);
 
   // Color de emisión aún más brillante, amarillo intenso
-  let emission_color = Color::new(255, 255, 102) * (intensity * 2.0); // Amarillo más claro y brillante
+  let emission_color = emission_color.scale_linear(intensity * 2.0);
 
   // Mezclamos el color base y el color de emisión
-  let final_color = base_color.lerp(&emission_color, 0.9); // Mayor peso del color de emisión
-
-  final_color
+  base_color.lerp(&emission_color, 0.9) // Mayor peso del color de emisión
 }
 
 pub fn earth_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-  let x = fragment.vertex_position.x;
   let y = fragment.vertex_position.y;
 
   // Colores representativos de la Tierra
-  let color_ocean = Color::new(0, 0, 255);  // Azul océano
-  let color_land = Color::new(34, 139, 34); // Verde tierra
-  let color_cloud = Color::new(255, 255, 255); // Blanco para nubes
+  let [color_ocean, color_land, color_cloud] = uniforms.palette.colors(
+      "earth",
+      [palette::EARTH_OCEAN, palette::EARTH_LAND, palette::EARTH_CLOUD],
+  );
 
-  let time = uniforms.time as f32 * 0.01; // Control de velocidad para animación
-  let band_pattern = ((y * 10.0 + time).sin() * 0.5 + 0.5).clamp(0.0, 1.0);
+  let time = uniforms.time * 0.01; // Control de velocidad para animación
+  let band_pattern = ((y * 10.0 * uniforms.material.params.band_frequency + time).sin() * 0.5 + 0.5).clamp(0.0, 1.0);
 
   // Decidimos el color dependiendo de la coordenada y para simular el océano y la tierra
-  let base_color = if band_pattern < 0.4 {
+  if band_pattern < 0.4 {
       color_ocean
   } else if band_pattern < 0.7 {
       color_land
   } else {
       color_cloud
-  };
-
-  base_color
+  }
 }
 
 pub fn uranus_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-  let x = fragment.vertex_position.x;
   let y = fragment.vertex_position.y;
 
   // Colores representativos de Urano
-  let color_uranus_base = Color::new(0, 255, 255); // Azul verdoso
-  let color_uranus_dark = Color::new(0, 128, 128); // Azul más oscuro para sombras
+  let [color_uranus_base, color_uranus_dark] =
+      uniforms.palette.colors("uranus", [palette::URANUS_BASE, palette::URANUS_DARK]);
 
-  let time = uniforms.time as f32 * 0.02; // Control de velocidad
-  let band_pattern = ((y * 10.0 + time).sin() * 0.5 + 0.5).clamp(0.0, 1.0);
+  let time = uniforms.time * 0.02; // Control de velocidad
+  let band_pattern = ((y * 10.0 * uniforms.material.params.band_frequency + time).sin() * 0.5 + 0.5).clamp(0.0, 1.0);
 
   // Base color para las bandas en Urano
-  let base_color = if band_pattern < 0.5 {
+  if band_pattern < 0.5 {
       color_uranus_base
   } else {
       color_uranus_dark
-  };
-
-  base_color
+  }
 }
 pub fn neptune_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-  let x = fragment.vertex_position.x;
   let y = fragment.vertex_position.y;
 
   // Colores representativos de Neptuno
-  let color_neptune_base = Color::new(0, 0, 255); // Azul profundo
-  let color_neptune_dark = Color::new(0, 0, 139); // Azul oscuro
+  let [color_neptune_base, color_neptune_dark] =
+      uniforms.palette.colors("neptune", [palette::NEPTUNE_BASE, palette::NEPTUNE_DARK]);
 
-  let time = uniforms.time as f32 * 0.02; // Control de velocidad
-  let band_pattern = ((y * 10.0 + time).sin() * 0.5 + 0.5).clamp(0.0, 1.0);
+  let time = uniforms.time * 0.02; // Control de velocidad
+  let band_pattern = ((y * 10.0 * uniforms.material.params.band_frequency + time).sin() * 0.5 + 0.5).clamp(0.0, 1.0);
 
   // Base color para las bandas en Neptuno
-  let base_color = if band_pattern < 0.5 {
+  if band_pattern < 0.5 {
       color_neptune_base
   } else {
       color_neptune_dark
-  };
-
-  base_color
+  }
 }
 
 
@@ -151,35 +266,33 @@ pub fn venus_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
   let y = fragment.vertex_position.y;
 
   // Colores base para simular las nubes densas de Venus
-  let color_soft_yellow = Color::new(255, 228, 181); // Amarillo suave
-  let color_light_gray = Color::new(220, 220, 220);  // Gris claro
-  let color_white = Color::new(255, 250, 240);       // Blanco suave
+  let [color_soft_yellow, color_light_gray, color_white] = uniforms.palette.colors(
+      "venus",
+      [palette::VENUS_HAZE, palette::VENUS_CLOUD, palette::VENUS_HIGHLIGHT],
+  );
 
   // Crear un patrón de franjas onduladas con valores de onda ajustados
-  let time = uniforms.time as f32 * 0.01; // Control de velocidad para movimiento sutil
-  let wave_pattern_x = ((x * 3.0 + time).sin() * 0.5 + 0.5).clamp(0.0, 1.0);
-  let wave_pattern_y = ((y * 3.0 + time).cos() * 0.5 + 0.5).clamp(0.0, 1.0);
+  let time = uniforms.time * 0.01; // Control de velocidad para movimiento sutil
+  let wave_pattern_x = ((x * 3.0 * uniforms.material.params.band_frequency + time).sin() * 0.5 + 0.5).clamp(0.0, 1.0);
+  let wave_pattern_y = ((y * 3.0 * uniforms.material.params.band_frequency + time).cos() * 0.5 + 0.5).clamp(0.0, 1.0);
 
   // Mezcla de colores para simular las capas de nubes con ondas
-  let base_color = color_soft_yellow.lerp(&color_light_gray, wave_pattern_x);
-  let final_color = base_color.lerp(&color_white, wave_pattern_y);
-
-  final_color
+  let base_color = color_soft_yellow.lerp_linear(&color_light_gray, wave_pattern_x);
+  base_color.lerp_linear(&color_white, wave_pattern_y)
 }
 
 pub fn jupiter_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
   let x = fragment.vertex_position.x;
   let y = fragment.vertex_position.y;
 
-  
-  let color_light_brown = Color::new(210, 180, 140); 
-  let color_dark_brown = Color::new(139, 69, 19);    
-  let color_white = Color::new(245, 245, 245);       
-  let color_red_spot = Color::new(255, 69, 0);       
+  let [color_light_brown, color_dark_brown, color_white, color_red_spot] = uniforms.palette.colors(
+      "jupiter",
+      [palette::JUPITER_LIGHT, palette::JUPITER_DARK, palette::JUPITER_WHITE, palette::JUPITER_SPOT],
+  );
 
 
-  let time = uniforms.time as f32 * 0.02; // Control de velocidad
-  let band_pattern = ((y * 10.0 + time).sin() * 0.5 + 0.5).clamp(0.0, 1.0);
+  let time = uniforms.time * 0.02; // Control de velocidad
+  let band_pattern = ((y * 10.0 * uniforms.material.params.band_frequency + time).sin() * 0.5 + 0.5).clamp(0.0, 1.0);
 
  
   let base_color = if band_pattern < 0.3 {
@@ -194,26 +307,24 @@ pub fn jupiter_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
   let red_spot_y = (y + 0.2).powi(2) / 0.2;
   let red_spot_intensity = 1.0 - (red_spot_x + red_spot_y).clamp(0.0, 1.0);
 
-  let final_color = if red_spot_intensity > 0.7 {
-      color_red_spot.lerp(&base_color, red_spot_intensity)
+  if red_spot_intensity > 0.7 {
+      color_red_spot.lerp_linear(&base_color, red_spot_intensity)
   } else {
       base_color
-  };
-
-  final_color
+  }
 }
 
 pub fn saturn_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-  let x = fragment.vertex_position.x;
   let y = fragment.vertex_position.y;
 
-  let color_light_brown = Color::new(205, 133, 63); 
-  let color_dark_brown = Color::new(139, 69, 19);
-  let color_ring = Color::new(160, 160, 160); // Anillos
-  let color_white = Color::new(245, 245, 245);
+  // Claro, oscuro, blanco y anillos
+  let [color_light_brown, color_dark_brown, color_white, color_ring] = uniforms.palette.colors(
+      "saturn",
+      [palette::SATURN_LIGHT, palette::SATURN_DARK, palette::SATURN_WHITE, palette::SATURN_RING],
+  );
 
-  let time = uniforms.time as f32 * 0.02; // Control de velocidad
-  let band_pattern = ((y * 10.0 + time).sin() * 0.5 + 0.5).clamp(0.0, 1.0);
+  let time = uniforms.time * 0.02; // Control de velocidad
+  let band_pattern = ((y * 10.0 * uniforms.material.params.band_frequency + time).sin() * 0.5 + 0.5).clamp(0.0, 1.0);
 
   // Base color para las bandas
   let base_color = if band_pattern < 0.3 {
@@ -226,25 +337,143 @@ pub fn saturn_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
 
   // Añadir anillos alrededor de Saturno
   let ring_pattern = (y.abs() - 0.5).clamp(0.0, 1.0);
-  let final_color = if ring_pattern > 0.3 {
+  if ring_pattern > 0.3 {
       color_ring
   } else {
       base_color
+  }
+}
+
+// Radios del mesh de los anillos, en radios del planeta
+pub const RING_INNER: f32 = 1.25;
+pub const RING_OUTER: f32 = 2.3;
+
+// Anillos de Urano, en radios del planeta
+pub const URANUS_RING_INNER: f32 = 1.6;
+pub const URANUS_RING_OUTER: f32 = 2.05;
+// Centro (entre 0 y 1) y medio ancho de cada anillo de Urano, del 6 al ε (el más
+// ancho, en el borde exterior)
+const URANUS_RINGLETS: [(f32, f32); 9] = [
+  (0.02, 0.008), (0.07, 0.008), (0.11, 0.008), (0.33, 0.01), (0.45, 0.01),
+  (0.55, 0.006), (0.66, 0.008), (0.74, 0.01), (0.96, 0.03),
+];
+
+// Posición radial en un anillo de radios `inner` y `outer`: 0 en el borde interior,
+// 1 en el exterior
+fn ring_position(fragment: &Fragment, inner: f32, outer: f32) -> f32 {
+  let p = fragment.vertex_position;
+  ((p.x.hypot(p.z) - inner) / (outer - inner)).clamp(0.0, 1.0)
+}
+
+pub fn ring_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+  let [color_ring, color_light] =
+      uniforms.palette.colors("saturn_ring", [palette::SATURN_RING, palette::SATURN_LIGHT]);
+  let t = ring_position(fragment, RING_INNER, RING_OUTER);
+  let bands = ((t * 40.0 * uniforms.material.params.band_frequency).sin() * 0.5 + 0.5) * 0.6;
+  color_ring.lerp_linear(&color_light, bands)
+}
+
+// Opacidad de los anillos: el anillo C interior es tenue, el B denso y la
+// división de Cassini casi vacía
+pub fn ring_opacity(fragment: &Fragment, uniforms: &Uniforms) -> f32 {
+  let t = ring_position(fragment, RING_INNER, RING_OUTER);
+  let fine = (t * 90.0 * uniforms.material.params.band_frequency).sin() * 0.1;
+  let base = if t < 0.18 {
+      0.25
+  } else if t < 0.58 {
+      0.85
+  } else if t < 0.66 {
+      0.05
+  } else {
+      0.6
   };
+  (base + fine).clamp(0.0, 1.0)
+}
 
-  final_color
+// Densidad de los anillos angostos de Urano: 1 en el centro de cada uno, 0 entre ellos
+fn uranus_ringlets(t: f32) -> f32 {
+  URANUS_RINGLETS
+      .iter()
+      .map(|(center, half_width)| (1.0 - (t - center).abs() / half_width).max(0.0))
+      .fold(0.0, f32::max)
+}
+
+// Anillos de Urano: angostos y oscuros, más claros en el centro de cada uno
+pub fn uranus_ring_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+  let [color_ring, color_light] =
+      uniforms.palette.colors("uranus_ring", [palette::URANUS_RING, palette::URANUS_RING_LIGHT]);
+  let t = ring_position(fragment, URANUS_RING_INNER, URANUS_RING_OUTER);
+  color_ring.lerp_linear(&color_light, uranus_ringlets(t))
+}
+
+// Casi vacíos entre los anillos, con un polvo muy tenue
+pub fn uranus_ring_opacity(fragment: &Fragment, _uniforms: &Uniforms) -> f32 {
+  let t = ring_position(fragment, URANUS_RING_INNER, URANUS_RING_OUTER);
+  (0.03 + 0.8 * uranus_ringlets(t)).clamp(0.0, 1.0)
+}
+
+// Capa de atmósfera: color plano (el tono se ajusta con hue_shift del material)
+pub fn atmosphere_shader(_fragment: &Fragment, uniforms: &Uniforms) -> Color {
+  let [tint] = uniforms.palette.colors("atmosphere", [Color::new(110, 170, 255)]);
+  tint
+}
+
+// Más opaca en el borde (más aire en la línea de visión) y del lado de día; la
+// cara trasera de la capa no se ve
+pub fn atmosphere_opacity(fragment: &Fragment, uniforms: &Uniforms) -> f32 {
+  let view_dir = (uniforms.camera_position - fragment.world_position).normalize();
+  let n_dot_v = fragment.normal.dot(&view_dir);
+  if n_dot_v <= 0.0 {
+      return 0.0;
+  }
+  let sunlit = uniforms.lights.first().map_or(0.0, |light| fragment.normal.dot(&light.direction(&fragment.world_position)).max(0.0));
+  let day = 0.1 + 0.9 * sunlit;
+  let rim = (1.0 - n_dot_v).powi(3);
+  ((0.05 + 0.75 * rim) * day).clamp(0.0, 1.0)
+}
+
+// Resplandor de los motores de las naves, del color de su luz puntual
+pub fn engine_glow_shader(_fragment: &Fragment, uniforms: &Uniforms) -> Color {
+  let [glow] = uniforms.palette.colors("engine", [Color::new(150, 190, 255)]);
+  glow
+}
+
+// Halo que se apaga hacia el contorno de la esfera, así no se le ve el borde
+pub fn engine_glow_opacity(fragment: &Fragment, uniforms: &Uniforms) -> f32 {
+  let view_dir = (uniforms.camera_position - fragment.world_position).normalize();
+  let n_dot_v = fragment.normal.dot(&view_dir).max(0.0);
+  0.45 * n_dot_v * n_dot_v
+}
+
+// Fantasmas del cuerpo seleccionado: ámbar los del pasado, celeste los del futuro
+pub fn ghost_past_shader(_fragment: &Fragment, _uniforms: &Uniforms) -> Color {
+  Color::new(255, 170, 80)
+}
+
+pub fn ghost_future_shader(_fragment: &Fragment, _uniforms: &Uniforms) -> Color {
+  Color::new(110, 200, 255)
+}
+
+// Tenues en el centro y más marcados en el contorno, para que se lean como siluetas
+pub fn ghost_opacity(fragment: &Fragment, uniforms: &Uniforms) -> f32 {
+  let view_dir = (uniforms.camera_position - fragment.world_position).normalize();
+  let n_dot_v = fragment.normal.dot(&view_dir);
+  if n_dot_v <= 0.0 {
+      return 0.0;
+  }
+  0.12 + 0.4 * (1.0 - n_dot_v).powi(2)
 }
 
 pub fn mars_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
   let x = fragment.vertex_position.x;
   let y = fragment.vertex_position.y;
 
-  let color_red = Color::new(204, 102, 51);   // Rojo-rojizo
-  let color_dark_red = Color::new(139, 69, 19); // Rojo oscuro
-  let color_rocky = Color::new(160, 82, 45);   // Superficie rocosa
+  // Rojo, rojo oscuro y superficie rocosa
+  let [color_red, color_dark_red, color_rocky] =
+      uniforms.palette.colors("mars", [palette::MARS_RED, palette::MARS_DARK, palette::MARS_ROCK]);
 
-  let time = uniforms.time as f32 * 0.05; // Control de velocidad
-  let band_pattern = ((y * 10.0 + time).sin() * 0.5 + 0.5).clamp(0.0, 1.0);
+  let time = uniforms.time * 0.05; // Control de velocidad
+  let band_pattern = ((y * 10.0 * uniforms.material.params.band_frequency + time).sin() * 0.5 + 0.5).clamp(0.0, 1.0);
 
   // Base color para las bandas
   let base_color = if band_pattern < 0.5 {
@@ -254,36 +483,26 @@ pub fn mars_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
   };
 
   // Superficie rocosa (más texturizada)
-  let rocky_pattern = ((x * y + time).sin() * 0.5 + 0.5).clamp(0.0, 1.0);
-  let final_color = if rocky_pattern > 0.7 {
+  let rocky_pattern = ((x * y * uniforms.material.params.band_frequency + time).sin() * 0.5 + 0.5).clamp(0.0, 1.0);
+  if rocky_pattern > 0.7 {
       color_rocky
   } else {
       base_color
-  };
-
-  final_color
-}
-
-
-// Función para cambiar el índice del shader activo
-pub fn switch_shader() {
-  unsafe {
-      SHADER_INDEX = (SHADER_INDEX + 1) % 7; 
   }
 }
 
-fn moon_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-  let zoom = 50.0; // Escala para definir detalles en la superficie
+pub fn moon_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+  let zoom = 50.0 * uniforms.material.params.noise_scale; // Escala para definir detalles en la superficie
   let x = fragment.vertex_position.x;
   let y = fragment.vertex_position.y;
-  let t = uniforms.time as f32 * 0.1; // Tiempo para simular ligera rotación
+  let t = uniforms.time * 0.1; // Tiempo para simular ligera rotación
 
   // Valor de ruido para la superficie de la luna
   let surface_noise = uniforms.noise.get_noise_2d(x * zoom + t, y * zoom + t);
 
   // Colores base para la luna
-  let gray_color = Color::new(200, 200, 200);  // Color gris para la luna
-  let crater_color = Color::new(150, 150, 150); // Color más oscuro para las áreas de cráteres
+  // Gris para la luna y uno más oscuro para las áreas de cráteres
+  let [gray_color, crater_color] = uniforms.palette.colors("moon", [palette::MOON_GRAY, palette::MOON_CRATER]);
 
   // Umbral para simular cráteres
   let crater_threshold = 0.4;
@@ -295,55 +514,26 @@ fn moon_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
       crater_color // Área más oscura (cráter)
   };
 
-  // Ajustar la intensidad del color final para simular la iluminación
-  base_color * fragment.intensity
-}
-
-fn sun_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-  let x = fragment.vertex_position.x;
-  let y = fragment.vertex_position.y;
-  let t = uniforms.time as f32 * 0.5; // Tiempo para simular movimiento dinámico del brillo
-
-  // Gradiente central hacia los bordes
-  let distance_from_center = (x * x + y * y).sqrt();
-  let glow_intensity = (1.0 - distance_from_center).clamp(0.0, 1.0); // Más brillante en el centro
-
-  // Color base del Sol (amarillo brillante)
-  let core_color = Color::new(255, 223, 0); // Amarillo intenso
-  let glow_color = Color::new(255, 140, 0); // Amarillo-naranja para el borde
-
-  // Simulación de destellos dinámicos usando ruido
-  let noise_value = uniforms.noise.get_noise_2d(x * 100.0 + t, y * 100.0 + t);
-  let flicker = (noise_value * 0.5 + 0.5).clamp(0.5, 1.0); // Ruido suavizado para destellos
-
-  // Color final mezclando gradiente y destellos
-  let base_color = core_color.lerp(&glow_color, 1.0 - glow_intensity); // Gradiente centro-borde
-  let final_color = base_color * (glow_intensity * flicker);
-
-  final_color
+  // La iluminación la aplica `shade`
+  base_color
 }
 
-
-
-
-fn black_and_white(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let seed = uniforms.time as f32 * fragment.vertex_position.y * fragment.vertex_position.x;
+pub fn black_and_white(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    let seed = uniforms.time * fragment.vertex_position.y * fragment.vertex_position.x;
   
     let mut rng = StdRng::seed_from_u64(seed.abs() as u64);
   
     let random_number = rng.gen_range(0..=100);
   
-    let black_or_white = if random_number < 50 {
-      Color::new(0, 0, 0)
+    if random_number < 50 {
+      Color::BLACK
     } else {
-      Color::new(255, 255, 255)
-    };
-  
-    black_or_white * fragment.intensity
+      Color::WHITE
+    }
 }
   
-fn dalmata_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let zoom = 100.0;
+pub fn dalmata_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    let zoom = 100.0 * uniforms.material.params.noise_scale;
     let ox = 0.0;
     let oy = 0.0;
     let x = fragment.vertex_position.x;
@@ -355,45 +545,41 @@ fn dalmata_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     );
   
     let spot_threshold = 0.5;
-    let spot_color = Color::new(255, 255, 255); // White
-    let base_color = Color::new(0, 0, 0); // Black
+    let spot_color = Color::WHITE;
+    let base_color = Color::BLACK;
   
-    let noise_color = if noise_value < spot_threshold {
+    if noise_value < spot_threshold {
       spot_color
     } else {
       base_color
-    };
-  
-    noise_color * fragment.intensity
+    }
 }
   
-fn cloud_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let zoom = 100.0;  // to move our values 
+pub fn cloud_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    let zoom = 100.0 * uniforms.material.params.noise_scale;  // to move our values 
     let ox = 100.0; // offset x in the noise map
     let oy = 100.0;
     let x = fragment.vertex_position.x;
     let y = fragment.vertex_position.y;
-    let t = uniforms.time as f32 * 0.5;
+    let t = uniforms.time * 0.5;
   
     let noise_value = uniforms.noise.get_noise_2d(x * zoom + ox + t, y * zoom + oy);
   
     // Define cloud threshold and colors
     let cloud_threshold = 0.5; // Adjust this value to change cloud density
-    let cloud_color = Color::new(255, 255, 255); // White for clouds
+    let cloud_color = Color::WHITE; // White for clouds
     let sky_color = Color::new(30, 97, 145); // Sky blue
   
     // Determine if the pixel is part of a cloud or sky
-    let noise_color = if noise_value > cloud_threshold {
+    if noise_value > cloud_threshold {
       cloud_color
     } else {
       sky_color
-    };
-  
-    noise_color * fragment.intensity
+    }
 }
   
-fn cellular_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let zoom = 30.0;  // Zoom factor to adjust the scale of the cell pattern
+pub fn cellular_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    let zoom = 30.0 * uniforms.material.params.noise_scale;  // Zoom factor to adjust the scale of the cell pattern
     let ox = 50.0;    // Offset x in the noise map
     let oy = 50.0;    // Offset y in the noise map
     let x = fragment.vertex_position.x;
@@ -409,7 +595,7 @@ fn cellular_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let cell_color_4 = Color::new(173, 255, 47);  // Yellow green
   
     // Use the noise value to assign a different color to each cell
-    let final_color = if cell_noise_value < 0.15 {
+    if cell_noise_value < 0.15 {
       cell_color_1
     } else if cell_noise_value < 0.7 {
       cell_color_2
@@ -417,16 +603,13 @@ fn cellular_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
       cell_color_3
     } else {
       cell_color_4
-    };
-  
-    // Adjust intensity to simulate lighting effects (optional)
-    final_color * fragment.intensity
+    }
 }
   
-fn lava_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+pub fn lava_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     // Base colors for the lava effect
-    let bright_color = Color::new(255, 240, 0); // Bright orange (lava-like)
-    let dark_color = Color::new(130, 20, 0);   // Darker red-orange
+    // Bright orange (lava-like) and darker red-orange
+    let [bright_color, dark_color] = uniforms.palette.colors("lava", [palette::LAVA_BRIGHT, palette::LAVA_DARK]);
   
     // Get fragment position
     let position = Vec3::new(
@@ -438,13 +621,13 @@ fn lava_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     // Base frequency and amplitude for the pulsating effect
     let base_frequency = 0.2;
     let pulsate_amplitude = 0.5;
-    let t = uniforms.time as f32 * 0.01;
+    let t = uniforms.time * 0.01;
   
     // Pulsate on the z-axis to change spot size
     let pulsate = (t * base_frequency).sin() * pulsate_amplitude;
   
     // Apply noise to coordinates with subtle pulsating on z-axis
-    let zoom = 1000.0; // Constant zoom factor
+    let zoom = 1000.0 * uniforms.material.params.noise_scale; // Zoom factor
     let noise_value1 = uniforms.noise.get_noise_3d(
       position.x * zoom,
       position.y * zoom,
@@ -458,7 +641,5 @@ fn lava_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let noise_value = (noise_value1 + noise_value2) * 0.5;  // Averaging noise for smoother transitions
   
     // Use lerp for color blending based on noise value
-    let color = dark_color.lerp(&bright_color, noise_value);
-  
-    color * fragment.intensity
+    dark_color.lerp_linear(&bright_color, noise_value)
 }
\ No newline at end of file
@@ -0,0 +1,151 @@
+use nalgebra_glm::{Mat4, Vec3, Vec4};
+use crate::memory::{vec_bytes, MemoryUsage};
+use crate::vertex::Vertex;
+
+// Lado de cada cara del mapa de sombras, en texels
+pub const SHADOW_MAP_SIZE: usize = 512;
+// Margen de profundidad, en texels del punto consultado, para que una superficie no
+// se sombree a sí misma (acné)
+const DEPTH_BIAS: f32 = 2.0;
+
+// Caras del cubo alrededor de la luz: hacia dónde mira cada una y los ejes de su imagen
+const FACES: [(Vec3, Vec3, Vec3); 6] = [
+    (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 1.0, 0.0)),
+    (Vec3::new(-1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 1.0, 0.0)),
+    (Vec3::new(0.0, 1.0, 0.0), Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0)),
+    (Vec3::new(0.0, -1.0, 0.0), Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0)),
+    (Vec3::new(0.0, 0.0, 1.0), Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0)),
+    (Vec3::new(0.0, 0.0, -1.0), Vec3::new(-1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0)),
+];
+
+// Mapa de sombras del Sol. Como es una luz puntual en medio del sistema, es un cubo:
+// seis vistas de 90° desde su posición, cada una con la distancia (sobre el eje de
+// la cara) al primer cuerpo que encuentra. Un punto está en sombra si algo quedó
+// más cerca de la luz en su dirección; así un planeta tapa a otro y la Luna
+// oscurece la Tierra en un eclipse.
+#[derive(Debug, Clone)]
+pub struct ShadowMap {
+    size: usize,
+    // Espacio de render
+    light: Vec3,
+    // Una cara tras otra, fila por fila
+    depth: Vec<f32>,
+}
+
+impl ShadowMap {
+    pub fn new(size: usize) -> Self {
+        ShadowMap { size, light: Vec3::zeros(), depth: vec![f32::INFINITY; 6 * size * size] }
+    }
+
+    // Vacía el mapa para una luz en `light`
+    pub fn clear(&mut self, light: Vec3) {
+        self.light = light;
+        self.depth.fill(f32::INFINITY);
+    }
+
+    // Pase de solo profundidad: rasteriza los triángulos de un cuerpo (en espacio de
+    // modelo) en cada cara que tocan
+    pub fn draw(&mut self, model_matrix: &Mat4, vertex_array: &[Vertex]) {
+        let world = |vertex: &Vertex| (model_matrix * Vec4::new(vertex.position.x, vertex.position.y, vertex.position.z, 1.0)).xyz();
+        for tri in vertex_array.chunks_exact(3) {
+            let offsets = [world(&tri[0]) - self.light, world(&tri[1]) - self.light, world(&tri[2]) - self.light];
+            for face in 0..FACES.len() {
+                self.rasterize(face, &offsets);
+            }
+        }
+    }
+
+    fn rasterize(&mut self, face: usize, offsets: &[Vec3; 3]) {
+        // Texel (x, y) y la inversa de la profundidad, que sí es lineal en la imagen
+        let mut projected = [(0.0, 0.0, 0.0); 3];
+        for (point, offset) in projected.iter_mut().zip(offsets) {
+            let Some((x, y, depth)) = self.project(face, offset) else {
+                // Los cuerpos son chicos al lado de su distancia al Sol: un triángulo
+                // que cruza detrás de la cara está entero en otra
+                return;
+            };
+            *point = (x, y, 1.0 / depth);
+        }
+        let [(x1, y1, z1), (x2, y2, z2), (x3, y3, z3)] = projected;
+        let area = (x2 - x1) * (y3 - y1) - (x3 - x1) * (y2 - y1);
+        if area.abs() < f32::EPSILON {
+            return;
+        }
+
+        let limit = self.size as f32 - 1.0;
+        let (min_x, max_x) = (x1.min(x2).min(x3).floor().max(0.0), x1.max(x2).max(x3).ceil().min(limit));
+        let (min_y, max_y) = (y1.min(y2).min(y3).floor().max(0.0), y1.max(y2).max(y3).ceil().min(limit));
+        if min_x > max_x || min_y > max_y {
+            return;
+        }
+        let base = face * self.size * self.size;
+        for y in min_y as usize..=max_y as usize {
+            for x in min_x as usize..=max_x as usize {
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                let w1 = ((x2 - px) * (y3 - py) - (x3 - px) * (y2 - py)) / area;
+                let w2 = ((x3 - px) * (y1 - py) - (x1 - px) * (y3 - py)) / area;
+                let w3 = 1.0 - w1 - w2;
+                if w1 < 0.0 || w2 < 0.0 || w3 < 0.0 {
+                    continue;
+                }
+                let depth = 1.0 / (w1 * z1 + w2 * z2 + w3 * z3);
+                let texel = &mut self.depth[base + y * self.size + x];
+                if depth < *texel {
+                    *texel = depth;
+                }
+            }
+        }
+    }
+
+    // Texel (sin recortar) y profundidad de un punto en una cara; None si está detrás
+    fn project(&self, face: usize, offset: &Vec3) -> Option<(f32, f32, f32)> {
+        let (forward, right, up) = FACES[face];
+        let depth = offset.dot(&forward);
+        if depth <= f32::EPSILON {
+            return None;
+        }
+        let half = self.size as f32 * 0.5;
+        Some(((offset.dot(&right) / depth + 1.0) * half, (offset.dot(&up) / depth + 1.0) * half, depth))
+    }
+
+    // Fracción de la luz que llega a un punto (1: a pleno sol, 0: en sombra), con un
+    // filtro de 3x3 texels para que el borde de la sombra no se vea escalonado
+    pub fn visibility(&self, position: &Vec3) -> f32 {
+        let offset = position - self.light;
+        let (x, y, z) = (offset.x.abs(), offset.y.abs(), offset.z.abs());
+        let face = if x >= y && x >= z {
+            if offset.x > 0.0 { 0 } else { 1 }
+        } else if y >= z {
+            if offset.y > 0.0 { 2 } else { 3 }
+        } else if offset.z > 0.0 {
+            4
+        } else {
+            5
+        };
+        let Some((u, v, depth)) = self.project(face, &offset) else {
+            return 1.0;
+        };
+
+        // Un texel mide 2·depth/size a esa distancia
+        let bias = DEPTH_BIAS * 2.0 * depth / self.size as f32;
+        let base = face * self.size * self.size;
+        let limit = self.size as i32 - 1;
+        let (u, v) = (u as i32, v as i32);
+        let mut lit = 0;
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let (tx, ty) = ((u + dx).clamp(0, limit) as usize, (v + dy).clamp(0, limit) as usize);
+                if depth - bias <= self.depth[base + ty * self.size + tx] {
+                    lit += 1;
+                }
+            }
+        }
+        lit as f32 / 9.0
+    }
+}
+
+impl MemoryUsage for ShadowMap {
+    fn memory_bytes(&self) -> usize {
+        vec_bytes(&self.depth)
+    }
+}
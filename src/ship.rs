@@ -0,0 +1,61 @@
+use nalgebra_glm::{self as glm, DMat3, DQuat, DVec3};
+
+// Nave como punto con inercia: la controla quien le aplique aceleraciones
+// (una IA o el jugador, ver pilot.rs)
+#[derive(Debug, Clone)]
+pub struct Ship {
+    pub position: DVec3,
+    pub velocity: DVec3,
+    // Por unidad de tiempo de simulación
+    pub max_speed: f64,
+    pub max_acceleration: f64,
+    // Del modelo al mundo: la nariz del modelo apunta a +Z y el techo a +Y
+    pub orientation: DQuat,
+}
+
+impl Ship {
+    pub fn new(position: DVec3, max_speed: f64, max_acceleration: f64) -> Self {
+        Ship { position, velocity: DVec3::zeros(), max_speed, max_acceleration, orientation: glm::quat_identity() }
+    }
+
+    // Euler semi-implícito con la aceleración y la velocidad limitadas
+    pub fn integrate(&mut self, acceleration: DVec3, dt: f64) {
+        let acceleration = clamp_length(acceleration, self.max_acceleration);
+        self.velocity = clamp_length(self.velocity + acceleration * dt, self.max_speed);
+        self.position += self.velocity * dt;
+    }
+
+    pub fn forward(&self) -> DVec3 {
+        glm::quat_rotate_vec3(&self.orientation, &DVec3::z())
+    }
+
+    pub fn up(&self) -> DVec3 {
+        glm::quat_rotate_vec3(&self.orientation, &DVec3::y())
+    }
+
+    pub fn rotation_matrix(&self) -> DMat3 {
+        glm::quat_to_mat3(&self.orientation)
+    }
+
+    // Giros en radianes alrededor de los ejes de la nave: cabeceo (nariz arriba),
+    // guiñada (nariz a la derecha) y alabeo (ala derecha abajo)
+    pub fn rotate(&mut self, pitch: f64, yaw: f64, roll: f64) {
+        let turn = glm::quat_angle_axis(-pitch, &DVec3::x()) * glm::quat_angle_axis(-yaw, &DVec3::y()) * glm::quat_angle_axis(roll, &DVec3::z());
+        self.orientation = glm::quat_normalize(&(self.orientation * turn));
+    }
+
+    // Apunta la nariz en `direction` con el techo lo más cerca posible de `up`
+    pub fn face(&mut self, direction: DVec3, up: DVec3) {
+        let forward = direction.normalize();
+        let Some(left) = up.cross(&forward).try_normalize(f64::EPSILON) else {
+            return;
+        };
+        let up = forward.cross(&left);
+        self.orientation = glm::mat3_to_quat(&DMat3::from_columns(&[left, up, forward]));
+    }
+}
+
+pub fn clamp_length(vector: DVec3, max: f64) -> DVec3 {
+    let length = vector.magnitude();
+    if length > max && length > 0.0 { vector * (max / length) } else { vector }
+}
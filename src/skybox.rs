@@ -0,0 +1,273 @@
+use std::path::Path;
+use fastnoise_lite::{FastNoiseLite, FractalType, NoiseType};
+use nalgebra_glm::{Mat4, Vec2, Vec3, Vec4};
+use rayon::prelude::*;
+use crate::framebuffer::Framebuffer;
+use crate::memory::{vec_bytes, MemoryUsage};
+use crate::starfield::{self, Starfield};
+use crate::texture::Texture;
+
+// Celdas por lado de cada cara del cubo del cielo; cada celda tiene a lo sumo una
+// estrella, cerca de su centro para que no la corte el borde
+const STAR_CELLS: f32 = 512.0;
+const STAR_MARGIN: f32 = 0.2;
+// Fracción de celdas con estrella donde la densidad vale 1
+const STAR_CHANCE: f32 = 0.3;
+// Brillo lineal de la estrella más brillante; el exponente hace que casi todas sean tenues
+const STAR_PEAK: f32 = 1.5;
+const STAR_FALLOFF: i32 = 6;
+// Ancho de una estrella en pixeles de la ventana (desvío de la gaussiana)
+const STAR_SIGMA: f32 = 0.6;
+// Tintes lineales, de las azuladas a las anaranjadas
+const STAR_TINTS: [[f32; 3]; 4] = [[0.75, 0.85, 1.0], [1.0, 1.0, 1.0], [1.0, 0.92, 0.78], [1.0, 0.75, 0.55]];
+// Mapa de densidad: texels por lado de cada cara del cubo; varía poco, así que
+// alcanza con pocos
+const DENSITY_CELLS: usize = 64;
+const DENSITY_FREQUENCY: f32 = 2.5;
+// Banda de la Vía Láctea: inclinación respecto de la eclíptica (en radianes) y ancho
+// (en seno de la latitud galáctica)
+const GALACTIC_TILT: f32 = 1.05;
+const BAND_WIDTH: f32 = 0.22;
+// Brillo difuso de la banda donde la densidad es máxima
+const GLOW: [f32; 3] = [0.012, 0.013, 0.017];
+
+// Qué se dibuja detrás de la escena
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum SkyboxSource {
+    // Fondo liso (el color de Framebuffer::set_background_color)
+    None,
+    // Campo de estrellas procedural
+    #[default]
+    Starfield,
+    // Unas miles de estrellas como puntos que titilan (ver starfield.rs)
+    Points,
+    // Mapa equirectangular del cielo
+    Map(String),
+}
+
+impl SkyboxSource {
+    // "none", "starfield", "points" o la ruta de una imagen
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "" => None,
+            "none" => Some(SkyboxSource::None),
+            "starfield" => Some(SkyboxSource::Starfield),
+            "points" => Some(SkyboxSource::Points),
+            path => Some(SkyboxSource::Map(path.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkyboxSettings {
+    pub source: SkyboxSource,
+    // Multiplica el brillo del cielo (1: sin cambio)
+    pub brightness: f32,
+}
+
+impl Default for SkyboxSettings {
+    fn default() -> Self {
+        SkyboxSettings { source: SkyboxSource::default(), brightness: 1.0 }
+    }
+}
+
+enum Sky {
+    Empty,
+    // Densidad relativa de estrellas (0..1), una cara del cubo tras otra
+    Starfield { density: Vec<f32> },
+    Points(Starfield),
+    Map(Texture),
+}
+
+// Cielo de fondo. Cada pixel se convierte en la dirección de su rayo con solo la
+// rotación de la cámara, así el cielo gira con ella pero no se acerca ni se aleja, y
+// se dibuja antes que la geometría sin escribir profundidad: todo lo demás queda
+// delante. El procedural reparte estrellas en una grilla sobre las caras de un cubo
+// (una por celda a lo sumo, con la probabilidad que marca un mapa de densidad de
+// ruido y la banda de la Vía Láctea) y las dibuja como puntos del ancho de un pixel
+// a cualquier resolución; el mapa se lee como equirectangular visto desde adentro.
+pub struct Skybox {
+    sky: Sky,
+    brightness: f32,
+}
+
+impl Skybox {
+    pub fn new(settings: &SkyboxSettings) -> Self {
+        let sky = match &settings.source {
+            SkyboxSource::None => Sky::Empty,
+            SkyboxSource::Starfield => Sky::Starfield { density: star_density() },
+            SkyboxSource::Points => Sky::Points(Starfield::new(starfield::STAR_COUNT)),
+            SkyboxSource::Map(path) => match Texture::load(Path::new(path)) {
+                Ok(map) => Sky::Map(map),
+                Err(err) => {
+                    eprintln!("No se pudo cargar el mapa del cielo {}; se usa el campo de estrellas", err);
+                    Sky::Starfield { density: star_density() }
+                }
+            },
+        };
+        Skybox { sky, brightness: settings.brightness }
+    }
+
+    // Suma el cielo a `hdr`, que todavía tiene solo el color de fondo. `time` es el
+    // de los uniforms (el titileo de Points)
+    pub fn draw(&self, framebuffer: &mut Framebuffer, view_matrix: &Mat4, projection_matrix: &Mat4, time: f32) {
+        if matches!(self.sky, Sky::Empty) || self.brightness <= 0.0 {
+            return;
+        }
+        let mut rotation = *view_matrix;
+        rotation.set_column(3, &Vec4::new(0.0, 0.0, 0.0, 1.0));
+        if let Sky::Points(stars) = &self.sky {
+            stars.draw(framebuffer, &(projection_matrix * rotation), time, self.brightness);
+            return;
+        }
+        let Some(inverse) = (projection_matrix * rotation).try_inverse() else {
+            return;
+        };
+        let (width, height) = (framebuffer.width, framebuffer.height);
+        // Ángulo que cubre un pixel de la ventana (con supermuestreo, varios del framebuffer)
+        let pixel = 2.0 / (projection_matrix[(1, 1)] * height as f32) * framebuffer.supersampling() as f32;
+
+        // El rayo es lineal en la pantalla: se avanza por columnas en vez de invertir cada pixel
+        let ray = |ndc_x: f32, ndc_y: f32| {
+            let far = inverse * Vec4::new(ndc_x, ndc_y, 1.0, 1.0);
+            far.xyz() / far.w
+        };
+        let step = (ray(1.0, 0.0) - ray(-1.0, 0.0)) / width as f32;
+
+        framebuffer.hdr.par_chunks_mut(width).enumerate().for_each(|(y, row)| {
+            let ndc_y = 1.0 - 2.0 * (y as f32 + 0.5) / height as f32;
+            let mut far = ray(-1.0, ndc_y) + step * 0.5;
+            for color in row.iter_mut() {
+                let sky = self.color(&far, pixel);
+                (0..3).for_each(|channel| color[channel] += sky[channel] * self.brightness);
+                far += step;
+            }
+        });
+    }
+
+    // Color lineal del cielo en una dirección del mundo (sin normalizar)
+    fn color(&self, direction: &Vec3, pixel: f32) -> [f32; 3] {
+        match &self.sky {
+            Sky::Empty | Sky::Points(_) => [0.0; 3],
+            Sky::Map(map) => {
+                let direction = direction.normalize();
+                // Desde adentro la longitud crece hacia la izquierda: sin espejar la imagen
+                let u = (direction.z.atan2(direction.x) / std::f32::consts::TAU).rem_euclid(1.0);
+                let v = 1.0 - direction.y.clamp(-1.0, 1.0).acos() / std::f32::consts::PI;
+                map.sample(Vec2::new(u, v)).to_linear()
+            }
+            Sky::Starfield { density } => {
+                let (face, a, b) = cube_face(direction);
+                let density = sample_density(density, face, a, b);
+                let glow = density * density;
+                let star = star(face, a, b, density, pixel);
+                [0, 1, 2].map(|channel| GLOW[channel] * glow + star[channel])
+            }
+        }
+    }
+}
+
+impl MemoryUsage for Skybox {
+    fn memory_bytes(&self) -> usize {
+        match &self.sky {
+            Sky::Empty => 0,
+            Sky::Starfield { density } => vec_bytes(density),
+            Sky::Points(stars) => stars.memory_bytes(),
+            Sky::Map(map) => map.memory_bytes(),
+        }
+    }
+}
+
+// La estrella de la celda del cubo que contiene el punto (a, b) de la cara, si tiene,
+// evaluada en ese punto
+fn star(face: u32, a: f32, b: f32, density: f32, pixel: f32) -> [f32; 3] {
+    let (cell_a, cell_b) = ((a + 1.0) * 0.5 * STAR_CELLS, (b + 1.0) * 0.5 * STAR_CELLS);
+    let (column, row) = (cell_a.floor(), cell_b.floor());
+    let mut seed = hash(face ^ hash(column as u32 ^ hash(row as u32)));
+    let mut next = || {
+        seed = hash(seed);
+        (seed >> 8) as f32 / (1 << 24) as f32
+    };
+    if next() >= STAR_CHANCE * density {
+        return [0.0; 3];
+    }
+    let span = 1.0 - 2.0 * STAR_MARGIN;
+    let (star_a, star_b) = (column + STAR_MARGIN + span * next(), row + STAR_MARGIN + span * next());
+    let brightness = STAR_PEAK * next().powi(STAR_FALLOFF);
+    let tint = STAR_TINTS[(next() * STAR_TINTS.len() as f32) as usize % STAR_TINTS.len()];
+
+    // Distancia en la cara llevada a ángulo (aproximado: la cara se estira hacia los bordes)
+    let offset = Vec2::new(cell_a - star_a, cell_b - star_b) * (2.0 / STAR_CELLS);
+    let angle = offset.magnitude() / (1.0 + a * a + b * b).sqrt();
+    let sigma = STAR_SIGMA * pixel;
+    let falloff = (-angle * angle / (2.0 * sigma * sigma)).exp();
+    tint.map(|channel| channel * brightness * falloff)
+}
+
+// Cara del cubo (0..6) que atraviesa la dirección y su posición en ella, en [-1, 1]
+fn cube_face(direction: &Vec3) -> (u32, f32, f32) {
+    let (x, y, z) = (direction.x.abs(), direction.y.abs(), direction.z.abs());
+    if x >= y && x >= z {
+        (if direction.x > 0.0 { 0 } else { 1 }, direction.y / x, direction.z / x)
+    } else if y >= z {
+        (if direction.y > 0.0 { 2 } else { 3 }, direction.x / y, direction.z / y)
+    } else {
+        (if direction.z > 0.0 { 4 } else { 5 }, direction.x / z, direction.y / z)
+    }
+}
+
+fn hash(value: u32) -> u32 {
+    let mut value = value.wrapping_mul(0x9E37_79B9) ^ 0x85EB_CA6B;
+    value ^= value >> 16;
+    value = value.wrapping_mul(0x7FEB_352D);
+    value ^= value >> 15;
+    value = value.wrapping_mul(0x846C_A68B);
+    value ^ (value >> 16)
+}
+
+// Ruido fractal sobre la esfera, más denso en la banda de la Vía Láctea
+fn star_density() -> Vec<f32> {
+    let mut noise = FastNoiseLite::with_seed(7);
+    noise.set_noise_type(Some(NoiseType::OpenSimplex2));
+    noise.set_fractal_type(Some(FractalType::FBm));
+    noise.set_fractal_octaves(Some(4));
+    noise.set_frequency(Some(DENSITY_FREQUENCY));
+    let pole = Vec3::new(0.0, GALACTIC_TILT.cos(), GALACTIC_TILT.sin());
+
+    (0..6 * DENSITY_CELLS * DENSITY_CELLS)
+        .into_par_iter()
+        .map(|index| {
+            let texel = index % (DENSITY_CELLS * DENSITY_CELLS);
+            let direction = face_direction(index / (DENSITY_CELLS * DENSITY_CELLS), texel % DENSITY_CELLS, texel / DENSITY_CELLS);
+            let band = (-(direction.dot(&pole) / BAND_WIDTH).powi(2)).exp();
+            let clumps = noise.get_noise_3d(direction.x, direction.y, direction.z) * 0.5 + 0.5;
+            ((0.25 + 0.75 * band) * (0.35 + 0.65 * clumps)).clamp(0.0, 1.0)
+        })
+        .collect()
+}
+
+// Centro del texel (x, y) de una cara del mapa de densidad (la inversa de cube_face)
+fn face_direction(face: usize, x: usize, y: usize) -> Vec3 {
+    let a = (x as f32 + 0.5) / DENSITY_CELLS as f32 * 2.0 - 1.0;
+    let b = (y as f32 + 0.5) / DENSITY_CELLS as f32 * 2.0 - 1.0;
+    let sign = if face.is_multiple_of(2) { 1.0 } else { -1.0 };
+    let direction = match face / 2 {
+        0 => Vec3::new(sign, a, b),
+        1 => Vec3::new(a, sign, b),
+        _ => Vec3::new(a, b, sign),
+    };
+    direction.normalize()
+}
+
+// Lectura bilineal dentro de la cara; en el borde se repite el último texel
+fn sample_density(density: &[f32], face: u32, a: f32, b: f32) -> f32 {
+    let last = DENSITY_CELLS as f32 - 1.0;
+    let x = ((a + 1.0) * 0.5 * DENSITY_CELLS as f32 - 0.5).clamp(0.0, last);
+    let y = ((b + 1.0) * 0.5 * DENSITY_CELLS as f32 - 0.5).clamp(0.0, last);
+    let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+    let (x1, y1) = ((x0 + 1).min(DENSITY_CELLS - 1), (y0 + 1).min(DENSITY_CELLS - 1));
+    let (tx, ty) = (x - x0 as f32, y - y0 as f32);
+    let base = face as usize * DENSITY_CELLS * DENSITY_CELLS;
+    let texel = |x: usize, y: usize| density[base + y * DENSITY_CELLS + x];
+    texel(x0, y0) * (1.0 - tx) * (1.0 - ty) + texel(x1, y0) * tx * (1.0 - ty) + texel(x0, y1) * (1.0 - tx) * ty + texel(x1, y1) * tx * ty
+}
@@ -0,0 +1,359 @@
+use std::f64::consts::PI;
+use nalgebra_glm::{self as glm, DMat3, DVec3, Mat4, Vec3};
+use crate::orbit::Orbit;
+use crate::scene_graph::{SceneGraph, Transform};
+
+#[derive(Debug, Clone)]
+pub struct Body {
+    pub name: &'static str,
+    // Radio del cuerpo; es la escala del modelo normalizado y su volumen de colisión
+    pub radius: f64,
+    // None: la posición no la calcula la órbita (el Sol, naves con su propia física)
+    pub orbit: Option<Orbit>,
+    // Cuerpo alrededor del cual orbita; None es el Sol. Un cuerpo con padre y sin
+    // órbita se queda en `position` relativa al padre.
+    pub parent: Option<&'static str>,
+    // La rotación queda acoplada a la órbita: siempre muestra la misma cara al padre
+    pub tidally_locked: bool,
+    // Posición en el mundo (la del nodo en el grafo de escena)
+    pub position: DVec3,
+    // Rotación propia alrededor del eje Y, en radianes
+    pub rotation: f64,
+    // Radianes por unidad de tiempo de simulación, en el sentido de las órbitas
+    // (negativo: rotación retrógrada)
+    pub spin: f64,
+    // Inclinación del eje de giro respecto de la normal al plano XZ, en radianes
+    // alrededor de X; los anillos la comparten
+    pub tilt: f64,
+}
+
+impl Body {
+    pub fn new(name: &'static str, radius: f64, orbit: Option<Orbit>) -> Self {
+        Body { name, radius, orbit, parent: None, tidally_locked: false, position: DVec3::zeros(), rotation: 0.0, spin: 0.0, tilt: 0.0 }
+    }
+
+    pub fn at(mut self, position: DVec3) -> Self {
+        self.position = position;
+        self
+    }
+
+    pub fn around(mut self, parent: &'static str) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+
+    // Los días no están a escala: son unas pocas veces más cortos que el año para
+    // que un amanecer visto desde la superficie dure unos segundos
+    pub fn spinning(mut self, spin: f64) -> Self {
+        self.spin = spin;
+        self
+    }
+
+    pub fn tilted(mut self, tilt: f64) -> Self {
+        self.tilt = tilt;
+        self
+    }
+
+    pub fn tidally_locked(mut self) -> Self {
+        self.tidally_locked = true;
+        self
+    }
+}
+
+// Estado de la simulación en doble precisión. El render solo ve posiciones f32
+// relativas al origen flotante, así que las corridas largas o a alta velocidad
+// no acumulan error visible. Cada cuerpo es un nodo del grafo de escena con el
+// mismo índice: la órbita es su traslación local y el mundo la compone con la del
+// padre (ver scene_graph.rs).
+#[derive(Debug, Clone)]
+pub struct SolarSystem {
+    pub time: f64,
+    bodies: Vec<Body>,
+    graph: SceneGraph,
+}
+
+const fn orbit(radius: f64, angular_speed: f64, height: f64) -> Option<Orbit> {
+    Some(Orbit::circular(radius, angular_speed, height))
+}
+
+impl SolarSystem {
+    pub fn new() -> Self {
+        // Los padres van antes que sus satélites. Las inclinaciones son las reales; la
+        // de Venus es chica porque su giro ya es retrógrado
+        let bodies = vec![
+            Body::new("sun", 1.0, None),
+            Body::new("mercury", 0.35, orbit(2.1, 0.08, 0.0)).spinning(0.09),
+            Body::new("venus", 0.425, orbit(3.3, 0.05, 0.0)).spinning(-0.004).tilted(0.046),
+            Body::new("earth", 0.5, orbit(5.1, 0.045, 0.0)).spinning(0.06).tilted(0.41),
+            Body::new("moon", 0.14, orbit(0.9, 0.2, 0.0)).around("earth").tidally_locked(),
+            Body::new("mars", 0.35, orbit(6.4, 0.04, 0.0)).spinning(0.058).tilted(0.44),
+            // Planeta enano dentro del cinturón de asteroides
+            Body::new(
+                "ceres",
+                0.1,
+                Some(
+                    Orbit::circular(7.1, 0.037, 0.0)
+                        .with_eccentricity(0.08, 73f64.to_radians())
+                        .with_inclination(10.6f64.to_radians(), 80f64.to_radians())
+                        .with_mean_anomaly(60f64.to_radians()),
+                ),
+            )
+            .spinning(0.15),
+            Body::new("jupiter", 1.05, orbit(7.9, 0.035, 0.0)).spinning(0.14).tilted(0.055),
+            // Lunas galileanas, de la más cercana (y rápida) a la más lejana
+            Body::new("io", 0.1, orbit(1.35, 0.3, 0.0)).around("jupiter").tidally_locked(),
+            Body::new("europa", 0.09, orbit(1.6, 0.22, 0.0)).around("jupiter").tidally_locked(),
+            Body::new("ganymede", 0.15, orbit(1.9, 0.15, 0.0)).around("jupiter").tidally_locked(),
+            Body::new("callisto", 0.13, orbit(2.25, 0.1, 0.0)).around("jupiter").tidally_locked(),
+            Body::new("saturn", 0.9, orbit(9.9, 0.03, 0.0)).spinning(0.13).tilted(0.47),
+            Body::new("uranus", 0.8, orbit(12.1, 0.025, 0.0)).spinning(-0.08).tilted(1.71),
+            Body::new("neptune", 0.8, orbit(15.2, 0.02, 0.0)).spinning(0.085).tilted(0.49),
+            // Planetas enanos del cinturón de Kuiper, con sus órbitas excéntricas e
+            // inclinadas; Plutón gira de costado y al revés, como Urano
+            Body::new(
+                "pluto",
+                0.12,
+                Some(
+                    Orbit::circular(17.5, 0.016, 0.0)
+                        .with_eccentricity(0.25, 114f64.to_radians())
+                        .with_inclination(17f64.to_radians(), 110f64.to_radians())
+                        .with_mean_anomaly(14f64.to_radians()),
+                ),
+            )
+            .spinning(-0.01)
+            .tilted(2.1),
+            Body::new(
+                "eris",
+                0.12,
+                Some(
+                    Orbit::circular(22.0, 0.011, 0.0)
+                        .with_eccentricity(0.44, 151f64.to_radians())
+                        .with_inclination(44f64.to_radians(), 36f64.to_radians())
+                        .with_mean_anomaly(205f64.to_radians()),
+                ),
+            )
+            .spinning(0.02),
+            // Cometa con el perihelio dentro de la órbita de Mercurio y el afelio pasado
+            // Neptuno; arranca poco antes del perihelio (ver comet.rs)
+            Body::new(
+                "halley",
+                0.08,
+                Some(
+                    Orbit::circular(11.0, 0.012, 0.0)
+                        .with_eccentricity(0.85, 126f64.to_radians())
+                        .with_inclination(17f64.to_radians(), 52f64.to_radians())
+                        .with_mean_anomaly(-46f64.to_radians()),
+                ),
+            )
+            .spinning(0.1),
+            Body::new("spaceship", 3.6, orbit(3.0, -0.016, -5.0)),
+            // La escolta se mueve con su propia física (ver escort.rs)
+            Body::new("navecita", 1.8, None).at(DVec3::new(3.0, 5.0, 0.0)),
+        ];
+
+        SolarSystem::from_bodies(bodies)
+    }
+
+    // Sistema con otros cuerpos (ver system_file.rs); los padres van antes que sus satélites
+    pub fn from_bodies(bodies: Vec<Body>) -> Self {
+        let mut system = SolarSystem { time: 0.0, bodies: Vec::with_capacity(bodies.len()), graph: SceneGraph::default() };
+        for body in bodies {
+            system.push(body);
+        }
+        system.update_positions();
+        system
+    }
+
+    // Agrega el cuerpo y su nodo, colgado del nodo del padre
+    fn push(&mut self, body: Body) {
+        debug_assert!(body.parent.is_none_or(|parent| self.body(parent).is_some()));
+        let parent = body.parent.and_then(|parent| self.graph.find(parent));
+        self.graph.add(body.name, parent, Transform::at(body.position));
+        self.bodies.push(body);
+    }
+
+    pub fn step(&mut self, dt: f64) {
+        self.time += dt;
+        self.update_positions();
+    }
+
+    fn update_positions(&mut self) {
+        let time = self.time;
+        for (i, body) in self.bodies.iter_mut().enumerate() {
+            if !body.tidally_locked {
+                // Un giro positivo en Y lleva +X hacia -Z: el sentido contrario a las órbitas
+                body.rotation = -body.spin * time;
+            }
+            let Some(orbit) = body.orbit else {
+                continue;
+            };
+            self.graph.set_local(i, Transform::at(orbit.position_at(time)));
+            if body.tidally_locked {
+                // El eje +X del modelo apunta al padre: un giro de π - ángulo orbital en Y
+                body.rotation = PI - orbit.angle_at(time);
+            }
+        }
+        self.update_graph();
+    }
+
+    // Compone el grafo y copia las posiciones en el mundo a los cuerpos
+    fn update_graph(&mut self) {
+        self.graph.update();
+        for (i, body) in self.bodies.iter_mut().enumerate() {
+            body.position = self.graph.world_position(i);
+        }
+    }
+
+    pub fn body(&self, name: &str) -> Option<&Body> {
+        self.bodies.iter().find(|body| body.name == name)
+    }
+
+    pub fn bodies(&self) -> &[Body] {
+        &self.bodies
+    }
+
+    // Cuerpo extra (escenas generadas, ver stress.rs); el padre tiene que existir ya
+    // para que se actualice antes que su satélite
+    pub fn add_body(&mut self, name: &'static str, radius: f64, orbit: Orbit, parent: Option<&'static str>, spin: f64) {
+        let mut body = Body::new(name, radius, Some(orbit)).spinning(spin);
+        body.parent = parent;
+        self.push(body);
+        self.update_positions();
+    }
+
+    // Cuerpo siguiente en la lista (vuelve al primero al final)
+    pub fn next_body(&self, current: &str) -> &'static str {
+        let index = self.bodies.iter().position(|body| body.name == current).map_or(0, |i| i + 1);
+        self.bodies[index % self.bodies.len()].name
+    }
+
+    pub fn position(&self, name: &str) -> DVec3 {
+        self.body(name).map_or(DVec3::zeros(), |body| body.position)
+    }
+
+    // Posición en otro instante según las órbitas (la del padre incluida); None para
+    // los cuerpos que mueve otro sistema
+    pub fn position_at(&self, name: &str, time: f64) -> Option<DVec3> {
+        let body = self.body(name)?;
+        let parent = match body.parent {
+            Some(parent) => self.position_at(parent, time)?,
+            None => DVec3::zeros(),
+        };
+        Some(parent + body.orbit?.position_at(time))
+    }
+
+    pub fn radius(&self, name: &str) -> f64 {
+        self.body(name).map_or(0.0, |body| body.radius)
+    }
+
+    // Para cuerpos sin órbita cuya posición calcula otro sistema; `position` es en el
+    // mundo y lo que cuelgue del cuerpo se mueve con él
+    pub fn set_position(&mut self, name: &str, position: DVec3) {
+        self.set_pose(name, position, &DMat3::identity());
+    }
+
+    // Posición y orientación en el mundo (una nave que vuela el jugador); la
+    // orientación se suma al giro propio y la inclinación del cuerpo
+    pub fn set_pose(&mut self, name: &str, position: DVec3, orientation: &DMat3) {
+        let Some(index) = self.bodies.iter().position(|body| body.name == name) else {
+            return;
+        };
+        let parent = self.graph.node(index).parent.map_or(DVec3::zeros(), |parent| self.graph.world_position(parent));
+        self.graph.set_local(index, Transform::oriented(position - parent, orientation));
+        self.update_graph();
+    }
+
+    // El cuerpo deja su órbita y queda donde está; desde ahí lo mueve otro sistema
+    pub fn release_orbit(&mut self, name: &str) {
+        if let Some(body) = self.bodies.iter_mut().find(|body| body.name == name) {
+            body.orbit = None;
+        }
+    }
+
+    pub fn rotation(&self, name: &str) -> f64 {
+        self.body(name).map_or(0.0, |body| body.rotation)
+    }
+
+    pub fn spin(&self, name: &str) -> f64 {
+        self.body(name).map_or(0.0, |body| body.spin)
+    }
+
+    pub fn tilt(&self, name: &str) -> f64 {
+        self.body(name).map_or(0.0, |body| body.tilt)
+    }
+
+    // Lleva una dirección del modelo del cuerpo al mundo: el giro propio en Y y
+    // después la inclinación del eje en X, como model_matrix
+    pub fn orient(&self, name: &str, direction: DVec3) -> DVec3 {
+        glm::rotate_x_vec3(&glm::rotate_y_vec3(&direction, self.rotation(name)), self.tilt(name))
+    }
+
+    pub fn orbit(&self, name: &str) -> Option<Orbit> {
+        self.body(name).and_then(|body| body.orbit)
+    }
+
+    // Posición lista para construir la matriz de modelo
+    pub fn render_position(&self, name: &str, origin: DVec3) -> Vec3 {
+        to_render(self.position(name), origin)
+    }
+
+    // Marco del cuerpo en el grafo, relativo al origen flotante: lo que se dibuje
+    // pegado a él (anillos, atmósferas) compone su matriz con esta
+    pub fn frame_matrix(&self, name: &str, origin: DVec3) -> Mat4 {
+        match self.bodies.iter().position(|body| body.name == name) {
+            Some(index) => self.graph.render_matrix(index, origin),
+            None => glm::translation(&to_render(DVec3::zeros(), origin)),
+        }
+    }
+
+    // Matriz de modelo del cuerpo: su marco con el radio como escala, el giro propio
+    // alrededor de su eje y la inclinación del eje, que no heredan sus satélites
+    pub fn model_matrix(&self, name: &str, origin: DVec3) -> Mat4 {
+        let spin = Vec3::new(0.0, self.rotation(name) as f32, 0.0);
+        self.axis_matrix(name, origin) * crate::create_model_matrix(Vec3::zeros(), self.radius(name) as f32, spin)
+    }
+
+    // Marco del cuerpo con el eje inclinado, sin el giro propio: el de los anillos
+    pub fn axis_matrix(&self, name: &str, origin: DVec3) -> Mat4 {
+        let tilt = Vec3::new(self.tilt(name) as f32, 0.0, 0.0);
+        self.frame_matrix(name, origin) * crate::create_model_matrix(Vec3::zeros(), 1.0, tilt)
+    }
+}
+
+impl Default for SolarSystem {
+    fn default() -> Self {
+        SolarSystem::new()
+    }
+}
+
+// Nombre para mostrar en pantalla; los identificadores internos quedan en inglés
+pub fn display_name(name: &str) -> &str {
+    match name {
+        "sun" => "Sol",
+        "mercury" => "Mercurio",
+        "venus" => "Venus",
+        "earth" => "Tierra",
+        "moon" => "Luna",
+        "io" => "Ío",
+        "europa" => "Europa",
+        "ganymede" => "Ganímedes",
+        "callisto" => "Calisto",
+        "mars" => "Marte",
+        "jupiter" => "Júpiter",
+        "saturn" => "Saturno",
+        "uranus" => "Urano",
+        "neptune" => "Neptuno",
+        "ceres" => "Ceres",
+        "pluto" => "Plutón",
+        "eris" => "Eris",
+        "halley" => "Cometa Halley",
+        "spaceship" => "Nave",
+        "navecita" => "Navecita",
+        other => other,
+    }
+}
+
+// La resta se hace en f64 y solo el resultado, pequeño cerca de la cámara, pasa a f32
+pub fn to_render(position: DVec3, origin: DVec3) -> Vec3 {
+    (position - origin).cast::<f32>()
+}
@@ -0,0 +1,96 @@
+use nalgebra_glm::{Mat4, Vec3, Vec4};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::framebuffer::Framebuffer;
+use crate::memory::{vec_bytes, MemoryUsage};
+
+// Estrellas repartidas sobre la esfera; la semilla fija deja siempre el mismo cielo
+pub const STAR_COUNT: usize = 4000;
+const SEED: u64 = 7;
+// Brillo lineal de la estrella más brillante; el exponente hace que casi todas sean tenues
+const STAR_PEAK: f32 = 1.2;
+const STAR_FALLOFF: i32 = 4;
+// Desde este brillo una estrella ocupa 2x2 pixeles de la ventana en vez de uno
+const LARGE_STAR: f32 = 0.35;
+// Titileo: fracción del brillo que oscila y rango de velocidades, en radianes por frame
+const TWINKLE_DEPTH: f32 = 0.45;
+const TWINKLE_RATE: (f32, f32) = (0.04, 0.18);
+// Tintes lineales, de las azuladas a las anaranjadas
+const STAR_TINTS: [[f32; 3]; 4] = [[0.75, 0.85, 1.0], [1.0, 1.0, 1.0], [1.0, 0.92, 0.78], [1.0, 0.75, 0.55]];
+
+#[derive(Debug, Clone)]
+struct Star {
+    // Dirección en el mundo, unitaria
+    direction: Vec3,
+    brightness: f32,
+    tint: [f32; 3],
+    // Fase y velocidad del titileo
+    phase: f32,
+    rate: f32,
+}
+
+// Campo de estrellas liviano: unas miles de direcciones fijas que cada frame se
+// proyectan con la rotación de la cámara (como puntos en el infinito, así no se
+// acercan al moverla) y se dibujan como puntos de 1 o 2 pixeles que titilan con el
+// tiempo. Cuesta una proyección por estrella en lugar de un rayo por pixel (ver
+// skybox.rs).
+#[derive(Debug, Clone)]
+pub struct Starfield {
+    stars: Vec<Star>,
+}
+
+impl Starfield {
+    pub fn new(count: usize) -> Self {
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let stars = (0..count)
+            .map(|_| {
+                // Uniforme sobre la esfera: altura uniforme y ángulo uniforme
+                let height: f32 = rng.gen_range(-1.0..1.0);
+                let angle: f32 = rng.gen_range(0.0..std::f32::consts::TAU);
+                let ring = (1.0 - height * height).sqrt();
+                Star {
+                    direction: Vec3::new(ring * angle.cos(), height, ring * angle.sin()),
+                    brightness: STAR_PEAK * rng.gen::<f32>().powi(STAR_FALLOFF),
+                    tint: STAR_TINTS[rng.gen_range(0..STAR_TINTS.len())],
+                    phase: rng.gen_range(0.0..std::f32::consts::TAU),
+                    rate: rng.gen_range(TWINKLE_RATE.0..TWINKLE_RATE.1),
+                }
+            })
+            .collect();
+        Starfield { stars }
+    }
+
+    // Suma las estrellas a `hdr`. `view_projection` sin la traslación de la cámara;
+    // `time` es el de los uniforms
+    pub fn draw(&self, framebuffer: &mut Framebuffer, view_projection: &Mat4, time: f32, brightness: f32) {
+        let (width, height) = (framebuffer.width as f32, framebuffer.height as f32);
+        let scale = framebuffer.supersampling();
+        for star in &self.stars {
+            let clip = view_projection * Vec4::new(star.direction.x, star.direction.y, star.direction.z, 0.0);
+            if clip.w <= 0.0 {
+                continue;
+            }
+            let x = (clip.x / clip.w + 1.0) * 0.5 * width;
+            let y = (1.0 - clip.y / clip.w) * 0.5 * height;
+            if x < 0.0 || y < 0.0 || x >= width || y >= height {
+                continue;
+            }
+            let twinkle = 1.0 - TWINKLE_DEPTH * (0.5 + 0.5 * (time * star.rate + star.phase).sin());
+            let color = star.tint.map(|channel| channel * star.brightness * twinkle * brightness);
+            let size = if star.brightness >= LARGE_STAR { 2 * scale } else { scale };
+            let (x, y) = (x as usize, y as usize);
+            for py in y..(y + size).min(framebuffer.height) {
+                for px in x..(x + size).min(framebuffer.width) {
+                    let pixel = &mut framebuffer.hdr[py * framebuffer.width + px];
+                    (0..3).for_each(|channel| pixel[channel] += color[channel]);
+                }
+            }
+        }
+    }
+}
+
+impl MemoryUsage for Starfield {
+    fn memory_bytes(&self) -> usize {
+        vec_bytes(&self.stars)
+    }
+}
@@ -0,0 +1,235 @@
+use std::fs::File;
+use std::io::{self, BufWriter, ErrorKind, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use crate::layers::{LAYERS, LAYER_COUNT};
+
+// Contadores del frame actual; el pipeline los incrementa desde cualquier render
+static DRAW_CALLS: AtomicUsize = AtomicUsize::new(0);
+static TRIANGLES: AtomicUsize = AtomicUsize::new(0);
+static FRAGMENTS: AtomicUsize = AtomicUsize::new(0);
+static CULLED_BODIES: AtomicUsize = AtomicUsize::new(0);
+// Fragmentos de la periferia foveada que no se sombrearon
+static SKIPPED_FRAGMENTS: AtomicUsize = AtomicUsize::new(0);
+// Cuerpos dibujados con la malla simple o como impostor (ver stress.rs)
+static LOW_DETAIL_BODIES: AtomicUsize = AtomicUsize::new(0);
+static IMPOSTORS: AtomicUsize = AtomicUsize::new(0);
+
+pub fn add_draw_call() {
+    DRAW_CALLS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn add_triangles(count: usize) {
+    TRIANGLES.fetch_add(count, Ordering::Relaxed);
+}
+
+pub fn add_fragments(count: usize) {
+    FRAGMENTS.fetch_add(count, Ordering::Relaxed);
+}
+
+pub fn add_culled_body() {
+    CULLED_BODIES.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn add_skipped_fragment() {
+    SKIPPED_FRAGMENTS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn add_low_detail_body() {
+    LOW_DETAIL_BODIES.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn add_impostor() {
+    IMPOSTORS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn begin_frame() {
+    DRAW_CALLS.store(0, Ordering::Relaxed);
+    TRIANGLES.store(0, Ordering::Relaxed);
+    FRAGMENTS.store(0, Ordering::Relaxed);
+    CULLED_BODIES.store(0, Ordering::Relaxed);
+    SKIPPED_FRAGMENTS.store(0, Ordering::Relaxed);
+    LOW_DETAIL_BODIES.store(0, Ordering::Relaxed);
+    IMPOSTORS.store(0, Ordering::Relaxed);
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FrameStats {
+    pub frame: u64,
+    pub frame_time: Duration,
+    pub draw_calls: usize,
+    pub triangles: usize,
+    pub fragments: usize,
+    pub culled_bodies: usize,
+    pub skipped_fragments: usize,
+    pub low_detail_bodies: usize,
+    pub impostors: usize,
+    pub memory_bytes: usize,
+    // Ritmo de frames: objetivo (0 sin límite), promedio logrado y variación
+    pub target_fps: u32,
+    pub achieved_fps: f32,
+    pub jitter_ms: f32,
+    // Escala del presupuesto de efectos decorativos
+    pub effects_scale: f32,
+    // Tiempo de dibujo de cada capa de render, en el orden de LAYERS
+    pub layer_ms: [f32; LAYER_COUNT],
+}
+
+impl FrameStats {
+    // Toma una instantánea de los contadores al terminar el frame
+    pub fn collect(frame: u64, frame_time: Duration) -> Self {
+        FrameStats {
+            frame,
+            frame_time,
+            draw_calls: DRAW_CALLS.load(Ordering::Relaxed),
+            triangles: TRIANGLES.load(Ordering::Relaxed),
+            fragments: FRAGMENTS.load(Ordering::Relaxed),
+            culled_bodies: CULLED_BODIES.load(Ordering::Relaxed),
+            skipped_fragments: SKIPPED_FRAGMENTS.load(Ordering::Relaxed),
+            low_detail_bodies: LOW_DETAIL_BODIES.load(Ordering::Relaxed),
+            impostors: IMPOSTORS.load(Ordering::Relaxed),
+            memory_bytes: resident_memory(),
+            target_fps: 0,
+            achieved_fps: 0.0,
+            jitter_ms: 0.0,
+            effects_scale: 1.0,
+            layer_ms: [0.0; LAYER_COUNT],
+        }
+    }
+
+    pub fn with_pacing(mut self, target_fps: u32, achieved_fps: f32, jitter_ms: f32) -> Self {
+        self.target_fps = target_fps;
+        self.achieved_fps = achieved_fps;
+        self.jitter_ms = jitter_ms;
+        self
+    }
+
+    pub fn with_effects(mut self, effects_scale: f32) -> Self {
+        self.effects_scale = effects_scale;
+        self
+    }
+
+    pub fn with_layers(mut self, layer_ms: [f32; LAYER_COUNT]) -> Self {
+        self.layer_ms = layer_ms;
+        self
+    }
+
+    pub fn fps(&self) -> f32 {
+        let seconds = self.frame_time.as_secs_f32();
+        if seconds > 0.0 { 1.0 / seconds } else { 0.0 }
+    }
+
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"frame\":{},\"fps\":{:.2},\"frame_ms\":{:.3},\"triangles\":{},\"fragments\":{},\"culled_bodies\":{},\"skipped_fragments\":{},\"low_detail_bodies\":{},\"impostors\":{},\"memory_bytes\":{},\"target_fps\":{},\"achieved_fps\":{:.2},\"jitter_ms\":{:.3},\"effects_scale\":{:.3},\"layers\":{{{}}}}}",
+            self.frame,
+            self.fps(),
+            self.frame_time.as_secs_f64() * 1000.0,
+            self.triangles,
+            self.fragments,
+            self.culled_bodies,
+            self.skipped_fragments,
+            self.low_detail_bodies,
+            self.impostors,
+            self.memory_bytes,
+            self.target_fps,
+            self.achieved_fps,
+            self.jitter_ms,
+            self.effects_scale,
+            LAYERS
+                .iter()
+                .zip(self.layer_ms)
+                .map(|(layer, ms)| format!("\"{}\":{:.3}", layer.name(), ms))
+                .collect::<Vec<_>>()
+                .join(","),
+        )
+    }
+}
+
+// Memoria residente del proceso (solo Linux; 0 en otras plataformas). VmRSS viene
+// en kB, así no depende del tamaño de página del sistema
+fn resident_memory() -> usize {
+    std::fs::read_to_string("/proc/self/status")
+        .ok()
+        .and_then(|status| {
+            let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+            line.split_whitespace().nth(1)?.parse::<usize>().ok()
+        })
+        .map(|kilobytes| kilobytes * 1024)
+        .unwrap_or(0)
+}
+
+// Lo que un cliente TCP todavía no recibió; si se acumula más de esto se lo descarta
+const MAX_PENDING_BYTES: usize = 1 << 20;
+
+pub struct StatsClient {
+    stream: TcpStream,
+    pending: Vec<u8>,
+}
+
+impl StatsClient {
+    // Escribe lo que el socket acepte sin bloquear; false si hay que descartarlo
+    fn send(&mut self, line: &str) -> bool {
+        self.pending.extend_from_slice(line.as_bytes());
+        self.pending.push(b'\n');
+        if self.pending.len() > MAX_PENDING_BYTES {
+            return false;
+        }
+        while !self.pending.is_empty() {
+            match self.stream.write(&self.pending) {
+                Ok(0) => return false,
+                Ok(written) => {
+                    self.pending.drain(..written);
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => return true,
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(_) => return false,
+            }
+        }
+        true
+    }
+}
+
+// Canal de salida de las estadísticas para herramientas externas
+pub enum StatsSink {
+    File(BufWriter<File>),
+    Tcp {
+        listener: TcpListener,
+        clients: Vec<StatsClient>,
+    },
+}
+
+impl StatsSink {
+    pub fn file(path: &str) -> io::Result<Self> {
+        Ok(StatsSink::File(BufWriter::new(File::create(path)?)))
+    }
+
+    pub fn tcp(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        listener.set_nonblocking(true)?;
+        Ok(StatsSink::Tcp { listener, clients: Vec::new() })
+    }
+
+    pub fn publish(&mut self, stats: &FrameStats) {
+        let line = stats.to_json();
+        match self {
+            StatsSink::File(writer) => {
+                if let Err(err) = writeln!(writer, "{}", line).and_then(|_| writer.flush()) {
+                    eprintln!("No se pudieron escribir las estadísticas: {}", err);
+                }
+            }
+            StatsSink::Tcp { listener, clients } => {
+                // Acepta clientes nuevos sin bloquear el frame
+                while let Ok((stream, _)) = listener.accept() {
+                    if stream.set_nonblocking(true).is_ok() {
+                        clients.push(StatsClient { stream, pending: Vec::new() });
+                    }
+                }
+                // Lo que no entra en el socket queda pendiente para el próximo frame;
+                // los clientes desconectados o demasiado atrasados se descartan
+                clients.retain_mut(|client| client.send(&line));
+            }
+        }
+    }
+}
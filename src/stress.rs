@@ -0,0 +1,183 @@
+use nalgebra_glm::{Mat4, Vec3, Vec4};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+use crate::material::Material;
+use crate::orbit::Orbit;
+use crate::solar_system::SolarSystem;
+use crate::stats;
+
+// Bajo este radio en pantalla (pixeles) un cuerpo se dibuja como impostor y bajo
+// LOW_DETAIL_PIXELS con la esfera de pocos triángulos
+pub const IMPOSTOR_PIXELS: f32 = 3.0;
+pub const LOW_DETAIL_PIXELS: f32 = 24.0;
+// Los planetas generados orbitan por fuera de Neptuno, separados ORBIT_SPACING
+const FIRST_ORBIT: f64 = 18.0;
+const ORBIT_SPACING: f64 = 0.6;
+const MAX_MOONS: usize = 3;
+// Luz ambiente de los impostores (lineal), para que el lado nocturno no desaparezca del todo
+const IMPOSTOR_AMBIENT: f32 = 0.008;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StressKind {
+    Rocky,
+    Gas,
+    Moon,
+}
+
+impl StressKind {
+    // Clave de shader de la cola: comparten la de Marte, Neptuno y la Luna
+    pub fn shader(self) -> &'static str {
+        match self {
+            StressKind::Rocky => "mars",
+            StressKind::Gas => "neptune",
+            StressKind::Moon => "moon",
+        }
+    }
+
+    // Color medio del shader, para los impostores
+    fn color(self) -> Color {
+        match self {
+            StressKind::Rocky => Color::new(170, 90, 60),
+            StressKind::Gas => Color::new(70, 110, 200),
+            StressKind::Moon => Color::new(150, 150, 150),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct StressBody {
+    pub name: &'static str,
+    pub kind: StressKind,
+    // Variación del material sobre el shader compartido
+    pub hue_shift: f32,
+    pub noise_scale: f32,
+}
+
+impl StressBody {
+    pub fn material(&self, base: Material) -> Material {
+        let mut material = base;
+        material.params.hue_shift = self.hue_shift;
+        material.params.noise_scale = self.noise_scale;
+        material
+    }
+}
+
+// Nivel de detalle según el radio en pantalla
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Detail {
+    Full,
+    Low,
+    Impostor,
+}
+
+pub fn detail(pixels: f32) -> Detail {
+    if pixels < IMPOSTOR_PIXELS {
+        Detail::Impostor
+    } else if pixels < LOW_DETAIL_PIXELS {
+        Detail::Low
+    } else {
+        Detail::Full
+    }
+}
+
+// Escena de estrés (`--stress N`): N cuerpos generados, planetas con hasta
+// MAX_MOONS lunas cada uno, que se suman al sistema solar. La semilla es N, así
+// que dos corridas con el mismo N (p. ej. con --benchmark) ven la misma escena.
+pub struct StressScene {
+    pub bodies: Vec<StressBody>,
+}
+
+impl StressScene {
+    pub fn new(system: &mut SolarSystem, count: usize) -> Self {
+        let mut rng = StdRng::seed_from_u64(count as u64);
+        let mut bodies = Vec::with_capacity(count);
+        let mut planet = 0;
+        while bodies.len() < count {
+            planet += 1;
+            // Los nombres viven toda la ejecución, como los del sistema solar
+            let name: &'static str = Box::leak(format!("estres-{}", planet).into_boxed_str());
+            let kind = if rng.gen_bool(0.4) { StressKind::Gas } else { StressKind::Rocky };
+            let radius = match kind {
+                StressKind::Gas => rng.gen_range(0.5..1.0),
+                _ => rng.gen_range(0.2..0.5),
+            };
+            let distance = FIRST_ORBIT + planet as f64 * ORBIT_SPACING;
+            // Kepler: la velocidad angular cae con la distancia a la 3/2
+            let angular_speed = 0.9 / distance.powf(1.5) * if rng.gen_bool(0.1) { -1.0 } else { 1.0 };
+            let orbit = Orbit::circular(distance, angular_speed, rng.gen_range(-1.5..1.5));
+            system.add_body(name, radius, orbit, None, rng.gen_range(-0.1..0.15));
+            bodies.push(StressBody { name, kind, hue_shift: rng.gen_range(-60.0..60.0), noise_scale: rng.gen_range(0.5..2.0) });
+
+            let moons = rng.gen_range(0..=MAX_MOONS).min(count - bodies.len());
+            for moon in 1..=moons {
+                let moon_name: &'static str = Box::leak(format!("{}-{}", name, moon).into_boxed_str());
+                let moon_distance = radius * (1.6 + moon as f64 * 0.7);
+                let moon_orbit = Orbit::circular(moon_distance, 0.3 / moon_distance.sqrt(), rng.gen_range(-0.1..0.1));
+                system.add_body(moon_name, rng.gen_range(0.05..0.15), moon_orbit, Some(name), 0.0);
+                bodies.push(StressBody { name: moon_name, kind: StressKind::Moon, hue_shift: 0.0, noise_scale: rng.gen_range(0.5..2.0) });
+            }
+        }
+        StressScene { bodies }
+    }
+}
+
+// Radio en pantalla (pixeles) de una esfera en espacio de render: 0 detrás de la
+// cámara e infinito si la cámara está dentro
+pub fn projected_radius(view: &Mat4, projection: &Mat4, height: usize, center: Vec3, radius: f32) -> f32 {
+    let depth = -(view * Vec4::new(center.x, center.y, center.z, 1.0)).z;
+    if depth <= radius {
+        return if depth + radius > 0.0 { f32::INFINITY } else { 0.0 };
+    }
+    projection[(1, 1)] * radius / depth * height as f32 / 2.0
+}
+
+// Cuerpo demasiado chico para su malla: un disco del tamaño de su proyección,
+// sombreado por pixel como la esfera que representa (normal reconstruida desde el
+// centro del disco) y con prueba y escritura de profundidad
+pub struct Impostor {
+    pub center: Vec3,
+    pub pixels: f32,
+    pub color: Color,
+}
+
+impl Impostor {
+    pub fn new(center: Vec3, pixels: f32, kind: StressKind) -> Self {
+        Impostor { center, pixels, color: kind.color() }
+    }
+
+    pub fn draw(&self, framebuffer: &mut Framebuffer, view_projection: (&Mat4, &Mat4), viewport: &Mat4, light_position: Vec3) {
+        let (view, projection) = view_projection;
+        let clip = projection * view * Vec4::new(self.center.x, self.center.y, self.center.z, 1.0);
+        let radius = self.pixels.max(1.0);
+        let ndc = clip.xyz() / clip.w;
+        let screen = viewport * Vec4::new(ndc.x, ndc.y, ndc.z, 1.0);
+        let (width, height) = (framebuffer.width as f32, framebuffer.height as f32);
+        if clip.w <= 0.0 || screen.x + radius < 0.0 || screen.y + radius < 0.0 || screen.x - radius > width || screen.y - radius > height {
+            stats::add_culled_body();
+            return;
+        }
+        let light = (view * (light_position - self.center).push(0.0)).xyz().normalize();
+
+        stats::add_impostor();
+        let reach = radius.ceil() as isize;
+        for dy in -reach..=reach {
+            for dx in -reach..=reach {
+                let (nx, ny) = (dx as f32 / radius, -dy as f32 / radius);
+                let squared = nx * nx + ny * ny;
+                if squared > 1.0 {
+                    continue;
+                }
+                let (x, y) = (screen.x as isize + dx, screen.y as isize + dy);
+                if x < 0 || y < 0 {
+                    continue;
+                }
+                let normal = Vec3::new(nx, ny, (1.0 - squared).sqrt());
+                let diffuse = normal.dot(&light).max(0.0);
+                let light = IMPOSTOR_AMBIENT + (1.0 - IMPOSTOR_AMBIENT) * diffuse;
+                framebuffer.point_linear(x as usize, y as usize, ndc.z, self.color.to_linear().map(|channel| channel * light));
+            }
+        }
+    }
+}
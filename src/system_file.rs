@@ -0,0 +1,165 @@
+use std::fs;
+use nalgebra_glm::{DVec3, Vec3};
+use crate::scene::{SceneBody, SceneMesh};
+use crate::orbit::{Orbit, MAX_ECCENTRICITY};
+use crate::solar_system::{Body, SolarSystem};
+
+// Sistema definido en un archivo (`--system <ruta>`) en lugar del de SolarSystem::new.
+// Un `[[body]]` por cuerpo, los padres antes que sus satélites:
+//
+//   [camera]
+//   eye = [0.0, 10.0, 30.0]
+//   target = [0.0, 0.0, 0.0]
+//
+//   [[body]]
+//   name = "tatooine"
+//   radius = 0.5
+//   orbit = { radius = 6.0, speed = 0.04, height = 0.0 }
+//   # opcionales en `orbit`, los ángulos en grados: eccentricity, periapsis,
+//   # inclination, node (nodo ascendente) y anomaly (anomalía media al empezar)
+//   parent = "sun"            # opcional; sin él orbita el origen
+//   spin = 0.06               # opcional, radianes por unidad de tiempo
+//   day = 105.0               # opcional, en lugar de spin: período de rotación
+//   tilt = 23.4               # opcional, inclinación del eje en grados
+//   tidally_locked = false    # opcional
+//   position = [0.0, 0.0, 0.0]  # cuerpos sin órbita
+//   shader = "mars"           # shader propio o uno del registro (por defecto "default")
+//   mesh = "planet"           # "planet" o "ship"
+//
+// Ver assets/system.example.toml, que describe el sistema incorporado.
+pub struct SystemFile {
+    pub system: SolarSystem,
+    pub scene: Vec<SceneBody>,
+    // Cuerpos con un shader del registro (ver shader_registry.rs) en vez de uno propio
+    pub shader_assignments: Vec<(&'static str, String)>,
+    // Ojo y punto mirado
+    pub camera: Option<(Vec3, Vec3)>,
+}
+
+impl SystemFile {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|err| format!("no se pudo leer {}: {}", path, err))?;
+        SystemFile::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let document: toml::Table = text.parse().map_err(|e: toml::de::Error| e.to_string())?;
+        let entries = match document.get("body") {
+            Some(toml::Value::Array(entries)) => entries,
+            Some(_) => return Err(String::from("'body' debe ser una lista de tablas ([[body]])")),
+            None => return Err(String::from("el sistema no tiene cuerpos ([[body]])")),
+        };
+
+        let mut bodies: Vec<Body> = Vec::with_capacity(entries.len());
+        let mut scene = Vec::with_capacity(entries.len());
+        let mut shader_assignments = Vec::new();
+        for (index, entry) in entries.iter().enumerate() {
+            let entry = entry.as_table().ok_or_else(|| format!("body[{}] debe ser una tabla", index))?;
+            let name = entry
+                .get("name")
+                .and_then(toml::Value::as_str)
+                .ok_or_else(|| format!("body[{}] necesita un nombre (name)", index))?;
+            if bodies.iter().any(|body| body.name == name) {
+                return Err(format!("el cuerpo {} está repetido", name));
+            }
+            // Los nombres viven toda la ejecución, como los del sistema incorporado
+            let name: &'static str = Box::leak(name.to_string().into_boxed_str());
+            let field = |key: &str| format!("{}.{}", name, key);
+
+            let radius = number(entry, "radius", name)?.ok_or_else(|| format!("{} necesita un radio", name))?;
+            let orbit_field = field("orbit");
+            let orbit = match entry.get("orbit") {
+                None => None,
+                Some(toml::Value::Table(orbit)) => {
+                    let radius = number(orbit, "radius", &orbit_field)?.ok_or_else(|| format!("{} necesita orbit.radius", name))?;
+                    let speed = number(orbit, "speed", &orbit_field)?.ok_or_else(|| format!("{} necesita orbit.speed", name))?;
+                    let height = number(orbit, "height", &orbit_field)?.unwrap_or(0.0);
+                    let angle = |key: &str| number(orbit, key, &orbit_field).map(|degrees| degrees.unwrap_or(0.0).to_radians());
+                    let eccentricity = number(orbit, "eccentricity", &orbit_field)?.unwrap_or(0.0);
+                    if !(0.0..=MAX_ECCENTRICITY).contains(&eccentricity) {
+                        return Err(format!("{}.eccentricity debe estar entre 0 y {}", orbit_field, MAX_ECCENTRICITY));
+                    }
+                    Some(
+                        Orbit::circular(radius, speed, height)
+                            .with_eccentricity(eccentricity, angle("periapsis")?)
+                            .with_inclination(angle("inclination")?, angle("node")?)
+                            .with_mean_anomaly(angle("anomaly")?),
+                    )
+                }
+                Some(_) => return Err(format!("{} debe ser una tabla", orbit_field)),
+            };
+
+            let spin = match (number(entry, "spin", name)?, number(entry, "day", name)?) {
+                (Some(_), Some(_)) => return Err(format!("{} tiene spin y day: el giro se da con uno solo", name)),
+                (_, Some(0.0)) => return Err(format!("{} no puede ser 0", field("day"))),
+                (_, Some(day)) => std::f64::consts::TAU / day,
+                (spin, None) => spin.unwrap_or(0.0),
+            };
+            let tilt = number(entry, "tilt", name)?.unwrap_or(0.0).to_radians();
+            let mut body = Body::new(name, radius, orbit).spinning(spin).tilted(tilt);
+            if let Some(parent) = entry.get("parent") {
+                let parent = parent.as_str().ok_or_else(|| format!("{} debe ser un texto", field("parent")))?;
+                // Se actualizan en orden: el padre tiene que estar antes
+                let parent = bodies
+                    .iter()
+                    .map(|body| body.name)
+                    .find(|existing| *existing == parent)
+                    .ok_or_else(|| format!("el padre de {} ({}) tiene que aparecer antes", name, parent))?;
+                body = body.around(parent);
+            }
+            if let Some(value) = entry.get("tidally_locked") {
+                if value.as_bool().ok_or_else(|| format!("{} debe ser booleano", field("tidally_locked")))? {
+                    body = body.tidally_locked();
+                }
+            }
+            if let Some(value) = entry.get("position") {
+                body = body.at(vector(value).ok_or_else(|| format!("{} debe ser [x, y, z]", field("position")))?);
+            }
+
+            let mesh = match entry.get("mesh").map(|value| value.as_str()) {
+                None | Some(Some("planet")) => SceneMesh::Planet,
+                Some(Some("ship")) => SceneMesh::Ship,
+                _ => return Err(format!("{} debe ser \"planet\" o \"ship\"", field("mesh"))),
+            };
+            let shader = match entry.get("shader") {
+                None => "default",
+                Some(value) => value.as_str().ok_or_else(|| format!("{} debe ser un texto", field("shader")))?,
+            };
+            let scene_body = SceneBody::with_shader(name, shader, mesh).unwrap_or_else(|| {
+                shader_assignments.push((name, shader.to_string()));
+                SceneBody::new(name, "default", crate::render, mesh)
+            });
+
+            bodies.push(body);
+            scene.push(scene_body);
+        }
+
+        let camera = match document.get("camera") {
+            None => None,
+            Some(toml::Value::Table(camera)) => {
+                let eye = camera.get("eye").and_then(vector).ok_or("camera.eye debe ser [x, y, z]")?;
+                let target = camera.get("target").map_or(Some(DVec3::zeros()), vector).ok_or("camera.target debe ser [x, y, z]")?;
+                Some((eye.cast::<f32>(), target.cast::<f32>()))
+            }
+            Some(_) => return Err(String::from("'camera' debe ser una tabla")),
+        };
+
+        Ok(SystemFile { system: SolarSystem::from_bodies(bodies), scene, shader_assignments, camera })
+    }
+}
+
+// None si falta; error si no es un número (`prefix` arma la ruta del mensaje)
+fn number(table: &toml::Table, key: &str, prefix: &str) -> Result<Option<f64>, String> {
+    match table.get(key) {
+        None => Ok(None),
+        Some(toml::Value::Float(value)) => Ok(Some(*value)),
+        Some(toml::Value::Integer(value)) => Ok(Some(*value as f64)),
+        Some(_) => Err(format!("{}.{} debe ser un número", prefix, key)),
+    }
+}
+
+fn vector(value: &toml::Value) -> Option<DVec3> {
+    let array = value.as_array().filter(|array| array.len() == 3)?;
+    let component = |value: &toml::Value| value.as_float().or_else(|| value.as_integer().map(|value| value as f64));
+    Some(DVec3::new(component(&array[0])?, component(&array[1])?, component(&array[2])?))
+}
@@ -0,0 +1,93 @@
+use nalgebra_glm::{Mat3, Vec2, Vec3};
+use crate::varyings::Varyings;
+
+// Base tangente de un vértice para mapas de normales: la tangente sigue a +u y la
+// bitangente a +v de las coordenadas de textura, las dos perpendiculares a la
+// normal. Viaja como datos extra del vértice (ver varyings.rs) hasta el fragmento.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TangentFrame {
+    pub tangent: Vec3,
+    pub bitangent: Vec3,
+}
+
+impl Default for TangentFrame {
+    fn default() -> Self {
+        TangentFrame { tangent: Vec3::new(1.0, 0.0, 0.0), bitangent: Vec3::new(0.0, 0.0, 1.0) }
+    }
+}
+
+impl Varyings for TangentFrame {
+    fn interpolate(a: &Self, b: &Self, c: &Self, w1: f32, w2: f32, w3: f32) -> Self {
+        TangentFrame {
+            tangent: a.tangent * w1 + b.tangent * w2 + c.tangent * w3,
+            bitangent: a.bitangent * w1 + b.bitangent * w2 + c.bitangent * w3,
+        }
+    }
+}
+
+impl TangentFrame {
+    // Pasa la base al espacio del mundo con la parte lineal de la matriz de modelo
+    pub fn transformed(&self, model: &Mat3) -> Self {
+        TangentFrame { tangent: model * self.tangent, bitangent: model * self.bitangent }
+    }
+
+    // Matriz TBN (columnas tangente, bitangente y normal) para llevar una normal del
+    // mapa al mundo; la interpolación desnormaliza la base, así que se rehace aquí
+    pub fn tbn(&self, normal: &Vec3) -> Mat3 {
+        let normal = normal.normalize();
+        let tangent = orthogonal(&self.tangent, &normal);
+        let handedness = if normal.cross(&tangent).dot(&self.bitangent) < 0.0 { -1.0 } else { 1.0 };
+        Mat3::from_columns(&[tangent, normal.cross(&tangent) * handedness, normal])
+    }
+}
+
+// Una base por vértice de una malla indexada. Cada triángulo aporta su tangente y su
+// bitangente (sin normalizar, así pesan según el área) a sus tres vértices; después
+// la tangente se ortogonaliza contra la normal (Gram-Schmidt) y la bitangente se
+// rehace como normal × tangente con el signo de la acumulada, que cambia en las
+// texturas espejadas. Sin coordenadas de textura útiles se elige una tangente
+// cualquiera perpendicular a la normal.
+pub fn compute(positions: &[Vec3], normals: &[Vec3], texcoords: &[Vec2], indices: &[u32]) -> Vec<TangentFrame> {
+    let mut tangents = vec![Vec3::zeros(); positions.len()];
+    let mut bitangents = vec![Vec3::zeros(); positions.len()];
+
+    for tri in indices.chunks_exact(3) {
+        let [i0, i1, i2] = [tri[0] as usize, tri[1] as usize, tri[2] as usize];
+        let (Some(uv0), Some(uv1), Some(uv2)) = (texcoords.get(i0), texcoords.get(i1), texcoords.get(i2)) else {
+            continue;
+        };
+        let edge1 = positions[i1] - positions[i0];
+        let edge2 = positions[i2] - positions[i0];
+        let (duv1, duv2) = (uv1 - uv0, uv2 - uv0);
+        let determinant = duv1.x * duv2.y - duv2.x * duv1.y;
+        if determinant.abs() < 1e-12 {
+            continue;
+        }
+        let tangent = (edge1 * duv2.y - edge2 * duv1.y) / determinant;
+        let bitangent = (edge2 * duv1.x - edge1 * duv2.x) / determinant;
+        for i in [i0, i1, i2] {
+            tangents[i] += tangent;
+            bitangents[i] += bitangent;
+        }
+    }
+
+    (0..positions.len())
+        .map(|i| {
+            let normal = normals.get(i).map_or(Vec3::new(0.0, 1.0, 0.0), |normal| normal.normalize());
+            let tangent = orthogonal(&tangents[i], &normal);
+            let handedness = if normal.cross(&tangent).dot(&bitangents[i]) < 0.0 { -1.0 } else { 1.0 };
+            TangentFrame { tangent, bitangent: normal.cross(&tangent) * handedness }
+        })
+        .collect()
+}
+
+// `tangent` sin su componente sobre `normal`, normalizada; si no queda nada, un eje
+// perpendicular a la normal
+fn orthogonal(tangent: &Vec3, normal: &Vec3) -> Vec3 {
+    let projected = tangent - normal * normal.dot(tangent);
+    if projected.magnitude_squared() > 1e-12 {
+        return projected.normalize();
+    }
+    let axis = if normal.x.abs() < 0.9 { Vec3::new(1.0, 0.0, 0.0) } else { Vec3::new(0.0, 1.0, 0.0) };
+    (axis - normal * normal.dot(&axis)).normalize()
+}
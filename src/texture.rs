@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use nalgebra_glm::{Vec2, Vec3};
+use crate::color::Color;
+use crate::memory::{vec_bytes, MemoryUsage};
+use crate::normal_map;
+
+// Carpeta de los mapas de los cuerpos: <cuerpo>.png o <cuerpo>.jpg, y los de normales
+// como <cuerpo>_normal.png
+pub const TEXTURE_DIR: &str = "assets/textures";
+const NORMAL_SUFFIX: &str = "_normal";
+const EXTENSIONS: [&str; 3] = ["png", "jpg", "jpeg"];
+
+// Cómo se lee un texel entre los centros de la imagen
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TextureFilter {
+    // El texel más cercano: se ven los pixeles del mapa de cerca
+    Nearest,
+    // Promedio de los cuatro vecinos, pesado por la distancia (en luz lineal)
+    #[default]
+    Bilinear,
+}
+
+impl TextureFilter {
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "nearest" => Some(TextureFilter::Nearest),
+            "bilinear" => Some(TextureFilter::Bilinear),
+            _ => None,
+        }
+    }
+}
+
+// Mapa equirectangular de un planeta (p. ej. los de la NASA): u es la longitud y
+// da la vuelta completa, v la latitud (1 en el polo norte, la fila de arriba)
+#[derive(Debug, Clone)]
+pub struct Texture {
+    width: usize,
+    height: usize,
+    // sRGB, fila por fila desde arriba
+    texels: Vec<Color>,
+    pub filter: TextureFilter,
+}
+
+impl Texture {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let image = image::open(path).map_err(|err| format!("{}: {}", path.display(), err))?.to_rgb8();
+        let (width, height) = (image.width() as usize, image.height() as usize);
+        if width == 0 || height == 0 {
+            return Err(format!("{}: imagen vacía", path.display()));
+        }
+        let texels = image.pixels().map(|pixel| Color::new(pixel[0], pixel[1], pixel[2])).collect();
+        Ok(Texture { width, height, texels, filter: TextureFilter::default() })
+    }
+
+    // Texels en sRGB, fila por fila desde arriba (mapas generados)
+    pub fn from_texels(width: usize, height: usize, texels: Vec<Color>) -> Self {
+        assert_eq!(texels.len(), width * height);
+        Texture { width, height, texels, filter: TextureFilter::default() }
+    }
+
+    pub fn sample(&self, uv: Vec2) -> Color {
+        match self.filter {
+            TextureFilter::Nearest => self.sample_nearest(uv),
+            TextureFilter::Bilinear => self.sample_bilinear(uv),
+        }
+    }
+
+    pub fn sample_nearest(&self, uv: Vec2) -> Color {
+        let (x, y) = self.texel_position(uv);
+        self.texel(x.floor() as i64, y.floor() as i64)
+    }
+
+    pub fn sample_bilinear(&self, uv: Vec2) -> Color {
+        let (x, y) = self.texel_position(uv);
+        // Centros de los texels en x + 0.5
+        let (x, y) = (x - 0.5, y - 0.5);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+        let corners = [
+            (self.texel(x0, y0), (1.0 - tx) * (1.0 - ty)),
+            (self.texel(x0 + 1, y0), tx * (1.0 - ty)),
+            (self.texel(x0, y0 + 1), (1.0 - tx) * ty),
+            (self.texel(x0 + 1, y0 + 1), tx * ty),
+        ];
+        let mut sum = [0.0; 3];
+        for (color, weight) in corners {
+            let linear = color.to_linear();
+            for channel in 0..3 {
+                sum[channel] += linear[channel] * weight;
+            }
+        }
+        Color::from_linear(sum)
+    }
+
+    // Mapa de normales en espacio tangente: cada canal lleva [-1, 1] a [0, 255] sin
+    // gamma, así que se interpola tal cual (bilineal siempre, para no ver escalones)
+    pub fn sample_normal(&self, uv: Vec2) -> Vec3 {
+        let (x, y) = self.texel_position(uv);
+        let (x, y) = (x - 0.5, y - 0.5);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+        let decode = |color: Color| Vec3::new(color.r() as f32, color.g() as f32, color.b() as f32) / 127.5 - Vec3::new(1.0, 1.0, 1.0);
+        decode(self.texel(x0, y0)) * ((1.0 - tx) * (1.0 - ty))
+            + decode(self.texel(x0 + 1, y0)) * (tx * (1.0 - ty))
+            + decode(self.texel(x0, y0 + 1)) * ((1.0 - tx) * ty)
+            + decode(self.texel(x0 + 1, y0 + 1)) * (tx * ty)
+    }
+
+    // En texels, sin recortar
+    fn texel_position(&self, uv: Vec2) -> (f32, f32) {
+        (uv.x * self.width as f32, (1.0 - uv.y) * self.height as f32)
+    }
+
+    // La longitud da la vuelta; la latitud se queda en el borde de los polos
+    fn texel(&self, x: i64, y: i64) -> Color {
+        let x = x.rem_euclid(self.width as i64) as usize;
+        let y = y.clamp(0, self.height as i64 - 1) as usize;
+        self.texels[y * self.width + x]
+    }
+}
+
+impl MemoryUsage for Texture {
+    fn memory_bytes(&self) -> usize {
+        vec_bytes(&self.texels)
+    }
+}
+
+// Mapas cargados de TEXTURE_DIR, por cuerpo. Un cuerpo con mapa se dibuja con él en
+// lugar de su shader procedural (ver shaders::textured_shader); uno con mapa de
+// normales lo usa para el relieve (ver normal_map.rs).
+#[derive(Debug, Default)]
+pub struct TextureLibrary {
+    textures: HashMap<String, Arc<Texture>>,
+    normal_maps: HashMap<String, Arc<Texture>>,
+}
+
+impl TextureLibrary {
+    pub fn load_dir(dir: &str, filter: TextureFilter) -> Self {
+        let mut library = TextureLibrary::default();
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            // Sin la carpeta no hay mapas: todos los cuerpos son procedurales
+            Err(_) => return library,
+        };
+        for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+            let extension = path.extension().and_then(|ext| ext.to_str()).map(str::to_lowercase);
+            if !extension.is_some_and(|ext| EXTENSIONS.contains(&ext.as_str())) {
+                continue;
+            }
+            let Some(body) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            match Texture::load(&path) {
+                Ok(mut texture) => {
+                    texture.filter = filter;
+                    match body.strip_suffix(NORMAL_SUFFIX) {
+                        Some(body) => {
+                            println!("Mapa de normales de {}: {}x{}", body, texture.width, texture.height);
+                            library.normal_maps.insert(body.to_string(), Arc::new(texture));
+                        }
+                        None => {
+                            println!("Textura de {}: {}x{}", body, texture.width, texture.height);
+                            library.textures.insert(body.to_string(), Arc::new(texture));
+                        }
+                    }
+                }
+                Err(err) => eprintln!("No se pudo cargar la textura {}", err),
+            }
+        }
+        library
+    }
+
+    pub fn get(&self, body: &str) -> Option<Arc<Texture>> {
+        self.textures.get(body).map(Arc::clone)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.textures.is_empty()
+    }
+
+    // Mapa de normales del cuerpo; si no trae uno se genera un relieve la primera vez
+    // que se pide (ver normal_map::generate)
+    pub fn normal_map(&mut self, body: &str) -> Arc<Texture> {
+        let map = self.normal_maps.entry(body.to_string()).or_insert_with(|| {
+            let seed = body.bytes().fold(0i32, |hash, byte| hash.wrapping_mul(31).wrapping_add(byte as i32));
+            Arc::new(normal_map::generate(seed))
+        });
+        Arc::clone(map)
+    }
+}
+
+impl MemoryUsage for TextureLibrary {
+    fn memory_bytes(&self) -> usize {
+        self.textures.values().chain(self.normal_maps.values()).map(|texture| texture.memory_bytes()).sum()
+    }
+}
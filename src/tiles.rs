@@ -0,0 +1,110 @@
+use rayon::prelude::*;
+use crate::color::Color;
+use crate::fragment::Fragment;
+use crate::framebuffer::Framebuffer;
+use crate::shaders::shade;
+use crate::stats;
+use crate::triangle::{calculate_bounding_box, triangle_in};
+use crate::vertex::Vertex;
+use crate::Uniforms;
+
+// Lado de los bloques en que se divide la pantalla, en pixeles
+pub const TILE_SIZE: usize = 64;
+
+// Bloque de pantalla; los del borde derecho e inferior pueden ser más chicos
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tile {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+// Cada bloque con los índices de los triángulos cuya caja envolvente lo toca, en el
+// orden de dibujo. Los bloques sin triángulos no aparecen.
+pub fn bin(triangles: &[[Vertex; 3]], width: usize, height: usize) -> Vec<(Tile, Vec<usize>)> {
+    let (columns, rows) = (width.div_ceil(TILE_SIZE), height.div_ceil(TILE_SIZE));
+    let mut bins: Vec<Vec<usize>> = vec![Vec::new(); columns * rows];
+    for (index, tri) in triangles.iter().enumerate() {
+        let (a, b, c) = (tri[0].transformed_position, tri[1].transformed_position, tri[2].transformed_position);
+        let (min_x, min_y, max_x, max_y) = calculate_bounding_box(&a, &b, &c);
+        if max_x < 0 || max_y < 0 || min_x >= width as i32 || min_y >= height as i32 {
+            continue;
+        }
+        let column = |x: i32| (x.clamp(0, width as i32 - 1) as usize) / TILE_SIZE;
+        let row = |y: i32| (y.clamp(0, height as i32 - 1) as usize) / TILE_SIZE;
+        for tile_row in row(min_y)..=row(max_y) {
+            for tile_column in column(min_x)..=column(max_x) {
+                bins[tile_row * columns + tile_column].push(index);
+            }
+        }
+    }
+
+    bins.into_iter()
+        .enumerate()
+        .filter(|(_, triangles)| !triangles.is_empty())
+        .map(|(index, triangles)| {
+            let (x, y) = (index % columns * TILE_SIZE, index / columns * TILE_SIZE);
+            let tile = Tile { x, y, width: TILE_SIZE.min(width - x), height: TILE_SIZE.min(height - y) };
+            (tile, triangles)
+        })
+        .collect()
+}
+
+// Modo por bloques (`tiled` en [render], --tiled): cada bloque se procesa en su hilo
+// con una copia local de su parte del z-buffer. Primero se resuelve qué fragmento
+// queda más cerca en cada pixel y solo ese se sombrea; al final se escriben los
+// resultados de todos los bloques, que no se pisan entre sí. Sin carreras en el
+// z-buffer y con la misma imagen que el camino inmediato, que sombrea cada fragmento
+// que pasa la prueba de profundidad.
+pub fn render_tiled(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    triangles: &[[Vertex; 3]],
+    shader: impl Fn(&Fragment, &Uniforms) -> Color + Sync,
+) {
+    let bins = bin(triangles, framebuffer.width, framebuffer.height);
+    let target = &*framebuffer;
+    let results: Vec<_> = bins
+        .par_iter()
+        .map(|(tile, indices)| {
+            let mut depth: Vec<f32> = (tile.y..tile.y + tile.height)
+                .flat_map(|y| target.zbuffer[y * target.width + tile.x..y * target.width + tile.x + tile.width].iter().copied())
+                .collect();
+            let mut nearest: Vec<Option<Fragment>> = (0..depth.len()).map(|_| None).collect();
+
+            let mut fragment_count = 0;
+            for &index in indices {
+                let tri = &triangles[index];
+                for fragment in triangle_in(&tri[0], &tri[1], &tri[2], uniforms, tile) {
+                    fragment_count += 1;
+                    let (x, y) = (fragment.position.x as usize, fragment.position.y as usize);
+                    if !target.shades(x, y) {
+                        stats::add_skipped_fragment();
+                        continue;
+                    }
+                    let local = (y - tile.y) * tile.width + (x - tile.x);
+                    if depth[local] > fragment.depth {
+                        depth[local] = fragment.depth;
+                        nearest[local] = Some(fragment);
+                    }
+                }
+            }
+            stats::add_fragments(fragment_count);
+
+            // Índice local, profundidad y color (lineal) de cada pixel que queda escrito
+            let shaded: Vec<(usize, f32, [f32; 3])> = nearest
+                .iter()
+                .enumerate()
+                .filter_map(|(local, fragment)| fragment.as_ref().map(|fragment| (local, fragment.depth, shade(fragment, uniforms, &shader))))
+                .collect();
+            (*tile, shaded)
+        })
+        .collect();
+
+    for (tile, shaded) in results {
+        for (local, depth, color) in shaded {
+            framebuffer.point_linear(tile.x + local % tile.width, tile.y + local / tile.width, depth, color);
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use rayon::prelude::*;
+use crate::color::linear_to_srgb;
+
+// Curva con que el color de la escena (lineal y sin tope, ver Framebuffer::hdr) se
+// lleva al rango de la pantalla, después de multiplicarlo por la exposición
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ToneMapping {
+    // Ajuste de Narkowicz de la curva fílmica ACES: más contraste y un hombro suave
+    #[default]
+    Aces,
+    // x / (1 + x) por canal: nunca satura, pero aplana los tonos claros
+    Reinhard,
+    // Recorta en 1, como se veía antes del buffer HDR
+    Clamp,
+}
+
+impl ToneMapping {
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "aces" => Some(ToneMapping::Aces),
+            "reinhard" => Some(ToneMapping::Reinhard),
+            "none" => Some(ToneMapping::Clamp),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ToneMapping::Aces => "ACES",
+            ToneMapping::Reinhard => "Reinhard",
+            ToneMapping::Clamp => "sin mapeo",
+        }
+    }
+
+    fn map(self, value: f32) -> f32 {
+        match self {
+            ToneMapping::Aces => (value * (2.51 * value + 0.03)) / (value * (2.43 * value + 0.59) + 0.14),
+            ToneMapping::Reinhard => value / (1.0 + value),
+            ToneMapping::Clamp => value,
+        }
+    }
+}
+
+// Curva y exposición con que se presenta el frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToneMap {
+    pub mapping: ToneMapping,
+    // Multiplica el color HDR antes de la curva (1: sin cambio)
+    pub exposure: f32,
+}
+
+impl Default for ToneMap {
+    fn default() -> Self {
+        ToneMap { mapping: ToneMapping::default(), exposure: 1.0 }
+    }
+}
+
+// Escribe cada pixel de `hdr` en `output` (0xRRGGBB, sRGB) como lo presenta minifb
+pub fn apply(hdr: &[[f32; 3]], output: &mut [u32], tone_map: ToneMap) {
+    let ToneMap { mapping, exposure } = tone_map;
+    output.par_iter_mut().zip(hdr.par_iter()).for_each(|(pixel, color)| {
+        let [r, g, b] = color.map(|channel| linear_to_srgb(mapping.map(channel.max(0.0) * exposure)) as u32);
+        *pixel = (r << 16) | (g << 8) | b;
+    });
+}
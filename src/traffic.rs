@@ -0,0 +1,79 @@
+use nalgebra_glm::DVec3;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use crate::solar_system::SolarSystem;
+
+// Planetas entre los que viajan las naves de tráfico
+const PORTS: [&str; 8] = ["mercury", "venus", "earth", "mars", "jupiter", "saturn", "uranus", "neptune"];
+
+// Unidades de distancia por unidad de tiempo de simulación
+const CRUISE_SPEED: f64 = 0.08;
+// Altura máxima del arco de la ruta sobre el plano orbital
+const ARC_HEIGHT: f64 = 1.0;
+// Probabilidad de que salga una nave en un paso si hay lugar
+const SPAWN_CHANCE: f64 = 0.05;
+
+pub const NPC_SCALE: f32 = 0.3;
+
+#[derive(Debug, Clone)]
+pub struct NpcShip {
+    pub from: &'static str,
+    pub to: &'static str,
+    // 0 al despegar, 1 al llegar
+    pub progress: f64,
+    duration: f64,
+    pub position: DVec3,
+}
+
+// Naves de fondo que viajan entre planetas al azar
+#[derive(Debug, Default)]
+pub struct Traffic {
+    pub ships: Vec<NpcShip>,
+    // Máximo de naves en vuelo a la vez
+    pub density: usize,
+}
+
+impl Traffic {
+    pub fn new(density: usize) -> Self {
+        Traffic { ships: Vec::with_capacity(density), density }
+    }
+
+    // Avanza los vuelos y devuelve los que llegaron en este paso
+    pub fn update(&mut self, system: &SolarSystem, dt: f64, rng: &mut impl Rng) -> Vec<NpcShip> {
+        if self.ships.len() < self.density && rng.gen_bool(SPAWN_CHANCE) {
+            let mut ports = PORTS.choose_multiple(rng, 2);
+            if let (Some(&from), Some(&to)) = (ports.next(), ports.next()) {
+                let distance = (system.position(to) - system.position(from)).magnitude();
+                let mut ship = NpcShip { from, to, progress: 0.0, duration: (distance / CRUISE_SPEED).max(1.0), position: DVec3::zeros() };
+                ship.position = route_position(system, &ship);
+                self.ships.push(ship);
+            }
+        }
+
+        for ship in &mut self.ships {
+            ship.progress = (ship.progress + dt / ship.duration).min(1.0);
+            ship.position = route_position(system, ship);
+        }
+
+        let (arrived, flying) = std::mem::take(&mut self.ships).into_iter().partition(|ship| ship.progress >= 1.0);
+        self.ships = flying;
+        arrived
+    }
+}
+
+// Ruta suavizada entre las superficies de ambos planetas, que siguen moviéndose
+fn route_position(system: &SolarSystem, ship: &NpcShip) -> DVec3 {
+    let from = system.position(ship.from);
+    let to = system.position(ship.to);
+    let direction = (to - from).try_normalize(1e-9).unwrap_or(DVec3::new(1.0, 0.0, 0.0));
+    let start = from + direction * system.radius(ship.from) * 1.5;
+    let end = to - direction * system.radius(ship.to) * 1.5;
+
+    let t = ease_in_out(ship.progress);
+    let arc = DVec3::new(0.0, (ship.progress * std::f64::consts::PI).sin() * ARC_HEIGHT, 0.0);
+    start + (end - start) * t + arc
+}
+
+fn ease_in_out(t: f64) -> f64 {
+    t * t * (3.0 - 2.0 * t)
+}
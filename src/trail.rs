@@ -0,0 +1,103 @@
+use nalgebra_glm::DVec3;
+use crate::color::Color;
+use crate::debug_draw;
+use crate::effects_budget;
+use crate::framebuffer::Framebuffer;
+use crate::memory::{vec_bytes, MemoryUsage};
+use crate::solar_system::{self, SolarSystem};
+use crate::Uniforms;
+
+// Posiciones que se dibujan de cada rastro (una por paso de la simulación) con el
+// presupuesto de efectos al 100%; se guardan las que pide el presupuesto máximo
+pub const TRAIL_LENGTH: usize = 120;
+
+// Últimas posiciones de un cuerpo en un buffer circular: la más nueva pisa a la
+// más vieja, así que guardar una cuesta lo mismo con el buffer lleno o no
+#[derive(Debug, Clone)]
+pub struct Trail {
+    points: Vec<DVec3>,
+    // Índice donde se escribe la próxima
+    next: usize,
+    capacity: usize,
+}
+
+impl Trail {
+    pub fn new(capacity: usize) -> Self {
+        Trail { points: Vec::with_capacity(capacity), next: 0, capacity: capacity.max(1) }
+    }
+
+    pub fn push(&mut self, position: DVec3) {
+        if self.points.len() < self.capacity {
+            self.points.push(position);
+        } else {
+            self.points[self.next] = position;
+        }
+        self.next = (self.next + 1) % self.capacity;
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    // De la más nueva a la más vieja
+    pub fn iter(&self) -> impl Iterator<Item = DVec3> + '_ {
+        let len = self.points.len();
+        (0..len).map(move |i| self.points[(self.next + len - 1 - i) % len])
+    }
+
+    // Polilínea por las `count` posiciones más nuevas, con prueba de profundidad y
+    // apagándose hacia la punta vieja; se mezcla según el material "trail" de los
+    // `uniforms` (matriz de modelo identidad, ver trail_uniforms en main).
+    pub fn draw(&self, framebuffer: &mut Framebuffer, uniforms: &Uniforms, origin: DVec3, color: Color, count: usize) {
+        let count = count.min(self.len());
+        if count < 2 {
+            return;
+        }
+        let points: Vec<_> = self.iter().take(count).map(|position| solar_system::to_render(position, origin)).collect();
+        for (i, segment) in points.windows(2).enumerate() {
+            let fade = 1.0 - i as f32 / (count - 1) as f32;
+            debug_draw::draw_segment_blended(framebuffer, uniforms, segment[0], segment[1], color, fade);
+        }
+    }
+}
+
+impl MemoryUsage for Trail {
+    fn memory_bytes(&self) -> usize {
+        vec_bytes(&self.points)
+    }
+}
+
+// Rastros de los cuerpos que dejan uno: se alimentan después de cada paso de la
+// simulación y se dibujan en la capa de rastros
+pub struct Trails {
+    trails: Vec<(&'static str, Color, Trail)>,
+}
+
+impl Trails {
+    pub fn new(bodies: &[(&'static str, Color)]) -> Self {
+        let capacity = effects_budget::scaled(TRAIL_LENGTH, effects_budget::MAX_SCALE);
+        Trails { trails: bodies.iter().map(|&(body, color)| (body, color, Trail::new(capacity))).collect() }
+    }
+
+    pub fn record(&mut self, system: &SolarSystem) {
+        for (body, _, trail) in &mut self.trails {
+            if let Some(body) = system.body(body) {
+                trail.push(body.position);
+            }
+        }
+    }
+
+    // `effects_scale` recorta el largo dibujado (ver effects_budget.rs)
+    pub fn draw(&self, framebuffer: &mut Framebuffer, uniforms: &Uniforms, origin: DVec3, effects_scale: f32) {
+        let count = effects_budget::scaled(TRAIL_LENGTH, effects_scale);
+        for (_, color, trail) in &self.trails {
+            trail.draw(framebuffer, uniforms, origin, *color, count);
+        }
+    }
+}
+
+impl MemoryUsage for Trails {
+    fn memory_bytes(&self) -> usize {
+        self.trails.iter().map(|(_, _, trail)| trail.memory_bytes()).sum()
+    }
+}
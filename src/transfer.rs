@@ -0,0 +1,178 @@
+use std::f64::consts::PI;
+use nalgebra_glm::DVec3;
+use crate::framebuffer::Framebuffer;
+use crate::hud;
+use crate::orbit::Orbit;
+use crate::solar_system::SolarSystem;
+use crate::text;
+
+// Ventanas que se muestran en el panel
+const SHOWN_PLANS: usize = 5;
+
+// Transferencia de Hohmann entre dos órbitas circulares, con la salida en un
+// instante concreto. Un "día" es una unidad de tiempo de simulación.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransferPlan {
+    pub from: &'static str,
+    pub to: &'static str,
+    pub departure: f64,
+    pub time_of_flight: f64,
+    // Suma de las dos maniobras de Hohmann
+    pub delta_v: f64,
+    // Cuánto le falta (o le sobra) al destino para estar en el punto de llegada, en radianes
+    pub phase_error: f64,
+    // delta-v más la corrección aproximada para compensar el error de fase
+    pub cost: f64,
+    departure_angle: f64,
+    r1: f64,
+    r2: f64,
+    mu: f64,
+    direction: f64,
+}
+
+impl TransferPlan {
+    pub fn arrival(&self) -> f64 {
+        self.departure + self.time_of_flight
+    }
+
+    // Punto de la media elipse de transferencia en `time` (None fuera del vuelo).
+    // Resuelve la ecuación de Kepler para que la nave acelere en el periapsis.
+    pub fn position_at(&self, time: f64) -> Option<DVec3> {
+        let t = (time - self.departure) / self.time_of_flight;
+        if !(0.0..=1.0).contains(&t) {
+            return None;
+        }
+        let semi_major = (self.r1 + self.r2) / 2.0;
+        let eccentricity = (self.r2 - self.r1).abs() / (self.r1 + self.r2);
+        // Hacia afuera se sale del periapsis; hacia adentro, del apoapsis
+        let start_anomaly = if self.r2 >= self.r1 { 0.0 } else { PI };
+        let mean_anomaly = start_anomaly + PI * t;
+
+        let mut eccentric = mean_anomaly;
+        for _ in 0..8 {
+            eccentric -= (eccentric - eccentricity * eccentric.sin() - mean_anomaly) / (1.0 - eccentricity * eccentric.cos());
+        }
+        let true_anomaly = 2.0 * ((1.0 + eccentricity).sqrt() * (eccentric / 2.0).sin())
+            .atan2((1.0 - eccentricity).sqrt() * (eccentric / 2.0).cos());
+        let radius = semi_major * (1.0 - eccentricity * eccentric.cos());
+
+        let angle = self.departure_angle + self.direction * (true_anomaly - start_anomaly);
+        Some(DVec3::new(radius * angle.cos(), 0.0, radius * angle.sin()))
+    }
+}
+
+// Tiempo de vuelo y delta-v total de una transferencia de Hohmann
+pub fn hohmann(r1: f64, r2: f64, mu: f64) -> (f64, f64) {
+    let semi_major = (r1 + r2) / 2.0;
+    let time_of_flight = PI * (semi_major.powi(3) / mu).sqrt();
+    let burn1 = (mu / r1).sqrt() * ((2.0 * r2 / (r1 + r2)).sqrt() - 1.0);
+    let burn2 = (mu / r2).sqrt() * (1.0 - (2.0 * r1 / (r1 + r2)).sqrt());
+    (time_of_flight, burn1.abs() + burn2.abs())
+}
+
+// Las órbitas de la escena no cumplen la tercera ley de Kepler entre sí, así que
+// el parámetro gravitacional del Sol se deduce de la órbita de origen (μ = ω² r³):
+// la nave sale moviéndose exactamente como su planeta. Un origen quieto o en el
+// centro daría μ = 0 y tiempos de vuelo infinitos.
+fn sun_mu(name: &str, orbit: &Orbit) -> Result<f64, String> {
+    let mu = orbit.angular_speed.powi(2) * orbit.radius.powi(3);
+    if mu > 0.0 && mu.is_finite() {
+        Ok(mu)
+    } else {
+        Err(format!("{} no se mueve en su órbita; no se puede calcular la transferencia", name))
+    }
+}
+
+fn wrap_angle(angle: f64) -> f64 {
+    (angle + PI).rem_euclid(2.0 * PI) - PI
+}
+
+// Barre las salidas de los próximos `days` días y devuelve los planes, el mejor primero
+pub fn plan(system: &SolarSystem, from: &str, to: &str, days: usize) -> Result<Vec<TransferPlan>, String> {
+    let planet_orbit = |name: &str| -> Result<(&'static str, Orbit), String> {
+        let body = system.body(name).ok_or_else(|| format!("Cuerpo desconocido: {}", name))?;
+        match (body.orbit, body.parent) {
+            (Some(orbit), None) => Ok((body.name, orbit)),
+            _ => Err(format!("{} no orbita el Sol", name)),
+        }
+    };
+    let (from, origin) = planet_orbit(from)?;
+    let (to, destination) = planet_orbit(to)?;
+    if from == to {
+        return Err(String::from("El origen y el destino deben ser distintos"));
+    }
+
+    if destination.radius <= 0.0 {
+        return Err(format!("{} está en el centro; no hay órbita a la que llegar", to));
+    }
+    let mu = sun_mu(from, &origin)?;
+    let (time_of_flight, delta_v) = hohmann(origin.radius, destination.radius, mu);
+    let direction = origin.angular_speed.signum();
+    let destination_speed = destination.angular_speed.abs() * destination.radius;
+
+    let mut plans: Vec<TransferPlan> = (0..days)
+        .map(|day| {
+            let departure = system.time + day as f64;
+            let departure_angle = origin.angle_at(departure);
+            let arrival_angle = departure_angle + direction * PI;
+            let phase_error = wrap_angle(destination.angle_at(departure + time_of_flight) - arrival_angle);
+            TransferPlan {
+                from,
+                to,
+                departure,
+                time_of_flight,
+                delta_v,
+                phase_error,
+                cost: delta_v + phase_error.abs() * destination_speed,
+                departure_angle,
+                r1: origin.radius,
+                r2: destination.radius,
+                mu,
+                direction,
+            }
+        })
+        .collect();
+    plans.sort_by(|a, b| a.cost.total_cmp(&b.cost));
+    Ok(plans)
+}
+
+// Panel del planificador: las mejores ventanas del último cálculo
+#[derive(Debug, Default)]
+pub struct TransferPanel {
+    pub plans: Vec<TransferPlan>,
+    pub visible: bool,
+}
+
+impl TransferPanel {
+    pub fn show(&mut self, plans: Vec<TransferPlan>) {
+        self.plans = plans;
+        self.plans.truncate(SHOWN_PLANS);
+        self.visible = true;
+    }
+
+    // Esquina inferior izquierda, debajo de todo lo demás del HUD
+    pub fn draw(&self, framebuffer: &mut Framebuffer, now: f64) {
+        let Some(best) = self.plans.first().filter(|_| self.visible) else {
+            return;
+        };
+        let width = 420.min(framebuffer.width);
+        let height = (self.plans.len() + 3) * text::LINE_HEIGHT + 8;
+        let y = framebuffer.height.saturating_sub(height);
+        hud::dim_rect(framebuffer, 0, y, width, height);
+
+        let title = format!("Transferencia {} -> {} (vuelo {:.0} días)", best.from, best.to, best.time_of_flight);
+        text::draw_text_shadowed(framebuffer, 6, y + 4, &title, 0x88CCFF, 1);
+        text::draw_text_shadowed(framebuffer, 6, y + 4 + text::LINE_HEIGHT, " #  sale en   dv      fase    costo", 0xAAAAAA, 1);
+        for (i, plan) in self.plans.iter().enumerate() {
+            let line = format!(
+                "{:>2}  {:>7.0}  {:>6.3}  {:>+6.1}  {:>6.3}",
+                i + 1,
+                plan.departure - now,
+                plan.delta_v,
+                plan.phase_error.to_degrees(),
+                plan.cost
+            );
+            text::draw_text_shadowed(framebuffer, 6, y + 4 + (i + 2) * text::LINE_HEIGHT, &line, 0xFFFFFF, 1);
+        }
+    }
+}
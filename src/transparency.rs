@@ -0,0 +1,145 @@
+use crate::color::Color;
+use crate::fragment::Fragment;
+use crate::framebuffer::Framebuffer;
+use crate::material::BlendMode;
+use crate::memory::{vec_bytes, MemoryUsage};
+use crate::shaders::{self, write_fragment};
+use crate::stats;
+use crate::vertex::Vertex;
+use crate::Uniforms;
+
+// Peso del frame actual al acumular la transparencia estocástica; con 0.3 la
+// imagen converge en unos 6-8 frames
+const HISTORY_BLEND: f32 = 0.3;
+
+// Cómo se dibujan los materiales translúcidos (anillos, atmósferas)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Transparency {
+    // Mezcla alfa de atrás hacia adelante: depende del orden de dibujo
+    #[default]
+    Sorted,
+    // "Screen-door": cada fragmento se dibuja opaco o se descarta comparando su
+    // opacidad con un umbral de ruido que cambia cada frame, y la acumulación
+    // temporal promedia el resultado. No depende del orden y funciona también
+    // con el camino diferido.
+    Stochastic,
+}
+
+impl Transparency {
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "sorted" => Some(Transparency::Sorted),
+            "stochastic" => Some(Transparency::Stochastic),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Transparency::Sorted => "mezcla ordenada",
+            Transparency::Stochastic => "estocástica",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Transparency::Sorted => Transparency::Stochastic,
+            Transparency::Stochastic => Transparency::Sorted,
+        }
+    }
+}
+
+// Umbral por pixel en [0, 1): ruido de gradiente intercalado (Jimenez 2014), una
+// aproximación barata de ruido azul, desplazado cada frame por la razón áurea
+// para que los pixeles descartados no se repitan
+pub fn threshold(x: usize, y: usize, frame: u32) -> f32 {
+    let noise = (52.982_918 * (0.067_110_56 * x as f32 + 0.005_837_15 * y as f32).fract()).fract();
+    (noise + frame as f32 * 0.618_034).fract()
+}
+
+// Triángulos de una malla translúcida de atrás hacia adelante (por la profundidad de
+// su centro), para que la mezcla encima vea primero las caras lejanas; la cola de
+// dibujo ya ordena así los objetos entre sí
+pub fn sort_back_to_front(triangles: &mut [[Vertex; 3]]) {
+    let depth = |tri: &[Vertex; 3]| tri.iter().map(|vertex| vertex.transformed_position.z).sum::<f32>();
+    triangles.sort_by(|a, b| depth(b).total_cmp(&depth(a)));
+}
+
+// Escribe un fragmento translúcido con su `alpha` y el modo de mezcla del material
+pub fn write_translucent_fragment<V>(
+    framebuffer: &mut Framebuffer,
+    fragment: &Fragment<V>,
+    uniforms: &Uniforms,
+    shader: impl Fn(&Fragment<V>, &Uniforms) -> Color,
+) {
+    let x = fragment.position.x as usize;
+    let y = fragment.position.y as usize;
+    if !framebuffer.shades(x, y) {
+        stats::add_skipped_fragment();
+        return;
+    }
+    let alpha = fragment.alpha.clamp(0.0, 1.0);
+    if alpha <= 0.0 {
+        return;
+    }
+
+    // La suma no depende del orden: se mezcla directo con cualquier modo
+    if uniforms.material.blend == BlendMode::Additive {
+        let color = shaders::shade(fragment, uniforms, shader);
+        framebuffer.blend_point(x, y, fragment.depth, color, alpha, BlendMode::Additive);
+        return;
+    }
+
+    // El G-buffer guarda una sola superficie por pixel: ahí solo sirve la estocástica
+    #[cfg(feature = "deferred")]
+    let sorted = framebuffer.transparency == Transparency::Sorted && !framebuffer.deferred_active();
+    #[cfg(not(feature = "deferred"))]
+    let sorted = framebuffer.transparency == Transparency::Sorted;
+
+    if sorted {
+        let color = shaders::shade(fragment, uniforms, shader);
+        framebuffer.blend_point(x, y, fragment.depth, color, alpha, BlendMode::Over);
+        return;
+    }
+
+    framebuffer.mark_translucent(x, y, fragment.depth);
+    if alpha >= threshold(x, y, framebuffer.frame) {
+        write_fragment(framebuffer, fragment, uniforms, shader);
+    }
+}
+
+// Acumulación temporal de los pixeles cubiertos por materiales estocásticos (este
+// frame o el anterior): promedia las muestras de varios frames. Con la cámara en
+// movimiento deja algo de estela en esas zonas.
+#[derive(Default)]
+pub struct TemporalResolve {
+    history: Vec<[f32; 3]>,
+    previous_mask: Vec<bool>,
+}
+
+impl TemporalResolve {
+    pub fn resolve(&mut self, framebuffer: &mut Framebuffer) {
+        if framebuffer.transparency != Transparency::Stochastic {
+            self.history.clear();
+            self.previous_mask.clear();
+            return;
+        }
+        if self.history.len() == framebuffer.hdr.len() {
+            for index in 0..framebuffer.hdr.len() {
+                if framebuffer.translucent[index] || self.previous_mask[index] {
+                    let previous = self.history[index];
+                    let current = &mut framebuffer.hdr[index];
+                    (0..3).for_each(|channel| current[channel] = previous[channel] + (current[channel] - previous[channel]) * HISTORY_BLEND);
+                }
+            }
+        }
+        self.history.clone_from(&framebuffer.hdr);
+        self.previous_mask.clone_from(&framebuffer.translucent);
+    }
+}
+
+impl MemoryUsage for TemporalResolve {
+    fn memory_bytes(&self) -> usize {
+        vec_bytes(&self.history) + vec_bytes(&self.previous_mask)
+    }
+}
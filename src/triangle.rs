@@ -1,50 +1,164 @@
 use nalgebra_glm::{Vec3, dot};
 use crate::fragment::Fragment;
 use crate::vertex::Vertex;
-use crate::color::Color;
+use crate::clipping;
+use crate::material::{CullMode, FrontFace, NormalMode};
+use crate::varyings::Varyings;
+use crate::lighting;
+use crate::tiles::Tile;
+use crate::Uniforms;
+
+// Ensamblado de primitivas: arma un triángulo por cada tres índices, lo recorta contra
+// el volumen de vista, descarta las caras traseras si el material lo pide y, en modo
+// plano, reemplaza sus normales por la normal geométrica de la cara
+pub fn assemble_triangles<V: Varyings>(vertices: &[Vertex<V>], indices: &[u32], uniforms: &Uniforms) -> Vec<[Vertex<V>; 3]> {
+  let material = &uniforms.material;
+  let vertex = |index: u32| vertices[index as usize].clone();
+  indices
+    .chunks_exact(3)
+    .flat_map(|tri| clipping::clip_triangle([vertex(tri[0]), vertex(tri[1]), vertex(tri[2])], &uniforms.viewport_matrix))
+    .filter(|tri| material.cull == CullMode::None || faces_camera(tri, material.front_face))
+    .map(|mut tri| {
+      if material.normals == NormalMode::Flat {
+        flatten_normals(&mut tri);
+      }
+      tri
+    })
+    .collect()
+}
 
-pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragment> {
-  let mut fragments = Vec::new();
+// Orden de los vértices ya en pantalla. La Y de pantalla crece hacia abajo, así que
+// un triángulo antihorario en el modelo tiene área positiva con `edge_function`.
+// Los degenerados (área 0) no producen fragmentos y se descartan también.
+fn faces_camera<V>(tri: &[Vertex<V>], front_face: FrontFace) -> bool {
+  let area = edge_function(&tri[0].transformed_position, &tri[1].transformed_position, &tri[2].transformed_position);
+  match front_face {
+    FrontFace::CounterClockwise => area > 0.0,
+    FrontFace::Clockwise => area < 0.0,
+  }
+}
+
+fn flatten_normals<V>(tri: &mut [Vertex<V>; 3]) {
+  let edge1 = tri[1].world_position - tri[0].world_position;
+  let edge2 = tri[2].world_position - tri[0].world_position;
+  let face_normal = edge1.cross(&edge2);
+  if face_normal.magnitude_squared() == 0.0 {
+    return;
+  }
+
+  // El orden de los vértices puede no coincidir con las normales del modelo:
+  // se orienta la normal de cara hacia el mismo lado que las de vértice
+  let average = tri[0].transformed_normal + tri[1].transformed_normal + tri[2].transformed_normal;
+  let mut face_normal = face_normal.normalize();
+  if dot(&face_normal, &average) < 0.0 {
+    face_normal = -face_normal;
+  }
+
+  for vertex in tri.iter_mut() {
+    vertex.transformed_normal = face_normal;
+  }
+}
+
+// Rasteriza un triángulo. La cobertura y la profundidad usan las coordenadas
+// baricéntricas de pantalla; la normal, las posiciones, el color de vértice y los
+// datos extra (`V`) se corrigen por perspectiva (pesos divididos por la w de recorte
+// de cada vértice), así no se deforman en triángulos grandes y cercanos
+pub fn triangle<V: Varyings>(v1: &Vertex<V>, v2: &Vertex<V>, v3: &Vertex<V>, uniforms: &Uniforms) -> Vec<Fragment<V>> {
   let (a, b, c) = (v1.transformed_position, v2.transformed_position, v3.transformed_position);
+  rasterize(v1, v2, v3, uniforms, calculate_bounding_box(&a, &b, &c))
+}
 
+// Solo los fragmentos que caen en `tile` (ver tiles.rs)
+pub fn triangle_in<V: Varyings>(v1: &Vertex<V>, v2: &Vertex<V>, v3: &Vertex<V>, uniforms: &Uniforms, tile: &Tile) -> Vec<Fragment<V>> {
+  let (a, b, c) = (v1.transformed_position, v2.transformed_position, v3.transformed_position);
   let (min_x, min_y, max_x, max_y) = calculate_bounding_box(&a, &b, &c);
+  let (tile_x, tile_y) = (tile.x as i32, tile.y as i32);
+  let bounds = (
+    min_x.max(tile_x),
+    min_y.max(tile_y),
+    max_x.min(tile_x + tile.width as i32 - 1),
+    max_y.min(tile_y + tile.height as i32 - 1),
+  );
+  rasterize(v1, v2, v3, uniforms, bounds)
+}
 
-  let light_dir = Vec3::new(0.0, 0.0, 1.0);
+fn rasterize<V: Varyings>(v1: &Vertex<V>, v2: &Vertex<V>, v3: &Vertex<V>, uniforms: &Uniforms, bounds: (i32, i32, i32, i32)) -> Vec<Fragment<V>> {
+  let mut fragments = Vec::new();
+  let (a, b, c) = (v1.transformed_position, v2.transformed_position, v3.transformed_position);
 
-  let triangle_area = edge_function(&a, &b, &c);
+  let (min_x, min_y, max_x, max_y) = bounds;
+
+  // Aristas en punto fijo (SUBPIXEL pasos por pixel): b→c, c→a y a→b, cada una
+  // opuesta al vértice cuyo peso da
+  let [fa, fb, fc] = [a, b, c].map(|v| (snap(v.x), snap(v.y)));
+  let mut area = fixed_edge(fa, fb, fc);
+  if area == 0 {
+    return fragments;
+  }
+  // Con cualquier orden de vértices el interior queda del lado positivo
+  let orientation = area.signum();
+  area *= orientation;
+  let edges = [(fb, fc), (fc, fa), (fa, fb)];
+  let bias = edges.map(|(from, to)| if is_top_left(from, to, orientation) { 0 } else { -1 });
+  // Después del recorte cercano w es positiva
+  let inverse_w = [v1.clip_position.w, v2.clip_position.w, v3.clip_position.w].map(|w| 1.0 / w.max(f32::EPSILON));
 
   for y in min_y..=max_y {
     for x in min_x..=max_x {
-      let point = Vec3::new(x as f32 + 0.5, y as f32 + 0.5, 0.0);
+      // Centro del pixel
+      let point = (x as i64 * SUBPIXEL + SUBPIXEL / 2, y as i64 * SUBPIXEL + SUBPIXEL / 2);
+      let e = edges.map(|(from, to)| fixed_edge(from, to, point) * orientation);
+
+      // Regla superior-izquierda: un pixel justo sobre una arista compartida es del
+      // triángulo para el que esa arista es superior o izquierda, nunca de los dos
+      if (0..3).all(|i| e[i] + bias[i] >= 0) {
+        let (w1, w2, w3) = (e[0] as f32 / area as f32, e[1] as f32 / area as f32, e[2] as f32 / area as f32);
 
-      let (w1, w2, w3) = barycentric_coordinates(&point, &a, &b, &c, triangle_area);
+        let depth = a.z * w1 + b.z * w2 + c.z * w3;
 
-      if w1 >= 0.0 && w1 <= 1.0 && 
-         w2 >= 0.0 && w2 <= 1.0 &&
-         w3 >= 0.0 && w3 <= 1.0 {
+        // Pesos corregidos por perspectiva para el resto de los atributos
+        let (p1, p2, p3) = (w1 * inverse_w[0], w2 * inverse_w[1], w3 * inverse_w[2]);
+        let sum = p1 + p2 + p3;
+        let (w1, w2, w3) = (p1 / sum, p2 / sum, p3 / sum);
 
         let normal = v1.transformed_normal * w1 + v2.transformed_normal * w2 + v3.transformed_normal * w3;
         let normal = normal.normalize();
 
-        let intensity = dot(&normal, &light_dir).max(0.0);
-
-        let base_color = Color::new(100, 100, 100);
-        let lit_color = base_color * intensity;
-
-        let depth = a.z * w1 + b.z * w2 + c.z * w3;
+        let world_position = v1.world_position * w1 + v2.world_position * w2 + v3.world_position * w3;
+        let view_dir = (uniforms.camera_position - world_position).normalize();
+        // Los cuerpos sin iluminación (el Sol) usan N·V para oscurecer el borde
+        let intensity = if uniforms.material.unlit {
+          lighting::diffuse(&normal, &view_dir, &view_dir, false)
+        } else {
+          uniforms.lights.first().map_or(0.0, |light| {
+            lighting::diffuse(&normal, &light.direction(&world_position), &view_dir, uniforms.material.two_sided)
+          })
+        };
+        // Solo del lado de día: del otro ya no llega luz
+        let sunlit = match &uniforms.shadow_map {
+          Some(shadow_map) if intensity > 0.0 && !uniforms.material.unlit => shadow_map.visibility(&world_position),
+          _ => 1.0,
+        };
+
+        let color = Varyings::interpolate(&v1.color, &v2.color, &v3.color, w1, w2, w3);
 
         let vertex_position = v1.position * w1 + v2.position * w2 + v3.position * w3;
+        let tex_coords = v1.tex_coords * w1 + v2.tex_coords * w2 + v3.tex_coords * w3;
 
         fragments.push(
             Fragment::new(
                 x as f32,
                 y as f32,
-                lit_color,
+                color,
                 depth,
                 normal,
-                intensity,
+                intensity * sunlit,
                 vertex_position,
+                world_position,
             )
+            .with_tex_coords(tex_coords)
+            .with_sunlit(sunlit)
+            .with_varyings(V::interpolate(&v1.varyings, &v2.varyings, &v3.varyings, w1, w2, w3))
         );
       }
     }
@@ -53,7 +167,7 @@ pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragment> {
   fragments
 }
 
-fn calculate_bounding_box(v1: &Vec3, v2: &Vec3, v3: &Vec3) -> (i32, i32, i32, i32) {
+pub fn calculate_bounding_box(v1: &Vec3, v2: &Vec3, v3: &Vec3) -> (i32, i32, i32, i32) {
     let min_x = v1.x.min(v2.x).min(v3.x).floor() as i32;
     let min_y = v1.y.min(v2.y).min(v3.y).floor() as i32;
     let max_x = v1.x.max(v2.x).max(v3.x).ceil() as i32;
@@ -62,12 +176,23 @@ fn calculate_bounding_box(v1: &Vec3, v2: &Vec3, v3: &Vec3) -> (i32, i32, i32, i3
     (min_x, min_y, max_x, max_y)
 }
 
-fn barycentric_coordinates(p: &Vec3, a: &Vec3, b: &Vec3, c: &Vec3, area: f32) -> (f32, f32, f32) {
-    let w1 = edge_function(b, c, p) / area;
-    let w2 = edge_function(c, a, p) / area;
-    let w3 = edge_function(a, b, p) / area;
+// Precisión de subpixel de la rasterización: posiciones redondeadas a 1/16 de pixel
+const SUBPIXEL: i64 = 16;
+
+fn snap(value: f32) -> i64 {
+    (value * SUBPIXEL as f32).round() as i64
+}
+
+// `edge_function` en enteros, sin errores de redondeo en las aristas compartidas
+fn fixed_edge(a: (i64, i64), b: (i64, i64), c: (i64, i64)) -> i64 {
+    (c.0 - a.0) * (b.1 - a.1) - (c.1 - a.1) * (b.0 - a.0)
+}
 
-    (w1, w2, w3)
+// Con el interior del lado positivo y la Y hacia abajo, una arista superior es
+// horizontal y va hacia -X, y una izquierda baja (+Y)
+fn is_top_left(from: (i64, i64), to: (i64, i64), orientation: i64) -> bool {
+    let (dx, dy) = ((to.0 - from.0) * orientation, (to.1 - from.1) * orientation);
+    (dy == 0 && dx < 0) || dy > 0
 }
 
 fn edge_function(a: &Vec3, b: &Vec3, c: &Vec3) -> f32 {
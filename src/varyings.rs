@@ -0,0 +1,70 @@
+use nalgebra_glm::{Vec2, Vec3, Vec4};
+use crate::color::Color;
+
+// Datos extra por vértice que el rasterizador interpola con las coordenadas
+// baricéntricas y entrega en cada fragmento (`Vertex::varyings` y
+// `Fragment::varyings`). Un material que necesite tangentes, oclusión por vértice
+// o velocidades define su tipo (o usa una tupla) y lo carga con
+// `Vertex::with_varyings`, sin tocar triangle.rs. Posición, normal, color y
+// profundidad siguen siendo campos fijos; `()` es el caso sin datos extra.
+pub trait Varyings: Clone {
+    fn interpolate(a: &Self, b: &Self, c: &Self, w1: f32, w2: f32, w3: f32) -> Self;
+}
+
+impl Varyings for () {
+    fn interpolate(_: &Self, _: &Self, _: &Self, _: f32, _: f32, _: f32) -> Self {}
+}
+
+impl Varyings for f32 {
+    fn interpolate(a: &Self, b: &Self, c: &Self, w1: f32, w2: f32, w3: f32) -> Self {
+        a * w1 + b * w2 + c * w3
+    }
+}
+
+impl Varyings for Vec2 {
+    fn interpolate(a: &Self, b: &Self, c: &Self, w1: f32, w2: f32, w3: f32) -> Self {
+        a * w1 + b * w2 + c * w3
+    }
+}
+
+impl Varyings for Vec3 {
+    fn interpolate(a: &Self, b: &Self, c: &Self, w1: f32, w2: f32, w3: f32) -> Self {
+        a * w1 + b * w2 + c * w3
+    }
+}
+
+impl Varyings for Vec4 {
+    fn interpolate(a: &Self, b: &Self, c: &Self, w1: f32, w2: f32, w3: f32) -> Self {
+        a * w1 + b * w2 + c * w3
+    }
+}
+
+// En flotante para no perder precisión al redondear
+impl Varyings for Color {
+    fn interpolate(a: &Self, b: &Self, c: &Self, w1: f32, w2: f32, w3: f32) -> Self {
+        let (r1, g1, b1) = a.to_f32();
+        let (r2, g2, b2) = b.to_f32();
+        let (r3, g3, b3) = c.to_f32();
+        Color::from_f32(
+            r1 * w1 + r2 * w2 + r3 * w3,
+            g1 * w1 + g2 * w2 + g3 * w3,
+            b1 * w1 + b2 * w2 + b3 * w3,
+        )
+    }
+}
+
+impl<A: Varyings, B: Varyings> Varyings for (A, B) {
+    fn interpolate(a: &Self, b: &Self, c: &Self, w1: f32, w2: f32, w3: f32) -> Self {
+        (A::interpolate(&a.0, &b.0, &c.0, w1, w2, w3), B::interpolate(&a.1, &b.1, &c.1, w1, w2, w3))
+    }
+}
+
+impl<A: Varyings, B: Varyings, C: Varyings> Varyings for (A, B, C) {
+    fn interpolate(a: &Self, b: &Self, c: &Self, w1: f32, w2: f32, w3: f32) -> Self {
+        (
+            A::interpolate(&a.0, &b.0, &c.0, w1, w2, w3),
+            B::interpolate(&a.1, &b.1, &c.1, w1, w2, w3),
+            C::interpolate(&a.2, &b.2, &c.2, w1, w2, w3),
+        )
+    }
+}
@@ -1,14 +1,19 @@
-use nalgebra_glm::{Vec2, Vec3};
+use nalgebra_glm::{Vec2, Vec3, Vec4};
 use crate::color::Color;
 
+// `varyings`: datos extra que se interpolan hasta el fragmento (ver varyings.rs)
 #[derive(Clone, Debug)]
-pub struct Vertex {
+pub struct Vertex<V = ()> {
   pub position: Vec3,
   pub normal: Vec3,
   pub tex_coords: Vec2,
   pub color: Color,
   pub transformed_position: Vec3,
   pub transformed_normal: Vec3,
+  pub world_position: Vec3,
+  // Posición antes de la división de perspectiva, para recortar (ver clipping.rs)
+  pub clip_position: Vec4,
+  pub varyings: V,
 }
 
 impl Vertex {
@@ -20,6 +25,9 @@ impl Vertex {
       color: Color::black(),
       transformed_position: position,
       transformed_normal: normal,
+      world_position: position,
+      clip_position: Vec4::new(position.x, position.y, position.z, 1.0),
+      varyings: (),
     }
   }
 
@@ -31,16 +39,31 @@ impl Vertex {
       color,
       transformed_position: Vec3::new(0.0, 0.0, 0.0),
       transformed_normal: Vec3::new(0.0, 0.0, 0.0),
+      world_position: position,
+      clip_position: Vec4::new(position.x, position.y, position.z, 1.0),
+      varyings: (),
     }
   }
+}
 
-  pub fn set_transformed(&mut self, position: Vec3, normal: Vec3) {
-    self.transformed_position = position;
-    self.transformed_normal = normal;
+impl<V> Vertex<V> {
+  // El mismo vértice con otros datos extra
+  pub fn with_varyings<W>(self, varyings: W) -> Vertex<W> {
+    Vertex {
+      position: self.position,
+      normal: self.normal,
+      tex_coords: self.tex_coords,
+      color: self.color,
+      transformed_position: self.transformed_position,
+      transformed_normal: self.transformed_normal,
+      world_position: self.world_position,
+      clip_position: self.clip_position,
+      varyings,
+    }
   }
 }
 
-impl Default for Vertex {
+impl<V: Default> Default for Vertex<V> {
   fn default() -> Self {
     Vertex {
       position: Vec3::new(0.0, 0.0, 0.0),
@@ -49,6 +72,9 @@ impl Default for Vertex {
       color: Color::black(),
       transformed_position: Vec3::new(0.0, 0.0, 0.0),
       transformed_normal: Vec3::new(0.0, 1.0, 0.0),
+      world_position: Vec3::new(0.0, 0.0, 0.0),
+      clip_position: Vec4::new(0.0, 0.0, 0.0, 1.0),
+      varyings: V::default(),
     }
   }
 }
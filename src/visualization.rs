@@ -0,0 +1,144 @@
+use nalgebra_glm::Vec3;
+use crate::color::{Color, Colormap};
+use crate::fragment::Fragment;
+use crate::framebuffer::Framebuffer;
+use crate::text;
+
+// Modo de visualización en falso color: reemplaza los shaders naturales por un
+// mapa de color aplicado a una magnitud escalar de cada cuerpo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VisualizationMode {
+    Natural,
+    Temperature,
+    Albedo,
+    Distance,
+}
+
+// Datos físicos aproximados: temperatura media de superficie (K) y albedo de Bond
+pub struct BodyInfo {
+    pub name: &'static str,
+    pub temperature: f32,
+    pub albedo: f32,
+}
+
+pub const BODY_INFO: [BodyInfo; 18] = [
+    BodyInfo { name: "sun", temperature: 5778.0, albedo: 0.0 },
+    BodyInfo { name: "mercury", temperature: 440.0, albedo: 0.088 },
+    BodyInfo { name: "venus", temperature: 737.0, albedo: 0.76 },
+    BodyInfo { name: "earth", temperature: 288.0, albedo: 0.306 },
+    BodyInfo { name: "moon", temperature: 250.0, albedo: 0.11 },
+    BodyInfo { name: "mars", temperature: 210.0, albedo: 0.25 },
+    BodyInfo { name: "ceres", temperature: 168.0, albedo: 0.09 },
+    BodyInfo { name: "jupiter", temperature: 165.0, albedo: 0.343 },
+    BodyInfo { name: "io", temperature: 110.0, albedo: 0.63 },
+    BodyInfo { name: "europa", temperature: 102.0, albedo: 0.67 },
+    BodyInfo { name: "ganymede", temperature: 110.0, albedo: 0.43 },
+    BodyInfo { name: "callisto", temperature: 134.0, albedo: 0.22 },
+    BodyInfo { name: "saturn", temperature: 134.0, albedo: 0.342 },
+    BodyInfo { name: "uranus", temperature: 76.0, albedo: 0.3 },
+    BodyInfo { name: "neptune", temperature: 72.0, albedo: 0.29 },
+    BodyInfo { name: "pluto", temperature: 44.0, albedo: 0.72 },
+    BodyInfo { name: "eris", temperature: 42.0, albedo: 0.96 },
+    BodyInfo { name: "halley", temperature: 200.0, albedo: 0.04 },
+];
+
+pub fn body_info(name: &str) -> Option<&'static BodyInfo> {
+    BODY_INFO.iter().find(|info| info.name == name)
+}
+
+// Distancia máxima al Sol en unidades de escena (órbita de Neptuno); lo que queda
+// más lejos toma el último color
+const MAX_DISTANCE: f32 = 16.0;
+const MIN_TEMPERATURE: f32 = 40.0;
+const MAX_TEMPERATURE: f32 = 6000.0;
+
+impl VisualizationMode {
+    pub fn next(self) -> Self {
+        match self {
+            VisualizationMode::Natural => VisualizationMode::Temperature,
+            VisualizationMode::Temperature => VisualizationMode::Albedo,
+            VisualizationMode::Albedo => VisualizationMode::Distance,
+            VisualizationMode::Distance => VisualizationMode::Natural,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            VisualizationMode::Natural => "Natural",
+            VisualizationMode::Temperature => "Temperatura superficial (K)",
+            VisualizationMode::Albedo => "Albedo de Bond",
+            VisualizationMode::Distance => "Distancia al Sol",
+        }
+    }
+
+    fn colormap(self) -> Colormap {
+        match self {
+            VisualizationMode::Temperature => Colormap::Inferno,
+            _ => Colormap::Viridis,
+        }
+    }
+
+    // Magnitud normalizada a [0, 1]; la temperatura usa escala logarítmica
+    fn normalized(self, info: &BodyInfo, position: Vec3) -> Option<f32> {
+        match self {
+            VisualizationMode::Natural => None,
+            VisualizationMode::Temperature => {
+                let (min, max) = (MIN_TEMPERATURE.ln(), MAX_TEMPERATURE.ln());
+                Some((info.temperature.ln() - min) / (max - min))
+            }
+            VisualizationMode::Albedo => Some(info.albedo),
+            VisualizationMode::Distance => Some(position.magnitude() / MAX_DISTANCE),
+        }
+    }
+
+    fn range_labels(self) -> (String, String) {
+        match self {
+            VisualizationMode::Natural => (String::new(), String::new()),
+            VisualizationMode::Temperature => (format!("{}", MIN_TEMPERATURE), format!("{}", MAX_TEMPERATURE)),
+            VisualizationMode::Albedo => (String::from("0.0"), String::from("1.0")),
+            VisualizationMode::Distance => (String::from("0"), format!("{}", MAX_DISTANCE)),
+        }
+    }
+
+    // Color falso de un cuerpo, o None en modo natural / cuerpo sin datos
+    pub fn false_color(self, body: &str, position: Vec3) -> Option<Color> {
+        let info = body_info(body)?;
+        self.normalized(info, position).map(|t| self.colormap().sample(t))
+    }
+}
+
+// Color falso sombreado con la iluminación difusa del fragmento
+pub fn false_color_shader<V>(fragment: &Fragment<V>, color: Color) -> Color {
+    color * (0.3 + 0.7 * fragment.intensity)
+}
+
+// Barra de leyenda en la esquina inferior izquierda
+pub fn draw_legend(framebuffer: &mut Framebuffer, mode: VisualizationMode) {
+    if mode == VisualizationMode::Natural {
+        return;
+    }
+
+    let bar_width = 256;
+    let bar_height = 12;
+    let x = 16;
+    // En una ventana más baja que la leyenda con su título no se dibuja
+    if framebuffer.height < 48 + text::LINE_HEIGHT + 2 {
+        return;
+    }
+    let y = framebuffer.height - 48;
+
+    text::draw_text_shadowed(framebuffer, x, y - text::LINE_HEIGHT - 2, mode.label(), 0xFFFFFF, 1);
+
+    framebuffer.fill_rect(x - 1, y - 1, bar_width + 2, bar_height + 2, 0xFFFFFF);
+    let colormap = mode.colormap();
+    for i in 0..bar_width {
+        let color = colormap.sample(i as f32 / (bar_width - 1) as f32);
+        framebuffer.fill_rect(x + i, y, 1, bar_height, color.to_hex());
+    }
+
+    let (min_label, max_label) = mode.range_labels();
+    let label_y = y + bar_height + 4;
+    text::draw_text_shadowed(framebuffer, x, label_y, &min_label, 0xFFFFFF, 1);
+    let max_x = (x + bar_width).saturating_sub(text::text_width(&max_label, 1));
+    text::draw_text_shadowed(framebuffer, max_x, label_y, &max_label, 0xFFFFFF, 1);
+}
@@ -0,0 +1,54 @@
+use nalgebra_glm::{self as glm, DVec3, Vec3};
+use crate::camera::{Camera, CameraAnimator};
+use crate::solar_system::{self, SolarSystem};
+
+// Destinos de las teclas 0 a 8: el Sol y los planetas en orden
+pub const WARP_TARGETS: [&str; 9] = ["sun", "mercury", "venus", "earth", "mars", "jupiter", "saturn", "uranus", "neptune"];
+// Frames que dura el viaje
+const DURATION: f32 = 90.0;
+// Encuadre: distancia al centro en radios del cuerpo, cuánto se corre del lado del
+// día para que se vea la fase y altura sobre el plano de la órbita (radianes)
+const FRAMING_DISTANCE: f64 = 4.0;
+const FRAMING_SIDE: f64 = 0.7;
+const FRAMING_ELEVATION: f64 = 0.35;
+
+// Viaje de la cámara libre hasta encuadrar un cuerpo. El encuadre se recalcula en
+// cada frame con la posición actual del cuerpo, así llega aunque siga en su órbita;
+// el ojo y el punto de mira salen despacio, aceleran y frenan al llegar.
+pub struct Warp {
+    pub body: &'static str,
+    animator: CameraAnimator,
+    // Desde dónde se mira al Sol, que no tiene lado iluminado
+    start_eye: Vec3,
+}
+
+impl Warp {
+    pub fn new(camera: &Camera, body: &'static str) -> Self {
+        Warp { body, animator: CameraAnimator::new(camera, DURATION), start_eye: camera.eye }
+    }
+
+    // Devuelve true al llegar; desde ahí la cámara queda libre
+    pub fn update(&mut self, camera: &mut Camera, system: &SolarSystem, dt: f32) -> bool {
+        self.animator.update(camera, &framing(system, self.body, self.start_eye), dt)
+    }
+
+    pub fn hud_line(&self) -> String {
+        format!("Viajando a {}", solar_system::display_name(self.body))
+    }
+}
+
+// Cámara que mira al cuerpo desde el lado iluminado, algo corrida y por encima del
+// plano; para el Sol, desde el lado en que estaba `eye`
+fn framing(system: &SolarSystem, body: &str, eye: Vec3) -> Camera {
+    let center = system.position(body);
+    let to_sun = system.position("sun") - center;
+    let side = if to_sun.magnitude() > f64::EPSILON {
+        glm::rotate_y_vec3(&to_sun, FRAMING_SIDE)
+    } else {
+        eye.cast::<f64>() - center
+    };
+    let horizontal = DVec3::new(side.x, 0.0, side.z).try_normalize(f64::EPSILON).unwrap_or(DVec3::z());
+    let direction = horizontal * FRAMING_ELEVATION.cos() + DVec3::y() * FRAMING_ELEVATION.sin();
+    let eye = center + direction * system.radius(body) * FRAMING_DISTANCE;
+    Camera::new(eye.cast(), center.cast(), Vec3::y())
+}
@@ -0,0 +1,84 @@
+use nalgebra_glm::{DVec3, Vec3};
+use crate::color::Color;
+use crate::debug_draw;
+use crate::effects_budget;
+use crate::framebuffer::Framebuffer;
+use crate::orbit_ruler;
+use crate::solar_system::{self, SolarSystem};
+use crate::Uniforms;
+
+const GLOW_COLOR: Color = Color::new(255, 236, 205);
+// Radio del disco de polvo, en UA
+const DUST_RADIUS: f32 = 2.5;
+// Espesor del disco: crece con la distancia al Sol (fracción de r) más un mínimo en UA
+const THICKNESS: f32 = 0.12;
+const MIN_THICKNESS: f32 = 0.03;
+// Muestras por rayo (con el presupuesto de efectos al 100%) y lado del bloque de
+// pixeles que comparte una misma muestra; con poco presupuesto los bloques crecen
+const SAMPLES: usize = 16;
+const MIN_SAMPLES: usize = 6;
+const BLOCK: usize = 2;
+const COARSE_BLOCK: usize = 4;
+const COARSE_BELOW: f32 = 0.6;
+// Escala para que intensity = 1 sea una luz tenue pero visible en una vista amplia
+const GAIN: f32 = 0.12;
+
+// Luz zodiacal: la luz del Sol dispersada por el polvo del plano de la eclíptica.
+// Por cada pixel de fondo se integra la densidad de un disco de polvo a lo largo
+// del rayo de la cámara (más densa cerca del Sol y concentrada en el plano y = 0)
+// y se suma un brillo cálido; así la cuña se orienta sola según la cámara. El
+// brillo varía poco entre pixeles vecinos: se evalúa uno por bloque de BLOCK x
+// BLOCK. `uniforms` con matriz de modelo identidad; `effects_scale` es la escala
+// del presupuesto de efectos (ver effects_budget.rs).
+pub fn draw(framebuffer: &mut Framebuffer, uniforms: &Uniforms, system: &SolarSystem, origin: DVec3, intensity: f32, effects_scale: f32) {
+    if intensity <= 0.0 {
+        return;
+    }
+    let Some(inverse) = (uniforms.projection_matrix * uniforms.view_matrix).try_inverse() else {
+        return;
+    };
+    let au = orbit_ruler::astronomical_unit(system) as f32;
+    let sun = solar_system::to_render(system.position("sun"), origin);
+    let eye = uniforms.camera_position;
+    // El rayo se recorre hasta salir del disco del lado opuesto al Sol
+    let length = (sun - eye).magnitude() + DUST_RADIUS * au;
+    let samples = effects_budget::scaled(SAMPLES, effects_scale).clamp(MIN_SAMPLES, SAMPLES);
+    let block = if effects_scale < COARSE_BELOW { COARSE_BLOCK } else { BLOCK };
+    let step = length / samples as f32;
+
+    for block_y in (0..framebuffer.height).step_by(block) {
+        for block_x in (0..framebuffer.width).step_by(block) {
+            let ray = (debug_draw::unproject_far(&inverse, framebuffer, block_x, block_y) - eye).normalize();
+            let optical_depth: f32 = (0..samples)
+                .map(|i| density(eye + ray * (i as f32 + 0.5) * step - sun, au) * step / au)
+                .sum();
+            let glow = GLOW_COLOR * (intensity * GAIN * optical_depth).min(1.0);
+            if glow.is_black() {
+                continue;
+            }
+            let glow = glow.to_linear();
+
+            for y in block_y..(block_y + block).min(framebuffer.height) {
+                for x in block_x..(block_x + block).min(framebuffer.width) {
+                    // Solo el fondo: los cuerpos tapan el polvo que está detrás
+                    let index = y * framebuffer.width + x;
+                    if framebuffer.zbuffer[index] == f32::INFINITY {
+                        (0..3).for_each(|channel| framebuffer.hdr[index][channel] += glow[channel]);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Densidad relativa del polvo en un punto relativo al Sol: cae como r^-1.3 y
+// exponencialmente al alejarse del plano; se corta en el borde del disco
+fn density(offset: Vec3, au: f32) -> f32 {
+    let r = offset.magnitude() / au;
+    if r > DUST_RADIUS {
+        return 0.0;
+    }
+    let height = offset.y.abs() / au;
+    let thickness = THICKNESS * r + MIN_THICKNESS;
+    (r + 0.05).powf(-1.3) * (-height / thickness).exp()
+}